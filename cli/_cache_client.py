@@ -28,6 +28,22 @@ def cmd_stats(args):
         return 1
 
 
+def cmd_version(args):
+    stub = get_stub(args.address)
+    try:
+        response = stub.GetVersion(memory_pb2.GetVersionRequest())
+        print(f"memory-rust at {args.address}:")
+        print(f"  Version: {response.version} (commit {response.git_commit})")
+        print(f"  Built: {response.build_timestamp}")
+        print(f"  Features: {', '.join(response.features) or '(none)'}")
+        print(f"  Schema: {response.schema_version} (proto {response.proto_fingerprint})")
+        print(f"  Embedding model: {response.embedding_model or '(not yet known)'}")
+        return 0
+    except Exception as e:
+        print(f"Error: {e}")
+        return 1
+
+
 def cmd_list(args):
     stub = get_stub(args.address)
     try:
@@ -71,12 +87,125 @@ def cmd_evict(args):
         return 1
 
 
+def cmd_export_heuristics(args):
+    stub = get_stub(args.address)
+    try:
+        response = stub.ExportHeuristics(memory_pb2.ExportHeuristicsRequest(
+            min_confidence=args.min_confidence,
+            source=args.source,
+            limit=args.limit,
+        ))
+        Path(args.file).write_text(response.heuristics_json + "\n", encoding="utf-8")
+        print(f"Exported {response.count} heuristics to {args.file}.")
+        return 0
+    except Exception as e:
+        print(f"Error: {e}")
+        return 1
+
+
+def cmd_import_heuristics(args):
+    stub = get_stub(args.address)
+    try:
+        heuristics_json = Path(args.file).read_text(encoding="utf-8")
+        response = stub.ImportHeuristics(memory_pb2.ImportHeuristicsRequest(heuristics_json=heuristics_json))
+        print(f"Imported {response.stored} of {response.received} heuristics ({response.cached} cached).")
+        if response.skipped:
+            print(f"Warning: skipped {response.skipped} corrupt or missing records in {args.file}.")
+        return 0 if response.stored == response.received else 1
+    except Exception as e:
+        print(f"Error: {e}")
+        return 1
+
+
+def cmd_prefetch(args):
+    stub = get_stub(args.address)
+    try:
+        response = stub.PrefetchHeuristics(memory_pb2.PrefetchHeuristicsRequest(topics=args.topics, source=args.source))
+        print(f"Loaded {response.heuristics_loaded} heuristics for {response.topics_queried} topics.")
+        for topic in response.failed_topics:
+            print(f"  Failed: {topic}")
+        return 1 if response.failed_topics else 0
+    except Exception as e:
+        print(f"Error: {e}")
+        return 1
+
+
+def cmd_diagnostics(args):
+    stub = get_stub(args.address)
+    try:
+        response = stub.RunDiagnostics(memory_pb2.RunDiagnosticsRequest(probe_text=args.probe))
+        for check in response.checks:
+            status = "PASS" if check.passed else "FAIL"
+            print(f"  {status}  {check.name:<18} {check.duration_ms:8.2f} ms  {check.detail}")
+        print(f"Diagnostics {'passed' if response.passed else 'FAILED'} in {response.total_ms:.2f} ms.")
+        return 0 if response.passed else 1
+    except Exception as e:
+        print(f"Error: {e}")
+        return 1
+
+
+def cmd_inject_faults(args):
+    stub = get_stub(args.address)
+    try:
+        response = stub.InjectFaults(memory_pb2.InjectFaultsRequest(
+            methods=args.methods,
+            latency_ms=args.latency_ms,
+            error_rate=args.error_rate,
+            error_kind=args.error_kind,
+            partial_rate=args.partial_rate,
+            clear=args.clear,
+        ))
+        print(f"Faults {'active' if response.active else 'cleared'}.")
+        print(f"  Injected so far: {response.delays_injected} delays, {response.errors_injected} errors, "
+              f"{response.partials_injected} partial responses")
+        return 0
+    except Exception as e:
+        print(f"Error: {e}")
+        return 1
+
+
+def cmd_summary(args):
+    stub = get_stub(args.address)
+    try:
+        response = stub.GetSalienceSummary(memory_pb2.GetSalienceSummaryRequest(
+            window_minutes=args.minutes, source=args.source))
+        print(f"Last {response.window_minutes} min:")
+        for source in response.sources:
+            print(f"  {source.source or '(no source)'}: {source.events} events, {source.match_rate:.0%} matched")
+            for dim in source.dimensions:
+                print(f"    {dim.dimension:<16} mean {dim.mean:.3f}  p95 {dim.p95:.3f}")
+        return 0
+    except Exception as e:
+        print(f"Error: {e}")
+        return 1
+
+
+def cmd_effectiveness(args):
+    stub = get_stub(args.address)
+    try:
+        response = stub.GetHeuristicEffectiveness(memory_pb2.GetHeuristicEffectivenessRequest(
+            overfire_per_hour=args.overfire_per_hour, limit=args.limit))
+        for h in response.heuristics:
+            flags = " ".join(f for f, on in [("DEAD", h.dead_weight), ("OVERFIRING", h.over_firing)] if on)
+            success = f"{h.success_ratio:.0%} of {h.fire_count}" if h.has_feedback else "-"
+            print(f"  {h.heuristic_id}  {h.name:<30} {h.hits_per_hour:8.1f}/h  sim {h.mean_similarity:.2f}  "
+                  f"success {success}  {flags}")
+        if not response.feedback_available:
+            print("Feedback outcomes unavailable from storage.")
+        print(f"{response.dead_weight_count} dead weight, {response.over_firing_count} over-firing.")
+        return 0
+    except Exception as e:
+        print(f"Error: {e}")
+        return 1
+
+
 def main():
     parser = argparse.ArgumentParser()
     parser.add_argument("--address", default="localhost:50052", help="memory-rust gRPC address")
     subparsers = parser.add_subparsers(dest="command", required=True)
 
     subparsers.add_parser("stats")
+    subparsers.add_parser("version")
 
     list_p = subparsers.add_parser("list")
     list_p.add_argument("--limit", type=int, default=0)
@@ -86,13 +215,53 @@ def main():
     evict_p = subparsers.add_parser("evict")
     evict_p.add_argument("id")
 
+    export_p = subparsers.add_parser("export-heuristics")
+    export_p.add_argument("file")
+    export_p.add_argument("--min-confidence", type=float, default=0.0)
+    export_p.add_argument("--source", default="")
+    export_p.add_argument("--limit", type=int, default=0)
+
+    import_p = subparsers.add_parser("import-heuristics")
+    import_p.add_argument("file")
+
+    prefetch_p = subparsers.add_parser("prefetch")
+    prefetch_p.add_argument("topics", nargs="+")
+    prefetch_p.add_argument("--source", default="")
+
+    diagnostics_p = subparsers.add_parser("diagnostics")
+    diagnostics_p.add_argument("--probe", default="", help="Probe text (default: built-in)")
+
+    summary_p = subparsers.add_parser("summary")
+    summary_p.add_argument("--minutes", type=int, default=0, help="Window (default: 5)")
+    summary_p.add_argument("--source", default="")
+
+    effectiveness_p = subparsers.add_parser("effectiveness")
+    effectiveness_p.add_argument("--overfire-per-hour", type=float, default=0.0, help="Default: 60")
+    effectiveness_p.add_argument("--limit", type=int, default=0)
+
+    faults_p = subparsers.add_parser("inject-faults", help="Requires a fault-injection build")
+    faults_p.add_argument("methods", nargs="*", help="Storage methods to affect (default: all)")
+    faults_p.add_argument("--latency-ms", type=int, default=0)
+    faults_p.add_argument("--error-rate", type=float, default=0.0)
+    faults_p.add_argument("--error-kind", choices=["unavailable", "timeout"], default="unavailable")
+    faults_p.add_argument("--partial-rate", type=float, default=0.0)
+    faults_p.add_argument("--clear", action="store_true")
+
     args = parser.parse_args()
 
     cmds = {
         "stats": cmd_stats,
+        "version": cmd_version,
         "list": cmd_list,
         "flush": cmd_flush,
         "evict": cmd_evict,
+        "export-heuristics": cmd_export_heuristics,
+        "import-heuristics": cmd_import_heuristics,
+        "prefetch": cmd_prefetch,
+        "diagnostics": cmd_diagnostics,
+        "summary": cmd_summary,
+        "effectiveness": cmd_effectiveness,
+        "inject-faults": cmd_inject_faults,
     }
 
     sys.exit(cmds[args.command](args))
@@ -464,6 +464,15 @@ class LocalBackend(ServiceBackend):
     def cache_evict(self, heuristic_id: str) -> int:
         return self._run_cache_cmd(["evict", heuristic_id])
 
+    def cache_export_heuristics(self, path: str, min_confidence: float = 0.0, source: str = "") -> int:
+        return self._run_cache_cmd(["export-heuristics", path, "--min-confidence", str(min_confidence), "--source", source])
+
+    def cache_import_heuristics(self, path: str) -> int:
+        return self._run_cache_cmd(["import-heuristics", path])
+
+    def cache_prefetch(self, topics: List[str], source: str = "") -> int:
+        return self._run_cache_cmd(["prefetch", *topics, "--source", source])
+
     def _run_queue_cmd(self, args: List[str]) -> int:
         """Run a queue client command against orchestrator."""
         address = f"localhost:{LOCAL_PORTS.orchestrator}"
@@ -101,6 +101,21 @@ class ServiceBackend(abc.ABC):
         """Evict heuristic from cache. Returns exit code."""
         pass
 
+    @abc.abstractmethod
+    def cache_export_heuristics(self, path: str, min_confidence: float = 0.0, source: str = "") -> int:
+        """Export stored heuristics to a JSON file. Returns exit code."""
+        pass
+
+    @abc.abstractmethod
+    def cache_import_heuristics(self, path: str) -> int:
+        """Import heuristics from a JSON file. Returns exit code."""
+        pass
+
+    @abc.abstractmethod
+    def cache_prefetch(self, topics: List[str], source: str = "") -> int:
+        """Load heuristics matching topics into the cache. Returns exit code."""
+        pass
+
     @abc.abstractmethod
     def queue_stats(self) -> int:
         """Show event queue statistics. Returns exit code."""
@@ -259,6 +274,21 @@ class ServiceManager:
         cache_evict.add_argument("id", help="Heuristic ID to evict")
         cache_evict.set_defaults(func=self.cmd_cache_evict)
 
+        cache_export = cache_sub.add_parser("export-heuristics", help="Export stored heuristics to a JSON file")
+        cache_export.add_argument("file", help="Output file")
+        cache_export.add_argument("--min-confidence", type=float, default=0.0, help="Skip heuristics below this confidence")
+        cache_export.add_argument("--source", default="", help="Only heuristics for this event source")
+        cache_export.set_defaults(func=self.cmd_cache_export_heuristics)
+
+        cache_import = cache_sub.add_parser("import-heuristics", help="Store heuristics from a JSON file")
+        cache_import.add_argument("file", help="File written by export-heuristics (or a seed file)")
+        cache_import.set_defaults(func=self.cmd_cache_import_heuristics)
+
+        cache_prefetch = cache_sub.add_parser("prefetch", help="Warm the cache with heuristics for upcoming topics")
+        cache_prefetch.add_argument("topics", nargs="+", help="Topic text, matched like event text")
+        cache_prefetch.add_argument("--source", default="", help="Only heuristics for this event source")
+        cache_prefetch.set_defaults(func=self.cmd_cache_prefetch)
+
         # Queue Management (orchestrator event queue)
         queue = subparsers.add_parser("queue", help="Event queue management (orchestrator)")
         queue_sub = queue.add_subparsers(dest="queue_command", required=True)
@@ -476,6 +506,15 @@ class ServiceManager:
     def cmd_cache_evict(self, args):
         return self.backend.cache_evict(args.id)
 
+    def cmd_cache_export_heuristics(self, args):
+        return self.backend.cache_export_heuristics(args.file, args.min_confidence, args.source)
+
+    def cmd_cache_import_heuristics(self, args):
+        return self.backend.cache_import_heuristics(args.file)
+
+    def cmd_cache_prefetch(self, args):
+        return self.backend.cache_prefetch(args.topics, args.source)
+
     def cmd_queue_stats(self, args):
         return self.backend.queue_stats()
 
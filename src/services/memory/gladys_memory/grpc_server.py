@@ -23,6 +23,9 @@ from . import memory_pb2
 from . import memory_pb2_grpc
 from . import types_pb2
 
+# memory.proto contract version ("major.minor"); bump the major on breaking changes
+SCHEMA_VERSION = "1.0"
+
 
 def _bytes_to_embedding(data: bytes) -> np.ndarray:
     """Convert bytes to 384-dim float32 embedding."""
@@ -338,7 +341,8 @@ class MemoryStorageServicer(memory_pb2_grpc.MemoryStorageServicer):
 
             embedding = self.embeddings.generate(request.text)
             return memory_pb2.GenerateEmbeddingResponse(
-                embedding=_embedding_to_bytes(embedding)
+                embedding=_embedding_to_bytes(embedding),
+                model_version=self.embeddings.model_name,
             )
         except Exception as e:
             await context.abort(grpc.StatusCode.INTERNAL, str(e))
@@ -510,6 +514,7 @@ class MemoryStorageServicer(memory_pb2_grpc.MemoryStorageServicer):
                     name=h["name"],
                     condition_text=condition.get("text", ""),
                     condition_embedding=embedding_bytes,
+                    embedding_model=self.embeddings.model_name if embedding_bytes else "",
                     effects_json=json.dumps(action),
                     confidence=h["confidence"],
                     origin=h.get("origin", "learned"),
@@ -1077,6 +1082,14 @@ class MemoryStorageServicer(memory_pb2_grpc.MemoryStorageServicer):
             context.set_details(str(e))
             return memory_pb2.ListFiresResponse()
 
+    async def GetSchemaVersion(self, request, context):
+        """Contract version handshake (the Rust fast path checks it at startup)."""
+        if request.client_version and request.client_version != SCHEMA_VERSION:
+            logger.warning(
+                f"Client schema version {request.client_version} differs from {SCHEMA_VERSION}"
+            )
+        return memory_pb2.GetSchemaVersionResponse(version=SCHEMA_VERSION)
+
     async def GetHealth(self, request, context):
         """Basic health check."""
         return types_pb2.GetHealthResponse(
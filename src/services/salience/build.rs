@@ -1,4 +1,17 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use prost::Message;
+use prost_types::FileDescriptorSet;
+
+/// Fingerprint of the proto contract this crate was last built against.
+/// Checked in; a build against different protos (or without the file)
+/// fails until it's updated.
+const FINGERPRINT_FILE: &str = "proto.fingerprint";
+
+/// Commit to report where there's no git checkout (e.g. Docker builds).
+const COMMIT_ENV: &str = "GLADYS_GIT_COMMIT";
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Proto directory locations:
@@ -12,7 +25,93 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         ("../proto", vec!["../proto/types.proto", "../proto/memory.proto"]) // Legacy local path (fallback)
     };
 
+    let descriptor_path = PathBuf::from(std::env::var("OUT_DIR")?).join("memory_descriptor.bin");
+    // Service traits only: messages and client stubs come from the
+    // gladys-memory-client crate, compiled from the same protos
     tonic_build::configure()
+        .build_client(false)
+        .extern_path(".gladys", "::gladys_memory_client::proto::gladys")
+        .file_descriptor_set_path(&descriptor_path)
         .compile_protos(&protos, &[proto_dir])?;
+
+    let fingerprint = contract_fingerprint(&std::fs::read(&descriptor_path)?)?;
+    check_fingerprint(&fingerprint, proto_dir)?;
+    println!("cargo:rustc-env=GLADYS_PROTO_FINGERPRINT={}", fingerprint);
+    println!("cargo:rerun-if-changed={}", FINGERPRINT_FILE);
+
+    println!("cargo:rustc-env=GLADYS_GIT_COMMIT={}", git_commit());
+    println!("cargo:rustc-env=GLADYS_BUILD_TIMESTAMP={}", build_timestamp());
+    println!("cargo:rerun-if-env-changed={}", COMMIT_ENV);
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
     Ok(())
 }
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    (output.status.success() && !stdout.trim().is_empty()).then(|| stdout.trim().to_string())
+}
+
+/// Short hash of the checked-out commit, or "unknown". Rebuilds when HEAD
+/// moves.
+fn git_commit() -> String {
+    if let Some(commit) = std::env::var(COMMIT_ENV).ok().filter(|s| !s.is_empty()) {
+        return commit;
+    }
+    let Some(commit) = git(&["rev-parse", "--short=12", "HEAD"]) else {
+        return "unknown".to_string();
+    };
+    let branch = git(&["symbolic-ref", "-q", "HEAD"]);
+    for reference in ["HEAD"].into_iter().chain(branch.as_deref()) {
+        if let Some(path) = git(&["rev-parse", "--git-path", reference]) {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+    commit
+}
+
+/// Seconds since the epoch: SOURCE_DATE_EPOCH for reproducible builds,
+/// else when this script ran.
+fn build_timestamp() -> u64 {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()))
+}
+
+/// FNV-1a over the descriptor set with source info (comments, spans)
+/// stripped, so only changes to the contract itself count.
+fn contract_fingerprint(descriptor: &[u8]) -> Result<String, prost::DecodeError> {
+    let mut set = FileDescriptorSet::decode(descriptor)?;
+    for file in &mut set.file {
+        file.source_code_info = None;
+    }
+    let hash = set.encode_to_vec().iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    Ok(format!("{:016x}", hash))
+}
+
+/// Compare with the checked-in fingerprint. A missing or different one
+/// fails the build with the command that updates it; the build never
+/// writes the file itself.
+fn check_fingerprint(fingerprint: &str, proto_dir: &str) -> Result<(), String> {
+    let update = format!(
+        "If the proto change is intended, run `echo {} > {}` in {} and commit it; \
+         otherwise the protos resolved here are stale.",
+        fingerprint,
+        FINGERPRINT_FILE,
+        env!("CARGO_MANIFEST_DIR"),
+    );
+    match std::fs::read_to_string(FINGERPRINT_FILE).map(|s| s.trim().to_string()) {
+        Ok(expected) if expected == fingerprint => Ok(()),
+        Ok(expected) => Err(format!(
+            "Proto contract drift: protos in {} fingerprint as {}, but {} records {}. {}",
+            proto_dir, fingerprint, FINGERPRINT_FILE, expected, update
+        )),
+        Err(e) => Err(format!(
+            "Missing {} ({}): protos in {} fingerprint as {}. {}",
+            FINGERPRINT_FILE, e, proto_dir, fingerprint, update
+        )),
+    }
+}
@@ -0,0 +1,14 @@
+use std::path::Path;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Same proto locations as the server crate's build.rs, one level deeper:
+    // - Docker build: ../proto/ (copied into build context)
+    // - Local development: ../../../../proto/ (shared proto at repo root)
+    let proto_dir = if Path::new("../proto/memory.proto").exists() { "../proto" } else { "../../../../proto" };
+    let protos = [format!("{}/types.proto", proto_dir), format!("{}/memory.proto", proto_dir)];
+
+    // Messages and client stubs only; the server crate generates its own
+    // service traits against these messages
+    tonic_build::configure().build_server(false).compile_protos(&protos, &[proto_dir])?;
+    Ok(())
+}
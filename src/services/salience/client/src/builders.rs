@@ -0,0 +1,397 @@
+//! Builder helpers for protobuf messages.
+//!
+//! `build()` takes whatever it was given; `try_build()` first checks for
+//! messages storage would reject or mis-store (a heuristic with nothing to
+//! embed, a confidence outside [0, 1], an embedding of the wrong length:
+//! `EMBEDDING_DIMENSIONS` unless `dimensions` says otherwise). The `*_from`
+//! constructors fill in the embedding from an `EmbeddingSource`, such as
+//! gladys-memory's StorageClient.
+
+use std::future::Future;
+
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::embedding::{bytes_to_embedding, embedding_to_bytes};
+use crate::proto::{ConditionExemplar, EpisodicEvent, Heuristic, SalienceResult};
+
+/// Embedding length of the model storage embeds with (all-MiniLM-L6-v2).
+pub const EMBEDDING_DIMENSIONS: usize = 384;
+
+/// Why `try_build` refused a message.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum BuildError {
+    #[error("{0} is empty")]
+    Empty(&'static str),
+
+    #[error("{field} = {value} outside [0, 1]")]
+    OutOfRange { field: String, value: f32 },
+
+    #[error("{field} has {actual} dimensions, expected {expected}")]
+    Dimensions { field: &'static str, expected: usize, actual: usize },
+}
+
+/// Embeds text for the `*_from` constructors.
+pub trait EmbeddingSource {
+    type Error;
+
+    /// The embedding of `text`, and the model that produced it (empty = unknown).
+    fn embed(&mut self, text: &str) -> impl Future<Output = Result<(Vec<f32>, String), Self::Error>> + Send;
+}
+
+fn check_unit(field: impl Into<String>, value: f32) -> Result<(), BuildError> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(BuildError::OutOfRange { field: field.into(), value })
+    }
+}
+
+fn check_dimensions(field: &'static str, bytes: &[u8], expected: usize) -> Result<(), BuildError> {
+    let actual = bytes_to_embedding(bytes).len();
+    if bytes.is_empty() || actual == expected {
+        Ok(())
+    } else {
+        Err(BuildError::Dimensions { field, expected, actual })
+    }
+}
+
+/// Builder for creating EpisodicEvent messages.
+pub struct EventBuilder {
+    event: EpisodicEvent,
+    dimensions: usize,
+}
+
+impl EventBuilder {
+    pub fn new(id: Uuid, source: &str, raw_text: &str) -> Self {
+        Self {
+            event: EpisodicEvent {
+                id: id.to_string(),
+                timestamp_ms: chrono_now_ms(),
+                source: source.to_string(),
+                raw_text: raw_text.to_string(),
+                embedding: Vec::new(),
+                salience: None,
+                structured_json: "{}".to_string(),
+                entity_ids: Vec::new(),
+                // Prediction instrumentation (Â§27) - defaults to 0.0/empty
+                predicted_success: 0.0,
+                prediction_confidence: 0.0,
+                response_id: String::new(),
+                response_text: String::new(),
+                matched_heuristic_id: String::new(),
+                llm_prompt_text: String::new(),
+                decision_path: String::new(),
+                episode_id: String::new(),
+                // Phase 2 sensor contract extensions
+                intent: String::new(),
+                evaluation_data_json: String::new(),
+            },
+            dimensions: EMBEDDING_DIMENSIONS,
+        }
+    }
+
+    /// An event with `raw_text` embedded by `embedder`.
+    pub async fn with_embedding_from<S: EmbeddingSource>(
+        embedder: &mut S,
+        id: Uuid,
+        source: &str,
+        raw_text: &str,
+    ) -> Result<Self, S::Error> {
+        let (embedding, _) = embedder.embed(raw_text).await?;
+        Ok(Self::new(id, source, raw_text).embedding(&embedding))
+    }
+
+    /// Embedding length `try_build` requires (default: `EMBEDDING_DIMENSIONS`).
+    pub fn dimensions(mut self, dimensions: usize) -> Self {
+        self.dimensions = dimensions;
+        self
+    }
+
+    pub fn timestamp_ms(mut self, ts: i64) -> Self {
+        self.event.timestamp_ms = ts;
+        self
+    }
+
+    pub fn embedding(mut self, embedding: &[f32]) -> Self {
+        self.event.embedding = embedding_to_bytes(embedding);
+        self
+    }
+
+    pub fn salience(mut self, salience: SalienceResult) -> Self {
+        self.event.salience = Some(salience);
+        self
+    }
+
+    pub fn structured_json(mut self, json: &str) -> Self {
+        self.event.structured_json = json.to_string();
+        self
+    }
+
+    pub fn entity_ids(mut self, ids: Vec<Uuid>) -> Self {
+        self.event.entity_ids = ids.into_iter().map(|id| id.to_string()).collect();
+        self
+    }
+
+    pub fn build(self) -> EpisodicEvent {
+        self.event
+    }
+
+    /// Build, refusing an event without a source or text, with salience
+    /// outside [0, 1], or with an embedding of the wrong length.
+    pub fn try_build(self) -> Result<EpisodicEvent, BuildError> {
+        let event = &self.event;
+        if event.source.is_empty() {
+            return Err(BuildError::Empty("source"));
+        }
+        if event.raw_text.is_empty() {
+            return Err(BuildError::Empty("raw_text"));
+        }
+        check_dimensions("embedding", &event.embedding, self.dimensions)?;
+        if let Some(salience) = &event.salience {
+            check_unit("salience.threat", salience.threat)?;
+            for (dimension, value) in &salience.vector {
+                check_unit(format!("salience.{}", dimension), *value)?;
+            }
+        }
+        Ok(self.event)
+    }
+}
+
+/// Builder for creating Heuristic messages (CBR schema).
+pub struct HeuristicBuilder {
+    heuristic: Heuristic,
+    dimensions: usize,
+}
+
+impl HeuristicBuilder {
+    pub fn new(id: Uuid, name: &str) -> Self {
+        Self {
+            heuristic: Heuristic {
+                id: id.to_string(),
+                name: name.to_string(),
+                condition_text: String::new(),
+                condition_embedding: Vec::new(),
+                similarity_threshold: 0.7,
+                effects_json: "{}".to_string(),
+                confidence: 0.5,
+                origin: "user".to_string(),
+                origin_id: String::new(),
+                next_heuristic_ids: Vec::new(),
+                is_terminal: true,
+                last_fired_ms: 0,
+                fire_count: 0,
+                success_count: 0,
+                created_at_ms: chrono_now_ms(),
+                updated_at_ms: chrono_now_ms(),
+                source: String::new(),
+                embedding_model: String::new(),
+                exemplars: Vec::new(),
+                negative_exemplars: Vec::new(),
+            },
+            dimensions: EMBEDDING_DIMENSIONS,
+        }
+    }
+
+    /// A heuristic with `condition_text` embedded by `embedder` (and the
+    /// embedding's model recorded).
+    pub async fn with_condition_embedding_from<S: EmbeddingSource>(
+        embedder: &mut S,
+        id: Uuid,
+        name: &str,
+        condition_text: &str,
+    ) -> Result<Self, S::Error> {
+        let (embedding, model) = embedder.embed(condition_text).await?;
+        Ok(Self::new(id, name)
+            .condition_text(condition_text)
+            .condition_embedding(&embedding)
+            .embedding_model(&model))
+    }
+
+    /// Embedding length `try_build` requires (default: `EMBEDDING_DIMENSIONS`).
+    pub fn dimensions(mut self, dimensions: usize) -> Self {
+        self.dimensions = dimensions;
+        self
+    }
+
+    pub fn source(mut self, source: &str) -> Self {
+        self.heuristic.source = source.to_string();
+        self
+    }
+
+    pub fn condition_text(mut self, text: &str) -> Self {
+        self.heuristic.condition_text = text.to_string();
+        self
+    }
+
+    /// Without one, storage embeds condition_text when the heuristic is stored.
+    pub fn condition_embedding(mut self, embedding: &[f32]) -> Self {
+        self.heuristic.condition_embedding = embedding_to_bytes(embedding);
+        self
+    }
+
+    pub fn effects_json(mut self, json: &str) -> Self {
+        self.heuristic.effects_json = json.to_string();
+        self
+    }
+
+    pub fn confidence(mut self, confidence: f32) -> Self {
+        self.heuristic.confidence = confidence;
+        self
+    }
+
+    pub fn origin(mut self, origin: &str) -> Self {
+        self.heuristic.origin = origin.to_string();
+        self
+    }
+
+    pub fn embedding_model(mut self, model: &str) -> Self {
+        self.heuristic.embedding_model = model.to_string();
+        self
+    }
+
+    /// Add an exemplar phrasing of the condition (weight 0 = 1.0); storage embeds it.
+    pub fn exemplar(mut self, text: &str, weight: f32) -> Self {
+        self.heuristic.exemplars.push(ConditionExemplar { text: text.to_string(), embedding: Vec::new(), weight });
+        self
+    }
+
+    /// Add a phrasing the heuristic must not match (weight 0 = 1.0); storage embeds it.
+    pub fn negative_exemplar(mut self, text: &str, weight: f32) -> Self {
+        self.heuristic.negative_exemplars.push(ConditionExemplar { text: text.to_string(), embedding: Vec::new(), weight });
+        self
+    }
+
+    pub fn build(self) -> Heuristic {
+        self.heuristic
+    }
+
+    /// Build, refusing a heuristic without a name, with nothing to embed
+    /// (no condition embedding and no condition_text), with a confidence,
+    /// threshold or exemplar weight outside [0, 1], or with a condition
+    /// embedding of the wrong length.
+    pub fn try_build(self) -> Result<Heuristic, BuildError> {
+        let heuristic = &self.heuristic;
+        if heuristic.name.trim().is_empty() {
+            return Err(BuildError::Empty("name"));
+        }
+        if heuristic.condition_text.trim().is_empty() && heuristic.condition_embedding.is_empty() {
+            return Err(BuildError::Empty("condition_text"));
+        }
+        check_dimensions("condition_embedding", &heuristic.condition_embedding, self.dimensions)?;
+        check_unit("confidence", heuristic.confidence)?;
+        check_unit("similarity_threshold", heuristic.similarity_threshold)?;
+        for exemplar in heuristic.exemplars.iter().chain(&heuristic.negative_exemplars) {
+            if exemplar.text.trim().is_empty() {
+                return Err(BuildError::Empty("exemplar text"));
+            }
+            check_unit("exemplar weight", exemplar.weight)?;
+        }
+        Ok(self.heuristic)
+    }
+}
+
+/// Get current time as milliseconds since Unix epoch.
+fn chrono_now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Embeds every text as the same fixed vector.
+    struct FixedEmbedder(Vec<f32>);
+
+    impl EmbeddingSource for FixedEmbedder {
+        type Error = String;
+
+        async fn embed(&mut self, text: &str) -> Result<(Vec<f32>, String), String> {
+            if text.is_empty() {
+                return Err("nothing to embed".to_string());
+            }
+            Ok((self.0.clone(), "test-model".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_event_builder() {
+        let id = Uuid::new_v4();
+        let event = EventBuilder::new(id, "test_sensor", "Something happened")
+            .embedding(&[0.1, 0.2, 0.3])
+            .structured_json(r#"{"key": "value"}"#)
+            .build();
+
+        assert_eq!(event.id, id.to_string());
+        assert_eq!(event.source, "test_sensor");
+        assert_eq!(event.raw_text, "Something happened");
+        assert!(!event.embedding.is_empty());
+    }
+
+    #[test]
+    fn test_heuristic_builder() {
+        let id = Uuid::new_v4();
+        let heuristic = HeuristicBuilder::new(id, "greet_user")
+            .condition_text("user entered the room")
+            .effects_json(r#"{"salience": {"social": 0.7}}"#)
+            .confidence(0.9)
+            .origin("test")
+            .exemplar("someone walked in", 0.8)
+            .build();
+
+        assert_eq!(heuristic.id, id.to_string());
+        assert_eq!(heuristic.name, "greet_user");
+        assert_eq!(heuristic.confidence, 0.9);
+        assert_eq!(heuristic.condition_text, "user entered the room");
+        assert_eq!(heuristic.exemplars.len(), 1);
+        assert_eq!(heuristic.exemplars[0].weight, 0.8);
+    }
+
+    #[test]
+    fn test_try_build_validates() {
+        let id = Uuid::new_v4();
+        assert!(EventBuilder::new(id, "sensor", "door opened").dimensions(3).embedding(&[0.1; 3]).try_build().is_ok());
+        assert_eq!(EventBuilder::new(id, "", "door opened").try_build().unwrap_err(), BuildError::Empty("source"));
+        assert!(EventBuilder::new(id, "sensor", "door opened").embedding(&[0.1; 384]).try_build().is_ok());
+        // The model's length is expected unless told otherwise
+        assert_eq!(
+            EventBuilder::new(id, "sensor", "door opened").embedding(&[0.1; 3]).try_build().unwrap_err(),
+            BuildError::Dimensions { field: "embedding", expected: EMBEDDING_DIMENSIONS, actual: 3 }
+        );
+
+        let heuristic = || HeuristicBuilder::new(id, "greet_user").condition_text("user entered the room");
+        assert!(heuristic().try_build().is_ok());
+        assert_eq!(HeuristicBuilder::new(id, "greet_user").try_build().unwrap_err(), BuildError::Empty("condition_text"));
+        assert!(HeuristicBuilder::new(id, "greet_user").condition_embedding(&[0.1; 384]).try_build().is_ok());
+        assert_eq!(
+            HeuristicBuilder::new(id, "greet_user").condition_embedding(&[0.1; 3]).try_build().unwrap_err(),
+            BuildError::Dimensions { field: "condition_embedding", expected: EMBEDDING_DIMENSIONS, actual: 3 }
+        );
+        assert_eq!(
+            heuristic().confidence(1.5).try_build().unwrap_err(),
+            BuildError::OutOfRange { field: "confidence".to_string(), value: 1.5 }
+        );
+        assert_eq!(heuristic().negative_exemplar(" ", 0.5).try_build().unwrap_err(), BuildError::Empty("exemplar text"));
+        // The lenient build() still takes anything
+        assert_eq!(heuristic().confidence(1.5).build().confidence, 1.5);
+    }
+
+    #[tokio::test]
+    async fn test_constructors_embed_from_source() {
+        let mut embedder = FixedEmbedder(vec![0.5; 4]);
+        let id = Uuid::new_v4();
+        let event = EventBuilder::with_embedding_from(&mut embedder, id, "sensor", "door opened").await.unwrap();
+        assert_eq!(bytes_to_embedding(&event.dimensions(4).try_build().unwrap().embedding), vec![0.5; 4]);
+
+        let heuristic = HeuristicBuilder::with_condition_embedding_from(&mut embedder, id, "greet", "user entered")
+            .await
+            .unwrap()
+            .build();
+        assert_eq!(heuristic.condition_embedding.len(), 16);
+        assert_eq!(heuristic.embedding_model, "test-model");
+        assert!(EventBuilder::with_embedding_from(&mut embedder, id, "sensor", "").await.is_err());
+    }
+}
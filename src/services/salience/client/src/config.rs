@@ -0,0 +1,78 @@
+//! Storage client configuration.
+
+use std::time::Duration;
+
+use tonic::Code;
+
+/// Retry policy for storage RPCs.
+///
+/// Only `Unavailable` is retried: the request most likely never reached the
+/// storage service, so retrying is safe even for writes.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt (0 = no retries)
+    pub max_retries: u32,
+    /// Backoff before the first retry; doubles on each subsequent retry
+    pub initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            initial_backoff: Duration::from_millis(50),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether to retry after `attempt` (0-based) failed with `code`.
+    pub fn should_retry(&self, attempt: u32, code: Code) -> bool {
+        attempt < self.max_retries && code == Code::Unavailable
+    }
+
+    /// Backoff before retry `attempt + 1`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff.saturating_mul(1 << attempt.min(16))
+    }
+}
+
+/// Configuration for the storage client.
+#[derive(Clone, Debug)]
+pub struct ClientConfig {
+    /// Address of the Python storage service (e.g., "http://localhost:50051")
+    pub address: String,
+    /// Connection timeout
+    pub connect_timeout: Duration,
+    /// Request timeout (also sent to the server as the per-call gRPC deadline)
+    pub request_timeout: Duration,
+    /// Retry policy applied to every RPC
+    pub retry: RetryPolicy,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            address: "http://localhost:50051".to_string(),
+            connect_timeout: Duration::from_secs(5),
+            request_timeout: Duration::from_secs(30),
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_policy_only_retries_unavailable() {
+        let policy = RetryPolicy { max_retries: 2, initial_backoff: Duration::from_millis(10) };
+        assert!(policy.should_retry(0, Code::Unavailable));
+        assert!(policy.should_retry(1, Code::Unavailable));
+        assert!(!policy.should_retry(2, Code::Unavailable));
+        assert!(!policy.should_retry(0, Code::InvalidArgument));
+        assert_eq!(policy.backoff(0), Duration::from_millis(10));
+        assert_eq!(policy.backoff(2), Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,40 @@
+//! Embedding conversion utilities.
+//!
+//! Embeddings travel as little-endian f32 bytes (`embedding`,
+//! `condition_embedding` and friends in memory.proto).
+
+/// Convert f32 slice to bytes (little-endian).
+pub fn embedding_to_bytes(embedding: &[f32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(embedding.len() * 4);
+    for &value in embedding {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
+
+/// Convert bytes to f32 vector (little-endian).
+pub fn bytes_to_embedding(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| {
+            let arr: [u8; 4] = chunk.try_into().unwrap();
+            f32::from_le_bytes(arr)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedding_roundtrip() {
+        let original: Vec<f32> = (0..384).map(|i| i as f32 * 0.001).collect();
+        let bytes = embedding_to_bytes(&original);
+        let recovered = bytes_to_embedding(&bytes);
+        assert_eq!(original.len(), recovered.len());
+        for (a, b) in original.iter().zip(recovered.iter()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! GLADyS memory fast path client.
+//!
+//! What another Rust component (a sensor adapter, say) needs to call
+//! SalienceGateway or the Python MemoryStorage service, without depending on
+//! the fast-path server: the generated messages and client stubs, the
+//! storage client configuration, message builders and the embedding byte
+//! encoding. The server crate (`gladys-memory`) re-exports all of it.
+//!
+//! ```text
+//! let mut gateway = SalienceGatewayClient::connect("http://localhost:50052").await?;
+//! let response = gateway.evaluate_salience(EvaluateSalienceRequest { .. }).await?;
+//! ```
+
+mod builders;
+mod config;
+mod embedding;
+
+pub use builders::{BuildError, EmbeddingSource, EventBuilder, HeuristicBuilder, EMBEDDING_DIMENSIONS};
+pub use config::{ClientConfig, RetryPolicy};
+pub use embedding::{bytes_to_embedding, embedding_to_bytes};
+
+/// Proto-generated types, organized by package.
+///
+/// The module hierarchy matches the proto package hierarchy:
+/// - gladys.types -> proto::gladys::types
+/// - gladys.memory -> proto::gladys::memory
+pub mod proto {
+    /// Container module matching the `gladys.*` proto packages
+    pub mod gladys {
+        /// Shared types from types.proto (package gladys.types)
+        pub mod types {
+            tonic::include_proto!("gladys.types");
+        }
+        /// Memory service from memory.proto (package gladys.memory)
+        pub mod memory {
+            tonic::include_proto!("gladys.memory");
+        }
+    }
+
+    // Re-export commonly used types at proto level for convenience
+    pub use gladys::types::SalienceResult;
+    pub use gladys::memory::*;
+}
@@ -0,0 +1,290 @@
+//! GLADyS salience scoring core.
+//!
+//! The pure parts of heuristic matching, shared by the fast-path service and
+//! anything that needs the exact same semantics without it: embedding
+//! similarity, ranking cached heuristics against an event, and applying a
+//! matched heuristic's salience boost.
+//!
+//! No I/O, no async, no dependencies. With the default `std` feature off the
+//! crate is `no_std` (it needs `alloc`), so it compiles to
+//! wasm32-unknown-unknown, letting the dashboard preview "what would match"
+//! against an exported cache snapshot client-side:
+//!
+//! ```text
+//! cargo build -p gladys-salience-core --no-default-features --target wasm32-unknown-unknown
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+/// Metric used to compare embeddings (novelty, event similarity, heuristic matching).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimilarityMetric {
+    /// Cosine similarity (normalized dot product), range [-1, 1]
+    #[default]
+    Cosine,
+    /// Raw dot product; for models trained for dot-product ranking
+    Dot,
+    /// Euclidean distance mapped to (0, 1] via 1 / (1 + distance)
+    Euclidean,
+}
+
+impl FromStr for SimilarityMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "cosine" => Ok(Self::Cosine),
+            "dot" => Ok(Self::Dot),
+            "euclidean" => Ok(Self::Euclidean),
+            other => Err(format!("Unknown similarity metric: {}", other)),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn sqrt(x: f32) -> f32 {
+    x.sqrt()
+}
+
+#[cfg(not(feature = "std"))]
+fn sqrt(x: f32) -> f32 {
+    soft_sqrt(x)
+}
+
+/// Square root without std (Newton's method from a bit-level estimate).
+#[cfg_attr(feature = "std", allow(dead_code))]
+fn soft_sqrt(x: f32) -> f32 {
+    if x.is_nan() || x < 0.0 {
+        return f32::NAN;
+    }
+    if x == 0.0 || x.is_infinite() {
+        return x;
+    }
+    let mut y = f32::from_bits((x.to_bits() >> 1) + 0x1FBD_1DF5);
+    for _ in 0..4 {
+        y = 0.5 * (y + x / y);
+    }
+    y
+}
+
+/// Similarity of two embeddings under `metric` (0 if lengths differ or empty).
+pub fn similarity(metric: SimilarityMetric, a: &[f32], b: &[f32]) -> f32 {
+    match metric {
+        SimilarityMetric::Cosine => cosine_similarity(a, b),
+        SimilarityMetric::Dot => dot_product(a, b),
+        SimilarityMetric::Euclidean => euclidean_similarity(a, b),
+    }
+}
+
+/// Compute the raw dot product between two vectors
+pub fn dot_product(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+/// Euclidean distance mapped to a similarity in (0, 1] (identical = 1.0)
+pub fn euclidean_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let distance = sqrt(a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum::<f32>());
+    1.0 / (1.0 + distance)
+}
+
+/// Compute cosine similarity between two vectors
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    let norm_a = sqrt(a.iter().map(|x| x * x).sum::<f32>());
+    let norm_b = sqrt(b.iter().map(|x| x * x).sum::<f32>());
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+
+    dot / (norm_a * norm_b)
+}
+
+/// Candidates at least `min_similarity` to `query`, most similar first
+/// (`limit` = 0 keeps all). Candidates with empty embeddings never match.
+pub fn rank_matches<'a, K>(
+    query: &[f32],
+    candidates: impl IntoIterator<Item = (K, &'a [f32])>,
+    metric: SimilarityMetric,
+    min_similarity: f32,
+    limit: usize,
+) -> Vec<(K, f32)> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches: Vec<(K, f32)> = candidates
+        .into_iter()
+        .filter(|(_, embedding)| !embedding.is_empty())
+        .filter_map(|(key, embedding)| {
+            let sim = similarity(metric, query, embedding);
+            (sim >= min_similarity).then_some((key, sim))
+        })
+        .collect();
+
+    matches.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(core::cmp::Ordering::Equal));
+
+    if limit > 0 && matches.len() > limit {
+        matches.truncate(limit);
+    }
+
+    matches
+}
+
+/// Salience vector dimensions a heuristic's boost can raise (besides threat).
+pub const BOOST_DIMENSIONS: [&str; 6] = ["novelty", "goal_relevance", "opportunity", "actionability", "social", "emotional"];
+
+/// A salience vector (dimension -> value).
+pub trait Dimensions {
+    fn dimension(&self, name: &str) -> Option<f32>;
+    fn set_dimension(&mut self, name: &str, value: f32);
+    /// Highest value (0 if empty).
+    fn max_dimension(&self) -> f32;
+}
+
+impl Dimensions for BTreeMap<String, f32> {
+    fn dimension(&self, name: &str) -> Option<f32> {
+        self.get(name).copied()
+    }
+
+    fn set_dimension(&mut self, name: &str, value: f32) {
+        self.insert(name.to_string(), value);
+    }
+
+    fn max_dimension(&self) -> f32 {
+        self.values().copied().reduce(f32::max).unwrap_or(0.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: std::hash::BuildHasher> Dimensions for std::collections::HashMap<String, f32, S> {
+    fn dimension(&self, name: &str) -> Option<f32> {
+        self.get(name).copied()
+    }
+
+    fn set_dimension(&mut self, name: &str, value: f32) {
+        self.insert(name.to_string(), value);
+    }
+
+    fn max_dimension(&self) -> f32 {
+        self.values().copied().reduce(f32::max).unwrap_or(0.0)
+    }
+}
+
+/// Raise `threat` and each boosted dimension to the boost's value where
+/// higher; `boost` looks up a value by name ("threat" or a dimension).
+/// Returns the resulting scalar salience (the highest dimension).
+pub fn apply_boost<D: Dimensions>(threat: &mut f32, vector: &mut D, boost: impl Fn(&str) -> Option<f32>) -> f32 {
+    if let Some(value) = boost("threat") {
+        *threat = threat.max(value);
+    }
+    for name in BOOST_DIMENSIONS {
+        if let Some(value) = boost(name) {
+            let existing = vector.dimension(name).unwrap_or(0.0);
+            vector.set_dimension(name, value.max(existing));
+        }
+    }
+    vector.max_dimension()
+}
+
+/// One heuristic from an exported cache snapshot.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SnapshotHeuristic {
+    pub id: String,
+    pub embedding: Vec<f32>,
+    pub confidence: f32,
+    /// Salience boost ("threat" or a dimension -> value)
+    pub boost: Vec<(String, f32)>,
+}
+
+/// What an event would match in a snapshot, and the salience it would get.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preview {
+    pub heuristic_id: String,
+    pub similarity: f32,
+    pub threat: f32,
+    pub salience: f32,
+    pub vector: BTreeMap<String, f32>,
+}
+
+/// Best match for `query` among `heuristics` with its boost applied to a
+/// zero baseline, as the fast path scores a cache hit (None = no match).
+pub fn preview(
+    heuristics: &[SnapshotHeuristic],
+    query: &[f32],
+    metric: SimilarityMetric,
+    min_similarity: f32,
+    min_confidence: f32,
+) -> Option<Preview> {
+    let candidates = heuristics
+        .iter()
+        .filter(|h| h.confidence >= min_confidence)
+        .map(|h| (h, h.embedding.as_slice()));
+    let (best, similarity) = rank_matches(query, candidates, metric, min_similarity, 1).into_iter().next()?;
+
+    let (mut threat, mut vector) = (0.0, BTreeMap::new());
+    let boost = |name: &str| best.boost.iter().find(|(n, _)| n == name).map(|(_, v)| *v);
+    let salience = apply_boost(&mut threat, &mut vector, boost);
+    Some(Preview { heuristic_id: best.id.clone(), similarity, threat, salience, vector })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_soft_sqrt_matches_std() {
+        for x in [0.0f32, 1e-8, 0.25, 2.0, 384.0, 1e12] {
+            let (soft, exact) = (soft_sqrt(x), x.sqrt());
+            assert!((soft - exact).abs() <= exact * 1e-6, "sqrt({}) = {} vs {}", x, soft, exact);
+        }
+        assert!(soft_sqrt(-1.0).is_nan());
+    }
+
+    #[test]
+    fn test_preview_ranks_and_boosts() {
+        let heuristic = |id: &str, embedding: Vec<f32>, confidence: f32, boost: Vec<(&str, f32)>| SnapshotHeuristic {
+            id: id.to_string(),
+            embedding,
+            confidence,
+            boost: boost.into_iter().map(|(n, v)| (n.to_string(), v)).collect(),
+        };
+        let heuristics = vec![
+            heuristic("near", vec![1.0, 0.1], 0.9, vec![("threat", 0.8), ("social", 0.4)]),
+            heuristic("exact-but-unconfident", vec![1.0, 0.0], 0.2, vec![("threat", 1.0)]),
+            heuristic("far", vec![0.0, 1.0], 0.9, vec![]),
+            heuristic("no-embedding", vec![], 0.9, vec![]),
+        ];
+
+        let result = preview(&heuristics, &[1.0, 0.0], SimilarityMetric::Cosine, 0.7, 0.5).unwrap();
+        assert_eq!(result.heuristic_id, "near");
+        assert!((result.threat - 0.8).abs() < 1e-6);
+        assert!((result.salience - 0.4).abs() < 1e-6);
+        assert_eq!(result.vector.len(), 1);
+        assert!(preview(&heuristics, &[-1.0, 0.0], SimilarityMetric::Cosine, 0.7, 0.5).is_none());
+
+        let ranked = rank_matches(&[1.0, 0.0], heuristics.iter().map(|h| (h.id.as_str(), h.embedding.as_slice())), SimilarityMetric::Cosine, 0.0, 0);
+        assert_eq!(ranked.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec!["exact-but-unconfident", "near", "far"]);
+    }
+}
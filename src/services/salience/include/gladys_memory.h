@@ -0,0 +1,92 @@
+/*
+ * C ABI for the GLADyS salience cache and scoring core.
+ *
+ * Kept in sync with src/ffi.rs by hand (a unit test checks every exported
+ * function is declared here). Link against libgladys_memory.
+ *
+ * Functions returning int32_t return -1 on error; gladys_last_error()
+ * describes it. Caches may be shared between threads.
+ */
+#ifndef GLADYS_MEMORY_H
+#define GLADYS_MEMORY_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define GLADYS_ABI_VERSION 1
+
+typedef struct GladysCache GladysCache;
+
+typedef struct GladysScore {
+    int32_t matched;          /* 1 if a heuristic matched */
+    char heuristic_id[37];    /* NUL-terminated UUID ("" if none) */
+    float similarity;
+    float confidence;
+    float threat;
+    float salience;
+    float novelty;
+    float goal_relevance;
+    float opportunity;
+    float actionability;
+    float social;
+    float emotional;
+} GladysScore;
+
+/* ABI version of the loaded library; compare with GLADYS_ABI_VERSION. */
+uint32_t gladys_abi_version(void);
+
+/* Last error on this thread ("" if none); valid until the next call. */
+const char *gladys_last_error(void);
+
+/* Create a cache holding up to max_heuristics (0 = default). NULL on error. */
+GladysCache *gladys_cache_new(uint32_t max_heuristics);
+
+/* Free a cache. NULL is ignored. */
+void gladys_cache_free(GladysCache *cache);
+
+/*
+ * Insert or replace a heuristic. id is a UUID string; effects_json may be
+ * NULL, or e.g. {"salience": {"threat": 0.9}}. embedding may be NULL to
+ * embed condition_text locally. Returns 0 or -1.
+ */
+int32_t gladys_cache_insert(GladysCache *cache,
+                            const char *id,
+                            const char *condition_text,
+                            const char *effects_json,
+                            float confidence,
+                            const float *embedding,
+                            size_t embedding_len);
+
+/* Remove a heuristic. Returns 1 if removed, 0 if absent, -1 on error. */
+int32_t gladys_cache_remove(GladysCache *cache, const char *id);
+
+/* Heuristics in the cache (0 for NULL). */
+size_t gladys_cache_len(const GladysCache *cache);
+
+/* Score an event embedding. Returns 1 if matched, 0 if not, -1 on error. */
+int32_t gladys_score_embedding(const GladysCache *cache,
+                               const float *embedding,
+                               size_t embedding_len,
+                               float min_similarity,
+                               float min_confidence,
+                               GladysScore *out);
+
+/* Score event text (embedded locally). Returns 1 if matched, 0 if not, -1 on error. */
+int32_t gladys_score_text(const GladysCache *cache,
+                          const char *text,
+                          float min_similarity,
+                          float min_confidence,
+                          GladysScore *out);
+
+/* Cosine similarity of two len-float vectors (0 if either is NULL). */
+float gladys_cosine_similarity(const float *a, const float *b, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GLADYS_MEMORY_H */
@@ -0,0 +1,220 @@
+//! System-1 direct action execution.
+//!
+//! Some responses are reflexes ("pause game audio on threat") where a round
+//! trip through the Executive is too slow. With actions enabled, a heuristic's
+//! effects can name a registered local action, which the fast path runs right
+//! after a live, high-confidence match:
+//!
+//! ```json
+//! {"message": "Threat nearby", "execute": {"action": "pause_audio", "args": {"fade_ms": 200}}}
+//! ```
+//!
+//! - Only whitelisted names run; registering anything else is refused
+//! - Only the best match, only at or above `min_confidence`, never on replays
+//!   or dry runs
+//! - Actions run inline on the request path and should return quickly
+//!
+//! The outcome is returned in the response (`executed_action`) and counted
+//! per action in `GetHealthDetails`, so the Executive can feed it back.
+//!
+//! Configuration via environment variables (see `ActionConfig`):
+//!   SYSTEM1_ACTIONS: Comma-separated action names allowed to run (default: none = disabled)
+//!   SYSTEM1_ACTION_MIN_CONFIDENCE: Heuristic confidence required to run (default: 0.9)
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use tracing::{info, warn};
+
+use crate::config::ActionConfig;
+use crate::domain::Effects;
+use crate::ScoredMatch;
+
+/// Effects key naming the action to run.
+pub const EXECUTE_KEY: &str = "execute";
+
+/// A local action: takes the effects' `args`, returns a short result.
+pub type ActionFn = Arc<dyn Fn(&serde_json::Value) -> Result<String, String> + Send + Sync>;
+
+/// The action a heuristic's effects ask for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionRequest {
+    pub action: String,
+    /// `null` when the effects give none
+    pub args: serde_json::Value,
+}
+
+impl ActionRequest {
+    /// The `execute` entry of `effects` (None if absent or not an object with an action name).
+    pub fn from_effects(effects: &Effects) -> Option<Self> {
+        let execute = effects.extra.get(EXECUTE_KEY)?.as_object()?;
+        let action = execute.get("action")?.as_str()?.trim();
+        if action.is_empty() {
+            return None;
+        }
+        let args = execute.get("args").cloned().unwrap_or(serde_json::Value::Null);
+        Some(Self { action: action.to_string(), args })
+    }
+}
+
+/// One action run and its outcome.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ActionExecution {
+    pub action: String,
+    pub heuristic_id: String,
+    pub success: bool,
+    /// The action's result, or why it failed
+    pub output: String,
+    pub duration_us: u64,
+}
+
+#[derive(Debug, Default)]
+struct ActionCounts {
+    succeeded: u64,
+    failed: u64,
+}
+
+/// Whitelisted local actions and their outcome counts.
+pub struct ActionRegistry {
+    config: ActionConfig,
+    actions: HashMap<String, ActionFn>,
+    counts: Mutex<BTreeMap<String, ActionCounts>>,
+    /// Requested actions that aren't allowed or registered
+    skipped: AtomicU64,
+}
+
+impl ActionRegistry {
+    pub fn new(config: ActionConfig) -> Self {
+        Self { config, actions: HashMap::new(), counts: Mutex::new(BTreeMap::new()), skipped: AtomicU64::new(0) }
+    }
+
+    /// Built-in actions, available once whitelisted:
+    /// - `log`: log the args at info level
+    pub fn with_builtins(mut self) -> Self {
+        self.register(
+            "log",
+            Arc::new(|args: &serde_json::Value| {
+                info!(args = %args, "System-1 action: log");
+                Ok("logged".to_string())
+            }),
+        );
+        self
+    }
+
+    /// Register `action` under `name`. Returns false (and registers nothing)
+    /// if `name` isn't whitelisted.
+    pub fn register(&mut self, name: &str, action: ActionFn) -> bool {
+        if !self.config.allowed.iter().any(|a| a == name) {
+            return false;
+        }
+        self.actions.insert(name.to_string(), action);
+        true
+    }
+
+    /// Run the action `best`'s effects name, if it's allowed, registered, and
+    /// the match is confident enough. None if nothing ran.
+    pub fn execute(&self, best: &ScoredMatch, trace_id: &str) -> Option<ActionExecution> {
+        let request = ActionRequest::from_effects(&best.effects)?;
+        if best.confidence < self.config.min_confidence {
+            return None;
+        }
+        let Some(action) = self.actions.get(&request.action) else {
+            warn!(trace_id = %trace_id, action = %request.action, heuristic_id = %best.heuristic_id, "Action not allowed or not registered; skipped");
+            self.skipped.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+
+        let started = Instant::now();
+        let outcome = action(&request.args);
+        let duration_us = started.elapsed().as_micros() as u64;
+        let success = outcome.is_ok();
+        let output = outcome.unwrap_or_else(|e| e);
+        if success {
+            info!(trace_id = %trace_id, action = %request.action, duration_us, "System-1 action executed");
+        } else {
+            warn!(trace_id = %trace_id, action = %request.action, error = %output, "System-1 action failed");
+        }
+
+        let mut counts = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = counts.entry(request.action.clone()).or_default();
+        if success {
+            entry.succeeded += 1;
+        } else {
+            entry.failed += 1;
+        }
+        Some(ActionExecution {
+            action: request.action,
+            heuristic_id: best.heuristic_id.clone(),
+            success,
+            output,
+            duration_us,
+        })
+    }
+
+    /// Summary key-value pairs for `GetHealthDetails`.
+    pub fn health_details(&self) -> HashMap<String, String> {
+        let mut registered: Vec<&str> = self.actions.keys().map(String::as_str).collect();
+        registered.sort_unstable();
+        let mut details = HashMap::from([
+            ("actions.registered".to_string(), registered.join(",")),
+            ("actions.skipped".to_string(), self.skipped.load(Ordering::Relaxed).to_string()),
+        ]);
+        for (action, counts) in self.counts.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            details.insert(format!("actions.{}.succeeded", action), counts.succeeded.to_string());
+            details.insert(format!("actions.{}.failed", action), counts.failed.to_string());
+        }
+        details
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scored(effects: serde_json::Value, confidence: f32) -> ScoredMatch {
+        ScoredMatch {
+            heuristic_id: "h1".to_string(),
+            similarity: 0.95,
+            confidence,
+            origin: String::new(),
+            condition_text: String::new(),
+            suggested_action: String::new(),
+            salience_boost: None,
+            effects: effects.into(),
+            method: Default::default(),
+            negative: None,
+        }
+    }
+
+    #[test]
+    fn test_only_whitelisted_confident_actions_run() {
+        let config = ActionConfig { allowed: vec!["log".to_string(), "pause_audio".to_string()], min_confidence: 0.9 };
+        let mut registry = ActionRegistry::new(config).with_builtins();
+        assert!(!registry.register("shutdown", Arc::new(|_: &serde_json::Value| Ok(String::new()))));
+        assert!(registry.register("pause_audio", Arc::new(|args: &serde_json::Value| match args["fade_ms"].as_u64() {
+            Some(ms) => Ok(format!("paused over {}ms", ms)),
+            None => Err("fade_ms required".to_string()),
+        })));
+
+        let pause = serde_json::json!({"execute": {"action": "pause_audio", "args": {"fade_ms": 200}}});
+        let run = registry.execute(&scored(pause.clone(), 0.95), "t").unwrap();
+        assert_eq!((run.action.as_str(), run.success, run.output.as_str()), ("pause_audio", true, "paused over 200ms"));
+        assert!(registry.execute(&scored(pause, 0.5), "t").is_none(), "below min_confidence");
+
+        let bad_args = serde_json::json!({"execute": {"action": "pause_audio"}});
+        let run = registry.execute(&scored(bad_args, 0.95), "t").unwrap();
+        assert!(!run.success && run.output == "fade_ms required");
+
+        let not_allowed = serde_json::json!({"execute": {"action": "shutdown"}});
+        assert!(registry.execute(&scored(not_allowed, 1.0), "t").is_none());
+        assert!(registry.execute(&scored(serde_json::json!({"message": "hi"}), 1.0), "t").is_none());
+
+        let details = registry.health_details();
+        assert_eq!(details["actions.registered"], "log,pause_audio");
+        assert_eq!(details["actions.pause_audio.succeeded"], "1");
+        assert_eq!(details["actions.pause_audio.failed"], "1");
+        assert_eq!(details["actions.skipped"], "1");
+    }
+}
@@ -0,0 +1,163 @@
+//! Lexicon-based affect scoring.
+//!
+//! Populates the emotional/social dimensions in the fast path without any
+//! model calls: event text is scanned for weighted terms from a wordlist,
+//! compiled once into an Aho-Corasick automaton.
+//!
+//! Wordlist format, one term per line (blank lines and `#` comments skipped):
+//!
+//! ```text
+//! # term, dimension, weight (0.0-1.0)
+//! furious, emotional, 0.8
+//! thank you, social, 0.4
+//! ```
+//!
+//! Terms match case-insensitively on word boundaries; multi-word terms are
+//! fine. Several hits on one dimension combine as a noisy-or
+//! (1 - product of (1 - weight)), so more evidence raises the score without
+//! ever exceeding 1.0.
+//!
+//! Configuration via environment variables:
+//!   SALIENCE_AFFECT_LEXICON: Path to the wordlist (default: none = disabled)
+
+use std::collections::HashMap;
+
+use aho_corasick::{AhoCorasick, MatchKind};
+
+/// One weighted lexicon term.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexiconEntry {
+    pub term: String,
+    /// Salience vector dimension the term contributes to (e.g., "emotional")
+    pub dimension: String,
+    pub weight: f32,
+}
+
+/// Errors loading a lexicon.
+#[derive(Debug, thiserror::Error)]
+pub enum LexiconError {
+    #[error("Failed to read lexicon {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Lexicon line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("Failed to build lexicon automaton: {0}")]
+    Build(#[from] aho_corasick::BuildError),
+}
+
+/// Parse a wordlist (see module docs for the format).
+pub fn parse_lexicon(contents: &str) -> Result<Vec<LexiconEntry>, LexiconError> {
+    let mut entries = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: &str| LexiconError::Parse { line: i + 1, message: message.to_string() };
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [term, dimension, weight] = fields[..] else {
+            return Err(error("expected: term, dimension, weight"));
+        };
+        if term.is_empty() || dimension.is_empty() {
+            return Err(error("term and dimension must be non-empty"));
+        }
+        let weight: f32 = weight.parse().map_err(|_| error("weight must be a number"))?;
+        entries.push(LexiconEntry {
+            term: term.to_string(),
+            dimension: dimension.to_string(),
+            weight: weight.clamp(0.0, 1.0),
+        });
+    }
+    Ok(entries)
+}
+
+/// A compiled lexicon.
+pub struct AffectLexicon {
+    automaton: AhoCorasick,
+    /// Indexed by automaton pattern ID
+    entries: Vec<LexiconEntry>,
+}
+
+impl AffectLexicon {
+    pub fn new(entries: Vec<LexiconEntry>) -> Result<Self, LexiconError> {
+        let automaton = AhoCorasick::builder()
+            .ascii_case_insensitive(true)
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(entries.iter().map(|e| e.term.as_str()))?;
+        Ok(Self { automaton, entries })
+    }
+
+    /// Read and compile a wordlist file.
+    pub fn load(path: &str) -> Result<Self, LexiconError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|source| LexiconError::Io { path: path.to_string(), source })?;
+        Self::new(parse_lexicon(&contents)?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Per-dimension scores for `text` (dimensions without hits are absent).
+    pub fn score(&self, text: &str) -> HashMap<String, f32> {
+        // Product of (1 - weight) per dimension
+        let mut misses: HashMap<&str, f32> = HashMap::new();
+        for m in self.automaton.find_iter(text) {
+            // Whole words only: "mad" shouldn't fire inside "nomad"
+            if !on_word_boundaries(text, m.start(), m.end()) {
+                continue;
+            }
+            let entry = &self.entries[m.pattern().as_usize()];
+            *misses.entry(entry.dimension.as_str()).or_insert(1.0) *= 1.0 - entry.weight;
+        }
+        misses
+            .into_iter()
+            .map(|(dimension, miss)| (dimension.to_string(), 1.0 - miss))
+            .collect()
+    }
+}
+
+/// Whether `text[start..end]` is a whole word (or words), not part of a longer one.
+pub(crate) fn on_word_boundaries(text: &str, start: usize, end: usize) -> bool {
+    let bytes = text.as_bytes();
+    let is_word = |i: usize| bytes.get(i).is_some_and(|b| b.is_ascii_alphanumeric());
+    (start == 0 || !is_word(start - 1)) && !is_word(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEXICON: &str = "# affect terms\nfurious, emotional, 0.8\nmad, emotional, 0.5\nthank you, social, 0.4\n\n";
+
+    #[test]
+    fn test_parse_lexicon() {
+        let entries = parse_lexicon(LEXICON).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[2], LexiconEntry { term: "thank you".to_string(), dimension: "social".to_string(), weight: 0.4 });
+
+        let err = parse_lexicon("ok, emotional, 0.1\nbroken line").unwrap_err();
+        assert!(err.to_string().starts_with("Lexicon line 2"));
+        assert!(parse_lexicon("angry, emotional, very").is_err());
+    }
+
+    #[test]
+    fn test_score_combines_hits_on_word_boundaries() {
+        let lexicon = AffectLexicon::new(parse_lexicon(LEXICON).unwrap()).unwrap();
+
+        let scores = lexicon.score("I'm FURIOUS and mad. Thank you anyway!");
+        // 1 - (1 - 0.8) * (1 - 0.5)
+        assert!((scores["emotional"] - 0.9).abs() < 0.001);
+        assert!((scores["social"] - 0.4).abs() < 0.001);
+
+        assert!(lexicon.score("a nomad walks").is_empty());
+        assert!(lexicon.score("").is_empty());
+    }
+}
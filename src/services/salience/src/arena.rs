@@ -0,0 +1,330 @@
+//! Slab storage for cached embeddings, compacted while idle.
+//!
+//! Every cached heuristic and event holds a 384-dim embedding. As separate
+//! `Vec<f32>`s, they are many small allocations that fragment the heap over
+//! long uptimes as entries churn. `Embedding` is either owned (how entries
+//! arrive) or a range in a shared slab. Compaction copies every embedding the
+//! cache holds into a few large slabs and re-points the entries at them:
+//! - Runs only while idle: no lookups and no new events since the last check
+//! - Only when needed: enough loose embeddings, or slabs mostly freed by eviction
+//! - A slab is freed once nothing points into it (clones handed out keep it alive)
+//!
+//! Each run's process RSS before and after (Linux only) and embedding counts
+//! are reported in `GetHealthDetails`.
+//!
+//! Configuration via environment variables (see `CacheConfig`):
+//!   CACHE_COMPACTION_INTERVAL_MS: Idle check interval (default: 0 = disabled)
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tracing::{debug, info};
+
+use crate::{CacheHandle, MemoryCache};
+
+/// Floats per slab (256 KiB, about 170 384-dim embeddings).
+const SLAB_FLOATS: usize = 64 * 1024;
+
+/// Loose embeddings that justify a compaction.
+const MIN_LOOSE: usize = 32;
+
+/// Share of slab space no longer referenced by the cache that justifies a compaction.
+const MAX_SLAB_WASTE: f32 = 0.25;
+
+/// An embedding vector, owned or packed into a shared slab.
+#[derive(Clone, Default)]
+pub struct Embedding(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    Owned(Vec<f32>),
+    Packed { slab: Arc<[f32]>, start: usize, len: usize },
+}
+
+impl Default for Repr {
+    fn default() -> Self {
+        Repr::Owned(Vec::new())
+    }
+}
+
+impl Embedding {
+    pub fn to_vec(&self) -> Vec<f32> {
+        self.as_slice().to_vec()
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        match &self.0 {
+            Repr::Owned(v) => v,
+            Repr::Packed { slab, start, len } => &slab[*start..*start + *len],
+        }
+    }
+
+    /// Whether this lives in a slab rather than its own allocation.
+    pub fn is_packed(&self) -> bool {
+        matches!(self.0, Repr::Packed { .. })
+    }
+}
+
+impl Deref for Embedding {
+    type Target = [f32];
+
+    fn deref(&self) -> &[f32] {
+        self.as_slice()
+    }
+}
+
+impl From<Vec<f32>> for Embedding {
+    fn from(v: Vec<f32>) -> Self {
+        Self(Repr::Owned(v))
+    }
+}
+
+impl From<&[f32]> for Embedding {
+    fn from(v: &[f32]) -> Self {
+        Self(Repr::Owned(v.to_vec()))
+    }
+}
+
+impl PartialEq for Embedding {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl PartialEq<Vec<f32>> for Embedding {
+    fn eq(&self, other: &Vec<f32>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl fmt::Debug for Embedding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// How the cache's embeddings are laid out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmbeddingLayout {
+    /// Embeddings in their own allocation
+    pub loose: usize,
+    /// Embeddings in a slab
+    pub packed: usize,
+    /// Distinct slabs referenced
+    pub slabs: usize,
+    /// Floats allocated across those slabs
+    pub slab_floats: usize,
+    /// Floats in those slabs still referenced by the cache
+    pub live_slab_floats: usize,
+}
+
+impl EmbeddingLayout {
+    /// Survey embeddings (empty ones aren't allocations and are skipped).
+    pub fn of<'a>(embeddings: impl IntoIterator<Item = &'a Embedding>) -> Self {
+        let mut layout = Self::default();
+        let mut seen = HashSet::new();
+        for embedding in embeddings {
+            match &embedding.0 {
+                Repr::Owned(v) if v.is_empty() => {}
+                Repr::Owned(_) => layout.loose += 1,
+                Repr::Packed { slab, len, .. } => {
+                    layout.packed += 1;
+                    layout.live_slab_floats += len;
+                    if seen.insert(Arc::as_ptr(slab) as *const f32 as usize) {
+                        layout.slabs += 1;
+                        layout.slab_floats += slab.len();
+                    }
+                }
+            }
+        }
+        layout
+    }
+
+    /// Share of slab space the cache no longer uses.
+    pub fn slab_waste(&self) -> f32 {
+        if self.slab_floats == 0 {
+            return 0.0;
+        }
+        1.0 - self.live_slab_floats as f32 / self.slab_floats as f32
+    }
+
+    pub fn needs_compaction(&self) -> bool {
+        self.loose >= MIN_LOOSE || self.slab_waste() > MAX_SLAB_WASTE
+    }
+}
+
+/// Copy `embeddings` into fresh slabs, back to back, and re-point them.
+/// Returns the number of slabs written.
+pub fn pack<'a>(embeddings: impl IntoIterator<Item = &'a mut Embedding>) -> usize {
+    let mut slabs = 0;
+    let mut buffer: Vec<f32> = Vec::new();
+    let mut pending: Vec<(&'a mut Embedding, usize, usize)> = Vec::new();
+    for embedding in embeddings {
+        if embedding.is_empty() {
+            *embedding = Embedding::default();
+            continue;
+        }
+        if !buffer.is_empty() && buffer.len() + embedding.len() > SLAB_FLOATS {
+            flush_slab(&mut buffer, &mut pending);
+            slabs += 1;
+        }
+        let start = buffer.len();
+        buffer.extend_from_slice(embedding);
+        let len = embedding.len();
+        pending.push((embedding, start, len));
+    }
+    if !pending.is_empty() {
+        flush_slab(&mut buffer, &mut pending);
+        slabs += 1;
+    }
+    slabs
+}
+
+fn flush_slab(buffer: &mut Vec<f32>, pending: &mut Vec<(&mut Embedding, usize, usize)>) {
+    let slab: Arc<[f32]> = Arc::from(std::mem::take(buffer));
+    for (embedding, start, len) in pending.drain(..) {
+        *embedding = Embedding(Repr::Packed { slab: slab.clone(), start, len });
+    }
+}
+
+/// Resident set size of this process in KiB (None off Linux).
+pub fn resident_kib() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Outcome of the most recent compactions, for health reporting.
+#[derive(Debug, Clone, Default)]
+pub struct CompactionStatus {
+    pub runs: u64,
+    /// When the last compaction ran (Unix ms, 0 = never)
+    pub last_run_ms: i64,
+    /// Embeddings packed by the last compaction
+    pub last_packed: usize,
+    pub last_slabs: usize,
+    /// Process RSS around the last compaction (KiB; None = unavailable)
+    pub rss_before_kib: Option<u64>,
+    pub rss_after_kib: Option<u64>,
+}
+
+impl CompactionStatus {
+    /// Summary key-value pairs for `GetHealthDetails`.
+    pub fn health_details(&self) -> HashMap<String, String> {
+        let mut details = HashMap::from([
+            ("compaction.runs".to_string(), self.runs.to_string()),
+            ("compaction.last_run_ms".to_string(), self.last_run_ms.to_string()),
+            ("compaction.last_packed".to_string(), self.last_packed.to_string()),
+            ("compaction.last_slabs".to_string(), self.last_slabs.to_string()),
+        ]);
+        if let Some(kib) = self.rss_before_kib {
+            details.insert("compaction.rss_before_kib".to_string(), kib.to_string());
+        }
+        if let Some(kib) = self.rss_after_kib {
+            details.insert("compaction.rss_after_kib".to_string(), kib.to_string());
+        }
+        details
+    }
+}
+
+/// Shared handle to the compaction status.
+pub type CompactionStatusHandle = Arc<Mutex<CompactionStatus>>;
+
+/// Check for idleness every `interval_ms` and compact when it's worth it
+/// (runs forever).
+pub async fn run_compaction_loop(interval_ms: u64, cache: CacheHandle, status: CompactionStatusHandle) {
+    let interval = Duration::from_millis(interval_ms.max(1));
+    info!(interval_ms = interval.as_millis() as u64, "Embedding compaction enabled");
+    let activity = |cache: &MemoryCache| {
+        let stats = cache.stats();
+        (stats.total_hits + stats.total_misses, stats.event_count, stats.heuristic_count)
+    };
+    let mut last_activity = activity(&*cache.read().await);
+
+    loop {
+        tokio::time::sleep(interval).await;
+        let mut cache = cache.write().await;
+        let current = activity(&cache);
+        let idle = current == last_activity;
+        last_activity = current;
+        let layout = cache.embedding_layout();
+        if !idle || !layout.needs_compaction() {
+            debug!(idle, loose = layout.loose, slab_waste = layout.slab_waste(), "Embedding compaction skipped");
+            continue;
+        }
+
+        let rss_before_kib = resident_kib();
+        let slabs = cache.compact_embeddings();
+        drop(cache);
+        let rss_after_kib = resident_kib();
+        let packed = layout.loose + layout.packed;
+        info!(packed, slabs, rss_before_kib = ?rss_before_kib, rss_after_kib = ?rss_after_kib, "Embeddings compacted");
+
+        let mut status = status.lock().unwrap_or_else(|e| e.into_inner());
+        status.runs += 1;
+        status.last_run_ms = crate::current_time_ms();
+        status.last_packed = packed;
+        status.last_slabs = slabs;
+        status.rss_before_kib = rss_before_kib;
+        status.rss_after_kib = rss_after_kib;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_shares_slabs_and_preserves_values() {
+        let mut embeddings: Vec<Embedding> = (0..40).map(|i| Embedding::from(vec![i as f32; 384])).collect();
+        embeddings.push(Embedding::default());
+        let layout = EmbeddingLayout::of(&embeddings);
+        assert_eq!((layout.loose, layout.packed), (40, 0));
+        assert!(layout.needs_compaction());
+
+        // 40 * 384 floats fit in one slab
+        assert_eq!(pack(embeddings.iter_mut()), 1);
+        assert!(embeddings[..40].iter().all(Embedding::is_packed));
+        assert_eq!(embeddings[7], vec![7.0; 384]);
+        let layout = EmbeddingLayout::of(&embeddings);
+        assert_eq!((layout.loose, layout.packed, layout.slabs), (0, 40, 1));
+        assert!(!layout.needs_compaction());
+
+        // Dropping most entries leaves the slab mostly unreferenced
+        embeddings.truncate(10);
+        let layout = EmbeddingLayout::of(&embeddings);
+        assert!(layout.slab_waste() > 0.7 && layout.needs_compaction());
+        pack(embeddings.iter_mut());
+        assert_eq!(EmbeddingLayout::of(&embeddings).slab_waste(), 0.0);
+        assert_eq!(embeddings[9].to_vec(), vec![9.0; 384]);
+    }
+
+    #[test]
+    fn test_compacted_cache_still_matches() {
+        let mut cache = MemoryCache::new(crate::CacheConfig::default());
+        let id = uuid::Uuid::new_v4();
+        cache.add_heuristic(crate::CachedHeuristic {
+            id,
+            name: "creeper".to_string(),
+            condition: crate::Condition::text("creeper"),
+            effects: serde_json::json!({}).into(),
+            confidence: 0.9,
+            origin: "user".to_string(),
+            source: String::new(),
+            condition_embedding: vec![1.0; 384].into(),
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        });
+        assert_eq!(cache.compact_embeddings(), 1);
+        assert_eq!(cache.embedding_layout().packed, 1);
+        let matches = cache.find_matching_heuristics(&[1.0; 384], 0.9, 0.5, 5);
+        assert_eq!(matches.first().map(|m| m.0), Some(id));
+    }
+}
@@ -0,0 +1,140 @@
+//! Audit log for administrative RPCs.
+//!
+//! Every cache mutation (flush, evict, change notification) is recorded with
+//! who asked for it, what it was, and when. Entries go to two places:
+//! - The `gladys_memory::audit` log target, for durable structured logs
+//! - A bounded in-memory ring buffer, queryable via `GetAuditLog`
+//!
+//! Callers identify themselves with the `x-gladys-caller` metadata header;
+//! the peer address is recorded regardless.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use tracing::info;
+
+/// Header name for the caller identity in gRPC metadata.
+pub const CALLER_HEADER: &str = "x-gladys-caller";
+
+/// Who issued an admin RPC.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditActor {
+    /// Self-reported identity from `CALLER_HEADER` (empty if absent)
+    pub caller: String,
+    /// Remote socket address (empty if unknown, e.g. in-process calls)
+    pub peer: String,
+}
+
+impl AuditActor {
+    /// Extract the actor from request metadata and connection info.
+    pub fn from_request<T>(request: &tonic::Request<T>) -> Self {
+        Self {
+            caller: request
+                .metadata()
+                .get(CALLER_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_string(),
+            peer: request.remote_addr().map(|a| a.to_string()).unwrap_or_default(),
+        }
+    }
+}
+
+/// One recorded admin operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub timestamp_ms: i64,
+    pub actor: AuditActor,
+    /// RPC name, e.g. "FlushCache"
+    pub operation: String,
+    /// What was acted on (heuristic ID, empty for whole-cache operations)
+    pub target: String,
+    /// Short result summary, e.g. "flushed=3" or "replayed found=true"
+    pub outcome: String,
+}
+
+/// Append-only ring buffer of admin operations.
+pub struct AuditLog {
+    capacity: usize,
+    entries: Mutex<VecDeque<AuditEntry>>,
+}
+
+impl AuditLog {
+    /// `capacity` = 0 keeps nothing in memory; entries are still logged.
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, entries: Mutex::new(VecDeque::new()) }
+    }
+
+    /// Log an operation and append it, dropping the oldest entry when full.
+    pub fn record(&self, actor: AuditActor, operation: &str, target: &str, outcome: String) {
+        info!(
+            caller = %actor.caller,
+            peer = %actor.peer,
+            operation,
+            target,
+            outcome = %outcome,
+            "Admin operation"
+        );
+        if self.capacity == 0 {
+            return;
+        }
+        let entry = AuditEntry {
+            timestamp_ms: crate::current_time_ms(),
+            actor,
+            operation: operation.to_string(),
+            target: target.to_string(),
+            outcome,
+        };
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        while entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Most recent entries, newest first (`limit` = 0 for all), optionally
+    /// restricted to one operation.
+    pub fn recent(&self, limit: usize, operation: Option<&str>) -> Vec<AuditEntry> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let matching = entries
+            .iter()
+            .rev()
+            .filter(|e| operation.is_none_or(|op| e.operation == op))
+            .cloned();
+        if limit == 0 {
+            matching.collect()
+        } else {
+            matching.take(limit).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_log_bounded_newest_first() {
+        let log = AuditLog::new(2);
+        let actor = AuditActor { caller: "dashboard".to_string(), peer: String::new() };
+        log.record(actor.clone(), "FlushCache", "", "flushed=1".to_string());
+        log.record(actor.clone(), "EvictFromCache", "abc", "found=true".to_string());
+        log.record(actor, "FlushCache", "", "flushed=0".to_string());
+
+        let all = log.recent(0, None);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].outcome, "flushed=0");
+        assert_eq!(all[1].operation, "EvictFromCache");
+        assert_eq!(log.recent(1, None).len(), 1);
+        assert_eq!(log.recent(0, Some("EvictFromCache"))[0].target, "abc");
+    }
+
+    #[test]
+    fn test_actor_from_metadata() {
+        let mut request = tonic::Request::new(());
+        request.metadata_mut().insert(CALLER_HEADER, "orchestrator".parse().unwrap());
+        let actor = AuditActor::from_request(&request);
+        assert_eq!(actor.caller, "orchestrator");
+        assert_eq!(actor.peer, "");
+    }
+}
@@ -0,0 +1,88 @@
+//! Offline tools for heuristic files (seed files and ExportHeuristics output).
+//!
+//! Usage:
+//!   memctl diff <snapshot_a.json> <snapshot_b.json> [--json]
+//!
+//! `diff` reports heuristics added, removed, or with changed confidence going
+//! from snapshot A to B (matched by ID; see the seed module). Output is one
+//! line per change ("+" added, "-" removed, "~" confidence), or a JSON object
+//! with --json. Like diff(1), it exits 0 when the snapshots match, 1 when they
+//! differ, and 2 on error.
+
+use std::process::ExitCode;
+
+use gladys_memory::seed::{diff_snapshots, parse_heuristic_file, SeedHeuristic, SnapshotDiff};
+
+const USAGE: &str = "usage: memctl diff <snapshot_a.json> <snapshot_b.json> [--json]";
+
+struct DiffArgs {
+    before: String,
+    after: String,
+    json: bool,
+}
+
+fn parse_args() -> Result<DiffArgs, String> {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("diff") => {}
+        Some("-h") | Some("--help") | None => return Err(USAGE.to_string()),
+        Some(other) => return Err(format!("unknown command: {}\n{}", other, USAGE)),
+    }
+    let mut files = Vec::new();
+    let mut json = false;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            other if !other.starts_with("--") && files.len() < 2 => files.push(other.to_string()),
+            other => return Err(format!("unexpected argument: {}\n{}", other, USAGE)),
+        }
+    }
+    let [before, after]: [String; 2] = files.try_into().map_err(|_| USAGE.to_string())?;
+    Ok(DiffArgs { before, after, json })
+}
+
+fn load(path: &str) -> Result<Vec<SeedHeuristic>, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let (heuristics, stats) = parse_heuristic_file(&contents).map_err(|e| format!("{}: {}", path, e))?;
+    if stats.skipped > 0 {
+        eprintln!("warning: {}: skipped {} corrupt or missing record(s)", path, stats.skipped);
+    }
+    Ok(heuristics)
+}
+
+fn label(h: &SeedHeuristic) -> &str {
+    h.name.as_deref().unwrap_or(&h.condition_text)
+}
+
+fn print_diff(diff: &SnapshotDiff) {
+    for h in &diff.added {
+        println!("+ {} {} (confidence {:.3})", h.id(), label(h), h.confidence);
+    }
+    for h in &diff.removed {
+        println!("- {} {} (confidence {:.3})", h.id(), label(h), h.confidence);
+    }
+    for c in &diff.confidence_changed {
+        println!("~ {} {} confidence {:.3} -> {:.3}", c.id, c.name, c.before, c.after);
+    }
+}
+
+fn main() -> ExitCode {
+    let result = parse_args().and_then(|args| {
+        let diff = diff_snapshots(&load(&args.before)?, &load(&args.after)?);
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&diff).map_err(|e| e.to_string())?);
+        } else {
+            print_diff(&diff);
+        }
+        Ok(diff.is_empty())
+    });
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(2)
+        }
+    }
+}
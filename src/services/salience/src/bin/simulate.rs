@@ -0,0 +1,122 @@
+//! Scenario simulator CLI.
+//!
+//! Replays a transcript through an in-process SalienceService and prints the
+//! salience timeline as JSON lines (one row per event) on stdout.
+//!
+//! Usage:
+//!   salience-simulate <transcript.jsonl> [--heuristics <file.json>] [--storage local|grpc]
+//!                     [--expect <timeline.json>]
+//!
+//! --storage local (default) uses hashed bag-of-words embeddings and the
+//! heuristics file only; --storage grpc uses the Python storage service at
+//! STORAGE_ADDRESS. Scoring and cache settings come from the usual
+//! environment variables (see the config module).
+//!
+//! With --expect, the run is compared against a golden timeline (a JSON list
+//! of rows) instead of printed; differences go to stderr and exit non-zero.
+
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use gladys_memory::affect::AffectLexicon;
+use gladys_memory::rate_limit::TokenBucket;
+use gladys_memory::seed::{parse_seed_file, seed_cache};
+use gladys_memory::simulate::{
+    diff_timelines, local_service, parse_transcript, run_simulation, TimelineEntry, DEFAULT_GOLDEN_TOLERANCE,
+};
+use gladys_memory::{CacheHandle, Config, EmbeddingSimilarityScorer, GrpcStorageBackend, MemoryCache, SalienceScorer, SalienceService, StorageBackend};
+
+const USAGE: &str = "usage: salience-simulate <transcript.jsonl> [--heuristics <file.json>] [--storage local|grpc] [--expect <timeline.json>]";
+
+struct Args {
+    transcript: String,
+    heuristics: Option<String>,
+    storage: String,
+    expect: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut transcript = None;
+    let mut heuristics = None;
+    let mut storage = "local".to_string();
+    let mut expect = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--heuristics" => heuristics = Some(args.next().ok_or("--heuristics needs a file")?),
+            "--storage" => storage = args.next().ok_or("--storage needs local or grpc")?,
+            "--expect" => expect = Some(args.next().ok_or("--expect needs a file")?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            other if transcript.is_none() && !other.starts_with("--") => transcript = Some(other.to_string()),
+            other => return Err(format!("unexpected argument: {}\n{}", other, USAGE)),
+        }
+    }
+    Ok(Args { transcript: transcript.ok_or(USAGE)?, heuristics, storage, expect })
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let args = parse_args()?;
+    let config = Config::from_env();
+    let events = parse_transcript(&std::fs::read_to_string(&args.transcript)?)?;
+    let seeds = match &args.heuristics {
+        Some(path) => parse_seed_file(&std::fs::read_to_string(path)?)?,
+        None => Vec::new(),
+    };
+
+    let (service, cache) = match args.storage.as_str() {
+        "local" => {
+            let heuristics = seeds.into_iter().map(|s| s.into_cached(vec![])).collect();
+            local_service(heuristics, config.cache.clone(), config.salience.clone())
+        }
+        "grpc" => {
+            let cache = CacheHandle::new(MemoryCache::new(config.cache.clone()));
+            let storage: Arc<dyn StorageBackend> = Arc::new(GrpcStorageBackend::new(config.storage.clone()));
+            // Seed the cache so the heuristics file applies to real storage too
+            seed_cache(seeds, &cache, storage.as_ref(), false).await;
+            let scorer: Box<dyn SalienceScorer> = Box::new(
+                EmbeddingSimilarityScorer::new(
+                    cache.clone(),
+                    storage.clone(),
+                    config.salience.min_heuristic_similarity,
+                    config.salience.min_heuristic_confidence,
+                )
+                .with_fallback_limit(TokenBucket::for_fallback(&config.salience))
+                .with_keyword_prefilter(config.salience.keyword_prefilter),
+            );
+            let service = SalienceService::with_scorer(cache.clone(), scorer, config.salience.clone()).with_storage(storage);
+            (service, cache)
+        }
+        other => return Err(format!("unknown storage: {} (expected local or grpc)", other).into()),
+    };
+
+    let service = match &config.salience.affect_lexicon_path {
+        Some(path) => service.with_affect_lexicon(AffectLexicon::load(path)?),
+        None => service,
+    };
+
+    let timeline = run_simulation(&service, &cache, &events).await;
+    if let Some(path) = &args.expect {
+        let expected: Vec<TimelineEntry> = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let diffs = diff_timelines(&expected, &timeline, DEFAULT_GOLDEN_TOLERANCE);
+        if !diffs.is_empty() {
+            return Err(format!("Timeline differs from {}:\n  {}", path, diffs.join("\n  ")).into());
+        }
+        eprintln!("{} events match {}", timeline.len(), path);
+        return Ok(());
+    }
+    for entry in timeline {
+        println!("{}", serde_json::to_string(&entry)?);
+    }
+    Ok(())
+}
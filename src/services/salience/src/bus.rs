@@ -0,0 +1,396 @@
+//! NATS event intake (enabled with the `nats` feature).
+//!
+//! Some sensors publish events to a message bus instead of calling gRPC.
+//! With NATS_ADDRESS set, the service subscribes to a subject, evaluates each
+//! message exactly as EvaluateSalience would, and publishes the decision to
+//! an output subject. Replicas join the same queue group, so each event is
+//! evaluated once however many replicas run.
+//!
+//! Events are JSON objects with the fields of EvaluateSalienceRequest (all
+//! optional), plus an optional `trace_id`:
+//!
+//! ```text
+//! {"event_id": "e-1", "source": "minecraft", "raw_text": "A creeper approaches"}
+//! ```
+//!
+//! Decisions carry the event ID, the salience result, the routing hint and
+//! any error, so consumers can correlate them without a reply subject.
+//! Messages that aren't valid events are logged and dropped.
+//!
+//! Only the core NATS protocol is spoken (CONNECT, SUB, PUB, MSG, PING/PONG);
+//! no TLS, auth or JetStream.
+//!
+//! Configuration via environment variables:
+//!   NATS_ADDRESS: NATS host:port (default: none = disabled)
+//!   NATS_SUBJECT: Subject to consume events from (default: "gladys.events")
+//!   NATS_OUTPUT_SUBJECT: Subject to publish decisions to (default: "gladys.salience")
+//!   NATS_QUEUE_GROUP: Queue group shared by replicas (default: "gladys-salience")
+//!   NATS_MAX_IN_FLIGHT: Events evaluated concurrently (default: 64)
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::{Mutex, Semaphore};
+use tonic::Request;
+use tracing::{debug, info, warn};
+
+use crate::config::BusConfig;
+use crate::intake::{decision_json, error_json};
+use crate::logging::TRACE_ID_HEADER;
+use crate::metrics::record_bus_message;
+use crate::proto::salience_gateway_server::SalienceGateway;
+use crate::proto::EvaluateSalienceRequest;
+
+/// Wait between reconnect attempts.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Errors talking to NATS.
+#[derive(Debug, thiserror::Error)]
+pub enum BusError {
+    #[error("NATS I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("NATS protocol error: {0}")]
+    Protocol(String),
+    #[error("NATS error: {0}")]
+    Nats(String),
+}
+
+/// A message delivered on a subscription.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NatsMessage {
+    pub subject: String,
+    pub reply_to: Option<String>,
+    pub payload: Vec<u8>,
+}
+
+/// Parse the arguments of a `MSG <subject> <sid> [reply-to] <#bytes>` line.
+fn parse_msg_line(args: &str) -> Result<(String, Option<String>, usize), BusError> {
+    let parts: Vec<&str> = args.split_whitespace().collect();
+    let (subject, reply_to, length) = match parts.as_slice() {
+        [subject, _sid, length] => (subject, None, length),
+        [subject, _sid, reply_to, length] => (subject, Some(reply_to.to_string()), length),
+        _ => return Err(BusError::Protocol(format!("bad MSG: {}", args))),
+    };
+    let length = length.parse().map_err(|_| BusError::Protocol(format!("bad MSG length: {}", length)))?;
+    Ok((subject.to_string(), reply_to, length))
+}
+
+/// Publishing half of a connection; cheap to clone.
+#[derive(Clone)]
+pub struct NatsPublisher {
+    writer: Arc<Mutex<OwnedWriteHalf>>,
+}
+
+impl NatsPublisher {
+    async fn write(&self, data: &[u8]) -> Result<(), BusError> {
+        self.writer.lock().await.write_all(data).await?;
+        Ok(())
+    }
+
+    pub async fn publish(&self, subject: &str, payload: &[u8]) -> Result<(), BusError> {
+        let mut frame = format!("PUB {} {}\r\n", subject, payload.len()).into_bytes();
+        frame.extend_from_slice(payload);
+        frame.extend_from_slice(b"\r\n");
+        self.write(&frame).await
+    }
+}
+
+/// One NATS connection.
+pub struct NatsConnection {
+    reader: BufReader<OwnedReadHalf>,
+    publisher: NatsPublisher,
+}
+
+impl NatsConnection {
+    /// Connect and complete the handshake (INFO, CONNECT, PING/PONG).
+    pub async fn connect(address: &str) -> Result<Self, BusError> {
+        let stream = TcpStream::connect(address).await?;
+        stream.set_nodelay(true)?;
+        let (reader, writer) = stream.into_split();
+        let mut connection = Self {
+            reader: BufReader::new(reader),
+            publisher: NatsPublisher { writer: Arc::new(Mutex::new(writer)) },
+        };
+
+        let info = connection.read_line().await?;
+        if !info.starts_with("INFO") {
+            return Err(BusError::Protocol(format!("expected INFO, got: {}", info)));
+        }
+        let connect = serde_json::json!({
+            "verbose": false,
+            "pedantic": false,
+            "name": "gladys-salience",
+            "lang": "rust",
+            "version": env!("CARGO_PKG_VERSION"),
+            "protocol": 1,
+        });
+        connection.publisher.write(format!("CONNECT {}\r\nPING\r\n", connect).as_bytes()).await?;
+        loop {
+            let line = connection.read_line().await?;
+            match line.split_once(' ').map_or(line.as_str(), |(op, _)| op) {
+                "PONG" => return Ok(connection),
+                "+OK" | "INFO" => continue,
+                "-ERR" => return Err(BusError::Nats(line[4..].trim().to_string())),
+                _ => return Err(BusError::Protocol(format!("unexpected handshake reply: {}", line))),
+            }
+        }
+    }
+
+    /// Subscribe to `subject` as a member of `queue_group`.
+    pub async fn subscribe(&self, subject: &str, queue_group: &str) -> Result<(), BusError> {
+        self.publisher.write(format!("SUB {} {} 1\r\n", subject, queue_group).as_bytes()).await
+    }
+
+    pub fn publisher(&self) -> NatsPublisher {
+        self.publisher.clone()
+    }
+
+    async fn read_line(&mut self) -> Result<String, BusError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line).await? == 0 {
+            return Err(BusError::Io(std::io::ErrorKind::UnexpectedEof.into()));
+        }
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    }
+
+    /// Wait for the next message, answering server PINGs meanwhile.
+    pub async fn next_message(&mut self) -> Result<NatsMessage, BusError> {
+        loop {
+            let line = self.read_line().await?;
+            let (op, args) = line.split_once(' ').unwrap_or((line.as_str(), ""));
+            match op {
+                "MSG" => {
+                    let (subject, reply_to, length) = parse_msg_line(args)?;
+                    let mut payload = vec![0; length + 2];
+                    self.reader.read_exact(&mut payload).await?;
+                    payload.truncate(length);
+                    return Ok(NatsMessage { subject, reply_to, payload });
+                }
+                "PING" => self.publisher.write(b"PONG\r\n").await?,
+                "PONG" | "+OK" | "INFO" => {}
+                "-ERR" => return Err(BusError::Nats(args.trim().to_string())),
+                _ => return Err(BusError::Protocol(format!("unexpected line: {}", line))),
+            }
+        }
+    }
+}
+
+/// An event as published on the bus.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct BusEvent {
+    event_id: String,
+    source: String,
+    raw_text: String,
+    structured_json: String,
+    entity_ids: Vec<String>,
+    skip_novelty_detection: bool,
+    min_similarity: Option<f32>,
+    min_confidence: Option<f32>,
+    debug: bool,
+    trace_id: Option<String>,
+}
+
+impl BusEvent {
+    fn into_request(self) -> Request<EvaluateSalienceRequest> {
+        let mut request = Request::new(EvaluateSalienceRequest {
+            event_id: self.event_id,
+            source: self.source,
+            raw_text: self.raw_text,
+            structured_json: self.structured_json,
+            entity_ids: self.entity_ids,
+            skip_novelty_detection: self.skip_novelty_detection,
+            min_similarity: self.min_similarity,
+            min_confidence: self.min_confidence,
+            debug: self.debug,
+        });
+        if let Some(value) = self.trace_id.and_then(|t| t.parse().ok()) {
+            request.metadata_mut().insert(TRACE_ID_HEADER, value);
+        }
+        request
+    }
+}
+
+/// Evaluate one bus message; None if it isn't a valid event.
+async fn evaluate_message<G: SalienceGateway>(gateway: &G, payload: &[u8]) -> Option<serde_json::Value> {
+    let event: BusEvent = match serde_json::from_slice(payload) {
+        Ok(event) => event,
+        Err(e) => {
+            record_bus_message("nats", "invalid");
+            warn!(error = %e, "Dropping invalid bus event");
+            return None;
+        }
+    };
+    let event_id = event.event_id.clone();
+    match gateway.evaluate_salience(event.into_request()).await {
+        Ok(response) => {
+            record_bus_message("nats", "evaluated");
+            Some(decision_json(&event_id, response.get_ref()))
+        }
+        Err(status) => {
+            record_bus_message("nats", "failed");
+            Some(error_json(&event_id, &status))
+        }
+    }
+}
+
+/// Consume events until the connection fails.
+async fn consume<G: SalienceGateway>(address: &str, config: &BusConfig, gateway: &Arc<G>) -> Result<(), BusError> {
+    let mut connection = NatsConnection::connect(address).await?;
+    connection.subscribe(&config.subject, &config.queue_group).await?;
+    info!(
+        address = %address,
+        subject = %config.subject,
+        output_subject = %config.output_subject,
+        queue_group = %config.queue_group,
+        "Consuming events from NATS"
+    );
+
+    let publisher = connection.publisher();
+    let in_flight = Arc::new(Semaphore::new(config.max_in_flight.max(1)));
+    loop {
+        let message = connection.next_message().await?;
+        // Stop reading while at capacity so NATS buffers instead of us
+        let Ok(permit) = in_flight.clone().acquire_owned().await else {
+            return Ok(());
+        };
+        let (gateway, publisher, output_subject) = (gateway.clone(), publisher.clone(), config.output_subject.clone());
+        tokio::spawn(async move {
+            let _permit = permit;
+            let Some(decision) = evaluate_message(gateway.as_ref(), &message.payload).await else {
+                return;
+            };
+            match publisher.publish(&output_subject, decision.to_string().as_bytes()).await {
+                Ok(()) => debug!(event_id = %decision["event_id"], "Published salience decision"),
+                Err(e) => warn!(error = %e, "Failed to publish salience decision"),
+            }
+        });
+    }
+}
+
+/// Consume events from NATS for as long as the process runs, reconnecting on failure.
+pub async fn run_consumer<G: SalienceGateway>(config: BusConfig, gateway: Arc<G>) {
+    let Some(address) = config.nats_address.clone() else {
+        return;
+    };
+    loop {
+        match consume(&address, &config, &gateway).await {
+            Ok(()) => return,
+            Err(e) => warn!(address = %address, error = %e, "NATS consumer disconnected; reconnecting"),
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulate::local_service;
+    use crate::{CacheConfig, CachedHeuristic, Condition, SalienceConfig};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_parse_msg_line() {
+        assert_eq!(parse_msg_line("gladys.events 1 12").unwrap(), ("gladys.events".to_string(), None, 12));
+        assert_eq!(
+            parse_msg_line("gladys.events 1 _INBOX.x 0").unwrap(),
+            ("gladys.events".to_string(), Some("_INBOX.x".to_string()), 0)
+        );
+        assert!(parse_msg_line("gladys.events").is_err());
+        assert!(parse_msg_line("gladys.events 1 -3").is_err());
+    }
+
+    /// Accepts one client, delivers `events`, and forwards every PUB payload.
+    async fn spawn_fake_nats(events: Vec<String>) -> (String, mpsc::UnboundedReceiver<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let (published, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+            writer.write_all(b"INFO {\"server_id\":\"fake\"}\r\n").await.unwrap();
+            let mut line = String::new();
+            loop {
+                line.clear();
+                if reader.read_line(&mut line).await.unwrap() == 0 {
+                    return;
+                }
+                let line = line.trim_end();
+                if line == "PING" {
+                    writer.write_all(b"PONG\r\n").await.unwrap();
+                } else if line.starts_with("SUB gladys.events gladys-salience ") {
+                    for event in &events {
+                        let frame = format!("MSG gladys.events 1 {}\r\n{}\r\n", event.len(), event);
+                        writer.write_all(frame.as_bytes()).await.unwrap();
+                    }
+                    // Server keepalive mid-stream
+                    writer.write_all(b"PING\r\n").await.unwrap();
+                } else if let Some(args) = line.strip_prefix("PUB ") {
+                    let (subject, length) = args.split_once(' ').unwrap();
+                    let mut payload = vec![0; length.parse::<usize>().unwrap() + 2];
+                    reader.read_exact(&mut payload).await.unwrap();
+                    payload.truncate(payload.len() - 2);
+                    let _ = published.send((subject.to_string(), String::from_utf8(payload).unwrap()));
+                }
+            }
+        });
+        (address, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_consumer_publishes_decisions() {
+        let heuristic = CachedHeuristic {
+            id: Uuid::new_v4(),
+            name: "creeper".to_string(),
+            condition: Condition::text("a creeper approaches the player"),
+            effects: serde_json::json!({"salience": {"threat": 0.9}}).into(),
+            confidence: 0.9,
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: Default::default(),
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        };
+        let (service, _cache) = local_service(vec![heuristic.clone()], CacheConfig::default(), SalienceConfig::default());
+
+        let events = vec![
+            serde_json::json!({"event_id": "e-1", "raw_text": "a creeper approaches the player"}).to_string(),
+            "not json".to_string(),
+            serde_json::json!({"event_id": "e-2", "raw_text": "the sun sets quietly"}).to_string(),
+        ];
+        let (address, mut published) = spawn_fake_nats(events).await;
+        let config = BusConfig {
+            nats_address: Some(address),
+            subject: "gladys.events".to_string(),
+            output_subject: "gladys.salience".to_string(),
+            queue_group: "gladys-salience".to_string(),
+            max_in_flight: 4,
+        };
+        let consumer = tokio::spawn(run_consumer(config, Arc::new(service)));
+
+        let mut decisions = std::collections::HashMap::new();
+        for _ in 0..2 {
+            let (subject, payload) = tokio::time::timeout(Duration::from_secs(5), published.recv()).await.unwrap().unwrap();
+            assert_eq!(subject, "gladys.salience");
+            let decision: serde_json::Value = serde_json::from_str(&payload).unwrap();
+            decisions.insert(decision["event_id"].as_str().unwrap().to_string(), decision);
+        }
+        assert_eq!(decisions["e-1"]["matched_heuristic_id"], heuristic.id.to_string());
+        assert!(decisions["e-1"]["salience"]["threat"].as_f64().unwrap() > 0.5);
+        assert!(decisions["e-1"]["routing_hint"].as_str().unwrap().starts_with("ROUTING_HINT_"));
+        assert_eq!(decisions["e-2"]["matched_heuristic_id"], "");
+
+        consumer.abort();
+    }
+}
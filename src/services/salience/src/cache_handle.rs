@@ -0,0 +1,187 @@
+//! Shared handle to the L0 cache.
+//!
+//! The server, scorers and background loops all share one `MemoryCache`
+//! behind a lock. `CacheHandle` owns that plumbing:
+//! - Cheap to clone; every clone refers to the same cache
+//! - `lookup`, `resolve`, `warm`, `apply_scoring_outcome` and `stats` take the lock they need and
+//!   release it before returning, so callers don't hold a guard across awaits
+//! - `lookup` scans and `resolve` turns the matches into scored matches under
+//!   read locks (concurrent with other lookups); everything a scored event
+//!   changes (counters, touches, cooldowns, text match, dedup) is applied by
+//!   `apply_scoring_outcome` under one write lock
+//! - `read`/`write` remain for operations without a dedicated method
+//!
+//! Lock acquisition order hasn't changed: nothing here takes another lock
+//! while holding the cache's.
+
+use std::sync::Arc;
+
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use uuid::Uuid;
+
+use crate::exemplars::NegativeMatch;
+use crate::{CacheStats, CachedHeuristic, MemoryCache, ScoredMatch, ScoringOutcome};
+
+/// A similarity scan of the cached heuristics.
+#[derive(Debug, Clone, Copy)]
+pub struct Lookup<'a> {
+    pub embedding: &'a [f32],
+    /// Hash of the event text, for match hysteresis
+    pub text_hash: u64,
+    pub source_filter: Option<&'a str>,
+    pub min_similarity: f32,
+    pub min_confidence: f32,
+    pub limit: usize,
+}
+
+/// What a lookup found: relevance to the active goals, matching heuristic
+/// ids with their similarity (best first), and the negative exemplars that
+/// counted against any of them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LookupResult {
+    pub goal_relevance: Option<f32>,
+    pub matches: Vec<(Uuid, f32)>,
+    pub negatives: Vec<(Uuid, NegativeMatch)>,
+}
+
+fn scan(cache: &MemoryCache, lookup: Lookup<'_>) -> LookupResult {
+    let matches = cache.find_matching_heuristics_with_hysteresis(
+        lookup.embedding,
+        lookup.text_hash,
+        lookup.source_filter,
+        lookup.min_similarity,
+        lookup.min_confidence,
+        lookup.limit,
+    );
+    let negatives = matches
+        .iter()
+        .filter_map(|(id, _)| cache.get_heuristic(id).and_then(|h| cache.negative_match(lookup.embedding, h)).map(|n| (*id, n)))
+        .collect();
+    LookupResult { goal_relevance: cache.goal_relevance(lookup.embedding), matches, negatives }
+}
+
+/// Thread-safe, clonable handle to the shared `MemoryCache`.
+#[derive(Clone)]
+pub struct CacheHandle(Arc<RwLock<MemoryCache>>);
+
+impl CacheHandle {
+    pub fn new(cache: MemoryCache) -> Self {
+        Self(Arc::new(RwLock::new(cache)))
+    }
+
+    /// Shared access, for reads without a dedicated method.
+    pub async fn read(&self) -> RwLockReadGuard<'_, MemoryCache> {
+        self.0.read().await
+    }
+
+    /// Exclusive access, for updates without a dedicated method.
+    pub async fn write(&self) -> RwLockWriteGuard<'_, MemoryCache> {
+        self.0.write().await
+    }
+
+    /// Shared access from a blocking thread (e.g., a worker pool job).
+    /// Panics if called from within the async runtime.
+    pub fn blocking_read(&self) -> RwLockReadGuard<'_, MemoryCache> {
+        self.0.blocking_read()
+    }
+
+    /// Scan the cached heuristics for `lookup`'s embedding.
+    pub async fn lookup(&self, lookup: Lookup<'_>) -> LookupResult {
+        scan(&*self.0.read().await, lookup)
+    }
+
+    /// `lookup` from a blocking thread (see `blocking_read`).
+    pub fn lookup_blocking(&self, lookup: Lookup<'_>) -> LookupResult {
+        scan(&self.0.blocking_read(), lookup)
+    }
+
+    /// Resolve lookup matches to scored matches. Matches evicted since the
+    /// lookup are dropped.
+    pub async fn resolve(&self, found: &LookupResult) -> Vec<ScoredMatch> {
+        let cache = self.0.read().await;
+        found
+            .matches
+            .iter()
+            .filter_map(|(id, sim)| {
+                let negative = found.negatives.iter().find(|(n, _)| n == id).map(|(_, m)| m.clone());
+                cache.get_heuristic(id).map(|h| ScoredMatch { negative, ..ScoredMatch::new(h, *sim) })
+            })
+            .collect()
+    }
+
+    /// Add heuristics found elsewhere (storage, peers), remembering the first
+    /// as the match for `text_hash` if given. Returns how many were cached.
+    pub async fn warm(&self, heuristics: &[CachedHeuristic], text_hash: Option<u64>) -> usize {
+        let mut cache = self.0.write().await;
+        let added = heuristics.iter().filter(|h| cache.add_heuristic((*h).clone())).count();
+        if let (Some(hash), Some(first)) = (text_hash, heuristics.first()) {
+            cache.record_text_match(hash, first.id);
+        }
+        added
+    }
+
+    /// Apply an evaluation's bookkeeping under one write lock; returns the
+    /// event it duplicates, if any.
+    pub async fn apply_scoring_outcome(&self, outcome: &ScoringOutcome) -> Option<String> {
+        self.0.write().await.apply_scoring_outcome(outcome)
+    }
+
+    pub async fn stats(&self) -> CacheStats {
+        self.0.read().await.stats()
+    }
+}
+
+impl From<MemoryCache> for CacheHandle {
+    fn from(cache: MemoryCache) -> Self {
+        Self::new(cache)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CacheConfig, Condition};
+
+    #[tokio::test]
+    async fn test_lookup_resolve_and_warm() {
+        let cache = CacheHandle::new(MemoryCache::new(CacheConfig::default()));
+        let creeper = CachedHeuristic {
+            id: Uuid::new_v4(),
+            name: "creeper".to_string(),
+            condition: Condition::text("creeper"),
+            effects: serde_json::json!({"message": "run"}).into(),
+            confidence: 0.9,
+            origin: "user".to_string(),
+            source: String::new(),
+            condition_embedding: vec![1.0; 384].into(),
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        };
+        assert_eq!(cache.warm(std::slice::from_ref(&creeper), Some(7)).await, 1);
+        assert_eq!(cache.clone().stats().await.heuristic_count, 1, "clones share the cache");
+
+        let embedding = vec![1.0; 384];
+        let lookup = Lookup {
+            embedding: &embedding,
+            text_hash: 7,
+            source_filter: None,
+            min_similarity: 0.9,
+            min_confidence: 0.5,
+            limit: 5,
+        };
+        let found = cache.lookup(lookup).await;
+        assert_eq!(found.matches.first().map(|m| m.0), Some(creeper.id));
+
+        // Resolving only reads: it goes through while another reader holds the cache
+        let reader = cache.read().await;
+        let scored = cache.resolve(&found).await;
+        drop(reader);
+        assert_eq!(scored[0].suggested_action, "run");
+        let unknown = LookupResult { matches: vec![(Uuid::new_v4(), 1.0)], ..Default::default() };
+        assert!(cache.resolve(&unknown).await.is_empty(), "unknown ids dropped");
+    }
+}
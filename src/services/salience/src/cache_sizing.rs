@@ -0,0 +1,344 @@
+//! Adaptive cache sizing.
+//!
+//! Picking `max_heuristics` / `max_events` up front is guesswork. With
+//! adaptive sizing enabled, a background task compares the cache counters
+//! every interval against the previous check and resizes within the
+//! configured bounds:
+//! - Heuristics grow when evicted ones are re-fetched within their TTL (churn),
+//!   or when the hit rate is below target while LRU evictions are happening
+//! - Heuristics shrink when the target is met, nothing was evicted, and the
+//!   cache is under half full
+//! - Events grow when evicted event text comes back within the TTL, and shrink
+//!   when nothing was evicted and the cache is under half full
+//!
+//! Each resize moves by `step` of the current capacity (never below what's
+//! cached when shrinking) and is logged with its reason. Capacities are
+//! reported in `GetCacheStats`.
+//!
+//! Configuration via environment variables (see `CacheSizingConfig`):
+//!   CACHE_ADAPTIVE_INTERVAL_MS: Resize check interval (default: 0 = disabled)
+//!   CACHE_ADAPTIVE_MIN_HEURISTICS / CACHE_ADAPTIVE_MAX_HEURISTICS: Bounds
+//!     (default: half / four times CACHE_MAX_HEURISTICS)
+//!   CACHE_ADAPTIVE_MIN_EVENTS / CACHE_ADAPTIVE_MAX_EVENTS: Bounds
+//!     (default: half / four times CACHE_MAX_EVENTS)
+//!   CACHE_ADAPTIVE_TARGET_HIT_RATE: Heuristic hit rate to hold (default: 0.8)
+//!   CACHE_ADAPTIVE_STEP: Fraction of capacity per resize (default: 0.25)
+
+use std::time::Duration;
+
+use tracing::{debug, info};
+
+use crate::config::CacheSizingConfig;
+use crate::{CacheHandle, CacheStats, MemoryCache};
+
+/// One capacity change and why it was made.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resize {
+    /// "heuristics" or "events"
+    pub cache: &'static str,
+    pub from: usize,
+    pub to: usize,
+    pub reason: &'static str,
+}
+
+/// Counter movement between two checks.
+#[derive(Debug, Default)]
+struct Delta {
+    hits: u64,
+    misses: u64,
+    heuristic_evictions: u64,
+    heuristic_refetches: u64,
+    event_evictions: u64,
+    event_refetches: u64,
+}
+
+impl Delta {
+    fn between(previous: &CacheStats, current: &CacheStats) -> Self {
+        Self {
+            hits: current.total_hits.saturating_sub(previous.total_hits),
+            misses: current.total_misses.saturating_sub(previous.total_misses),
+            heuristic_evictions: current.evictions.heuristic_lru.saturating_sub(previous.evictions.heuristic_lru),
+            heuristic_refetches: current.refetches.heuristics.saturating_sub(previous.refetches.heuristics),
+            event_evictions: current.evictions.event_capacity.saturating_sub(previous.evictions.event_capacity),
+            event_refetches: current.refetches.events.saturating_sub(previous.refetches.events),
+        }
+    }
+
+    fn hit_rate(&self) -> Option<f32> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f32 / lookups as f32)
+    }
+}
+
+/// Resize policy plus the counters seen at the previous check.
+pub struct CacheSizer {
+    config: CacheSizingConfig,
+    heuristic_bounds: (usize, usize),
+    event_bounds: (usize, usize),
+    previous: Option<CacheStats>,
+}
+
+impl CacheSizer {
+    /// Bounds not configured are derived from the starting capacities.
+    pub fn new(config: CacheSizingConfig, max_heuristics: usize, max_events: usize) -> Self {
+        let bounds = |min: Option<usize>, max: Option<usize>, initial: usize| {
+            let min = min.unwrap_or(initial / 2).max(1);
+            (min, max.unwrap_or(initial.saturating_mul(4)).max(min))
+        };
+        Self {
+            heuristic_bounds: bounds(config.min_heuristics, config.max_heuristics, max_heuristics),
+            event_bounds: bounds(config.min_events, config.max_events, max_events),
+            config,
+            previous: None,
+        }
+    }
+
+    /// Compare the counters to the previous check and resize the cache.
+    /// The first check only records a baseline.
+    pub fn check(&mut self, cache: &mut MemoryCache) -> Vec<Resize> {
+        let stats = cache.stats();
+        let Some(previous) = self.previous.replace(stats.clone()) else {
+            return Vec::new();
+        };
+        let delta = Delta::between(&previous, &stats);
+
+        let mut resizes = Vec::new();
+        let heuristics = self.size_heuristics(&stats, &delta);
+        let events = self.size_events(&stats, &delta);
+        if let Some(resize) = heuristics.clone() {
+            resizes.push(resize);
+        }
+        if let Some(resize) = events.clone() {
+            resizes.push(resize);
+        }
+        if !resizes.is_empty() {
+            cache.set_capacity(
+                heuristics.map_or(stats.max_heuristics, |r| r.to),
+                events.map_or(stats.max_events, |r| r.to),
+            );
+            // Evictions from shrinking aren't churn the next check should react to
+            self.previous = Some(cache.stats());
+        }
+        resizes
+    }
+
+    fn size_heuristics(&self, stats: &CacheStats, delta: &Delta) -> Option<Resize> {
+        let hit_rate = delta.hit_rate();
+        let below_target = hit_rate.is_some_and(|r| r < self.config.target_hit_rate);
+        let (to, reason) = if delta.heuristic_refetches > 0 {
+            (self.grow(stats.max_heuristics, self.heuristic_bounds), "evicted heuristics re-fetched within TTL")
+        } else if below_target && delta.heuristic_evictions > 0 {
+            (self.grow(stats.max_heuristics, self.heuristic_bounds), "hit rate below target with LRU evictions")
+        } else if !below_target && delta.heuristic_evictions == 0 && stats.heuristic_count * 2 < stats.max_heuristics {
+            let to = self.shrink(stats.max_heuristics, stats.heuristic_count, self.heuristic_bounds);
+            (to, "under half full at target hit rate")
+        } else {
+            return None;
+        };
+        (to != stats.max_heuristics).then_some(Resize { cache: "heuristics", from: stats.max_heuristics, to, reason })
+    }
+
+    fn size_events(&self, stats: &CacheStats, delta: &Delta) -> Option<Resize> {
+        let (to, reason) = if delta.event_refetches > 0 {
+            (self.grow(stats.max_events, self.event_bounds), "evicted event text seen again within TTL")
+        } else if delta.event_evictions == 0 && stats.event_count * 2 < stats.max_events {
+            (self.shrink(stats.max_events, stats.event_count, self.event_bounds), "under half full without evictions")
+        } else {
+            return None;
+        };
+        (to != stats.max_events).then_some(Resize { cache: "events", from: stats.max_events, to, reason })
+    }
+
+    fn step_size(&self, capacity: usize) -> usize {
+        ((capacity as f32 * self.config.step) as usize).max(1)
+    }
+
+    fn grow(&self, capacity: usize, (min, max): (usize, usize)) -> usize {
+        capacity.saturating_add(self.step_size(capacity)).clamp(min, max)
+    }
+
+    /// Never below what's currently cached, so shrinking doesn't evict.
+    fn shrink(&self, capacity: usize, used: usize, (min, max): (usize, usize)) -> usize {
+        capacity.saturating_sub(self.step_size(capacity)).max(used).clamp(min, max)
+    }
+}
+
+/// Check and resize the cache every interval (runs forever).
+pub async fn run_cache_sizing(config: CacheSizingConfig, cache: CacheHandle) {
+    let interval = Duration::from_millis(config.interval_ms.max(1));
+    let (max_heuristics, max_events) = {
+        let cache = cache.read().await;
+        (cache.config().max_heuristics, cache.config().max_events)
+    };
+    let mut sizer = CacheSizer::new(config, max_heuristics, max_events);
+    info!(
+        heuristic_bounds = ?sizer.heuristic_bounds,
+        event_bounds = ?sizer.event_bounds,
+        interval_ms = interval.as_millis() as u64,
+        "Adaptive cache sizing enabled"
+    );
+
+    loop {
+        tokio::time::sleep(interval).await;
+        let resizes = sizer.check(&mut *cache.write().await);
+        if resizes.is_empty() {
+            debug!("Cache sizes unchanged");
+        }
+        for resize in resizes {
+            info!(cache = resize.cache, from = resize.from, to = resize.to, reason = resize.reason, "Cache resized");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{current_time_ms, CacheConfig, CachedEvent, CachedHeuristic, Condition};
+    use uuid::Uuid;
+
+    fn heuristic(id: Uuid) -> CachedHeuristic {
+        CachedHeuristic {
+            id,
+            name: "h".to_string(),
+            condition: Condition::text("h"),
+            effects: serde_json::json!({}).into(),
+            confidence: 0.9,
+            origin: "user".to_string(),
+            source: String::new(),
+            condition_embedding: Default::default(),
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        }
+    }
+
+    fn event(text: &str, timestamp_ms: i64) -> CachedEvent {
+        CachedEvent {
+            id: Uuid::new_v4(),
+            timestamp_ms,
+            source: "game".to_string(),
+            raw_text: text.to_string(),
+            embedding: Default::default(),
+            access_count: 0,
+            last_accessed_ms: 0,
+        }
+    }
+
+    fn sizing_config() -> CacheSizingConfig {
+        CacheSizingConfig {
+            interval_ms: 1000,
+            min_heuristics: Some(2),
+            max_heuristics: Some(6),
+            min_events: None,
+            max_events: None,
+            target_hit_rate: 0.8,
+            step: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_sizer_grows_on_churn_and_shrinks_when_idle() {
+        let mut cache = MemoryCache::new(CacheConfig { max_heuristics: 4, max_events: 8, ..CacheConfig::default() });
+        let mut sizer = CacheSizer::new(sizing_config(), 4, 8);
+        let ids: Vec<Uuid> = (0..5).map(|_| Uuid::new_v4()).collect();
+        for id in &ids {
+            cache.add_heuristic(heuristic(*id));
+        }
+        assert!(sizer.check(&mut cache).is_empty(), "first check only records a baseline");
+
+        // The evicted heuristic comes back: churn, so capacity grows (and is capped)
+        let evicted = ids.iter().find(|id| cache.get_heuristic(id).is_none()).copied().unwrap();
+        cache.add_heuristic(heuristic(evicted));
+        let resizes = sizer.check(&mut cache);
+        assert_eq!(resizes[0], Resize { cache: "heuristics", from: 4, to: 6, reason: "evicted heuristics re-fetched within TTL" });
+        assert_eq!(cache.config().max_heuristics, 6);
+        // The event cache is empty and shrinks toward its derived minimum
+        assert_eq!(resizes[1].cache, "events");
+        assert_eq!(cache.config().max_events, 4);
+
+        // Hit rate on target, no evictions, well under half full: shrink to the bound
+        for id in &ids {
+            cache.remove_heuristic(id);
+        }
+        cache.add_heuristic(heuristic(Uuid::new_v4()));
+        cache.record_hit();
+        sizer.check(&mut cache);
+        assert_eq!(cache.config().max_heuristics, 3);
+        sizer.check(&mut cache);
+        sizer.check(&mut cache);
+        assert_eq!(cache.config().max_heuristics, 2);
+        assert_eq!(cache.stats().heuristic_count, 1);
+    }
+
+    #[test]
+    fn test_sizer_grows_on_missed_target_only_with_evictions() {
+        let config = CacheSizingConfig { min_events: Some(8), max_events: Some(8), ..sizing_config() };
+        let mut cache = MemoryCache::new(CacheConfig { max_heuristics: 4, max_events: 8, ..CacheConfig::default() });
+        let mut sizer = CacheSizer::new(config, 4, 8);
+        cache.add_heuristic(heuristic(Uuid::new_v4()));
+        sizer.check(&mut cache);
+
+        // Below target but nothing evicted: hold (and don't shrink either)
+        cache.record_hit();
+        cache.record_miss();
+        assert!(sizer.check(&mut cache).is_empty());
+
+        // Below target with LRU evictions: grow
+        for _ in 0..4 {
+            cache.add_heuristic(heuristic(Uuid::new_v4()));
+            cache.record_miss();
+        }
+        let resizes = sizer.check(&mut cache);
+        assert_eq!(resizes, vec![Resize { cache: "heuristics", from: 4, to: 6, reason: "hit rate below target with LRU evictions" }]);
+
+        // Already at the upper bound: no resize to report
+        for _ in 0..4 {
+            cache.add_heuristic(heuristic(Uuid::new_v4()));
+            cache.record_miss();
+        }
+        assert!(sizer.check(&mut cache).is_empty());
+        assert_eq!(cache.config().max_heuristics, 6);
+    }
+
+    #[test]
+    fn test_sizer_grows_events_on_refetch() {
+        let config = CacheSizingConfig { min_heuristics: Some(4), max_heuristics: Some(4), ..sizing_config() };
+        let mut cache = MemoryCache::new(CacheConfig { max_heuristics: 4, max_events: 2, ..CacheConfig::default() });
+        let mut sizer = CacheSizer::new(config, 4, 2);
+        let now = current_time_ms();
+        cache.add_event(event("creeper nearby", now - 2));
+        cache.add_event(event("lava ahead", now - 1));
+        sizer.check(&mut cache);
+
+        // Full with evictions and no refetch: hold
+        cache.add_event(event("zombie at the door", now));
+        assert!(sizer.check(&mut cache).is_empty());
+
+        // The evicted text comes back
+        cache.add_event(event("creeper nearby", now));
+        let resizes = sizer.check(&mut cache);
+        assert_eq!(resizes, vec![Resize { cache: "events", from: 2, to: 3, reason: "evicted event text seen again within TTL" }]);
+        assert_eq!(cache.config().max_events, 3);
+    }
+
+    #[test]
+    fn test_bounds_are_sane() {
+        let unset = CacheSizingConfig {
+            min_heuristics: None,
+            max_heuristics: None,
+            min_events: None,
+            max_events: None,
+            ..sizing_config()
+        };
+        let sizer = CacheSizer::new(unset, 1, 0);
+        assert_eq!(sizer.heuristic_bounds, (1, 4));
+        assert_eq!(sizer.event_bounds, (1, 1));
+
+        // A maximum below the minimum is raised to it
+        let inverted = CacheSizingConfig { min_heuristics: Some(10), max_heuristics: Some(5), ..sizing_config() };
+        assert_eq!(CacheSizer::new(inverted, 8, 8).heuristic_bounds, (10, 10));
+    }
+}
@@ -0,0 +1,305 @@
+//! Similarity calibration: raw cosine similarity to match probability.
+//!
+//! A raw similarity means different things for different corpora (0.7 is a
+//! near-paraphrase for one deployment's short sensor lines and a loose topical
+//! hit for another's chat), so one global `min_similarity` is brittle. Each
+//! deployment can label matches instead: SubmitCalibrationFeedback takes
+//! (similarity, relevant) pairs, and a logistic curve
+//! `p = 1 / (1 + exp(-(slope * similarity + intercept)))` is fitted to them
+//! (Platt scaling: Newton's method, with Platt's smoothed targets so a
+//! cleanly separated sample set doesn't push the slope to infinity).
+//!
+//! Once enough samples covering both labels are in, the curve is active:
+//! - Embedding matches report their calibrated probability
+//!   (`match_probability` in the match explanation)
+//! - Embedding matches below SALIENCE_MIN_MATCH_PROBABILITY are dropped, so
+//!   the next best match (or none) wins; lexical and storage matches have
+//!   their own scales and aren't calibrated
+//!
+//! The newest samples are kept (up to the configured maximum) and the curve is
+//! refitted after every submission. GetCalibrationCurve reports the fit and
+//! the curve sampled across [0, 1] for the dashboard, along with the raw
+//! similarity the probability floor corresponds to. With a path configured,
+//! samples are saved there after every submission and reloaded at startup.
+//!
+//! Configuration via environment variables (see `SalienceConfig`):
+//!   SALIENCE_CALIBRATION_MIN_SAMPLES: Samples needed before the curve applies (default: 50)
+//!   SALIENCE_CALIBRATION_MAX_SAMPLES: Newest samples kept (default: 5000)
+//!   SALIENCE_MIN_MATCH_PROBABILITY: Calibrated probability below which
+//!     embedding matches are dropped (default: 0 = never)
+//!   SALIENCE_CALIBRATION_PATH: File the samples are saved to (default: none = in memory only)
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Points the curve is sampled at for GetCalibrationCurve.
+const CURVE_POINTS: usize = 21;
+
+/// Newton iterations per fit (converges in well under this on real data).
+const MAX_ITERATIONS: usize = 50;
+
+/// L2 penalty keeping the fit finite on degenerate samples (all one similarity).
+const RIDGE: f64 = 1e-6;
+
+/// One labeled match: its raw similarity and whether it was relevant.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationSample {
+    pub similarity: f32,
+    pub relevant: bool,
+}
+
+/// Fitted mapping from raw similarity to match probability.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationCurve {
+    pub slope: f32,
+    pub intercept: f32,
+}
+
+impl CalibrationCurve {
+    /// Fit a curve to `samples` (None without samples).
+    pub fn fit(samples: &[CalibrationSample]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let positives = samples.iter().filter(|s| s.relevant).count() as f64;
+        let negatives = samples.len() as f64 - positives;
+        let (hi, lo) = ((positives + 1.0) / (positives + 2.0), 1.0 / (negatives + 2.0));
+
+        let (mut a, mut b) = (0.0f64, 0.0f64);
+        for _ in 0..MAX_ITERATIONS {
+            // Gradient and Hessian of the negative log-likelihood
+            let (mut ga, mut gb) = (RIDGE * a, RIDGE * b);
+            let (mut haa, mut hab, mut hbb) = (RIDGE, 0.0, RIDGE);
+            for sample in samples {
+                let s = sample.similarity as f64;
+                let p = sigmoid(a * s + b);
+                let residual = p - if sample.relevant { hi } else { lo };
+                let weight = (p * (1.0 - p)).max(1e-12);
+                ga += residual * s;
+                gb += residual;
+                haa += weight * s * s;
+                hab += weight * s;
+                hbb += weight;
+            }
+            let det = haa * hbb - hab * hab;
+            if det.abs() < 1e-18 {
+                break;
+            }
+            let (da, db) = ((hbb * ga - hab * gb) / det, (haa * gb - hab * ga) / det);
+            a -= da;
+            b -= db;
+            if da.abs() < 1e-9 && db.abs() < 1e-9 {
+                break;
+            }
+        }
+        (a.is_finite() && b.is_finite()).then_some(Self { slope: a as f32, intercept: b as f32 })
+    }
+
+    /// Calibrated probability that a match at `similarity` is relevant.
+    pub fn probability(&self, similarity: f32) -> f32 {
+        sigmoid((self.slope * similarity + self.intercept) as f64) as f32
+    }
+
+    /// Raw similarity at which the curve reaches `probability` (None if the
+    /// curve doesn't rise with similarity, or for a probability of 0 or 1).
+    pub fn similarity_for(&self, probability: f32) -> Option<f32> {
+        if self.slope <= 0.0 || probability <= 0.0 || probability >= 1.0 {
+            return None;
+        }
+        let logit = (probability / (1.0 - probability)).ln();
+        Some((logit - self.intercept) / self.slope)
+    }
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// The current calibration, as reported by GetCalibrationCurve.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationStatus {
+    /// Whether the curve applies to scoring
+    pub active: bool,
+    pub samples: usize,
+    pub positives: usize,
+    /// None until any samples are in
+    pub curve: Option<CalibrationCurve>,
+}
+
+#[derive(Debug, Default)]
+struct CalibrationState {
+    samples: VecDeque<CalibrationSample>,
+    curve: Option<CalibrationCurve>,
+    positives: usize,
+    path: Option<PathBuf>,
+}
+
+impl CalibrationState {
+    fn refit(&mut self) {
+        self.positives = self.samples.iter().filter(|s| s.relevant).count();
+        self.curve = CalibrationCurve::fit(self.samples.make_contiguous());
+    }
+}
+
+/// Labeled samples and the curve fitted to them.
+#[derive(Debug)]
+pub struct Calibrator {
+    min_samples: usize,
+    max_samples: usize,
+    state: Mutex<CalibrationState>,
+}
+
+impl Calibrator {
+    pub fn new(min_samples: usize, max_samples: usize) -> Self {
+        Self { min_samples, max_samples: max_samples.max(1), state: Mutex::new(CalibrationState::default()) }
+    }
+
+    /// Save samples to `path` from now on, first loading any saved there.
+    /// Returns how many were loaded.
+    pub fn persist_to(&self, path: impl AsRef<Path>) -> std::io::Result<usize> {
+        let path = path.as_ref();
+        let loaded: Vec<CalibrationSample> = match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(std::io::Error::other)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.path = Some(path.to_path_buf());
+        let count = loaded.len();
+        state.samples.extend(loaded);
+        self.trim(&mut state);
+        state.refit();
+        Ok(count)
+    }
+
+    /// Add labeled samples (non-finite similarities are skipped) and refit.
+    /// Returns how many were accepted.
+    pub fn submit(&self, samples: impl IntoIterator<Item = CalibrationSample>) -> usize {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut accepted = 0;
+        for sample in samples.into_iter().filter(|s| s.similarity.is_finite()) {
+            state.samples.push_back(sample);
+            accepted += 1;
+        }
+        if accepted == 0 {
+            return 0;
+        }
+        self.trim(&mut state);
+        state.refit();
+        if let Some(path) = state.path.clone() {
+            let saved = serde_json::to_string(&state.samples)
+                .map_err(std::io::Error::other)
+                .and_then(|json| std::fs::write(&path, json));
+            if let Err(e) = saved {
+                warn!(path = %path.display(), error = %e, "Calibration samples not saved");
+            }
+        }
+        accepted
+    }
+
+    fn trim(&self, state: &mut CalibrationState) {
+        while state.samples.len() > self.max_samples {
+            state.samples.pop_front();
+        }
+    }
+
+    /// The curve, if it applies to scoring: enough samples, both labels present.
+    pub fn active_curve(&self) -> Option<CalibrationCurve> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let both_labels = state.positives > 0 && state.positives < state.samples.len();
+        state.curve.filter(|_| both_labels && state.samples.len() >= self.min_samples)
+    }
+
+    /// Calibrated probability for `similarity` (None while inactive).
+    pub fn probability(&self, similarity: f32) -> Option<f32> {
+        self.active_curve().map(|curve| curve.probability(similarity))
+    }
+
+    pub fn status(&self) -> CalibrationStatus {
+        let active = self.active_curve().is_some();
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        CalibrationStatus { active, samples: state.samples.len(), positives: state.positives, curve: state.curve }
+    }
+}
+
+/// `curve` sampled at evenly spaced similarities across [0, 1].
+pub fn curve_points(curve: &CalibrationCurve) -> Vec<(f32, f32)> {
+    (0..CURVE_POINTS)
+        .map(|i| {
+            let similarity = i as f32 / (CURVE_POINTS - 1) as f32;
+            (similarity, curve.probability(similarity))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labeled(pairs: &[(f32, bool)]) -> Vec<CalibrationSample> {
+        pairs.iter().map(|&(similarity, relevant)| CalibrationSample { similarity, relevant }).collect()
+    }
+
+    /// Relevant above 0.8, irrelevant below, with some overlap around it.
+    fn corpus() -> Vec<CalibrationSample> {
+        (0..100)
+            .map(|i| {
+                let similarity = 0.6 + 0.4 * i as f32 / 100.0;
+                let relevant = similarity > 0.8 || (similarity > 0.75 && i % 2 == 0);
+                CalibrationSample { similarity, relevant }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fit_rises_with_similarity() {
+        let curve = CalibrationCurve::fit(&corpus()).unwrap();
+        assert!(curve.slope > 0.0);
+        assert!(curve.probability(0.95) > 0.9);
+        assert!(curve.probability(0.65) < 0.1);
+        let midpoint = curve.similarity_for(0.5).unwrap();
+        assert!((0.74..0.82).contains(&midpoint), "midpoint {}", midpoint);
+        assert!((curve.probability(midpoint) - 0.5).abs() < 1e-3);
+
+        // Separable samples still fit to a finite curve
+        let separable = CalibrationCurve::fit(&labeled(&[(0.9, true), (0.2, false)])).unwrap();
+        assert!(separable.probability(0.9) > 0.5 && separable.probability(0.2) < 0.5);
+        assert!(CalibrationCurve::fit(&[]).is_none());
+    }
+
+    #[test]
+    fn test_inactive_until_enough_samples_of_both_labels() {
+        let calibrator = Calibrator::new(4, 3);
+        assert_eq!(calibrator.submit(labeled(&[(0.9, true), (0.95, true), (f32::NAN, false)])), 2);
+        assert!(calibrator.probability(0.9).is_none());
+
+        calibrator.submit(labeled(&[(0.3, false), (0.2, false)]));
+        // Capped at the newest 3
+        let status = calibrator.status();
+        assert_eq!((status.samples, status.positives), (3, 1));
+        assert!(!status.active);
+
+        let calibrator = Calibrator::new(4, 100);
+        calibrator.submit(corpus());
+        assert!(calibrator.status().active);
+        assert!(calibrator.probability(0.95).unwrap() > 0.9);
+        assert_eq!(curve_points(&calibrator.active_curve().unwrap()).len(), CURVE_POINTS);
+    }
+
+    #[test]
+    fn test_persists_samples() {
+        let path = std::env::temp_dir().join(format!("calibration-{}.json", uuid::Uuid::new_v4()));
+        let calibrator = Calibrator::new(1, 100);
+        assert_eq!(calibrator.persist_to(&path).unwrap(), 0);
+        calibrator.submit(labeled(&[(0.9, true), (0.3, false)]));
+
+        let reloaded = Calibrator::new(1, 100);
+        assert_eq!(reloaded.persist_to(&path).unwrap(), 2);
+        assert_eq!(reloaded.status().curve, calibrator.status().curve);
+        let _ = std::fs::remove_file(&path);
+    }
+}
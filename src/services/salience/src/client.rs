@@ -1,21 +1,31 @@
 //! gRPC client for Python storage backend.
 //!
 //! This module provides a Rust client to communicate with the Python
-//! MemoryStorage gRPC service for persistent storage operations.
+//! MemoryStorage gRPC service for persistent storage operations. Its
+//! configuration, the message builders and the embedding byte encoding live
+//! in the `gladys-memory-client` crate (re-exported here), so other Rust
+//! components can use them without the server.
 
-use std::time::Duration;
+use std::future::Future;
+use std::time::Instant;
 use thiserror::Error;
 use tonic::transport::{Channel, Endpoint};
-use tonic::Request;
-use tracing::{debug, instrument};
-use uuid::Uuid;
+use tonic::{Request, Response, Status};
+use tracing::{debug, instrument, warn};
 
 use crate::logging::TRACE_ID_HEADER;
+use crate::metrics::{code_label, storage_client_metrics};
+
+pub use gladys_memory_client::{
+    bytes_to_embedding, embedding_to_bytes, BuildError, ClientConfig, EmbeddingSource, EventBuilder, HeuristicBuilder,
+    RetryPolicy,
+};
 
 use crate::proto::{
-    memory_storage_client::MemoryStorageClient, EpisodicEvent, GenerateEmbeddingRequest,
-    Heuristic, HeuristicMatch, QueryByTimeRequest, QueryBySimilarityRequest, QueryHeuristicsRequest,
-    QueryMatchingHeuristicsRequest, SalienceResult, StoreEventRequest, StoreHeuristicRequest,
+    memory_storage_client::MemoryStorageClient, EpisodicEvent, EventSalienceUpdate, GenerateEmbeddingRequest,
+    GetHeuristicRequest, GetSchemaVersionRequest, Heuristic, HeuristicMatch, QueryByTimeRequest, QueryBySimilarityRequest,
+    QueryHeuristicsRequest, QueryHeuristicsResponse, QueryMatchingHeuristicsRequest, StoreEventRequest, StoreHeuristicRequest,
+    UpdateEventSalienceRequest,
 };
 
 /// Errors from the storage client.
@@ -34,27 +44,6 @@ pub enum ClientError {
     InvalidResponse,
 }
 
-/// Configuration for the storage client.
-#[derive(Clone, Debug)]
-pub struct ClientConfig {
-    /// Address of the Python storage service (e.g., "http://localhost:50051")
-    pub address: String,
-    /// Connection timeout
-    pub connect_timeout: Duration,
-    /// Request timeout
-    pub request_timeout: Duration,
-}
-
-impl Default for ClientConfig {
-    fn default() -> Self {
-        Self {
-            address: "http://localhost:50051".to_string(),
-            connect_timeout: Duration::from_secs(5),
-            request_timeout: Duration::from_secs(30),
-        }
-    }
-}
-
 /// Client for the Python storage backend.
 pub struct StorageClient {
     client: MemoryStorageClient<Channel>,
@@ -73,7 +62,11 @@ impl StorageClient {
             .connect_timeout(config.connect_timeout)
             .timeout(config.request_timeout);
 
-        let channel = endpoint.connect().await?;
+        let started = Instant::now();
+        let channel = endpoint.connect().await;
+        let code = if channel.is_ok() { "OK" } else { "ConnectFailed" };
+        storage_client_metrics().record("connect", started.elapsed(), code);
+        let channel = channel?;
         let client = MemoryStorageClient::new(channel);
 
         debug!("Connected to storage service");
@@ -97,22 +90,91 @@ impl StorageClient {
         request
     }
 
+    /// Issue one RPC with trace metadata, deadline, retries, and metrics applied.
+    ///
+    /// `storage_error` extracts the error payload from a response that
+    /// succeeded at the gRPC level but failed in the storage service.
+    async fn call<Req, Resp, F, Fut>(
+        &self,
+        method: &'static str,
+        message: Req,
+        mut rpc: F,
+        storage_error: impl Fn(&Resp) -> Option<String>,
+    ) -> Result<Resp, ClientError>
+    where
+        Req: Clone,
+        F: FnMut(MemoryStorageClient<Channel>, Request<Req>) -> Fut,
+        Fut: Future<Output = Result<Response<Resp>, Status>>,
+    {
+        let metrics = storage_client_metrics();
+        let mut attempt = 0;
+
+        loop {
+            let mut request = self.add_trace_header(Request::new(message.clone()));
+            request.set_timeout(self.config.request_timeout);
+
+            let started = Instant::now();
+            match rpc(self.client.clone(), request).await {
+                Ok(response) => {
+                    let response = response.into_inner();
+                    if let Some(error) = storage_error(&response) {
+                        metrics.record(method, started.elapsed(), "StorageError");
+                        return Err(ClientError::StorageError(error));
+                    }
+                    metrics.record(method, started.elapsed(), "OK");
+                    return Ok(response);
+                }
+                Err(status) => {
+                    metrics.record(method, started.elapsed(), &code_label(status.code()));
+                    if !self.config.retry.should_retry(attempt, status.code()) {
+                        return Err(status.into());
+                    }
+                    let backoff = self.config.retry.backoff(attempt);
+                    attempt += 1;
+                    warn!(method, attempt, backoff_ms = backoff.as_millis() as u64, "Retrying storage RPC");
+                    tokio::time::sleep(backoff).await;
+                }
+            }
+        }
+    }
+
     /// Store an episodic event.
     #[instrument(skip(self, event), fields(event_id = %event.id))]
     pub async fn store_event(&mut self, event: EpisodicEvent) -> Result<(), ClientError> {
         debug!("Storing event");
 
         let request = StoreEventRequest { event: Some(event) };
-        let response = self.client.store_event(request).await?.into_inner();
-
-        if !response.success {
-            return Err(ClientError::StorageError(response.error));
-        }
+        self.call(
+            "store_event",
+            request,
+            |mut c, r| async move { c.store_event(r).await },
+            |r| (!r.success).then(|| r.error.clone()),
+        )
+        .await?;
 
         debug!("Event stored successfully");
         Ok(())
     }
 
+    /// Record salience decisions on stored events; returns how many were updated.
+    #[instrument(skip(self, updates), fields(count = updates.len()))]
+    pub async fn update_event_salience(&mut self, updates: Vec<EventSalienceUpdate>) -> Result<i32, ClientError> {
+        debug!("Updating event salience");
+
+        let request = UpdateEventSalienceRequest { updates };
+        let response = self
+            .call(
+                "update_event_salience",
+                request,
+                |mut c, r| async move { c.update_event_salience(r).await },
+                |r| (!r.success).then(|| r.error.clone()),
+            )
+            .await?;
+
+        debug!(updated = response.updated, "Event salience updated");
+        Ok(response.updated)
+    }
+
     /// Query events by time range.
     #[instrument(skip(self))]
     pub async fn query_by_time(
@@ -131,11 +193,14 @@ impl StorageClient {
             limit,
         };
 
-        let response = self.client.query_by_time(request).await?.into_inner();
-
-        if !response.error.is_empty() {
-            return Err(ClientError::StorageError(response.error));
-        }
+        let response = self
+            .call(
+                "query_by_time",
+                request,
+                |mut c, r| async move { c.query_by_time(r).await },
+                |r| error_field(&r.error),
+            )
+            .await?;
 
         debug!(count = response.events.len(), "Retrieved events");
         Ok(response.events)
@@ -161,34 +226,46 @@ impl StorageClient {
             limit,
         };
 
-        let response = self.client.query_by_similarity(request).await?.into_inner();
-
-        if !response.error.is_empty() {
-            return Err(ClientError::StorageError(response.error));
-        }
+        let response = self
+            .call(
+                "query_by_similarity",
+                request,
+                |mut c, r| async move { c.query_by_similarity(r).await },
+                |r| error_field(&r.error),
+            )
+            .await?;
 
         debug!(count = response.events.len(), "Retrieved similar events");
         Ok(response.events)
     }
 
     /// Generate embedding for text.
-    #[instrument(skip(self, text))]
     pub async fn generate_embedding(&mut self, text: &str) -> Result<Vec<f32>, ClientError> {
+        Ok(self.generate_embedding_with_model(text).await?.0)
+    }
+
+    /// Generate embedding for text, with the version of the model that
+    /// produced it (empty if storage doesn't report one).
+    #[instrument(skip(self, text))]
+    pub async fn generate_embedding_with_model(&mut self, text: &str) -> Result<(Vec<f32>, String), ClientError> {
         debug!("Generating embedding");
 
         let request = GenerateEmbeddingRequest {
             text: text.to_string(),
         };
 
-        let response = self.client.generate_embedding(request).await?.into_inner();
-
-        if !response.error.is_empty() {
-            return Err(ClientError::StorageError(response.error));
-        }
+        let response = self
+            .call(
+                "generate_embedding",
+                request,
+                |mut c, r| async move { c.generate_embedding(r).await },
+                |r| error_field(&r.error),
+            )
+            .await?;
 
         let embedding = bytes_to_embedding(&response.embedding);
-        debug!(dims = embedding.len(), "Generated embedding");
-        Ok(embedding)
+        debug!(dims = embedding.len(), model = %response.model_version, "Generated embedding");
+        Ok((embedding, response.model_version))
     }
 
     /// Store a heuristic.
@@ -203,24 +280,70 @@ impl StorageClient {
             generate_embedding,
         };
 
-        let response = self.client.store_heuristic(request).await?.into_inner();
-
-        if !response.success {
-            return Err(ClientError::StorageError(response.error));
-        }
+        self.call(
+            "store_heuristic",
+            request,
+            |mut c, r| async move { c.store_heuristic(r).await },
+            |r| (!r.success).then(|| r.error.clone()),
+        )
+        .await?;
 
         debug!("Heuristic stored successfully");
         Ok(())
     }
 
+    /// Fetch one heuristic by ID (including its fire and success counts).
+    #[instrument(skip(self))]
+    pub async fn get_heuristic(&mut self, id: &str) -> Result<Heuristic, ClientError> {
+        let request = GetHeuristicRequest { id: id.to_string() };
+        let response = self
+            .call(
+                "get_heuristic",
+                request,
+                |mut c, r| async move { c.get_heuristic(r).await },
+                |r| error_field(&r.error),
+            )
+            .await?;
+        response.heuristic.ok_or(ClientError::InvalidResponse)
+    }
+
+    /// Storage's memory.proto contract version, announcing ours.
+    #[instrument(skip(self))]
+    pub async fn get_schema_version(&mut self, client_version: &str) -> Result<String, ClientError> {
+        let request = GetSchemaVersionRequest { client_version: client_version.to_string() };
+        let response = self
+            .call(
+                "get_schema_version",
+                request,
+                |mut c, r| async move { c.get_schema_version(r).await },
+                |_| None,
+            )
+            .await?;
+        Ok(response.version)
+    }
+
     /// Query heuristics above a confidence threshold.
     /// Returns HeuristicMatch which includes similarity scores (CBR schema).
-    #[instrument(skip(self))]
     pub async fn query_heuristics(
         &mut self,
         min_confidence: f32,
         limit: i32,
     ) -> Result<Vec<HeuristicMatch>, ClientError> {
+        Ok(self.query_heuristics_since(min_confidence, limit, 0).await?.matches)
+    }
+
+    /// Query heuristics updated after `updated_since_ms` (0 = all).
+    /// The response also lists heuristics deleted since then.
+    ///
+    /// Matches are also filtered locally, so older storage servers that
+    /// ignore the field still behave as a conditional fetch.
+    #[instrument(skip(self))]
+    pub async fn query_heuristics_since(
+        &mut self,
+        min_confidence: f32,
+        limit: i32,
+        updated_since_ms: i64,
+    ) -> Result<QueryHeuristicsResponse, ClientError> {
         debug!("Querying heuristics");
 
         let request = QueryHeuristicsRequest {
@@ -229,16 +352,29 @@ impl StorageClient {
             min_similarity: 0.0,
             min_confidence,
             limit,
+            updated_since_ms,
         };
 
-        let response = self.client.query_heuristics(request).await?.into_inner();
-
-        if !response.error.is_empty() {
-            return Err(ClientError::StorageError(response.error));
-        }
-
-        debug!(count = response.matches.len(), "Retrieved heuristics");
-        Ok(response.matches)
+        let response = self
+            .call(
+                "query_heuristics",
+                request,
+                |mut c, r| async move { c.query_heuristics(r).await },
+                |r| error_field(&r.error),
+            )
+            .await?;
+
+        let mut response = response;
+        response.matches.retain(|m| {
+            updated_since_ms == 0
+                || m.heuristic.as_ref().is_some_and(|h| h.updated_at_ms > updated_since_ms)
+        });
+        debug!(
+            count = response.matches.len(),
+            deleted = response.deleted_ids.len(),
+            "Retrieved heuristics"
+        );
+        Ok(response)
     }
 
     /// Query heuristics matching event text using PostgreSQL full-text search.
@@ -260,12 +396,14 @@ impl StorageClient {
             source_filter: source_filter.unwrap_or("").to_string(),
         };
 
-        let request = self.add_trace_header(Request::new(request));
-        let response = self.client.query_matching_heuristics(request).await?.into_inner();
-
-        if !response.error.is_empty() {
-            return Err(ClientError::StorageError(response.error));
-        }
+        let response = self
+            .call(
+                "query_matching_heuristics",
+                request,
+                |mut c, r| async move { c.query_matching_heuristics(r).await },
+                |r| error_field(&r.error),
+            )
+            .await?;
 
         debug!(count = response.matches.len(), "Retrieved matching heuristics");
         Ok(response.matches)
@@ -277,207 +415,75 @@ impl StorageClient {
     }
 }
 
-// ============================================================================
-// Embedding conversion utilities
-// ============================================================================
+/// Lets the message builders embed through storage
+/// (`EventBuilder::with_embedding_from` and friends).
+impl EmbeddingSource for StorageClient {
+    type Error = ClientError;
 
-/// Convert f32 slice to bytes (little-endian).
-pub fn embedding_to_bytes(embedding: &[f32]) -> Vec<u8> {
-    let mut bytes = Vec::with_capacity(embedding.len() * 4);
-    for &value in embedding {
-        bytes.extend_from_slice(&value.to_le_bytes());
+    fn embed(&mut self, text: &str) -> impl Future<Output = Result<(Vec<f32>, String), ClientError>> + Send {
+        self.generate_embedding_with_model(text)
     }
-    bytes
-}
-
-/// Convert bytes to f32 vector (little-endian).
-pub fn bytes_to_embedding(bytes: &[u8]) -> Vec<f32> {
-    bytes
-        .chunks_exact(4)
-        .map(|chunk| {
-            let arr: [u8; 4] = chunk.try_into().unwrap();
-            f32::from_le_bytes(arr)
-        })
-        .collect()
 }
 
-// ============================================================================
-// Builder helpers for protobuf messages
-// ============================================================================
-
-/// Builder for creating EpisodicEvent messages.
-pub struct EventBuilder {
-    event: EpisodicEvent,
+/// Treat a non-empty `error` string field as a storage error.
+fn error_field(error: &str) -> Option<String> {
+    (!error.is_empty()).then(|| error.to_string())
 }
 
-impl EventBuilder {
-    pub fn new(id: Uuid, source: &str, raw_text: &str) -> Self {
-        Self {
-            event: EpisodicEvent {
-                id: id.to_string(),
-                timestamp_ms: chrono_now_ms(),
-                source: source.to_string(),
-                raw_text: raw_text.to_string(),
-                embedding: Vec::new(),
-                salience: None,
-                structured_json: "{}".to_string(),
-                entity_ids: Vec::new(),
-                // Prediction instrumentation (Â§27) - defaults to 0.0/empty
-                predicted_success: 0.0,
-                prediction_confidence: 0.0,
-                response_id: String::new(),
-                response_text: String::new(),
-                matched_heuristic_id: String::new(),
-                llm_prompt_text: String::new(),
-                decision_path: String::new(),
-                episode_id: String::new(),
-                // Phase 2 sensor contract extensions
-                intent: String::new(),
-                evaluation_data_json: String::new(),
-            },
-        }
-    }
-
-    pub fn timestamp_ms(mut self, ts: i64) -> Self {
-        self.event.timestamp_ms = ts;
-        self
-    }
-
-    pub fn embedding(mut self, embedding: &[f32]) -> Self {
-        self.event.embedding = embedding_to_bytes(embedding);
-        self
-    }
-
-    pub fn salience(mut self, salience: SalienceResult) -> Self {
-        self.event.salience = Some(salience);
-        self
-    }
-
-    pub fn structured_json(mut self, json: &str) -> Self {
-        self.event.structured_json = json.to_string();
-        self
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tonic::Code;
+
+    #[tokio::test]
+    async fn test_trace_header_added_when_set() {
+        let channel = Endpoint::from_static("http://localhost:1").connect_lazy();
+        let client = StorageClient {
+            client: MemoryStorageClient::new(channel),
+            config: ClientConfig::default(),
+            trace_id: None,
+        };
 
-    pub fn entity_ids(mut self, ids: Vec<Uuid>) -> Self {
-        self.event.entity_ids = ids.into_iter().map(|id| id.to_string()).collect();
-        self
-    }
+        let request = client.add_trace_header(Request::new(()));
+        assert!(request.metadata().get(TRACE_ID_HEADER).is_none());
 
-    pub fn build(self) -> EpisodicEvent {
-        self.event
+        let client = client.with_trace_id("abc123def456".to_string());
+        let request = client.add_trace_header(Request::new(()));
+        assert_eq!(
+            request.metadata().get(TRACE_ID_HEADER).and_then(|v| v.to_str().ok()),
+            Some("abc123def456")
+        );
     }
-}
 
-/// Builder for creating Heuristic messages (CBR schema).
-pub struct HeuristicBuilder {
-    heuristic: Heuristic,
-}
-
-impl HeuristicBuilder {
-    pub fn new(id: Uuid, name: &str) -> Self {
-        Self {
-            heuristic: Heuristic {
-                id: id.to_string(),
-                name: name.to_string(),
-                condition_text: String::new(),
-                condition_embedding: Vec::new(),
-                similarity_threshold: 0.7,
-                effects_json: "{}".to_string(),
-                confidence: 0.5,
-                origin: "user".to_string(),
-                origin_id: String::new(),
-                next_heuristic_ids: Vec::new(),
-                is_terminal: true,
-                last_fired_ms: 0,
-                fire_count: 0,
-                success_count: 0,
-                created_at_ms: chrono_now_ms(),
-                updated_at_ms: chrono_now_ms(),
-                source: String::new(),
+    #[tokio::test]
+    async fn test_call_retries_and_records_every_attempt() {
+        let channel = Endpoint::from_static("http://127.0.0.1:1").connect_lazy();
+        let mut client = StorageClient {
+            client: MemoryStorageClient::new(channel),
+            config: ClientConfig {
+                retry: RetryPolicy { max_retries: 2, initial_backoff: Duration::from_millis(1) },
+                ..ClientConfig::default()
             },
-        }
-    }
-
-    pub fn source(mut self, source: &str) -> Self {
-        self.heuristic.source = source.to_string();
-        self
-    }
-
-    pub fn condition_text(mut self, text: &str) -> Self {
-        self.heuristic.condition_text = text.to_string();
-        self
-    }
-
-    pub fn effects_json(mut self, json: &str) -> Self {
-        self.heuristic.effects_json = json.to_string();
-        self
-    }
-
-    pub fn confidence(mut self, confidence: f32) -> Self {
-        self.heuristic.confidence = confidence;
-        self
-    }
-
-    pub fn origin(mut self, origin: &str) -> Self {
-        self.heuristic.origin = origin.to_string();
-        self
-    }
-
-    pub fn build(self) -> Heuristic {
-        self.heuristic
-    }
-}
-
-/// Get current time as milliseconds since Unix epoch.
-fn chrono_now_ms() -> i64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as i64
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
+            trace_id: None,
+        };
 
-    #[test]
-    fn test_embedding_roundtrip() {
-        let original: Vec<f32> = (0..384).map(|i| i as f32 * 0.001).collect();
-        let bytes = embedding_to_bytes(&original);
-        let recovered = bytes_to_embedding(&bytes);
-        assert_eq!(original.len(), recovered.len());
-        for (a, b) in original.iter().zip(recovered.iter()) {
-            assert!((a - b).abs() < 1e-6);
-        }
-    }
+        let before = storage_client_metrics()
+            .snapshot()
+            .get("generate_embedding")
+            .map_or(0, |s| s.calls);
+        let result = client.generate_embedding("unreachable").await;
+        assert!(matches!(result, Err(ClientError::RpcFailed(ref s)) if s.code() == Code::Unavailable));
 
-    #[test]
-    fn test_event_builder() {
-        let id = Uuid::new_v4();
-        let event = EventBuilder::new(id, "test_sensor", "Something happened")
-            .embedding(&[0.1, 0.2, 0.3])
-            .structured_json(r#"{"key": "value"}"#)
-            .build();
-
-        assert_eq!(event.id, id.to_string());
-        assert_eq!(event.source, "test_sensor");
-        assert_eq!(event.raw_text, "Something happened");
-        assert!(!event.embedding.is_empty());
+        let after = storage_client_metrics().snapshot()["generate_embedding"].calls;
+        assert_eq!(after - before, 3);
     }
 
-    #[test]
-    fn test_heuristic_builder() {
-        let id = Uuid::new_v4();
-        let heuristic = HeuristicBuilder::new(id, "greet_user")
-            .condition_text("user entered the room")
-            .effects_json(r#"{"salience": {"social": 0.7}}"#)
-            .confidence(0.9)
-            .origin("test")
-            .build();
-
-        assert_eq!(heuristic.id, id.to_string());
-        assert_eq!(heuristic.name, "greet_user");
-        assert_eq!(heuristic.confidence, 0.9);
-        assert_eq!(heuristic.condition_text, "user entered the room");
+    #[tokio::test]
+    async fn test_builders_embed_through_storage() {
+        let channel = Endpoint::from_static("http://127.0.0.1:1").connect_lazy();
+        let mut client = StorageClient { client: MemoryStorageClient::new(channel), config: ClientConfig::default(), trace_id: None };
+        let result = EventBuilder::with_embedding_from(&mut client, uuid::Uuid::new_v4(), "sensor", "door opened").await;
+        assert!(matches!(result, Err(ClientError::RpcFailed(ref s)) if s.code() == Code::Unavailable));
     }
 }
@@ -0,0 +1,386 @@
+//! Compatibility layer between the wire messages and the evaluation path.
+//!
+//! Orchestrator builds roll out independently of this service: older ones
+//! send requests without fields added since (prost fills in zero values), and
+//! newer ones expect response fields the older handler code never set. The
+//! evaluation path works on `EvaluationRequest` / `Evaluation` instead of the
+//! proto messages, and the `From` impls here are the only place that decides
+//! what a missing, malformed or deprecated field means:
+//!
+//! - Threshold overrides that are unset or not finite use the server defaults.
+//!   A NaN override would otherwise be clamped to NaN and block every match.
+//! - `skip_novelty_detection` is deprecated. The fast path never runs novelty
+//!   detection, so the field is ignored and warned about once per process.
+//! - `structured_json` and `entity_ids` are not used by the fast path and are
+//!   not carried over.
+//! - Every response field is always populated. `from_cache` is derived from
+//!   the match, and `novelty_detection_skipped` is always true.
+//! - The matched heuristic's effects go out whole as `matched_effects_json`
+//!   (keys the fast path doesn't interpret included), so structured actions
+//!   reach the Executive as authored.
+//! - A storage heuristic with a malformed ID is dropped. Malformed
+//!   `effects_json` is logged and read as no effects (flagged as a validation
+//!   issue at cache insert).
+//! - Flush filter fields left empty (or a negative age) match anything.
+//!
+//! `SalienceResult` is the shared value type (post-processing, boosts and
+//! routing all work on it), so it passes through unchanged.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tracing::warn;
+
+use crate::actions::ActionExecution;
+use crate::client::{bytes_to_embedding, embedding_to_bytes, HeuristicBuilder};
+use crate::conflicts::Conflict;
+use crate::domain::{Condition, Effects, Event, Exemplar, Heuristic};
+use crate::embedding_model::ReEmbedProgress;
+use crate::explain::MatchExplanation;
+use crate::proto::{self, EvaluateSalienceRequest, EvaluateSalienceResponse, RoutingHint, SalienceResult};
+use crate::{HeuristicFilter, ScoreThresholds};
+
+/// One event to evaluate, with wire defaults resolved.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EvaluationRequest {
+    pub event: Event,
+    /// Per-request threshold overrides (None = server/variant defaults)
+    pub min_similarity: Option<f32>,
+    pub min_confidence: Option<f32>,
+    /// Report per-stage timings
+    pub debug: bool,
+}
+
+/// Result of evaluating one event, before it is put on the wire.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    pub salience: SalienceResult,
+    /// Best matching heuristic (None = no match)
+    pub matched_heuristic_id: Option<String>,
+    /// "<CODE>: <message>" when scoring failed
+    pub error: Option<String>,
+    /// Thresholds actually used (after overrides/clamping)
+    pub thresholds: ScoreThresholds,
+    pub dominant_dimension: String,
+    pub composite_score: f32,
+    pub routing_hint: RoutingHint,
+    /// Stage -> milliseconds; only filled for debug requests
+    pub timings_ms: HashMap<String, f32>,
+    /// Experiment variant that scored the event
+    pub experiment_variant: Option<String>,
+    /// Fallback was rate limited: baseline salience only
+    pub degraded: bool,
+    /// Scored without a dependency; the degradation policy asks for a re-evaluation
+    pub retry_suggested: bool,
+    /// Earlier event with the same text within the dedup window
+    pub duplicate_of: Option<String>,
+    /// The matched heuristic's effects (None = no match)
+    pub matched_effects: Option<Effects>,
+    /// System-1 action run for the match (None = none ran)
+    pub executed_action: Option<ActionExecution>,
+    /// Matches held back by their heuristic's cooldown
+    pub cooled_down: Vec<CooledDown>,
+    /// Opposing boosts among the top matches, as resolved
+    pub conflict: Option<Conflict>,
+    /// Detected language of the event text (None = detection off or undetermined)
+    pub language: Option<String>,
+    /// Why the match matched (None = no match)
+    pub explanation: Option<MatchExplanation>,
+}
+
+/// A match held back because its heuristic fired recently.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CooledDown {
+    pub heuristic_id: String,
+    pub remaining_ms: i64,
+    /// Kept with scaled-down boosts rather than dropped
+    pub dampened: bool,
+}
+
+static SKIP_NOVELTY_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Warn about a deprecated field the first time any request sets it.
+fn warn_deprecated(flag: &AtomicBool, field: &str, note: &str) {
+    if !flag.swap(true, Ordering::Relaxed) {
+        warn!(field = field, "Request uses deprecated field ({}); further uses are not logged", note);
+    }
+}
+
+/// An override that isn't a finite number is treated as unset.
+fn threshold_override(field: &str, value: Option<f32>) -> Option<f32> {
+    match value {
+        Some(v) if !v.is_finite() => {
+            warn!(field = field, value = %v, "Ignoring non-finite threshold override");
+            None
+        }
+        other => other,
+    }
+}
+
+impl From<&EvaluateSalienceRequest> for EvaluationRequest {
+    fn from(req: &EvaluateSalienceRequest) -> Self {
+        if req.skip_novelty_detection {
+            warn_deprecated(&SKIP_NOVELTY_WARNED, "skip_novelty_detection", "the fast path never runs novelty detection");
+        }
+        Self {
+            event: Event {
+                id: req.event_id.clone(),
+                source: req.source.clone(),
+                raw_text: req.raw_text.clone(),
+            },
+            min_similarity: threshold_override("min_similarity", req.min_similarity),
+            min_confidence: threshold_override("min_confidence", req.min_confidence),
+            debug: req.debug,
+        }
+    }
+}
+
+impl From<Evaluation> for EvaluateSalienceResponse {
+    fn from(evaluation: Evaluation) -> Self {
+        Self {
+            salience: Some(evaluation.salience),
+            from_cache: evaluation.matched_heuristic_id.is_some(),
+            matched_heuristic_id: evaluation.matched_heuristic_id.unwrap_or_default(),
+            error: evaluation.error.unwrap_or_default(),
+            // Rust fast path never does novelty detection (no embedding model)
+            novelty_detection_skipped: true,
+            effective_min_similarity: evaluation.thresholds.min_similarity,
+            effective_min_confidence: evaluation.thresholds.min_confidence,
+            dominant_dimension: evaluation.dominant_dimension,
+            composite_score: evaluation.composite_score,
+            routing_hint: evaluation.routing_hint.into(),
+            timings_ms: evaluation.timings_ms,
+            experiment_variant: evaluation.experiment_variant.unwrap_or_default(),
+            degraded: evaluation.degraded,
+            retry_suggested: evaluation.retry_suggested,
+            duplicate_of: evaluation.duplicate_of.unwrap_or_default(),
+            matched_effects_json: evaluation.matched_effects.map(|e| e.to_json()).unwrap_or_default(),
+            executed_action: evaluation.executed_action.map(proto::ActionExecution::from),
+            cooled_down: evaluation
+                .cooled_down
+                .into_iter()
+                .map(|c| proto::CooledDownMatch {
+                    heuristic_id: c.heuristic_id,
+                    remaining_ms: c.remaining_ms,
+                    dampened: c.dampened,
+                })
+                .collect(),
+            conflict: evaluation.conflict.map(|c| proto::MatchConflict {
+                heuristic_ids: c.heuristic_ids,
+                dimensions: c.dimensions,
+                resolution: c.resolution.as_str().to_string(),
+            }),
+            language: evaluation.language.unwrap_or_default(),
+            explanation: evaluation.explanation.map(proto::MatchExplanation::from),
+            filtered: false,
+            shed: false,
+            maintenance: false,
+        }
+    }
+}
+
+impl From<MatchExplanation> for proto::MatchExplanation {
+    fn from(explanation: MatchExplanation) -> Self {
+        Self {
+            method: explanation.method.as_str().to_string(),
+            spans: explanation
+                .spans
+                .into_iter()
+                .map(|s| proto::MatchedSpan { term: s.term, start: s.start as i32, end: s.end as i32 })
+                .collect(),
+            condition_sentence: explanation.condition_sentence,
+            similarity: explanation.similarity,
+            runner_up_heuristic_id: explanation.runner_up_heuristic_id.unwrap_or_default(),
+            similarity_margin: explanation.similarity_margin,
+            match_probability: explanation.match_probability,
+            negative_exemplar: explanation.negative.as_ref().map(|n| n.text.clone()).unwrap_or_default(),
+            negative_similarity: explanation.negative.as_ref().map_or(0.0, |n| n.similarity),
+            negative_penalty: explanation.negative.as_ref().map_or(0.0, |n| n.penalty),
+        }
+    }
+}
+
+impl From<ReEmbedProgress> for proto::ReEmbedCacheProgress {
+    fn from(progress: ReEmbedProgress) -> Self {
+        Self {
+            total: progress.total as i32,
+            embedded: progress.embedded as i32,
+            failed: progress.failed as i32,
+            swapped: progress.swapped as i32,
+            done: progress.done,
+            embedding_model: progress.model,
+        }
+    }
+}
+
+impl From<&proto::FlushFilter> for HeuristicFilter {
+    fn from(filter: &proto::FlushFilter) -> Self {
+        let set = |s: &str| (!s.is_empty()).then(|| s.to_string());
+        Self {
+            group: set(&filter.group),
+            source: set(&filter.source),
+            origin: set(&filter.origin),
+            min_age_ms: filter.min_age_ms.max(0),
+        }
+    }
+}
+
+impl From<ActionExecution> for proto::ActionExecution {
+    fn from(run: ActionExecution) -> Self {
+        Self {
+            action: run.action,
+            heuristic_id: run.heuristic_id,
+            success: run.success,
+            output: run.output,
+            duration_us: run.duration_us as i64,
+        }
+    }
+}
+
+/// Convert a storage heuristic (None if the ID is malformed).
+pub fn heuristic_from_proto(h: proto::Heuristic) -> Option<Heuristic> {
+    let id = match uuid::Uuid::parse_str(&h.id) {
+        Ok(uuid) => uuid,
+        Err(e) => {
+            warn!(id = %h.id, error = %e, "Failed to parse heuristic UUID");
+            return None;
+        }
+    };
+    let effects = Effects::parse(&h.effects_json).unwrap_or_else(|e| {
+        warn!(id = %h.id, error = %e, "Failed to parse effects JSON");
+        Effects::malformed(e)
+    });
+
+    Some(Heuristic {
+        id,
+        name: h.name,
+        condition: Condition::text(h.condition_text)
+            .with_exemplars(h.exemplars.into_iter().map(exemplar_from_proto).collect())
+            .with_negatives(h.negative_exemplars.into_iter().map(exemplar_from_proto).collect()),
+        effects,
+        confidence: h.confidence,
+        origin: h.origin,
+        source: h.source,
+        condition_embedding: bytes_to_embedding(&h.condition_embedding),
+        embedding_model: h.embedding_model,
+    })
+}
+
+/// Storage message for a heuristic (fields the domain type doesn't model
+/// take the builder's defaults).
+impl From<&Heuristic> for proto::Heuristic {
+    fn from(h: &Heuristic) -> Self {
+        let mut proto = HeuristicBuilder::new(h.id, &h.name)
+            .condition_text(&h.condition.text)
+            .effects_json(&h.effects.to_json())
+            .confidence(h.confidence)
+            .origin(&h.origin)
+            .source(&h.source)
+            .embedding_model(&h.embedding_model)
+            .build();
+        proto.condition_embedding = embedding_to_bytes(&h.condition_embedding);
+        proto.exemplars = h.condition.exemplars.iter().map(proto::ConditionExemplar::from).collect();
+        proto.negative_exemplars = h.condition.negatives.iter().map(proto::ConditionExemplar::from).collect();
+        proto
+    }
+}
+
+fn exemplar_from_proto(e: proto::ConditionExemplar) -> Exemplar {
+    Exemplar::new(e.text, e.weight, bytes_to_embedding(&e.embedding))
+}
+
+impl From<&Exemplar> for proto::ConditionExemplar {
+    fn from(e: &Exemplar) -> Self {
+        Self { text: e.text.clone(), embedding: embedding_to_bytes(&e.embedding), weight: e.weight }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_defaults_malformed_and_deprecated_fields() {
+        let legacy = EvaluateSalienceRequest {
+            event_id: "e1".to_string(),
+            raw_text: "door opened".to_string(),
+            skip_novelty_detection: true,
+            min_similarity: Some(f32::NAN),
+            min_confidence: Some(0.4),
+            ..Default::default()
+        };
+        let request = EvaluationRequest::from(&legacy);
+        assert_eq!(request.event.id, "e1");
+        assert_eq!(request.min_similarity, None);
+        assert_eq!(request.min_confidence, Some(0.4));
+        assert!(request.event.source.is_empty());
+        assert!(SKIP_NOVELTY_WARNED.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_response_populates_every_field() {
+        let evaluation = Evaluation {
+            salience: SalienceResult::default(),
+            matched_heuristic_id: None,
+            error: None,
+            thresholds: ScoreThresholds { min_similarity: 0.7, min_confidence: 0.5 },
+            dominant_dimension: String::new(),
+            composite_score: 0.2,
+            routing_hint: RoutingHint::Accumulate,
+            timings_ms: HashMap::new(),
+            experiment_variant: None,
+            degraded: false,
+            retry_suggested: false,
+            duplicate_of: None,
+            matched_effects: None,
+            executed_action: None,
+            cooled_down: Vec::new(),
+            conflict: None,
+            language: None,
+            explanation: None,
+        };
+        let response = EvaluateSalienceResponse::from(evaluation.clone());
+        assert!(response.salience.is_some());
+        assert!(!response.from_cache && response.matched_heuristic_id.is_empty());
+        assert!(response.novelty_detection_skipped);
+        assert_eq!(response.effective_min_similarity, 0.7);
+        assert_eq!(response.routing_hint, RoutingHint::Accumulate as i32);
+
+        assert!(response.matched_effects_json.is_empty());
+
+        let effects = Effects::parse(r#"{"message": "Take cover", "action": {"tool": "move", "args": {"to": "shelter"}}}"#).unwrap();
+        let matched = Evaluation { matched_heuristic_id: Some("h1".to_string()), matched_effects: Some(effects), ..evaluation };
+        let response = EvaluateSalienceResponse::from(matched);
+        assert!(response.from_cache);
+        assert_eq!(response.matched_heuristic_id, "h1");
+        let passed: serde_json::Value = serde_json::from_str(&response.matched_effects_json).unwrap();
+        assert_eq!(passed["action"]["args"]["to"], "shelter");
+    }
+
+    #[test]
+    fn test_heuristic_proto_round_trip() {
+        let heuristic = Heuristic {
+            id: uuid::Uuid::new_v4(),
+            name: "creeper".to_string(),
+            condition: Condition::text("creeper approaching")
+                .with_exemplars(vec![Exemplar::new("hissing behind you", 0.8, vec![0.25, 0.5])])
+                .with_negatives(vec![Exemplar::new("creeper plushie", 1.0, vec![0.5, 0.5])]),
+            effects: serde_json::json!({"salience": {"threat": 0.8}, "cooldown_s": 30}).into(),
+            confidence: 0.9,
+            origin: "llm".to_string(),
+            source: "minecraft".to_string(),
+            condition_embedding: vec![0.5, -0.25],
+            embedding_model: "all-MiniLM-L6-v2".to_string(),
+        };
+        assert_eq!(heuristic_from_proto(proto::Heuristic::from(&heuristic)), Some(heuristic));
+
+        let malformed = proto::Heuristic {
+            id: uuid::Uuid::new_v4().to_string(),
+            effects_json: "{not json".to_string(),
+            ..Default::default()
+        };
+        let effects = heuristic_from_proto(malformed).unwrap().effects;
+        assert!(effects.salience.is_none() && effects.malformed.is_some());
+        assert!(heuristic_from_proto(proto::Heuristic { id: "nope".to_string(), ..Default::default() }).is_none());
+    }
+}
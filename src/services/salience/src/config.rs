@@ -3,7 +3,9 @@
 //! All configuration values can be set via environment variables.
 //! This mirrors the Python config pattern using pydantic Settings.
 
+use std::collections::HashMap;
 use std::env;
+use std::str::FromStr;
 use std::time::Duration;
 
 /// Server configuration for the gRPC service.
@@ -13,6 +15,18 @@ pub struct ServerConfig {
     pub host: String,
     /// Port to listen on (default: 50052)
     pub port: u16,
+    /// Port for the Prometheus metrics endpoint (default: 0 = disabled)
+    pub metrics_port: u16,
+    /// Port for the WebSocket debug feed (default: 0 = disabled; needs the `ws` feature)
+    pub ws_port: u16,
+    /// How often the WebSocket feed checks cache stats for changes in ms (default: 1000)
+    pub ws_stats_interval_ms: u64,
+    /// Host for the admin listener (default: 127.0.0.1)
+    pub admin_host: String,
+    /// Port serving admin RPCs, refused on `port` (default: 0 = admin RPCs on `port`; see planes module)
+    pub admin_port: u16,
+    /// Unix socket serving admin RPCs instead of `admin_port` (default: none)
+    pub admin_socket: Option<String>,
 }
 
 impl Default for ServerConfig {
@@ -23,24 +37,71 @@ impl Default for ServerConfig {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(50052),
+            metrics_port: env::var("METRICS_PORT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            ws_port: env::var("WS_PORT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            ws_stats_interval_ms: env::var("WS_STATS_INTERVAL_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(1000),
+            admin_host: env::var("ADMIN_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
+            admin_port: env::var("ADMIN_PORT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            admin_socket: env::var("ADMIN_SOCKET").ok().filter(|s| !s.is_empty()),
         }
     }
 }
 
+impl ServerConfig {
+    /// Whether admin RPCs get their own listener.
+    pub fn admin_listener(&self) -> bool {
+        self.admin_port != 0 || self.admin_socket.is_some()
+    }
+}
+
 /// Storage client configuration for connecting to Python backend.
 #[derive(Debug, Clone)]
 pub struct StorageConfig {
+    /// Which storage to use (default: grpc, the Python service)
+    pub backend: StorageKind,
     /// Address of the Python storage service (default: http://localhost:50051)
     pub address: String,
     /// Connection timeout in seconds (default: 5)
     pub connect_timeout_secs: u64,
     /// Request timeout in seconds (default: 30)
     pub request_timeout_secs: u64,
+    /// Retries for RPCs that fail with UNAVAILABLE (default: 0)
+    pub max_retries: u32,
+    /// Backoff before the first retry in milliseconds, doubling per retry (default: 50)
+    pub retry_backoff_ms: u64,
+    /// Append every storage interaction to this file (see the recording module; default: none)
+    pub record_path: Option<String>,
+    /// Serve storage from this recording instead of `address` (default: none)
+    pub replay_path: Option<String>,
+    /// What to do when storage's schema major version differs (default: refuse)
+    pub schema_mismatch: SchemaMismatchPolicy,
+    /// SQLite database file when `backend` is sqlite (default: gladys-memory.db)
+    pub sqlite_path: String,
+    /// Postgres connection URL when `backend` is postgres (default: postgres://gladys@localhost:5432/gladys)
+    pub postgres_url: String,
+    /// Postgres connections held when `backend` is postgres (default: 4)
+    pub postgres_max_connections: u32,
 }
 
 impl Default for StorageConfig {
     fn default() -> Self {
         Self {
+            backend: env::var("STORAGE_BACKEND")
+                .ok()
+                .and_then(|s| s.parse().map_err(|e| tracing::warn!("{}; using grpc", e)).ok())
+                .unwrap_or_default(),
             address: env::var("STORAGE_ADDRESS")
                 .unwrap_or_else(|_| "http://localhost:50051".to_string()),
             connect_timeout_secs: env::var("STORAGE_CONNECT_TIMEOUT_SECS")
@@ -51,6 +112,32 @@ impl Default for StorageConfig {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(30),
+            max_retries: env::var("STORAGE_MAX_RETRIES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            retry_backoff_ms: env::var("STORAGE_RETRY_BACKOFF_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(50),
+            record_path: env::var("STORAGE_RECORD_PATH").ok().filter(|s| !s.is_empty()),
+            replay_path: env::var("STORAGE_REPLAY_PATH").ok().filter(|s| !s.is_empty()),
+            schema_mismatch: env::var("STORAGE_SCHEMA_MISMATCH")
+                .ok()
+                .and_then(|s| s.parse().map_err(|e| tracing::warn!("{}; refusing on mismatch", e)).ok())
+                .unwrap_or_default(),
+            sqlite_path: env::var("STORAGE_SQLITE_PATH")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| "gladys-memory.db".to_string()),
+            postgres_url: env::var("STORAGE_POSTGRES_URL")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| "postgres://gladys@localhost:5432/gladys".to_string()),
+            postgres_max_connections: env::var("STORAGE_POSTGRES_MAX_CONNECTIONS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(4),
         }
     }
 }
@@ -63,6 +150,389 @@ impl StorageConfig {
     pub fn request_timeout(&self) -> Duration {
         Duration::from_secs(self.request_timeout_secs)
     }
+
+    pub fn retry_backoff(&self) -> Duration {
+        Duration::from_millis(self.retry_backoff_ms)
+    }
+}
+
+pub use gladys_salience_core::SimilarityMetric;
+
+/// Optional normalization applied to the salience vector after clamping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VectorNormalization {
+    /// Leave values as-is
+    #[default]
+    None,
+    /// Divide by the largest value (largest dimension becomes 1.0)
+    Max,
+    /// Divide by the sum (dimensions sum to 1.0)
+    Sum,
+    /// Softmax across dimensions
+    Softmax,
+}
+
+impl FromStr for VectorNormalization {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" | "" => Ok(Self::None),
+            "max" => Ok(Self::Max),
+            "sum" => Ok(Self::Sum),
+            "softmax" => Ok(Self::Softmax),
+            other => Err(format!("Unknown vector normalization: {}", other)),
+        }
+    }
+}
+
+/// A step tried, in order, when the event embedding can't be generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingFallback {
+    /// Match heuristic keywords in the cache (see keywords module)
+    Keywords,
+    /// Query storage for matching heuristics (shared tier first, rate limited)
+    Storage,
+}
+
+impl EmbeddingFallback {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Keywords => "keywords",
+            Self::Storage => "storage",
+        }
+    }
+}
+
+impl FromStr for EmbeddingFallback {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "keywords" => Ok(Self::Keywords),
+            "storage" => Ok(Self::Storage),
+            other => Err(format!("Unknown embedding fallback: {}", other)),
+        }
+    }
+}
+
+/// How opposing boosts among the top matches are reconciled (see conflicts module).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictResolution {
+    /// The most confident of the conflicting matches wins outright
+    #[default]
+    HighestConfidence,
+    /// The conflicting matches' boosts are averaged per dimension
+    Average,
+    /// A match boosting a veto dimension wins; otherwise highest confidence
+    Veto,
+}
+
+impl ConflictResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::HighestConfidence => "highest_confidence",
+            Self::Average => "average",
+            Self::Veto => "veto",
+        }
+    }
+}
+
+impl FromStr for ConflictResolution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "highest_confidence" | "" => Ok(Self::HighestConfidence),
+            "average" => Ok(Self::Average),
+            "veto" => Ok(Self::Veto),
+            other => Err(format!("Unknown conflict resolution: {}", other)),
+        }
+    }
+}
+
+/// How a replica takes part in heuristic refresh (see refresh module).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RefreshRole {
+    /// Refresh from storage on its own schedule
+    #[default]
+    Independent,
+    /// Refresh from storage and broadcast the changes to peers
+    Leader,
+    /// Apply the leader's broadcasts; refresh directly only while it's silent
+    Follower,
+}
+
+impl RefreshRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Independent => "independent",
+            Self::Leader => "leader",
+            Self::Follower => "follower",
+        }
+    }
+}
+
+impl FromStr for RefreshRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "independent" | "" => Ok(Self::Independent),
+            "leader" => Ok(Self::Leader),
+            "follower" => Ok(Self::Follower),
+            other => Err(format!("Unknown refresh role: {}", other)),
+        }
+    }
+}
+
+/// Where heuristics are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StorageKind {
+    /// The Python storage service, over gRPC
+    #[default]
+    Grpc,
+    /// A local SQLite file (see sqlite module; needs the `sqlite` feature)
+    Sqlite,
+    /// Matching read straight from Postgres, the rest through Python (see
+    /// postgres module; needs the `postgres` feature)
+    Postgres,
+}
+
+impl StorageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Grpc => "grpc",
+            Self::Sqlite => "sqlite",
+            Self::Postgres => "postgres",
+        }
+    }
+}
+
+impl FromStr for StorageKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "grpc" | "" => Ok(Self::Grpc),
+            "sqlite" => Ok(Self::Sqlite),
+            "postgres" => Ok(Self::Postgres),
+            other => Err(format!("Unknown storage backend: {}", other)),
+        }
+    }
+}
+
+/// Startup behavior when storage speaks an incompatible schema (see schema module).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaMismatchPolicy {
+    /// Exit instead of serving
+    #[default]
+    Refuse,
+    /// Serve anyway, reporting DEGRADED health
+    Degraded,
+}
+
+impl SchemaMismatchPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Refuse => "refuse",
+            Self::Degraded => "degraded",
+        }
+    }
+}
+
+impl FromStr for SchemaMismatchPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "refuse" | "" => Ok(Self::Refuse),
+            "degraded" => Ok(Self::Degraded),
+            other => Err(format!("Unknown schema mismatch policy: {}", other)),
+        }
+    }
+}
+
+/// What EvaluateSalience does with input over a configured limit (see limits module).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OversizePolicy {
+    /// Cut the input down to the limit and evaluate
+    #[default]
+    Truncate,
+    /// Fail the request with INVALID_ARGUMENT
+    Reject,
+}
+
+impl OversizePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Truncate => "truncate",
+            Self::Reject => "reject",
+        }
+    }
+}
+
+impl FromStr for OversizePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "truncate" | "" => Ok(Self::Truncate),
+            "reject" => Ok(Self::Reject),
+            other => Err(format!("Unknown oversize policy: {}", other)),
+        }
+    }
+}
+
+/// What the cache does with heuristics embedded by a previous model version
+/// (see embedding_model module).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModelChangePolicy {
+    /// Evict them; storage fallbacks fetch them again
+    #[default]
+    Flush,
+    /// Keep them (keyword matching only) until re-embedded in the background
+    Reembed,
+}
+
+impl ModelChangePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Flush => "flush",
+            Self::Reembed => "reembed",
+        }
+    }
+}
+
+impl FromStr for ModelChangePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "flush" | "" => Ok(Self::Flush),
+            "reembed" | "re-embed" => Ok(Self::Reembed),
+            other => Err(format!("Unknown model change policy: {}", other)),
+        }
+    }
+}
+
+/// How a heuristic's condition and exemplar similarities combine into its
+/// match similarity (see exemplars module).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExemplarAggregation {
+    /// The best weighted similarity
+    #[default]
+    Max,
+    /// The weighted mean similarity
+    Mean,
+}
+
+impl ExemplarAggregation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Max => "max",
+            Self::Mean => "mean",
+        }
+    }
+}
+
+impl FromStr for ExemplarAggregation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "max" | "" => Ok(Self::Max),
+            "mean" | "avg" => Ok(Self::Mean),
+            other => Err(format!("Unknown exemplar aggregation: {}", other)),
+        }
+    }
+}
+
+/// What closeness to a heuristic's negative exemplar does to its match
+/// (see exemplars module).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NegativePolicy {
+    /// Subtract the negative's weighted similarity from the match similarity
+    #[default]
+    Subtract,
+    /// Drop the match
+    Veto,
+}
+
+impl NegativePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Subtract => "subtract",
+            Self::Veto => "veto",
+        }
+    }
+}
+
+impl FromStr for NegativePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "subtract" | "" => Ok(Self::Subtract),
+            "veto" => Ok(Self::Veto),
+            other => Err(format!("Unknown negative exemplar policy: {}", other)),
+        }
+    }
+}
+
+/// Parse opposing dimension pairs ("threat:social,threat:opportunity"), skipping malformed entries.
+fn parse_dimension_pairs(s: &str) -> Vec<(String, String)> {
+    s.split(',')
+        .filter_map(|pair| {
+            let (a, b) = pair.split_once(':')?;
+            let (a, b) = (a.trim(), b.trim());
+            (!a.is_empty() && !b.is_empty() && a != b).then(|| (a.to_string(), b.to_string()))
+        })
+        .collect()
+}
+
+/// Parse a fallback order ("keywords,storage"); "none" or empty means no fallback.
+fn parse_fallback_order(s: &str) -> Vec<EmbeddingFallback> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|step| !step.is_empty() && !step.eq_ignore_ascii_case("none"))
+        .filter_map(|step| step.parse().map_err(|e| tracing::warn!("{}; ignoring", e)).ok())
+        .collect()
+}
+
+/// What an evaluation does when a dependency fails.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FailurePolicy {
+    /// Salience to report ("threat" or a vector dimension -> value), replacing
+    /// the baseline for those dimensions (empty = the built-in behavior)
+    pub salience: HashMap<String, f32>,
+    /// Mark the response `retry_suggested` so the caller re-evaluates later
+    pub retry: bool,
+}
+
+impl FailurePolicy {
+    /// Read `<prefix>_SALIENCE` and `<prefix>_RETRY`.
+    fn from_env(prefix: &str) -> Self {
+        Self {
+            salience: env::var(format!("{}_SALIENCE", prefix))
+                .map(|s| parse_dimension_map(&s))
+                .unwrap_or_default(),
+            retry: env::var(format!("{}_RETRY", prefix))
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Parse "key=value,key=value" (dimensions, origins) into a map, skipping malformed entries.
+fn parse_dimension_map(s: &str) -> HashMap<String, f32> {
+    s.split(',')
+        .filter_map(|pair| {
+            let (dim, value) = pair.split_once('=')?;
+            Some((dim.trim().to_string(), value.trim().parse().ok()?))
+        })
+        .collect()
 }
 
 /// Cache configuration for the L0 in-memory cache.
@@ -77,6 +547,39 @@ pub struct CacheConfig {
     /// TTL for cached heuristic confidence in milliseconds (default: 300000 = 5 minutes)
     /// Safety net for stale entries; push invalidation handles normal updates
     pub heuristic_ttl_ms: i64,
+    /// Similarity metric for all embedding comparisons (default: cosine)
+    pub similarity_metric: SimilarityMetric,
+    /// Threshold reduction for a heuristic that recently matched the same text (default: 0.05)
+    /// Keeps near-threshold events from flip-flopping between matched and unmatched
+    pub match_hysteresis_margin: f32,
+    /// How long a text -> heuristic match keeps the reduced threshold, in ms (default: 60000)
+    pub match_hysteresis_window_ms: i64,
+    /// Repeats of an event's text (same source) within this many ms of the
+    /// first are reported as duplicates of it (default: 0 = disabled)
+    pub dedup_window_ms: i64,
+    /// Idle check interval for packing embeddings into slabs, in ms
+    /// (default: 0 = disabled; see arena module)
+    pub compaction_interval_ms: u64,
+    /// How long a heuristic deleted by notification can't be cached again, in ms
+    /// (default: 30000, 0 = no tombstones)
+    pub tombstone_ttl_ms: i64,
+    /// How much longer each similarity-check access keeps an event cached, in ms;
+    /// with a weight, eviction also counts from the last access
+    /// (default: 0 = evict purely by event age)
+    pub event_access_weight_ms: i64,
+    /// Heuristic evictions kept for GetEvictionLog (default: 256, 0 = log only;
+    /// see eviction_log module)
+    pub eviction_log_size: usize,
+    /// What happens to cached embeddings when the embedding model changes
+    /// (default: flush; see embedding_model module)
+    pub model_change_policy: ModelChangePolicy,
+    /// How exemplar similarities combine (default: max; see exemplars module)
+    pub exemplar_aggregation: ExemplarAggregation,
+    /// Similarity to a negative exemplar at or above which it counts against
+    /// a match (default: 0.8)
+    pub negative_threshold: f32,
+    /// What a negative exemplar over the threshold does (default: subtract)
+    pub negative_policy: NegativePolicy,
 }
 
 impl Default for CacheConfig {
@@ -98,6 +601,54 @@ impl Default for CacheConfig {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(300_000), // 5 minutes default
+            similarity_metric: env::var("CACHE_SIMILARITY_METRIC")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or_default(),
+            match_hysteresis_margin: env::var("CACHE_MATCH_HYSTERESIS_MARGIN")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.05),
+            match_hysteresis_window_ms: env::var("CACHE_MATCH_HYSTERESIS_WINDOW_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(60_000),
+            dedup_window_ms: env::var("CACHE_DEDUP_WINDOW_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            compaction_interval_ms: env::var("CACHE_COMPACTION_INTERVAL_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            tombstone_ttl_ms: env::var("CACHE_TOMBSTONE_TTL_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(30_000),
+            event_access_weight_ms: env::var("CACHE_EVENT_ACCESS_WEIGHT_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            eviction_log_size: env::var("CACHE_EVICTION_LOG_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(256),
+            model_change_policy: env::var("CACHE_MODEL_CHANGE_POLICY")
+                .ok()
+                .and_then(|s| s.parse().map_err(|e| tracing::warn!("{}; flushing", e)).ok())
+                .unwrap_or_default(),
+            exemplar_aggregation: env::var("CACHE_EXEMPLAR_AGGREGATION")
+                .ok()
+                .and_then(|s| s.parse().map_err(|e| tracing::warn!("{}; using max", e)).ok())
+                .unwrap_or_default(),
+            negative_threshold: env::var("CACHE_NEGATIVE_THRESHOLD")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.8),
+            negative_policy: env::var("CACHE_NEGATIVE_POLICY")
+                .ok()
+                .and_then(|s| s.parse().map_err(|e| tracing::warn!("{}; subtracting", e)).ok())
+                .unwrap_or_default(),
         }
     }
 }
@@ -113,6 +664,125 @@ pub struct SalienceConfig {
     pub baseline_novelty: f32,
     /// Novelty boost when no heuristic matches (default: 0.4)
     pub unmatched_novelty_boost: f32,
+    /// Bounds for per-request min_similarity overrides (default: 0.5..=0.95)
+    pub min_similarity_override_floor: f32,
+    pub min_similarity_override_ceiling: f32,
+    /// Bounds for per-request min_confidence overrides (default: 0.0..=1.0)
+    pub min_confidence_override_floor: f32,
+    pub min_confidence_override_ceiling: f32,
+    /// Recent EvaluateSalience decisions kept for ReplayDecisions (default: 256, 0 = disabled)
+    pub decision_history_size: usize,
+    /// Word-overlap scorer: fraction of condition words the event must contain (default: 0.5)
+    pub word_overlap_min_ratio: f32,
+    /// Word-overlap scorer: minimum shared words (default: 2)
+    pub word_overlap_min_words: usize,
+    /// Per-dimension minimums, e.g. "novelty=0.1" (default: none; "threat" applies to the threat scalar)
+    pub dimension_floors: HashMap<String, f32>,
+    /// Per-dimension maximums, e.g. "social=0.5" (default: none)
+    pub dimension_ceilings: HashMap<String, f32>,
+    /// Vector normalization after clamping (default: none)
+    pub vector_normalization: VectorNormalization,
+    /// Weights for the composite score; "threat" weights the threat scalar
+    /// (default: threat=0.35,goal_relevance=0.2,novelty=0.15,opportunity=0.1,actionability=0.1,social=0.1)
+    pub composite_weights: HashMap<String, f32>,
+    /// Composite score at or above which events are routed immediately (default: 0.5)
+    pub immediate_threshold: f32,
+    /// Threat at or above which events are routed immediately regardless of composite (default: 0.7)
+    pub immediate_threat_threshold: f32,
+    /// Composite score below which events may be discarded (default: 0.0 = never)
+    pub discard_threshold: f32,
+    /// How long admin RPC idempotency keys are remembered, in ms (default: 300000, 0 = disabled)
+    pub idempotency_window_ms: i64,
+    /// Admin operations kept in memory for GetAuditLog (default: 256, 0 = log only)
+    pub audit_log_size: usize,
+    /// Minimum match similarity by heuristic origin, e.g. "llm=0.85" (default: none)
+    pub origin_min_similarity: HashMap<String, f32>,
+    /// Minimum heuristic confidence by origin, e.g. "llm=0.7" (default: none)
+    pub origin_min_confidence: HashMap<String, f32>,
+    /// Storage fallback queries per second (default: 0 = unlimited; see rate_limit module)
+    pub fallback_rate_per_sec: f64,
+    /// Storage fallback burst capacity (default: 10)
+    pub fallback_burst: u32,
+    /// Similarity to an active goal below which goal_relevance isn't set (default: 0.3)
+    pub goal_min_similarity: f32,
+    /// Affect wordlist for emotional/social scoring (default: none; see affect module)
+    pub affect_lexicon_path: Option<String>,
+    /// Match heuristic keywords before embedding (default: false; see keywords module)
+    pub keyword_prefilter: bool,
+    /// Worker threads for cache similarity scans (default: 0 = inline; see worker_pool module)
+    pub scoring_workers: usize,
+    /// Scoring jobs that can wait for a worker (default: 256)
+    pub scoring_queue_size: usize,
+    /// Steps tried when embedding fails (default: storage; see `EmbeddingFallback`)
+    pub embedding_fallback: Vec<EmbeddingFallback>,
+    /// Embedding failed and no fallback step matched (default: novelty boost, no retry)
+    pub embedding_failure: FailurePolicy,
+    /// Storage fallback query (or scan) failed (default: novelty boost, no retry)
+    pub storage_failure: FailurePolicy,
+    /// Storage fallback skipped by the rate limiter (default: baseline, no retry)
+    pub rate_limited: FailurePolicy,
+    /// Habituation reported for a duplicate event (default: 0.95; see CACHE_DEDUP_WINDOW_MS)
+    pub duplicate_habituation: f32,
+    /// Minutes of decisions kept for GetSalienceSummary (default: 60, 0 = disabled; see summary module)
+    pub summary_retention_minutes: i64,
+    /// How long a storage fallback that matched nothing is remembered (default: 0 = not remembered)
+    pub fallback_negative_ttl_ms: i64,
+    /// Cooldown after a heuristic fires, unless its effects set `cooldown_ms` (default: 0 = none)
+    pub heuristic_cooldown_ms: u64,
+    /// Boost multiplier for a match still cooling down (default: 0.0 = suppress the match)
+    pub cooldown_dampening: f32,
+    /// Dimension pairs whose boosts contradict each other (default: none = no conflict detection)
+    pub conflict_pairs: Vec<(String, String)>,
+    /// Top matches checked against the best one for conflicts (default: 3)
+    pub conflict_top_k: usize,
+    /// How a detected conflict is resolved (default: highest_confidence)
+    pub conflict_resolution: ConflictResolution,
+    /// Dimensions that win any conflict under the veto strategy (default: threat)
+    pub conflict_veto_dimensions: Vec<String>,
+    /// Maximum raw_text size in bytes (default: 65536, 0 = unlimited; see limits module)
+    pub max_text_bytes: usize,
+    /// Maximum entity_ids per event (default: 256, 0 = unlimited)
+    pub max_entity_ids: usize,
+    /// Maximum structured_json size in bytes (default: 262144, 0 = unlimited)
+    pub max_structured_json_bytes: usize,
+    /// What happens to input over a limit (default: truncate)
+    pub oversize_policy: OversizePolicy,
+    /// Normalize event text before matching (default: true; see normalize module)
+    pub normalize_text: bool,
+    /// Fold case while normalizing (default: true)
+    pub case_fold: bool,
+    /// Drop emoji while normalizing (default: false)
+    pub strip_emoji: bool,
+    /// Tag events with their detected language (default: false; see language module)
+    pub language_detection: bool,
+    /// Detection confidence below which a language is undetermined (default: 0.5)
+    pub language_min_confidence: f64,
+    /// Labeled samples needed before the calibration curve applies (default: 50; see calibration module)
+    pub calibration_min_samples: usize,
+    /// Newest calibration samples kept (default: 5000)
+    pub calibration_max_samples: usize,
+    /// Calibrated probability below which embedding matches are dropped (default: 0.0 = never)
+    pub min_match_probability: f32,
+    /// File calibration samples are saved to and loaded from (default: none)
+    pub calibration_path: Option<String>,
+    /// Sources evaluated at all (default: empty = every source; see source_filter module)
+    pub source_allowlist: Vec<String>,
+    /// Sources answered with baseline salience, unscored (default: none)
+    pub source_denylist: Vec<String>,
+    /// In-flight evaluations at which low-value events start being shed (default: 0 = off; see load_shed module)
+    pub shed_queue_start: usize,
+    /// In-flight evaluations at which the shed rate peaks (default: 0 = twice the start)
+    pub shed_queue_full: usize,
+    /// Largest share of a low-value source's events shed (default: 0.9)
+    pub shed_max_rate: f32,
+    /// Mean composite score at or below which a source is low-value (default: 0.1)
+    pub shed_max_score: f32,
+    /// Scored events before a source's mean counts (default: 100)
+    pub shed_min_events: u64,
+    /// Start in maintenance mode, answering with canned salience (default: false; see maintenance module)
+    pub maintenance: bool,
+    /// Canned salience profile as JSON (default: none = baseline)
+    pub maintenance_profile: Option<String>,
 }
 
 impl Default for SalienceConfig {
@@ -134,10 +804,663 @@ impl Default for SalienceConfig {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(0.4),
+            min_similarity_override_floor: env::var("SALIENCE_MIN_SIMILARITY_OVERRIDE_FLOOR")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.5),
+            min_similarity_override_ceiling: env::var("SALIENCE_MIN_SIMILARITY_OVERRIDE_CEILING")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.95),
+            min_confidence_override_floor: env::var("SALIENCE_MIN_CONFIDENCE_OVERRIDE_FLOOR")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.0),
+            min_confidence_override_ceiling: env::var("SALIENCE_MIN_CONFIDENCE_OVERRIDE_CEILING")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(1.0),
+            decision_history_size: env::var("SALIENCE_DECISION_HISTORY_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(256),
+            word_overlap_min_ratio: env::var("SALIENCE_WORD_OVERLAP_MIN_RATIO")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.5),
+            word_overlap_min_words: env::var("SALIENCE_WORD_OVERLAP_MIN_WORDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(2),
+            dimension_floors: env::var("SALIENCE_DIMENSION_FLOORS")
+                .map(|s| parse_dimension_map(&s))
+                .unwrap_or_default(),
+            dimension_ceilings: env::var("SALIENCE_DIMENSION_CEILINGS")
+                .map(|s| parse_dimension_map(&s))
+                .unwrap_or_default(),
+            vector_normalization: env::var("SALIENCE_VECTOR_NORMALIZATION")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or_default(),
+            composite_weights: parse_dimension_map(
+                &env::var("SALIENCE_COMPOSITE_WEIGHTS").unwrap_or_else(|_| {
+                    "threat=0.35,goal_relevance=0.2,novelty=0.15,opportunity=0.1,actionability=0.1,social=0.1"
+                        .to_string()
+                }),
+            ),
+            immediate_threshold: env::var("SALIENCE_IMMEDIATE_THRESHOLD")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.5),
+            immediate_threat_threshold: env::var("SALIENCE_IMMEDIATE_THREAT_THRESHOLD")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.7),
+            discard_threshold: env::var("SALIENCE_DISCARD_THRESHOLD")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.0),
+            idempotency_window_ms: env::var("SALIENCE_IDEMPOTENCY_WINDOW_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(300_000),
+            audit_log_size: env::var("SALIENCE_AUDIT_LOG_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(256),
+            origin_min_similarity: env::var("SALIENCE_ORIGIN_MIN_SIMILARITY")
+                .map(|s| parse_dimension_map(&s))
+                .unwrap_or_default(),
+            origin_min_confidence: env::var("SALIENCE_ORIGIN_MIN_CONFIDENCE")
+                .map(|s| parse_dimension_map(&s))
+                .unwrap_or_default(),
+            fallback_rate_per_sec: env::var("SALIENCE_FALLBACK_RATE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.0),
+            fallback_burst: env::var("SALIENCE_FALLBACK_BURST")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(10),
+            goal_min_similarity: env::var("SALIENCE_GOAL_MIN_SIMILARITY")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.3),
+            affect_lexicon_path: env::var("SALIENCE_AFFECT_LEXICON").ok().filter(|s| !s.is_empty()),
+            keyword_prefilter: env::var("SALIENCE_KEYWORD_PREFILTER")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+            scoring_workers: env::var("SALIENCE_SCORING_WORKERS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            scoring_queue_size: env::var("SALIENCE_SCORING_QUEUE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(256),
+            embedding_fallback: env::var("DEGRADE_EMBEDDING_FALLBACK")
+                .map(|s| parse_fallback_order(&s))
+                .unwrap_or_else(|_| vec![EmbeddingFallback::Storage]),
+            embedding_failure: FailurePolicy::from_env("DEGRADE_EMBEDDING"),
+            storage_failure: FailurePolicy::from_env("DEGRADE_STORAGE"),
+            rate_limited: FailurePolicy::from_env("DEGRADE_RATE_LIMITED"),
+            duplicate_habituation: env::var("SALIENCE_DUPLICATE_HABITUATION")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.95),
+            summary_retention_minutes: env::var("SALIENCE_SUMMARY_RETENTION_MINUTES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(60),
+            fallback_negative_ttl_ms: env::var("SALIENCE_FALLBACK_NEGATIVE_TTL_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            heuristic_cooldown_ms: env::var("SALIENCE_HEURISTIC_COOLDOWN_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            cooldown_dampening: env::var("SALIENCE_COOLDOWN_DAMPENING")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.0),
+            conflict_pairs: env::var("SALIENCE_CONFLICT_PAIRS")
+                .map(|s| parse_dimension_pairs(&s))
+                .unwrap_or_default(),
+            conflict_top_k: env::var("SALIENCE_CONFLICT_TOP_K")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3),
+            conflict_resolution: env::var("SALIENCE_CONFLICT_RESOLUTION")
+                .ok()
+                .and_then(|s| s.parse().map_err(|e| tracing::warn!("{}; using highest_confidence", e)).ok())
+                .unwrap_or_default(),
+            conflict_veto_dimensions: env::var("SALIENCE_CONFLICT_VETO")
+                .map(|s| s.split(',').map(str::trim).filter(|d| !d.is_empty()).map(str::to_string).collect())
+                .unwrap_or_else(|_| vec!["threat".to_string()]),
+            max_text_bytes: env::var("SALIENCE_MAX_TEXT_BYTES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(65_536),
+            max_entity_ids: env::var("SALIENCE_MAX_ENTITY_IDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(256),
+            max_structured_json_bytes: env::var("SALIENCE_MAX_STRUCTURED_JSON_BYTES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(262_144),
+            oversize_policy: env::var("SALIENCE_OVERSIZE_POLICY")
+                .ok()
+                .and_then(|s| s.parse().map_err(|e| tracing::warn!("{}; truncating", e)).ok())
+                .unwrap_or_default(),
+            normalize_text: env::var("SALIENCE_NORMALIZE_TEXT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(true),
+            case_fold: env::var("SALIENCE_CASE_FOLD")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(true),
+            strip_emoji: env::var("SALIENCE_STRIP_EMOJI")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+            language_detection: env::var("SALIENCE_LANGUAGE_DETECTION")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+            language_min_confidence: env::var("SALIENCE_LANGUAGE_MIN_CONFIDENCE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.5),
+            calibration_min_samples: env::var("SALIENCE_CALIBRATION_MIN_SAMPLES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(50),
+            calibration_max_samples: env::var("SALIENCE_CALIBRATION_MAX_SAMPLES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(5000),
+            min_match_probability: env::var("SALIENCE_MIN_MATCH_PROBABILITY")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.0),
+            calibration_path: env::var("SALIENCE_CALIBRATION_PATH").ok().filter(|s| !s.is_empty()),
+            source_allowlist: env::var("SALIENCE_SOURCE_ALLOW")
+                .map(|s| s.split(',').map(str::trim).filter(|a| !a.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default(),
+            source_denylist: env::var("SALIENCE_SOURCE_DENY")
+                .map(|s| s.split(',').map(str::trim).filter(|a| !a.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default(),
+            shed_queue_start: env::var("SALIENCE_SHED_QUEUE_START")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            shed_queue_full: env::var("SALIENCE_SHED_QUEUE_FULL")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            shed_max_rate: env::var("SALIENCE_SHED_MAX_RATE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.9),
+            shed_max_score: env::var("SALIENCE_SHED_MAX_SCORE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.1),
+            shed_min_events: env::var("SALIENCE_SHED_MIN_EVENTS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(100),
+            maintenance: env::var("SALIENCE_MAINTENANCE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+            maintenance_profile: env::var("SALIENCE_MAINTENANCE_PROFILE").ok().filter(|s| !s.is_empty()),
+        }
+    }
+}
+
+/// Background heuristic refresh configuration.
+#[derive(Debug, Clone)]
+pub struct RefreshConfig {
+    /// Base refresh interval in milliseconds (default: 0 = disabled, heuristics load on demand)
+    pub interval_ms: u64,
+    /// Upper bound for the adaptive interval in milliseconds (default: 60000)
+    /// The interval doubles toward this on failures and while nothing changes
+    pub max_interval_ms: u64,
+    /// Maximum heuristics fetched per refresh (default: 50)
+    pub batch_limit: i32,
+    /// Part this replica plays in fleet refresh (default: independent)
+    pub role: RefreshRole,
+    /// How long a follower waits without a leader broadcast before
+    /// refreshing from storage itself, in ms (default: 180000)
+    pub leader_timeout_ms: u64,
+}
+
+impl Default for RefreshConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: env::var("REFRESH_INTERVAL_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            max_interval_ms: env::var("REFRESH_MAX_INTERVAL_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(60_000),
+            batch_limit: env::var("REFRESH_BATCH_LIMIT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(50),
+            role: env::var("REFRESH_ROLE")
+                .ok()
+                .and_then(|s| s.parse().map_err(|e| tracing::warn!("{}; using independent", e)).ok())
+                .unwrap_or_default(),
+            leader_timeout_ms: env::var("REFRESH_LEADER_TIMEOUT_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(180_000),
+        }
+    }
+}
+
+impl RefreshConfig {
+    pub fn enabled(&self) -> bool {
+        self.interval_ms > 0
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms)
+    }
+
+    pub fn max_interval(&self) -> Duration {
+        Duration::from_millis(self.max_interval_ms.max(self.interval_ms))
+    }
+}
+
+/// Cold-start heuristic seeding configuration.
+#[derive(Debug, Clone)]
+pub struct SeedConfig {
+    /// JSON seed file loaded into the cache at startup (default: none)
+    pub path: Option<String>,
+    /// Also store seeded heuristics in storage (default: false)
+    pub persist: bool,
+}
+
+impl Default for SeedConfig {
+    fn default() -> Self {
+        Self {
+            path: env::var("SEED_HEURISTICS_PATH").ok().filter(|s| !s.is_empty()),
+            persist: env::var("SEED_HEURISTICS_PERSIST")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Shared (Redis) cache tier configuration.
+#[derive(Debug, Clone)]
+pub struct SharedCacheConfig {
+    /// Redis host:port (default: none = disabled; see shared_cache module)
+    pub redis_address: Option<String>,
+    /// Prefix for keys and the invalidation channel (default: "gladys:salience:")
+    pub key_prefix: String,
+    /// Lifetime of shared match results in ms (default: 300000)
+    pub ttl_ms: u64,
+    /// Lifetime of shared "no match" results in ms (default: 30000)
+    pub negative_ttl_ms: u64,
+    /// Per-command timeout in ms (default: 100)
+    pub timeout_ms: u64,
+}
+
+impl Default for SharedCacheConfig {
+    fn default() -> Self {
+        Self {
+            redis_address: env::var("SHARED_CACHE_REDIS_ADDRESS").ok().filter(|s| !s.is_empty()),
+            key_prefix: env::var("SHARED_CACHE_KEY_PREFIX").unwrap_or_else(|_| "gladys:salience:".to_string()),
+            ttl_ms: env::var("SHARED_CACHE_TTL_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(300_000),
+            negative_ttl_ms: env::var("SHARED_CACHE_NEGATIVE_TTL_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(30_000),
+            timeout_ms: env::var("SHARED_CACHE_TIMEOUT_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(100),
+        }
+    }
+}
+
+/// Peer replica forwarding configuration.
+#[derive(Debug, Clone)]
+pub struct PeerConfig {
+    /// host:port of the other replicas' admin listeners (default: none = disabled; see peers module)
+    pub addresses: Vec<String>,
+    /// Per-peer connect + call timeout in ms (default: 500)
+    pub timeout_ms: u64,
+}
+
+impl Default for PeerConfig {
+    fn default() -> Self {
+        Self {
+            addresses: env::var("PEER_ADDRESSES")
+                .map(|s| s.split(',').map(str::trim).filter(|a| !a.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default(),
+            timeout_ms: env::var("PEER_TIMEOUT_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(500),
+        }
+    }
+}
+
+/// Evaluation router configuration (see router module).
+#[derive(Debug, Clone)]
+pub struct RouterConfig {
+    /// host:port of the instances to route to (default: none = evaluate locally)
+    pub backends: Vec<String>,
+    /// Per-backend call timeout in ms (default: 200)
+    pub timeout_ms: u64,
+    /// Points per backend on the hash ring (default: 64)
+    pub virtual_nodes: usize,
+}
+
+impl Default for RouterConfig {
+    fn default() -> Self {
+        Self {
+            backends: env::var("ROUTER_BACKENDS")
+                .map(|s| s.split(',').map(str::trim).filter(|a| !a.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default(),
+            timeout_ms: env::var("ROUTER_TIMEOUT_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(200),
+            virtual_nodes: env::var("ROUTER_VIRTUAL_NODES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(64),
+        }
+    }
+}
+
+/// Message bus intake configuration (used with the `nats` feature).
+#[derive(Debug, Clone)]
+pub struct BusConfig {
+    /// NATS host:port (default: none = disabled; see bus module)
+    pub nats_address: Option<String>,
+    /// Subject events are consumed from (default: "gladys.events")
+    pub subject: String,
+    /// Subject decisions are published to (default: "gladys.salience")
+    pub output_subject: String,
+    /// Queue group, so replicas split the stream (default: "gladys-salience")
+    pub queue_group: String,
+    /// Events evaluated concurrently (default: 64)
+    pub max_in_flight: usize,
+}
+
+impl Default for BusConfig {
+    fn default() -> Self {
+        Self {
+            nats_address: env::var("NATS_ADDRESS").ok().filter(|s| !s.is_empty()),
+            subject: env::var("NATS_SUBJECT").unwrap_or_else(|_| "gladys.events".to_string()),
+            output_subject: env::var("NATS_OUTPUT_SUBJECT").unwrap_or_else(|_| "gladys.salience".to_string()),
+            queue_group: env::var("NATS_QUEUE_GROUP").unwrap_or_else(|_| "gladys-salience".to_string()),
+            max_in_flight: env::var("NATS_MAX_IN_FLIGHT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(64),
+        }
+    }
+}
+
+/// MQTT intake configuration (used with the `mqtt` feature).
+#[derive(Debug, Clone)]
+pub struct MqttConfig {
+    /// Broker host:port (default: none = disabled; see mqtt module)
+    pub broker_address: Option<String>,
+    /// Client identifier (default: "gladys-salience")
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Topic filter -> event template, as JSON (default: none)
+    pub topics: Option<String>,
+    /// Topic decisions are published to (default: none = don't publish)
+    pub output_topic: Option<String>,
+    /// Keep-alive interval in seconds (default: 30)
+    pub keep_alive_secs: u16,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            broker_address: env::var("MQTT_BROKER_ADDRESS").ok().filter(|s| !s.is_empty()),
+            client_id: env::var("MQTT_CLIENT_ID").unwrap_or_else(|_| "gladys-salience".to_string()),
+            username: env::var("MQTT_USERNAME").ok().filter(|s| !s.is_empty()),
+            password: env::var("MQTT_PASSWORD").ok().filter(|s| !s.is_empty()),
+            topics: env::var("MQTT_TOPICS").ok().filter(|s| !s.is_empty()),
+            output_topic: env::var("MQTT_OUTPUT_TOPIC").ok().filter(|s| !s.is_empty()),
+            keep_alive_secs: env::var("MQTT_KEEP_ALIVE_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(30),
+        }
+    }
+}
+
+/// Decision write-back configuration (see the writeback module).
+#[derive(Debug, Clone)]
+pub struct WritebackConfig {
+    /// Record each decision on the stored event (default: false)
+    pub enabled: bool,
+    /// Updates per storage call (default: 50)
+    pub batch_size: usize,
+    /// Longest a queued update waits for its batch in milliseconds (default: 1000)
+    pub flush_interval_ms: u64,
+    /// Queued updates before new ones are dropped (default: 1024)
+    pub queue_capacity: usize,
+}
+
+impl Default for WritebackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: env::var("OUTCOME_WRITEBACK")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+            batch_size: env::var("OUTCOME_WRITEBACK_BATCH_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(50),
+            flush_interval_ms: env::var("OUTCOME_WRITEBACK_FLUSH_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(1000),
+            queue_capacity: env::var("OUTCOME_WRITEBACK_QUEUE_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(1024),
+        }
+    }
+}
+
+/// Latency SLO configuration (see the slo module).
+#[derive(Debug, Clone)]
+pub struct SloConfig {
+    /// Latency target in milliseconds (None = SLO tracking disabled)
+    pub target_ms: Option<f64>,
+    /// Percentile held to the target, 0-100 (default: 99)
+    pub percentile: f64,
+    /// Evaluations measured: "cache_hit" (served from cache) or "all" (default: "cache_hit")
+    pub scope: String,
+    /// Length of one evaluation window in seconds (default: 60)
+    pub window_secs: u64,
+    /// Consecutive breached windows before alerting (default: 3)
+    pub breach_windows: u32,
+}
+
+impl Default for SloConfig {
+    fn default() -> Self {
+        Self {
+            target_ms: env::var("SLO_TARGET_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|ms: &f64| *ms > 0.0),
+            percentile: env::var("SLO_PERCENTILE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(99.0),
+            scope: env::var("SLO_SCOPE").unwrap_or_else(|_| "cache_hit".to_string()),
+            window_secs: env::var("SLO_WINDOW_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(60),
+            breach_windows: env::var("SLO_BREACH_WINDOWS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3),
+        }
+    }
+}
+
+/// Adaptive cache sizing configuration (see the cache_sizing module).
+#[derive(Debug, Clone)]
+pub struct CacheSizingConfig {
+    /// Resize check interval in milliseconds (default: 0 = fixed capacities)
+    pub interval_ms: u64,
+    /// Heuristic capacity bounds (default: half / four times CACHE_MAX_HEURISTICS)
+    pub min_heuristics: Option<usize>,
+    pub max_heuristics: Option<usize>,
+    /// Event capacity bounds (default: half / four times CACHE_MAX_EVENTS)
+    pub min_events: Option<usize>,
+    pub max_events: Option<usize>,
+    /// Heuristic hit rate to hold, 0-1 (default: 0.8)
+    pub target_hit_rate: f32,
+    /// Fraction of the current capacity added or removed per resize (default: 0.25)
+    pub step: f32,
+}
+
+impl Default for CacheSizingConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: env::var("CACHE_ADAPTIVE_INTERVAL_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            min_heuristics: env::var("CACHE_ADAPTIVE_MIN_HEURISTICS").ok().and_then(|s| s.parse().ok()),
+            max_heuristics: env::var("CACHE_ADAPTIVE_MAX_HEURISTICS").ok().and_then(|s| s.parse().ok()),
+            min_events: env::var("CACHE_ADAPTIVE_MIN_EVENTS").ok().and_then(|s| s.parse().ok()),
+            max_events: env::var("CACHE_ADAPTIVE_MAX_EVENTS").ok().and_then(|s| s.parse().ok()),
+            target_hit_rate: env::var("CACHE_ADAPTIVE_TARGET_HIT_RATE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.8),
+            step: env::var("CACHE_ADAPTIVE_STEP")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.25),
+        }
+    }
+}
+
+impl CacheSizingConfig {
+    pub fn enabled(&self) -> bool {
+        self.interval_ms > 0
+    }
+}
+
+/// Cache warm-up readiness configuration (see the warmup module).
+#[derive(Debug, Clone)]
+pub struct WarmupConfig {
+    /// Cached heuristics required before reporting ready (default: 0 = no gating)
+    pub min_heuristics: usize,
+    /// Report ready after this many milliseconds regardless (default: 60000, 0 = no limit)
+    pub max_wait_ms: u64,
+}
+
+impl Default for WarmupConfig {
+    fn default() -> Self {
+        Self {
+            min_heuristics: env::var("WARMUP_MIN_HEURISTICS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            max_wait_ms: env::var("WARMUP_MAX_WAIT_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(60_000),
+        }
+    }
+}
+
+impl WarmupConfig {
+    pub fn enabled(&self) -> bool {
+        self.min_heuristics > 0
+    }
+}
+
+/// Warm standby configuration (see the standby module).
+#[derive(Debug, Clone)]
+pub struct StandbyConfig {
+    /// Instance to copy the cache from, host:port of its admin listener (default: none)
+    pub source_address: Option<String>,
+    /// How often the source sends cache changes, in milliseconds (default: 1000)
+    pub sync_interval_ms: u64,
+}
+
+impl Default for StandbyConfig {
+    fn default() -> Self {
+        Self {
+            source_address: env::var("STANDBY_SOURCE_ADDRESS").ok().filter(|s| !s.is_empty()),
+            sync_interval_ms: env::var("STANDBY_SYNC_INTERVAL_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(1000),
         }
     }
 }
 
+impl StandbyConfig {
+    pub fn enabled(&self) -> bool {
+        self.source_address.is_some()
+    }
+}
+
+/// System-1 local action configuration (see the actions module).
+#[derive(Debug, Clone)]
+pub struct ActionConfig {
+    /// Action names allowed to run (default: none = disabled)
+    pub allowed: Vec<String>,
+    /// Heuristic confidence required to run an action (default: 0.9)
+    pub min_confidence: f32,
+}
+
+impl Default for ActionConfig {
+    fn default() -> Self {
+        Self {
+            allowed: env::var("SYSTEM1_ACTIONS")
+                .map(|s| s.split(',').map(str::trim).filter(|a| !a.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default(),
+            min_confidence: env::var("SYSTEM1_ACTION_MIN_CONFIDENCE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.9),
+        }
+    }
+}
+
+impl ActionConfig {
+    pub fn enabled(&self) -> bool {
+        !self.allowed.is_empty()
+    }
+}
+
 /// Root configuration that aggregates all config sections.
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -145,8 +1468,29 @@ pub struct Config {
     pub storage: StorageConfig,
     pub cache: CacheConfig,
     pub salience: SalienceConfig,
-    /// Scorer implementation to use ("embedding" default)
+    pub refresh: RefreshConfig,
+    pub seed: SeedConfig,
+    pub shared_cache: SharedCacheConfig,
+    pub peers: PeerConfig,
+    pub router: RouterConfig,
+    pub bus: BusConfig,
+    pub mqtt: MqttConfig,
+    pub writeback: WritebackConfig,
+    pub slo: SloConfig,
+    pub cache_sizing: CacheSizingConfig,
+    pub warmup: WarmupConfig,
+    pub standby: StandbyConfig,
+    pub actions: ActionConfig,
+    /// Scorer implementation to use ("embedding" default, "word_overlap" with the feature)
     pub scorer: String,
+    /// A/B experiment definition as JSON (see the experiments module; default: none)
+    pub experiment: Option<String>,
+    /// Per-language scoring profiles as JSON (see the language module; default: none)
+    pub language_profiles: Option<String>,
+    /// Per-source QoS classes as JSON (see the qos module; default: none)
+    pub qos_classes: Option<String>,
+    /// QoS class for sources no class lists (default: none = unbudgeted)
+    pub qos_default_class: Option<String>,
 }
 
 impl Default for Config {
@@ -156,7 +1500,24 @@ impl Default for Config {
             storage: StorageConfig::default(),
             cache: CacheConfig::default(),
             salience: SalienceConfig::default(),
+            refresh: RefreshConfig::default(),
+            seed: SeedConfig::default(),
+            shared_cache: SharedCacheConfig::default(),
+            peers: PeerConfig::default(),
+            router: RouterConfig::default(),
+            bus: BusConfig::default(),
+            mqtt: MqttConfig::default(),
+            writeback: WritebackConfig::default(),
+            slo: SloConfig::default(),
+            cache_sizing: CacheSizingConfig::default(),
+            warmup: WarmupConfig::default(),
+            standby: StandbyConfig::default(),
+            actions: ActionConfig::default(),
             scorer: "embedding".to_string(),
+            experiment: None,
+            language_profiles: None,
+            qos_classes: None,
+            qos_default_class: None,
         }
     }
 }
@@ -168,6 +1529,10 @@ impl Config {
         if let Ok(s) = env::var("SALIENCE_SCORER") {
             config.scorer = s;
         }
+        config.experiment = env::var("SALIENCE_EXPERIMENT").ok().filter(|s| !s.is_empty());
+        config.language_profiles = env::var("SALIENCE_LANGUAGE_PROFILES").ok().filter(|s| !s.is_empty());
+        config.qos_classes = env::var("SALIENCE_QOS_CLASSES").ok().filter(|s| !s.is_empty());
+        config.qos_default_class = env::var("SALIENCE_QOS_DEFAULT_CLASS").ok().filter(|s| !s.is_empty());
         config
     }
 
@@ -176,11 +1541,60 @@ impl Config {
         tracing::info!(
             server_host = %self.server.host,
             server_port = self.server.port,
+            metrics_port = self.server.metrics_port,
+            ws_port = self.server.ws_port,
+            admin_port = self.server.admin_port,
+            admin_socket = ?self.server.admin_socket,
+            storage_backend = self.storage.backend.as_str(),
             storage_address = %self.storage.address,
+            storage_sqlite_path = %self.storage.sqlite_path,
+            // Host and database only; the URL may carry a password
+            storage_postgres = %self.storage.postgres_url.rsplit('@').next().unwrap_or_default(),
+            storage_postgres_max_connections = self.storage.postgres_max_connections,
+            storage_record_path = ?self.storage.record_path,
+            storage_replay_path = ?self.storage.replay_path,
+            storage_schema_mismatch = self.storage.schema_mismatch.as_str(),
             cache_max_events = self.cache.max_events,
             cache_max_heuristics = self.cache.max_heuristics,
             novelty_threshold = self.cache.novelty_threshold,
+            similarity_metric = ?self.cache.similarity_metric,
+            dedup_window_ms = self.cache.dedup_window_ms,
+            cache_adaptive_interval_ms = self.cache_sizing.interval_ms,
+            cache_compaction_interval_ms = self.cache.compaction_interval_ms,
+            cache_event_access_weight_ms = self.cache.event_access_weight_ms,
+            cache_model_change_policy = self.cache.model_change_policy.as_str(),
+            cache_exemplar_aggregation = self.cache.exemplar_aggregation.as_str(),
+            cache_negative_threshold = self.cache.negative_threshold,
+            cache_negative_policy = self.cache.negative_policy.as_str(),
             min_heuristic_confidence = self.salience.min_heuristic_confidence,
+            max_text_bytes = self.salience.max_text_bytes,
+            oversize_policy = self.salience.oversize_policy.as_str(),
+            normalize_text = self.salience.normalize_text,
+            language_detection = self.salience.language_detection,
+            language_profiles = ?self.language_profiles,
+            qos_classes = ?self.qos_classes,
+            qos_default_class = ?self.qos_default_class,
+            min_match_probability = self.salience.min_match_probability,
+            calibration_path = ?self.salience.calibration_path,
+            source_allowlist = ?self.salience.source_allowlist,
+            source_denylist = ?self.salience.source_denylist,
+            shed_queue_start = self.salience.shed_queue_start,
+            shed_max_rate = self.salience.shed_max_rate,
+            maintenance = self.salience.maintenance,
+            maintenance_profile = ?self.salience.maintenance_profile,
+            refresh_interval_ms = self.refresh.interval_ms,
+            refresh_role = self.refresh.role.as_str(),
+            seed_path = ?self.seed.path,
+            warmup_min_heuristics = self.warmup.min_heuristics,
+            standby_source = ?self.standby.source_address,
+            system1_actions = ?self.actions.allowed,
+            shared_cache = ?self.shared_cache.redis_address,
+            peers = ?self.peers.addresses,
+            router_backends = ?self.router.backends,
+            nats_address = ?self.bus.nats_address,
+            mqtt_broker = ?self.mqtt.broker_address,
+            outcome_writeback = self.writeback.enabled,
+            slo_target_ms = ?self.slo.target_ms,
             scorer = %self.scorer,
             "Configuration loaded"
         );
@@ -198,5 +1612,40 @@ mod tests {
         assert_eq!(config.cache.max_events, 1000);
         assert!((config.cache.novelty_threshold - 0.7).abs() < 0.001);
         assert_eq!(config.scorer, "embedding");
+        assert_eq!(config.cache.similarity_metric, SimilarityMetric::Cosine);
+        assert!(!config.refresh.enabled());
+    }
+
+    #[test]
+    fn test_parse_dimension_map() {
+        let map = parse_dimension_map("novelty=0.1, social = 0.5,bogus,threat=x");
+        assert_eq!(map.len(), 2);
+        assert!((map["novelty"] - 0.1).abs() < 0.001);
+        assert!((map["social"] - 0.5).abs() < 0.001);
+        assert_eq!("Softmax".parse::<VectorNormalization>(), Ok(VectorNormalization::Softmax));
+    }
+
+    #[test]
+    fn test_parse_dimension_pairs() {
+        let pairs = parse_dimension_pairs("threat:social, threat : opportunity,bogus,novelty:novelty,:x");
+        assert_eq!(pairs, vec![("threat".to_string(), "social".to_string()), ("threat".to_string(), "opportunity".to_string())]);
+        assert_eq!("Average".parse::<ConflictResolution>(), Ok(ConflictResolution::Average));
+    }
+
+    #[test]
+    fn test_parse_fallback_order() {
+        assert_eq!(
+            parse_fallback_order("Keywords, storage,bogus"),
+            vec![EmbeddingFallback::Keywords, EmbeddingFallback::Storage]
+        );
+        assert!(parse_fallback_order("none").is_empty());
+        assert!(parse_fallback_order("").is_empty());
+    }
+
+    #[test]
+    fn test_similarity_metric_parse() {
+        assert_eq!("DOT".parse::<SimilarityMetric>(), Ok(SimilarityMetric::Dot));
+        assert_eq!("euclidean".parse::<SimilarityMetric>(), Ok(SimilarityMetric::Euclidean));
+        assert!("manhattan".parse::<SimilarityMetric>().is_err());
     }
 }
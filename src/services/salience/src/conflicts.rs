@@ -0,0 +1,165 @@
+//! Conflict detection between heuristics matching the same event.
+//!
+//! Only the best match's boost is applied, so when the runner-up says the
+//! opposite (one boosts threat, another social) that disagreement is lost.
+//! With opposing dimension pairs configured, the top matches are checked
+//! against the best one:
+//! - A conflict is the best match boosting one side of a pair while another
+//!   top match boosts the other
+//! - `highest_confidence`: the most confident of the conflicting matches wins
+//!   and its boost is applied
+//! - `average`: the conflicting matches' boosts are averaged per dimension
+//!   (a match that doesn't boost a dimension counts as 0)
+//! - `veto`: the match boosting a veto dimension (e.g., threat) wins; with
+//!   none involved, highest confidence decides
+//!
+//! The conflict (matches, dimension pairs, strategy, winner) is reported in
+//! the response and logged.
+//!
+//! Configuration via environment variables (see `SalienceConfig`):
+//!   SALIENCE_CONFLICT_PAIRS: Opposing dimensions, "a:b,c:d" (default: none = disabled)
+//!   SALIENCE_CONFLICT_TOP_K: Matches checked, including the best (default: 3)
+//!   SALIENCE_CONFLICT_RESOLUTION: highest_confidence, average or veto (default: highest_confidence)
+//!   SALIENCE_CONFLICT_VETO: Dimensions that win under veto (default: threat)
+
+use std::collections::BTreeSet;
+
+use crate::config::{ConflictResolution, SalienceConfig};
+use crate::domain::SalienceBoost;
+use crate::ScoredMatch;
+
+/// Opposing boosts among the top matches and how they were settled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// The conflicting matches, best first
+    pub heuristic_ids: Vec<String>,
+    /// Opposing pairs found, as "a:b" (the best match's side first)
+    pub dimensions: Vec<String>,
+    pub resolution: ConflictResolution,
+    /// Index into the matches of the one reported as matched
+    pub winner: usize,
+    /// The boost to apply in place of the best match's
+    pub boost: SalienceBoost,
+}
+
+fn boosts(m: &ScoredMatch, dimension: &str) -> bool {
+    m.salience_boost.as_ref().and_then(|b| b.get(dimension)).is_some_and(|v| *v > 0.0)
+}
+
+/// Check the top matches (best first) for boosts opposing the best one's
+/// and resolve them. None when detection is off or nothing conflicts.
+pub fn resolve_conflicts(matches: &[ScoredMatch], config: &SalienceConfig) -> Option<Conflict> {
+    let best = matches.first()?;
+    if config.conflict_pairs.is_empty() {
+        return None;
+    }
+    let mut involved = vec![0];
+    let mut dimensions = BTreeSet::new();
+    for (i, other) in matches.iter().enumerate().take(config.conflict_top_k.max(1)).skip(1) {
+        for (a, b) in &config.conflict_pairs {
+            for (mine, theirs) in [(a, b), (b, a)] {
+                if boosts(best, mine) && boosts(other, theirs) {
+                    dimensions.insert(format!("{}:{}", mine, theirs));
+                    if !involved.contains(&i) {
+                        involved.push(i);
+                    }
+                }
+            }
+        }
+    }
+    if involved.len() < 2 {
+        return None;
+    }
+
+    let most_confident = || {
+        involved
+            .iter()
+            .copied()
+            .reduce(|w, i| if matches[i].confidence > matches[w].confidence { i } else { w })
+            .unwrap_or(0)
+    };
+    let boost_of = |i: usize| matches[i].salience_boost.clone().unwrap_or_default();
+    let (winner, boost) = match config.conflict_resolution {
+        ConflictResolution::HighestConfidence => {
+            let winner = most_confident();
+            (winner, boost_of(winner))
+        }
+        ConflictResolution::Average => {
+            let mut sum = SalienceBoost::new();
+            for &i in &involved {
+                for (dimension, value) in boost_of(i) {
+                    *sum.entry(dimension).or_default() += value;
+                }
+            }
+            let n = involved.len() as f32;
+            (0, sum.into_iter().map(|(dimension, value)| (dimension, value / n)).collect())
+        }
+        ConflictResolution::Veto => {
+            let vetoing = involved
+                .iter()
+                .copied()
+                .find(|&i| config.conflict_veto_dimensions.iter().any(|d| boosts(&matches[i], d)));
+            let winner = vetoing.unwrap_or_else(most_confident);
+            (winner, boost_of(winner))
+        }
+    };
+
+    Some(Conflict {
+        heuristic_ids: involved.iter().map(|&i| matches[i].heuristic_id.clone()).collect(),
+        dimensions: dimensions.into_iter().collect(),
+        resolution: config.conflict_resolution,
+        winner,
+        boost,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scored(id: &str, confidence: f32, boost: &[(&str, f32)]) -> ScoredMatch {
+        ScoredMatch {
+            heuristic_id: id.to_string(),
+            similarity: 0.9,
+            confidence,
+            origin: String::new(),
+            condition_text: String::new(),
+            suggested_action: String::new(),
+            salience_boost: Some(boost.iter().map(|(d, v)| (d.to_string(), *v)).collect()),
+            effects: Default::default(),
+            method: Default::default(),
+            negative: None,
+        }
+    }
+
+    #[test]
+    fn test_conflicting_boosts_resolve_per_strategy() {
+        let mut config = SalienceConfig {
+            conflict_pairs: vec![("threat".to_string(), "social".to_string())],
+            ..SalienceConfig::default()
+        };
+        let matches = vec![
+            scored("joke", 0.6, &[("social", 0.8)]),
+            scored("ambush", 0.9, &[("threat", 0.6)]),
+            scored("unrelated", 1.0, &[("novelty", 0.5)]),
+        ];
+        assert!(resolve_conflicts(&matches[..1], &config).is_none());
+        let conflict = resolve_conflicts(&matches, &config).unwrap();
+        assert_eq!(conflict.heuristic_ids, vec!["joke", "ambush"]);
+        assert_eq!(conflict.dimensions, vec!["social:threat"]);
+        assert_eq!((conflict.winner, conflict.boost.get("threat").copied()), (1, Some(0.6)));
+
+        config.conflict_resolution = ConflictResolution::Average;
+        let conflict = resolve_conflicts(&matches, &config).unwrap();
+        assert_eq!(conflict.winner, 0);
+        assert_eq!(conflict.boost, SalienceBoost::from([("social".to_string(), 0.4), ("threat".to_string(), 0.3)]));
+
+        // The veto dimension wins even against a more confident match
+        config.conflict_resolution = ConflictResolution::Veto;
+        let swapped = vec![scored("ambush", 0.5, &[("threat", 0.6)]), scored("joke", 0.9, &[("social", 0.8)])];
+        assert_eq!(resolve_conflicts(&swapped, &config).unwrap().winner, 0);
+
+        config.conflict_pairs.clear();
+        assert!(resolve_conflicts(&matches, &config).is_none());
+    }
+}
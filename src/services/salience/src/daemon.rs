@@ -0,0 +1,289 @@
+//! Service manager integration: graceful shutdown and readiness.
+//!
+//! `run_server` serves until `shutdown_signal()` resolves, then stops
+//! accepting connections and lets in-flight RPCs finish. The signal is:
+//! - Ctrl+C everywhere, and SIGTERM on Unix
+//! - A stop or system shutdown from the Windows Service Control Manager
+//!
+//! Under systemd (`Type=notify`), READY=1 is sent once the gRPC listener is
+//! bound and STOPPING=1 when shutdown begins. The sd_notify datagram protocol
+//! is spoken directly; without `NOTIFY_SOCKET` this is a no-op.
+//!
+//! On Windows, `memory-fast-path --service` runs under the Service Control
+//! Manager, and `--install-service` / `--uninstall-service` register it via
+//! sc.exe. Services don't see the user's environment: set configuration
+//! variables on the service (its `Environment` registry value).
+
+use std::sync::OnceLock;
+
+use tokio::sync::Notify;
+use tracing::{debug, info, warn};
+
+/// Service name registered with the Windows Service Control Manager.
+pub const SERVICE_NAME: &str = "GLADySSalience";
+
+fn stop_requested() -> &'static Notify {
+    static STOP: OnceLock<Notify> = OnceLock::new();
+    STOP.get_or_init(Notify::new)
+}
+
+/// Ask the server to shut down gracefully (as a signal would).
+pub fn request_stop() {
+    // notify_one keeps a permit, so a stop before anyone waits isn't lost
+    stop_requested().notify_one();
+}
+
+/// Resolves when the process is asked to stop.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!(error = %e, "Ctrl+C handler unavailable");
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                warn!(error = %e, "SIGTERM handler unavailable");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Ctrl+C received; shutting down"),
+        _ = terminate => info!("SIGTERM received; shutting down"),
+        _ = stop_requested().notified() => info!("Stop requested; shutting down"),
+    }
+    notify_systemd("STOPPING=1");
+}
+
+/// Send a state update (e.g. "READY=1") to systemd. No-op unless started
+/// by systemd with `NOTIFY_SOCKET` set; failures are only logged.
+pub fn notify_systemd(state: &str) {
+    #[cfg(unix)]
+    {
+        if let Some(socket) = std::env::var_os("NOTIFY_SOCKET") {
+            match send_notify(&socket, state) {
+                Ok(()) => debug!(state, "Notified systemd"),
+                Err(e) => warn!(state, error = %e, "Failed to notify systemd"),
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = state;
+}
+
+#[cfg(unix)]
+fn send_notify(socket: &std::ffi::OsStr, state: &str) -> std::io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::net::UnixDatagram;
+
+    let datagram = UnixDatagram::unbound()?;
+    match socket.as_bytes().strip_prefix(b"@") {
+        // Abstract namespace socket (Linux only)
+        #[cfg(target_os = "linux")]
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            datagram.send_to_addr(state.as_bytes(), &addr)?;
+        }
+        #[cfg(not(target_os = "linux"))]
+        Some(_) => {
+            return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "abstract sockets need Linux"));
+        }
+        None => {
+            datagram.send_to(state.as_bytes(), socket)?;
+        }
+    }
+    Ok(())
+}
+
+/// Windows Service Control Manager hosting (advapi32, no extra dependencies).
+#[cfg(windows)]
+pub mod windows {
+    use std::ffi::c_void;
+    use std::io;
+    use std::process::Command;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::OnceLock;
+
+    use super::SERVICE_NAME;
+
+    type ServiceMain = extern "system" fn(u32, *mut *mut u16);
+    type HandlerEx = extern "system" fn(u32, u32, *mut c_void, *mut c_void) -> u32;
+
+    #[repr(C)]
+    struct ServiceTableEntry {
+        name: *mut u16,
+        main: Option<ServiceMain>,
+    }
+
+    #[repr(C)]
+    struct ServiceStatus {
+        service_type: u32,
+        current_state: u32,
+        controls_accepted: u32,
+        win32_exit_code: u32,
+        service_specific_exit_code: u32,
+        check_point: u32,
+        wait_hint: u32,
+    }
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn StartServiceCtrlDispatcherW(table: *const ServiceTableEntry) -> i32;
+        fn RegisterServiceCtrlHandlerExW(name: *const u16, handler: HandlerEx, context: *mut c_void) -> *mut c_void;
+        fn SetServiceStatus(handle: *mut c_void, status: *const ServiceStatus) -> i32;
+    }
+
+    const SERVICE_WIN32_OWN_PROCESS: u32 = 0x10;
+    const SERVICE_STOPPED: u32 = 1;
+    const SERVICE_STOP_PENDING: u32 = 3;
+    const SERVICE_RUNNING: u32 = 4;
+    const SERVICE_ACCEPT_STOP: u32 = 0x1;
+    const SERVICE_ACCEPT_SHUTDOWN: u32 = 0x4;
+    const SERVICE_CONTROL_STOP: u32 = 1;
+    const SERVICE_CONTROL_INTERROGATE: u32 = 4;
+    const SERVICE_CONTROL_SHUTDOWN: u32 = 5;
+    const NO_ERROR: u32 = 0;
+    const ERROR_CALL_NOT_IMPLEMENTED: u32 = 120;
+    const ERROR_SERVICE_SPECIFIC_ERROR: u32 = 1066;
+
+    /// How long a stop may take before the SCM gives up on us, in ms.
+    const STOP_WAIT_HINT_MS: u32 = 30_000;
+
+    type Entry = fn() -> Result<(), Box<dyn std::error::Error>>;
+
+    static ENTRY: OnceLock<Entry> = OnceLock::new();
+    static STATUS_HANDLE: AtomicUsize = AtomicUsize::new(0);
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(Some(0)).collect()
+    }
+
+    fn set_state(state: u32, exit_code: u32) {
+        let handle = STATUS_HANDLE.load(Ordering::SeqCst) as *mut c_void;
+        if handle.is_null() {
+            return;
+        }
+        let status = ServiceStatus {
+            service_type: SERVICE_WIN32_OWN_PROCESS,
+            current_state: state,
+            controls_accepted: if state == SERVICE_RUNNING { SERVICE_ACCEPT_STOP | SERVICE_ACCEPT_SHUTDOWN } else { 0 },
+            win32_exit_code: exit_code,
+            service_specific_exit_code: (exit_code == ERROR_SERVICE_SPECIFIC_ERROR) as u32,
+            check_point: 0,
+            wait_hint: if state == SERVICE_STOP_PENDING { STOP_WAIT_HINT_MS } else { 0 },
+        };
+        // SAFETY: handle came from RegisterServiceCtrlHandlerExW; status outlives the call
+        unsafe { SetServiceStatus(handle, &status) };
+    }
+
+    extern "system" fn control_handler(control: u32, _event: u32, _data: *mut c_void, _context: *mut c_void) -> u32 {
+        match control {
+            SERVICE_CONTROL_STOP | SERVICE_CONTROL_SHUTDOWN => {
+                set_state(SERVICE_STOP_PENDING, NO_ERROR);
+                super::request_stop();
+                NO_ERROR
+            }
+            SERVICE_CONTROL_INTERROGATE => NO_ERROR,
+            _ => ERROR_CALL_NOT_IMPLEMENTED,
+        }
+    }
+
+    extern "system" fn service_main(_argc: u32, _argv: *mut *mut u16) {
+        let name = wide(SERVICE_NAME);
+        // SAFETY: name is NUL-terminated and outlives the call; the handler is 'static
+        let handle = unsafe { RegisterServiceCtrlHandlerExW(name.as_ptr(), control_handler, std::ptr::null_mut()) };
+        if handle.is_null() {
+            return;
+        }
+        STATUS_HANDLE.store(handle as usize, Ordering::SeqCst);
+        set_state(SERVICE_RUNNING, NO_ERROR);
+
+        let result = ENTRY.get().map_or(Ok(()), |entry| entry());
+        if let Err(e) = &result {
+            tracing::error!(error = %e, "Service stopped with an error");
+        }
+        set_state(SERVICE_STOPPED, if result.is_ok() { NO_ERROR } else { ERROR_SERVICE_SPECIFIC_ERROR });
+    }
+
+    /// Run `entry` under the Service Control Manager; returns once the
+    /// service has stopped. Fails when not started by the SCM.
+    pub fn run_service(entry: Entry) -> io::Result<()> {
+        let _ = ENTRY.set(entry);
+        let mut name = wide(SERVICE_NAME);
+        let table = [
+            ServiceTableEntry { name: name.as_mut_ptr(), main: Some(service_main) },
+            ServiceTableEntry { name: std::ptr::null_mut(), main: None },
+        ];
+        // SAFETY: the table is NULL-terminated and lives until the dispatcher returns
+        if unsafe { StartServiceCtrlDispatcherW(table.as_ptr()) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn sc(args: &[&str]) -> io::Result<()> {
+        let status = Command::new("sc.exe").args(args).status()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("sc.exe {} failed ({})", args[0], status)));
+        }
+        Ok(())
+    }
+
+    /// Register this executable as an auto-start service.
+    pub fn install_service() -> io::Result<()> {
+        let bin_path = format!("\"{}\" --service", std::env::current_exe()?.display());
+        sc(&[
+            "create",
+            SERVICE_NAME,
+            "binPath=",
+            &bin_path,
+            "start=",
+            "auto",
+            "DisplayName=",
+            "GLADyS Salience Fast Path",
+        ])
+    }
+
+    /// Remove the service registration (stop it first).
+    pub fn uninstall_service() -> io::Result<()> {
+        sc(&["delete", SERVICE_NAME])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stop_request_before_waiting_is_kept() {
+        request_stop();
+        tokio::time::timeout(std::time::Duration::from_secs(1), shutdown_signal())
+            .await
+            .expect("shutdown_signal should resolve on a pending stop request");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_notify_socket_receives_state() {
+        let dir = std::env::temp_dir().join(format!("gladys-notify-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notify.sock");
+        let listener = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+
+        send_notify(path.as_os_str(), "READY=1").unwrap();
+        let mut buf = [0u8; 16];
+        let n = listener.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"READY=1");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+//! Degradation policy: what an evaluation does when a dependency fails.
+//!
+//! Operators choose per failure type how conservative the fast path is
+//! while storage or the embedding model is flaky:
+//! - Embedding failure: the fallback steps tried, in order (cache keyword
+//!   match, storage query, or none). A step that matches ends scoring;
+//!   with no steps, the evaluation fails as an embedding error.
+//! - Each failure type (embedding with no fallback match, storage query,
+//!   rate-limited fallback) can report a salience profile instead of the
+//!   built-in default, e.g. a high threat so events escalate while blind, or
+//!   zeros so they're dropped.
+//! - Each failure type can set `retry_suggested` on the response, asking the
+//!   caller to re-evaluate the event once the dependency recovers.
+//!
+//! The defaults match the previous hard-coded behavior: storage fallback on
+//! embedding failure, the unmatched novelty boost on errors, baseline when
+//! rate limited, and no retries.
+//!
+//! Configuration via environment variables (see `SalienceConfig`):
+//!   DEGRADE_EMBEDDING_FALLBACK: Ordered steps, "keywords,storage" or "none" (default: storage)
+//!   DEGRADE_EMBEDDING_SALIENCE: Profile when no fallback step can score, "threat=0.8,novelty=1.0" (default: none)
+//!   DEGRADE_EMBEDDING_RETRY: Suggest retry when embedding failed (default: false)
+//!   DEGRADE_STORAGE_SALIENCE: Profile when the storage query fails (default: none)
+//!   DEGRADE_STORAGE_RETRY: Suggest retry when the storage query fails (default: false)
+//!   DEGRADE_RATE_LIMITED_SALIENCE: Profile when the fallback is rate limited (default: none)
+//!   DEGRADE_RATE_LIMITED_RETRY: Suggest retry when rate limited (default: false)
+
+use crate::config::FailurePolicy;
+use crate::postprocess::THREAT_DIMENSION;
+use crate::proto::SalienceResult;
+
+/// Report a failure policy's salience profile: each listed dimension (or
+/// "threat") takes the profile value, whether above or below the baseline.
+pub(crate) fn apply_failure_salience(salience: &mut SalienceResult, policy: &FailurePolicy) {
+    if policy.salience.is_empty() {
+        return;
+    }
+    for (dimension, value) in &policy.salience {
+        if dimension == THREAT_DIMENSION {
+            salience.threat = *value;
+        } else {
+            salience.vector.insert(dimension.clone(), *value);
+        }
+    }
+    salience.salience = salience.vector.values().copied().fold(0.0, f32::max);
+    salience.model_id = "degraded_profile_v1".to_string();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn baseline() -> SalienceResult {
+        SalienceResult {
+            threat: 0.1,
+            salience: 0.6,
+            habituation: 0.0,
+            vector: HashMap::from([("novelty".to_string(), 0.6), ("goal_relevance".to_string(), 0.3)]),
+            model_id: "heuristic_v1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_empty_policy_keeps_salience() {
+        let mut salience = baseline();
+        apply_failure_salience(&mut salience, &FailurePolicy { salience: HashMap::new(), retry: true });
+        assert_eq!(salience, baseline());
+    }
+
+    #[test]
+    fn test_profile_overrides_listed_dimensions() {
+        // Escalate while blind: threat up, novelty down, other dimensions untouched
+        let policy = FailurePolicy {
+            salience: HashMap::from([("threat".to_string(), 0.8), ("novelty".to_string(), 0.2)]),
+            retry: false,
+        };
+        let mut salience = baseline();
+        apply_failure_salience(&mut salience, &policy);
+        assert_eq!(salience.threat, 0.8);
+        assert_eq!(salience.vector["novelty"], 0.2);
+        assert_eq!(salience.vector["goal_relevance"], 0.3);
+        assert!(!salience.vector.contains_key("threat"));
+        assert_eq!(salience.salience, 0.3);
+        assert_eq!(salience.model_id, "degraded_profile_v1");
+
+        // Drop while blind: zeros win over the baseline
+        let drop = FailurePolicy {
+            salience: HashMap::from([("novelty".to_string(), 0.0), ("goal_relevance".to_string(), 0.0)]),
+            retry: false,
+        };
+        let mut salience = baseline();
+        apply_failure_salience(&mut salience, &drop);
+        assert_eq!(salience.salience, 0.0);
+        assert_eq!(salience.threat, 0.1);
+    }
+}
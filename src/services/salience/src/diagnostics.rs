@@ -0,0 +1,89 @@
+//! Self-test behind the RunDiagnostics RPC.
+//!
+//! A post-deploy smoke check needs more than GetHealth (which only proves the
+//! process is up). RunDiagnostics drives a probe text through each step of
+//! the fast path and reports every step as a timed pass/fail check:
+//! - `embedding`: GenerateEmbedding for the probe
+//! - `cache_round_trip`: insert, match and evict a probe heuristic in a
+//!   scratch cache with the live cache's configuration (the live cache is
+//!   never touched by this step)
+//! - `storage_query`: QueryMatchingHeuristics for the probe
+//! - `evaluate`: a full evaluation, not recorded in metrics, replay history
+//!   or write-back
+//!
+//! Steps run in order and a failure doesn't stop later ones, so a single
+//! report shows everything that is broken.
+
+use std::time::Instant;
+
+use uuid::Uuid;
+
+use crate::proto::DiagnosticCheck;
+use crate::{CacheConfig, CachedHeuristic, Condition, MemoryCache};
+
+/// Probe text used when the request doesn't supply one.
+pub const PROBE_TEXT: &str = "diagnostics probe: the front door opened";
+
+/// Embedding for the cache round trip when the embedding step failed.
+const FALLBACK_EMBEDDING: [f32; 4] = [0.5, 0.5, 0.5, 0.5];
+
+/// A timed check from a step's outcome (Ok = summary, Err = failure reason).
+pub fn check(name: &str, started: Instant, outcome: Result<String, String>) -> DiagnosticCheck {
+    let duration_ms = started.elapsed().as_secs_f32() * 1000.0;
+    let (passed, detail) = match outcome {
+        Ok(summary) => (true, summary),
+        Err(reason) => (false, reason),
+    };
+    DiagnosticCheck { name: name.to_string(), passed, duration_ms, detail }
+}
+
+/// Insert a probe heuristic into a scratch cache, match it by its own
+/// embedding, then remove it.
+pub fn cache_round_trip(config: &CacheConfig, embedding: Option<&[f32]>) -> Result<String, String> {
+    let embedding = embedding.filter(|e| !e.is_empty()).unwrap_or(&FALLBACK_EMBEDDING);
+    let mut cache = MemoryCache::new(config.clone());
+    let probe = CachedHeuristic {
+        id: Uuid::new_v4(),
+        name: "diagnostics-probe".to_string(),
+        condition: Condition::text(PROBE_TEXT),
+        effects: serde_json::json!({"message": "diagnostics probe"}).into(),
+        confidence: 1.0,
+        origin: "system".to_string(),
+        source: String::new(),
+        condition_embedding: embedding.into(),
+        last_accessed_ms: 0,
+        cached_at_ms: 0,
+        hit_count: 0,
+        last_hit_ms: 0,
+        cooldown_until_ms: 0,
+        embedding_model: String::new(),
+    };
+    let id = probe.id;
+    cache.add_heuristic(probe);
+
+    let similarity = match cache.find_matching_heuristics(embedding, 0.0, 0.0, 1).first() {
+        Some((matched, similarity)) if *matched == id => *similarity,
+        Some((matched, _)) => return Err(format!("lookup returned {} instead of the probe", matched)),
+        None => return Err("probe heuristic not found by its own embedding".to_string()),
+    };
+    if !cache.remove_heuristic(&id) {
+        return Err("probe heuristic could not be removed".to_string());
+    }
+    Ok(format!("self-similarity {:.3} ({:?})", similarity, config.similarity_metric))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_round_trip() {
+        let config = CacheConfig::default();
+        assert!(cache_round_trip(&config, Some(&[1.0, 0.0, 0.0])).unwrap().starts_with("self-similarity 1.000"));
+        // Falls back to a canned embedding when the embedding step failed
+        assert!(cache_round_trip(&config, None).is_ok());
+
+        let failed = check("embedding", Instant::now(), Err("timeout".to_string()));
+        assert!(!failed.passed && failed.detail == "timeout");
+    }
+}
@@ -0,0 +1,260 @@
+//! Domain types for events and heuristics.
+//!
+//! The cache and scorers work on these instead of proto strings and raw
+//! JSON, so the wire format can change without touching cache semantics.
+//! Conversions from and to the proto messages live in `compat`.
+//!
+//! `Effects` is the typed form of a heuristic's `effects_json`. Keys the fast
+//! path doesn't use are kept in `extra`, so a heuristic written back to
+//! storage (or exported as a seed file) loses nothing.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use gladys_salience_core::BOOST_DIMENSIONS;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::postprocess::THREAT_DIMENSION;
+
+/// An event to score.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Event {
+    pub id: String,
+    /// Event source; empty = unscoped (matches heuristics for any source)
+    pub source: String,
+    pub raw_text: String,
+}
+
+/// What a heuristic matches on.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Condition {
+    #[serde(default)]
+    pub text: String,
+    /// Further phrasings matched alongside `text` (see exemplars module)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exemplars: Vec<Exemplar>,
+    /// Phrasings that must not match: closeness to one counts against a match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub negatives: Vec<Exemplar>,
+}
+
+impl Condition {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self { text: text.into(), exemplars: Vec::new(), negatives: Vec::new() }
+    }
+
+    pub fn with_exemplars(mut self, exemplars: Vec<Exemplar>) -> Self {
+        self.exemplars = exemplars;
+        self
+    }
+
+    pub fn with_negatives(mut self, negatives: Vec<Exemplar>) -> Self {
+        self.negatives = negatives;
+        self
+    }
+
+    /// Exemplars and negatives together.
+    pub fn all_exemplars_mut(&mut self) -> impl Iterator<Item = &mut Exemplar> {
+        self.exemplars.iter_mut().chain(self.negatives.iter_mut())
+    }
+}
+
+/// One exemplar phrasing of a condition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exemplar {
+    pub text: String,
+    /// Scales this exemplar's similarity (a negative's: its penalty), in
+    /// (0, 1] (the condition text's is 1)
+    #[serde(default = "default_exemplar_weight")]
+    pub weight: f32,
+    /// Empty = not embedded yet (heuristic files don't carry embeddings)
+    #[serde(skip)]
+    pub embedding: Vec<f32>,
+}
+
+fn default_exemplar_weight() -> f32 {
+    1.0
+}
+
+impl Exemplar {
+    pub fn new(text: impl Into<String>, weight: f32, embedding: Vec<f32>) -> Self {
+        Self { text: text.into(), weight, embedding }
+    }
+
+    /// The weight applied at match time: outside (0, 1] (including the
+    /// proto's unset 0) counts as 1.
+    pub fn effective_weight(&self) -> f32 {
+        if self.weight > 0.0 && self.weight <= 1.0 { self.weight } else { 1.0 }
+    }
+}
+
+/// Per-dimension salience boost ("threat" or a vector dimension -> value).
+pub type SalienceBoost = BTreeMap<String, f32>;
+
+/// What a matched heuristic does.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Effects {
+    /// Suggested action text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Salience boost applied on match (None = leave the baseline alone).
+    /// Non-numeric values are skipped.
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "numeric_entries")]
+    pub salience: Option<SalienceBoost>,
+    /// Trigger keywords for the keyword pre-filter (non-strings are skipped)
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "string_items")]
+    pub keywords: Vec<String>,
+    /// After a match, hold this heuristic back for this long (None = config
+    /// default; non-numeric or negative values are ignored)
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "duration_ms")]
+    pub cooldown_ms: Option<u64>,
+    /// Rule pack this heuristic belongs to (e.g., "gaming"), toggled with
+    /// EnableGroup / DisableGroup (None = ungrouped, always active)
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "group_name")]
+    pub group: Option<String>,
+    /// Keys the fast path doesn't interpret, preserved for round trips
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+    /// Why the source JSON was rejected (these effects are then empty)
+    #[serde(skip)]
+    pub malformed: Option<String>,
+}
+
+fn numeric_entries<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<SalienceBoost>, D::Error> {
+    let entries = Option::<BTreeMap<String, serde_json::Value>>::deserialize(deserializer)?;
+    Ok(entries.map(|entries| {
+        entries
+            .into_iter()
+            .filter_map(|(dimension, value)| value.as_f64().map(|v| (dimension, v as f32)))
+            .collect()
+    }))
+}
+
+fn duration_ms<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.and_then(|v| v.as_f64()).filter(|ms| *ms >= 0.0).map(|ms| ms as u64))
+}
+
+fn group_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.as_ref().and_then(|v| v.as_str()).map(str::trim).filter(|g| !g.is_empty()).map(str::to_string))
+}
+
+fn string_items<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    let items = Vec::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(items.into_iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+}
+
+impl Effects {
+    /// Parse an `effects_json` string (empty = no effects).
+    pub fn parse(json: &str) -> Result<Self, serde_json::Error> {
+        if json.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(json)
+    }
+
+    /// Empty effects standing in for JSON that failed to parse.
+    pub fn malformed(error: impl std::fmt::Display) -> Self {
+        Self { malformed: Some(error.to_string()), ..Self::default() }
+    }
+
+    /// Lint findings: malformed source JSON, boost keys nothing reads,
+    /// boost values outside [0, 1], and a missing action message.
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+        if let Some(error) = &self.malformed {
+            issues.push(format!("malformed effects: {}", error));
+        }
+        for (dimension, value) in self.salience.iter().flatten() {
+            if dimension != THREAT_DIMENSION && !BOOST_DIMENSIONS.contains(&dimension.as_str()) {
+                issues.push(format!("unknown salience key '{}' (ignored)", dimension));
+            }
+            if !(0.0..=1.0).contains(value) {
+                issues.push(format!("salience.{} = {} outside [0, 1]", dimension, value));
+            }
+        }
+        if self.message.as_deref().is_none_or(|m| m.trim().is_empty()) {
+            issues.push("no action message".to_string());
+        }
+        issues
+    }
+
+    /// Serialize back to the `effects_json` wire form.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+/// Lenient: `null` is no effects, and a value of the wrong shape is logged
+/// and treated as no effects (as malformed `effects_json` from storage is).
+impl From<serde_json::Value> for Effects {
+    fn from(value: serde_json::Value) -> Self {
+        if value.is_null() {
+            return Self::default();
+        }
+        serde_json::from_value(value).unwrap_or_else(|e| {
+            warn!(error = %e, "Ignoring malformed heuristic effects");
+            Self::malformed(e)
+        })
+    }
+}
+
+/// A heuristic as storage owns it (no cache bookkeeping).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Heuristic {
+    pub id: Uuid,
+    pub name: String,
+    pub condition: Condition,
+    pub effects: Effects,
+    pub confidence: f32,
+    /// Where the heuristic came from ("user", "llm", "system"; empty if unknown)
+    pub origin: String,
+    /// Event source this heuristic is scoped to (empty = any source)
+    pub source: String,
+    /// Condition embedding (empty = not embedded yet)
+    pub condition_embedding: Vec<f32>,
+    /// Version of the model that produced condition_embedding (empty = unknown)
+    pub embedding_model: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effects_round_trip_keeps_unknown_keys() {
+        let json = r#"{"message": "Run", "salience": {"threat": 0.9}, "keywords": ["creeper"], "cooldown_s": 30}"#;
+        let effects = Effects::parse(json).unwrap();
+        assert_eq!(effects.message.as_deref(), Some("Run"));
+        assert_eq!(effects.salience.as_ref().and_then(|s| s.get("threat")).copied(), Some(0.9));
+        assert_eq!(effects.keywords, vec!["creeper"]);
+        assert_eq!(effects.extra.get("cooldown_s"), Some(&serde_json::json!(30)));
+        assert_eq!(Effects::parse(&effects.to_json()).unwrap(), effects);
+
+        assert_eq!(Effects::parse("").unwrap(), Effects::default());
+        let lenient = Effects::parse(r#"{"salience": {"threat": "high", "social": 0.5}, "keywords": ["lava", 3]}"#).unwrap();
+        assert_eq!(lenient.salience, Some(SalienceBoost::from([("social".to_string(), 0.5)])));
+        assert_eq!(lenient.keywords, vec!["lava"]);
+        assert_eq!(Effects::parse(r#"{"cooldown_ms": 5000}"#).unwrap().cooldown_ms, Some(5000));
+        assert_eq!(Effects::parse(r#"{"cooldown_ms": -1}"#).unwrap().cooldown_ms, None);
+        assert_eq!(Effects::parse(r#"{"group": " gaming "}"#).unwrap().group.as_deref(), Some("gaming"));
+        assert_eq!(Effects::parse(r#"{"group": 7}"#).unwrap().group, None);
+        assert_eq!(Effects::from(serde_json::json!({"salience": "high"})).salience, None);
+    }
+
+    #[test]
+    fn test_validate_flags_suspect_effects() {
+        let clean = Effects::parse(r#"{"message": "Run", "salience": {"threat": 0.9, "social": 0.2}}"#).unwrap();
+        assert!(clean.validate().is_empty());
+
+        let suspect = Effects::parse(r#"{"salience": {"threat": 1.5, "urgency": 0.4}}"#).unwrap();
+        let issues = suspect.validate();
+        assert_eq!(issues.len(), 3, "{:?}", issues);
+        assert!(issues.iter().any(|i| i.contains("urgency")));
+        assert!(issues.iter().any(|i| i.contains("salience.threat = 1.5")));
+
+        assert!(Effects::malformed("bad json").validate()[0].starts_with("malformed effects"));
+    }
+}
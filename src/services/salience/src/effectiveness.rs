@@ -0,0 +1,123 @@
+//! Heuristic effectiveness report behind GetHeuristicEffectiveness.
+//!
+//! Combines what the cache knows about each cached heuristic (hits since it
+//! was cached, similarity of its cache matches) with the fire outcomes storage
+//! has recorded, when the backend can supply them:
+//! - Ranked by hits per hour, then mean match similarity, then success ratio
+//! - Dead weight: never matched since it was cached
+//! - Over-firing: more hits per hour than the request's threshold
+//!
+//! Hit counts and similarities are cache-local and reset when a heuristic is
+//! evicted or refreshed; fire and success counts are storage's all-time totals.
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::proto::HeuristicEffectiveness;
+use crate::{HeuristicFeedback, MemoryCache};
+
+/// Over-firing threshold when the request doesn't set one (hits per hour).
+pub const DEFAULT_OVERFIRE_PER_HOUR: f32 = 60.0;
+
+/// Shortest time since caching used for hits per hour, so a heuristic that
+/// matched right after it was cached doesn't read as thousands per hour.
+const MIN_RATE_WINDOW_MS: i64 = 60_000;
+
+const HOUR_MS: f32 = 3_600_000.0;
+
+/// Every cached heuristic, best first.
+pub fn effectiveness_report(
+    cache: &MemoryCache,
+    feedback: &HashMap<Uuid, HeuristicFeedback>,
+    overfire_per_hour: f32,
+    now_ms: i64,
+) -> Vec<HeuristicEffectiveness> {
+    let mut report: Vec<HeuristicEffectiveness> = cache
+        .list_heuristics(0)
+        .into_iter()
+        .map(|h| {
+            let cached_for_ms = (now_ms - h.cached_at_ms).max(MIN_RATE_WINDOW_MS);
+            let hits_per_hour = h.hit_count as f32 * HOUR_MS / cached_for_ms as f32;
+            let outcomes = feedback.get(&h.id);
+            let (fire_count, success_count) = outcomes.map_or((0, 0), |f| (f.fire_count, f.success_count));
+            HeuristicEffectiveness {
+                heuristic_id: h.id.to_string(),
+                name: h.name.clone(),
+                origin: h.origin.clone(),
+                source: h.source.clone(),
+                hit_count: h.hit_count as i64,
+                hits_per_hour,
+                mean_similarity: cache.mean_match_similarity(&h.id).unwrap_or(0.0),
+                has_feedback: outcomes.is_some(),
+                fire_count: fire_count as i32,
+                success_count: success_count as i32,
+                success_ratio: if fire_count > 0 { success_count as f32 / fire_count as f32 } else { 0.0 },
+                dead_weight: h.hit_count == 0,
+                over_firing: hits_per_hour > overfire_per_hour,
+                cached_at_ms: h.cached_at_ms,
+            }
+        })
+        .collect();
+
+    report.sort_by(|a, b| {
+        b.hits_per_hour
+            .total_cmp(&a.hits_per_hour)
+            .then(b.mean_similarity.total_cmp(&a.mean_similarity))
+            .then(b.success_ratio.total_cmp(&a.success_ratio))
+    });
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CacheConfig, CachedHeuristic, Condition};
+
+    fn heuristic(name: &str, cached_at_ms: i64, hit_count: u64) -> CachedHeuristic {
+        CachedHeuristic {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            condition: Condition::text(name),
+            effects: serde_json::json!({"message": name}).into(),
+            confidence: 0.9,
+            origin: "user".to_string(),
+            source: String::new(),
+            condition_embedding: Default::default(),
+            last_accessed_ms: cached_at_ms,
+            cached_at_ms,
+            hit_count,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_report_ranks_and_flags_heuristics() {
+        let now = 10 * HOUR_MS as i64;
+        let mut cache = MemoryCache::new(CacheConfig::default());
+        let noisy = heuristic("noisy", now - HOUR_MS as i64, 500);
+        let useful = heuristic("useful", now - HOUR_MS as i64, 5);
+        let idle = heuristic("idle", now - 2 * HOUR_MS as i64, 0);
+        let (noisy_id, useful_id) = (noisy.id, useful.id);
+        for h in [noisy, useful, idle] {
+            cache.add_heuristic(h);
+        }
+        cache.record_match_similarity(&useful_id, 0.9);
+        cache.record_match_similarity(&useful_id, 0.8);
+        let feedback = HashMap::from([(useful_id, HeuristicFeedback { fire_count: 4, success_count: 3 })]);
+
+        let report = effectiveness_report(&cache, &feedback, DEFAULT_OVERFIRE_PER_HOUR, now);
+        let names: Vec<&str> = report.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["noisy", "useful", "idle"]);
+
+        assert_eq!(report[0].heuristic_id, noisy_id.to_string());
+        assert!(report[0].over_firing && !report[0].has_feedback);
+        assert!((report[1].hits_per_hour - 5.0).abs() < 0.01);
+        assert!((report[1].mean_similarity - 0.85).abs() < 0.001);
+        assert!((report[1].success_ratio - 0.75).abs() < 0.001);
+        assert!(!report[1].over_firing && !report[1].dead_weight);
+        assert!(report[2].dead_weight);
+    }
+}
@@ -0,0 +1,296 @@
+//! Embedding model versions and mismatch protection.
+//!
+//! Embeddings from different models (or versions of one) live in different
+//! spaces, so a similarity between them is meaningless. Storage reports the
+//! model version with each embedding (`model_version` in
+//! GenerateEmbeddingResponse) and with each heuristic's condition embedding
+//! (`embedding_model` in Heuristic), and cache entries keep it. The cache
+//! holds embeddings from one version at a time:
+//! - The first version storage reports becomes current; heuristics cached
+//!   with another version's embedding are not compared by embedding (keyword
+//!   matching still applies)
+//! - When the version changes (storage swapped models), cached events and
+//!   active goals are dropped, and heuristics embedded by the previous version
+//!   are flushed or re-embedded in the background, per the policy
+//!
+//! Storage that doesn't report versions leaves everything as it was. The
+//! current version is reported as `embedding_model` in GetCacheStats.
+//!
+//! After a planned model switch, the ReEmbedCache admin RPC re-embeds the
+//! whole cache in one go: it requests a fresh embedding for each cached
+//! heuristic's condition text, paced to a requested rate, and streams
+//! progress. The new embeddings are swapped in under one write lock at the
+//! end, so lookups never see a mix of old and new. A run stops without
+//! swapping anything if the caller goes away or storage's model changes
+//! mid-run, and only one runs at a time.
+//!
+//! Configuration via environment variables (see `CacheConfig`):
+//!   CACHE_MODEL_CHANGE_POLICY: "flush" or "reembed" (default: flush)
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+use crate::{CacheHandle, StorageBackend};
+
+/// Heuristics re-embedded per pass.
+const REEMBED_BATCH: usize = 32;
+
+/// Pause between re-embedding passes.
+const REEMBED_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Default ReEmbedCache pace, in embedding requests per second.
+pub const DEFAULT_REEMBED_PER_SECOND: f32 = 10.0;
+
+/// Progress of a bulk re-embed (see `reembed_cache`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReEmbedProgress {
+    pub total: usize,
+    pub embedded: usize,
+    pub failed: usize,
+    /// Swapped in (final report only)
+    pub swapped: usize,
+    pub done: bool,
+    /// Model version of the new embeddings (empty = unknown)
+    pub model: String,
+}
+
+/// Re-embed cached heuristics (all, or only stale ones) at up to
+/// `per_second` embedding requests per second, then swap the new embeddings
+/// in at once. Reports progress after each heuristic and when done.
+pub async fn reembed_cache(
+    cache: CacheHandle,
+    storage: Arc<dyn StorageBackend>,
+    per_second: f32,
+    only_stale: bool,
+    progress: mpsc::Sender<ReEmbedProgress>,
+) -> ReEmbedProgress {
+    let candidates = cache.read().await.reembed_candidates(only_stale);
+    let mut report = ReEmbedProgress { total: candidates.len(), ..Default::default() };
+    info!(total = report.total, per_second, only_stale, "Re-embedding cached heuristics");
+
+    let mut pace = tokio::time::interval(Duration::from_secs_f32(1.0 / per_second.max(0.001)));
+    pace.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut embeddings = Vec::with_capacity(candidates.len());
+    for (id, text) in candidates {
+        pace.tick().await;
+        match storage.generate_embedding(&text, None).await {
+            Ok(embedding) => {
+                let model = storage.embedding_model().unwrap_or_default();
+                if report.embedded > 0 && model != report.model {
+                    warn!(previous = %report.model, model = %model, "Embedding model changed mid re-embed; nothing swapped");
+                    report.done = true;
+                    let _ = progress.send(report.clone()).await;
+                    return report;
+                }
+                report.model = model;
+                report.embedded += 1;
+                embeddings.push((id, text, embedding));
+            }
+            Err(e) => {
+                warn!(heuristic_id = %id, error = %e, "Re-embedding failed; keeping the cached embedding");
+                report.failed += 1;
+            }
+        }
+        if progress.send(report.clone()).await.is_err() {
+            info!(embedded = report.embedded, "Re-embed abandoned by the caller; nothing swapped");
+            return report;
+        }
+    }
+
+    report.swapped = cache.write().await.swap_embeddings(&report.model, embeddings);
+    report.done = true;
+    info!(swapped = report.swapped, failed = report.failed, model = %report.model, "Re-embedded cached heuristics");
+    let _ = progress.send(report.clone()).await;
+    report
+}
+
+/// Make `model` the cache's current version if it isn't already (a read
+/// lock when it is).
+pub async fn observe_model(cache: &CacheHandle, model: &str) {
+    if cache.read().await.embedding_model() == Some(model) {
+        return;
+    }
+    let mut cache = cache.write().await;
+    let previous = cache.embedding_model().map(str::to_string);
+    if previous.as_deref() == Some(model) {
+        return;
+    }
+    let stale = cache.set_embedding_model(model);
+    let policy = cache.config().model_change_policy;
+    drop(cache);
+
+    match previous {
+        None => info!(model, mismatched = stale.len(), "Embedding model version"),
+        Some(previous) => warn!(
+            previous = %previous,
+            model,
+            heuristics = stale.len(),
+            policy = policy.as_str(),
+            "Embedding model changed; dropped embeddings from the previous version"
+        ),
+    }
+}
+
+/// Re-embed heuristics left without an embedding by a model change (runs forever).
+pub async fn run_reembed_loop(cache: CacheHandle, storage: Arc<dyn StorageBackend>) {
+    info!(interval_s = REEMBED_INTERVAL.as_secs(), "Re-embedding on model change enabled");
+    loop {
+        tokio::time::sleep(REEMBED_INTERVAL).await;
+        reembed_pending(&cache, storage.as_ref()).await;
+    }
+}
+
+/// One re-embedding pass. Returns how many heuristics got a current embedding.
+pub async fn reembed_pending(cache: &CacheHandle, storage: &dyn StorageBackend) -> usize {
+    let pending = cache.read().await.heuristics_to_reembed(REEMBED_BATCH);
+    let mut reembedded = 0;
+    for (id, text) in pending {
+        let embedding = match storage.generate_embedding(&text, None).await {
+            Ok(embedding) => embedding,
+            Err(e) => {
+                warn!(heuristic_id = %id, error = %e, "Re-embedding failed; retrying next pass");
+                break;
+            }
+        };
+        let Some(model) = storage.embedding_model() else {
+            break;
+        };
+        if cache.write().await.set_heuristic_embedding(&id, embedding, &model) {
+            reembedded += 1;
+        }
+    }
+    if reembedded > 0 {
+        info!(reembedded, "Re-embedded heuristics with the current model");
+    }
+    reembedded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CacheConfig, ModelChangePolicy};
+    use crate::{CachedHeuristic, Condition, Heuristic, MemoryCache, StorageError};
+
+    fn heuristic(name: &str, model: &str) -> CachedHeuristic {
+        CachedHeuristic::from(Heuristic {
+            id: uuid::Uuid::new_v4(),
+            name: name.to_string(),
+            condition: Condition::text(name),
+            condition_embedding: vec![1.0; 4],
+            embedding_model: model.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn cache(policy: ModelChangePolicy) -> MemoryCache {
+        MemoryCache::new(CacheConfig { model_change_policy: policy, ..CacheConfig::default() })
+    }
+
+    #[test]
+    fn test_model_change_flushes_previous_version() {
+        let mut cache = cache(ModelChangePolicy::Flush);
+        let (tagged, untagged, foreign) = (heuristic("tagged", "m1"), heuristic("untagged", ""), heuristic("foreign", "m0"));
+        let ids = (tagged.id, untagged.id, foreign.id);
+        cache.add_heuristic(tagged);
+        cache.add_heuristic(untagged);
+
+        // First version: untagged entries are assumed current
+        assert!(cache.set_embedding_model("m1").is_empty());
+        assert_eq!(cache.embedding_model(), Some("m1"));
+        // Another version's embedding is never compared
+        cache.add_heuristic(foreign);
+        assert!(cache.get_heuristic(&ids.2).unwrap().condition_embedding.is_empty());
+
+        let mut stale = cache.set_embedding_model("m2");
+        stale.sort();
+        let mut expected = vec![ids.0, ids.1];
+        expected.sort();
+        assert_eq!(stale, expected);
+        assert!(cache.get_heuristic(&ids.0).is_none());
+        assert!(cache.get_heuristic(&ids.1).is_none());
+        assert!(cache.set_embedding_model("m2").is_empty());
+    }
+
+    struct VersionedStorage;
+
+    #[tonic::async_trait]
+    impl StorageBackend for VersionedStorage {
+        async fn query_matching_heuristics(
+            &self,
+            _event_text: &str,
+            _min_confidence: f32,
+            _limit: i32,
+            _source_filter: Option<&str>,
+            _trace_id: Option<&str>,
+        ) -> Result<Vec<CachedHeuristic>, StorageError> {
+            Ok(Vec::new())
+        }
+
+        async fn generate_embedding(&self, _text: &str, _trace_id: Option<&str>) -> Result<Vec<f32>, StorageError> {
+            Ok(vec![0.5; 4])
+        }
+
+        fn embedding_model(&self) -> Option<String> {
+            Some("m2".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_model_change_reembeds_previous_version() {
+        let mut cache = cache(ModelChangePolicy::Reembed);
+        let (tagged, untagged) = (heuristic("tagged", "m1"), heuristic("untagged", ""));
+        let ids = [tagged.id, untagged.id];
+        cache.add_heuristic(tagged);
+        cache.add_heuristic(untagged);
+        cache.set_embedding_model("m1");
+        let cache = CacheHandle::new(cache);
+
+        observe_model(&cache, "m2").await;
+        {
+            let cache = cache.read().await;
+            for id in &ids {
+                // Kept for keyword matching, but not compared until re-embedded
+                assert!(cache.get_heuristic(id).unwrap().condition_embedding.is_empty());
+            }
+            assert_eq!(cache.heuristics_to_reembed(10).len(), 2);
+        }
+
+        assert_eq!(reembed_pending(&cache, &VersionedStorage).await, 2);
+        let cache = cache.read().await;
+        for id in &ids {
+            let h = cache.get_heuristic(id).unwrap();
+            assert_eq!(h.condition_embedding.to_vec(), vec![0.5; 4]);
+            assert_eq!(h.embedding_model, "m2");
+        }
+        assert!(cache.heuristics_to_reembed(10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bulk_reembed_swaps_when_done() {
+        let mut cache = cache(ModelChangePolicy::Flush);
+        let h = heuristic("creeper", "m1");
+        let id = h.id;
+        cache.add_heuristic(h);
+        cache.set_embedding_model("m1");
+        let cache = CacheHandle::new(cache);
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let report = reembed_cache(cache.clone(), Arc::new(VersionedStorage), 1000.0, false, tx).await;
+        assert_eq!((report.total, report.embedded, report.swapped, report.done), (1, 1, 1, true));
+        let mut reports = Vec::new();
+        while let Some(progress) = rx.recv().await {
+            reports.push(progress);
+        }
+        assert_eq!(reports.len(), 2);
+        assert!(!reports[0].done && reports[1].done);
+
+        let cache = cache.read().await;
+        assert_eq!(cache.embedding_model(), Some("m2"));
+        assert_eq!(cache.get_heuristic(&id).unwrap().embedding_model, "m2");
+        assert!(cache.reembed_candidates(true).is_empty());
+    }
+}
@@ -0,0 +1,247 @@
+//! Eviction log: why a heuristic left the cache.
+//!
+//! Every heuristic removal is recorded with its reason and a snapshot of the
+//! victim's stats taken just before it went, so "why did my heuristic
+//! disappear" has an answer. Entries go to two places:
+//! - The `gladys_memory::eviction_log` log target, as structured events
+//! - A bounded ring buffer in the cache, queryable via `GetEvictionLog`
+//!
+//! Event evictions are only counted (see `EvictionCounts`); they're frequent
+//! and carry no learned state.
+//!
+//! Configuration via environment variables (see `CacheConfig`):
+//!   CACHE_EVICTION_LOG_SIZE: Evictions kept in memory (default: 256, 0 = log only)
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+use tracing::info;
+use uuid::Uuid;
+
+use crate::CachedHeuristic;
+
+/// Why a heuristic was removed from the cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvictionReason {
+    /// Least recently used, to make room at capacity
+    Lru,
+    /// Capacity lowered (adaptive sizing)
+    Resize,
+    /// Removed on request (EvictFromCache, diagnostics, FFI)
+    Removed,
+    /// Changed or deleted in storage (NotifyHeuristicChange, replica invalidation)
+    Notify,
+    /// Deleted in storage, seen by the refresh loop
+    Refresh,
+    /// FlushCache, whole or selective
+    Flush,
+    /// Embedded by a previous embedding model version (see embedding_model module)
+    ModelChange,
+}
+
+impl EvictionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvictionReason::Lru => "lru",
+            EvictionReason::Resize => "resize",
+            EvictionReason::Removed => "removed",
+            EvictionReason::Notify => "notify",
+            EvictionReason::Refresh => "refresh",
+            EvictionReason::Flush => "flush",
+            EvictionReason::ModelChange => "model_change",
+        }
+    }
+}
+
+impl fmt::Display for EvictionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EvictionReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "lru" => Ok(EvictionReason::Lru),
+            "resize" => Ok(EvictionReason::Resize),
+            "removed" => Ok(EvictionReason::Removed),
+            "notify" => Ok(EvictionReason::Notify),
+            "refresh" => Ok(EvictionReason::Refresh),
+            "flush" => Ok(EvictionReason::Flush),
+            "model_change" => Ok(EvictionReason::ModelChange),
+            other => Err(format!("unknown eviction reason: {}", other)),
+        }
+    }
+}
+
+/// One evicted heuristic and its stats at the time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvictionRecord {
+    pub timestamp_ms: i64,
+    pub reason: EvictionReason,
+    pub heuristic_id: Uuid,
+    pub name: String,
+    pub origin: String,
+    pub source: String,
+    pub confidence: f32,
+    pub hit_count: u64,
+    pub cached_at_ms: i64,
+    /// Last match (0 = never matched while cached)
+    pub last_hit_ms: i64,
+}
+
+/// Bounded ring buffer of heuristic evictions, newest last.
+#[derive(Debug, Default)]
+pub struct EvictionLog {
+    capacity: usize,
+    entries: VecDeque<EvictionRecord>,
+}
+
+impl EvictionLog {
+    /// `capacity` = 0 keeps nothing in memory; evictions are still logged.
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, entries: VecDeque::new() }
+    }
+
+    /// Log an eviction and append it, dropping the oldest entry when full.
+    pub fn record(&mut self, heuristic: &CachedHeuristic, reason: EvictionReason) {
+        info!(
+            heuristic_id = %heuristic.id,
+            name = %heuristic.name,
+            reason = reason.as_str(),
+            hit_count = heuristic.hit_count,
+            confidence = heuristic.confidence,
+            cached_at_ms = heuristic.cached_at_ms,
+            last_hit_ms = heuristic.last_hit_ms,
+            "Heuristic evicted"
+        );
+        if self.capacity == 0 {
+            return;
+        }
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(EvictionRecord {
+            timestamp_ms: crate::current_time_ms(),
+            reason,
+            heuristic_id: heuristic.id,
+            name: heuristic.name.clone(),
+            origin: heuristic.origin.clone(),
+            source: heuristic.source.clone(),
+            confidence: heuristic.confidence,
+            hit_count: heuristic.hit_count,
+            cached_at_ms: heuristic.cached_at_ms,
+            last_hit_ms: heuristic.last_hit_ms,
+        });
+    }
+
+    /// Most recent evictions, newest first (`limit` = 0 for all), optionally
+    /// restricted to one reason and/or heuristic.
+    pub fn recent(&self, limit: usize, reason: Option<EvictionReason>, heuristic_id: Option<Uuid>) -> Vec<EvictionRecord> {
+        let matching = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| reason.is_none_or(|r| e.reason == r))
+            .filter(|e| heuristic_id.is_none_or(|id| e.heuristic_id == id))
+            .cloned();
+        if limit == 0 {
+            matching.collect()
+        } else {
+            matching.take(limit).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CacheConfig, Condition, MemoryCache};
+
+    fn heuristic(name: &str) -> CachedHeuristic {
+        CachedHeuristic {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            condition: Condition::text(name),
+            effects: Default::default(),
+            origin: "learned".to_string(),
+            source: String::new(),
+            condition_embedding: vec![1.0; 384].into(),
+            confidence: 0.8,
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_evictions_recorded_with_reason_and_stats() {
+        let mut cache = MemoryCache::new(CacheConfig { max_heuristics: 2, eviction_log_size: 3, ..CacheConfig::default() });
+        let (creeper, lava, zombie) = (heuristic("creeper"), heuristic("lava"), heuristic("zombie"));
+        cache.add_heuristic(creeper.clone());
+        cache.touch_heuristic(&creeper.id);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        cache.add_heuristic(lava.clone());
+        cache.touch_heuristic(&lava.id);
+        cache.add_heuristic(zombie.clone());
+        cache.remove_heuristic(&lava.id);
+        cache.tombstone_heuristic(&zombie.id);
+
+        let log = cache.eviction_log(0, None, None);
+        let reasons: Vec<&str> = log.iter().map(|e| e.reason.as_str()).collect();
+        assert_eq!(reasons, vec!["notify", "removed", "lru"]);
+        assert_eq!((log[2].name.as_str(), log[2].hit_count), ("creeper", 1));
+        assert!(log[2].last_hit_ms > 0);
+        assert_eq!(cache.eviction_log(0, Some(EvictionReason::Removed), None)[0].heuristic_id, lava.id);
+        assert_eq!(cache.eviction_log(0, None, Some(zombie.id)).len(), 1);
+
+        // Bounded: the oldest entry goes first
+        cache.add_heuristic(heuristic("skeleton"));
+        cache.flush_heuristics();
+        let log = cache.eviction_log(0, None, None);
+        assert_eq!(log.len(), 3);
+        assert_eq!(log[0].reason, EvictionReason::Flush);
+        assert_eq!(cache.eviction_log(0, Some(EvictionReason::Lru), None).len(), 0);
+        assert_eq!("FLUSH".parse::<EvictionReason>(), Ok(EvictionReason::Flush));
+        assert!("ttl".parse::<EvictionReason>().is_err());
+    }
+
+    #[test]
+    fn test_log_only_and_limits() {
+        // Capacity 0: evictions are logged but nothing is kept
+        let mut log = EvictionLog::new(0);
+        log.record(&heuristic("creeper"), EvictionReason::Lru);
+        assert!(log.recent(0, None, None).is_empty());
+
+        let mut log = EvictionLog::new(8);
+        for name in ["creeper", "lava", "zombie"] {
+            log.record(&heuristic(name), EvictionReason::Resize);
+        }
+        let names: Vec<String> = log.recent(2, None, None).into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["zombie", "lava"]);
+        assert!(log.recent(0, Some(EvictionReason::Resize), Some(Uuid::new_v4())).is_empty());
+    }
+
+    #[test]
+    fn test_reason_names_round_trip() {
+        for reason in [
+            EvictionReason::Lru,
+            EvictionReason::Resize,
+            EvictionReason::Removed,
+            EvictionReason::Notify,
+            EvictionReason::Refresh,
+            EvictionReason::Flush,
+            EvictionReason::ModelChange,
+        ] {
+            assert_eq!(reason.to_string().parse::<EvictionReason>(), Ok(reason));
+        }
+        assert_eq!(" Model_Change ".parse::<EvictionReason>(), Ok(EvictionReason::ModelChange));
+        assert_eq!("".parse::<EvictionReason>(), Err("unknown eviction reason: ".to_string()));
+    }
+}
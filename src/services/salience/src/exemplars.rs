@@ -0,0 +1,220 @@
+//! Multi-vector conditions: exemplar phrasings per heuristic.
+//!
+//! One condition_text often can't cover how an event gets phrased ("watch
+//! out", "behind you", "incoming" all mean the same warning). A heuristic can
+//! carry exemplars besides its condition text (`exemplars` in Heuristic, or in
+//! a heuristic file), each with its own embedding and a weight in (0, 1]. At
+//! match time the event is compared with the condition embedding (weight 1)
+//! and every embedded exemplar, and the similarities combine per
+//! CACHE_EXEMPLAR_AGGREGATION:
+//! - max: the best weighted similarity, so any one phrasing can match
+//! - mean: the weighted mean, so a match has to sit near the phrasings overall
+//!
+//! Negative exemplars (`negative_exemplars` in Heuristic, `negatives` in a
+//! heuristic file) suppress false positives, such as sarcasm a heuristic
+//! keeps matching. When the event's similarity to a negative reaches
+//! CACHE_NEGATIVE_THRESHOLD, the closest such negative counts against the
+//! match per CACHE_NEGATIVE_POLICY:
+//! - subtract: its similarity, scaled by its weight, comes off the match
+//!   similarity (which then has to clear the minimum as usual)
+//! - veto: the match is dropped
+//!
+//! A match that loses a penalty reports both sides in its explanation: the
+//! negative, its similarity and the penalty (the similarity before the
+//! penalty is the reported one plus the penalty). Keyword matches are
+//! conclusive and aren't penalized.
+//!
+//! Heuristics without exemplars score exactly as before. Exemplar embeddings
+//! follow the condition embedding's model version: they're dropped along
+//! with it on a model change and come back with the next fetch from storage.
+//!
+//! Configuration via environment variables (see `CacheConfig`):
+//!   CACHE_EXEMPLAR_AGGREGATION: "max" or "mean" (default: max)
+//!   CACHE_NEGATIVE_THRESHOLD: Similarity at which a negative counts (default: 0.8)
+//!   CACHE_NEGATIVE_POLICY: "subtract" or "veto" (default: subtract)
+
+use gladys_salience_core::{similarity, SimilarityMetric};
+
+use crate::config::{CacheConfig, ExemplarAggregation, NegativePolicy};
+use crate::domain::{Condition, Exemplar};
+use crate::CachedHeuristic;
+
+/// The negative exemplar that counted against a match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NegativeMatch {
+    pub text: String,
+    /// The event's similarity to it
+    pub similarity: f32,
+    /// Taken off the match similarity under the subtract policy
+    pub penalty: f32,
+}
+
+/// A heuristic's match similarity for `query` after its negatives, with the
+/// negative that counted (None if nothing is embedded or a negative vetoed it).
+pub fn condition_score(query: &[f32], heuristic: &CachedHeuristic, config: &CacheConfig) -> Option<(f32, Option<NegativeMatch>)> {
+    let positive = condition_similarity(query, heuristic, config.similarity_metric, config.exemplar_aggregation)?;
+    let Some(negative) = negative_match(query, heuristic, config.similarity_metric, config.negative_threshold) else {
+        return Some((positive, None));
+    };
+    match config.negative_policy {
+        NegativePolicy::Veto => None,
+        NegativePolicy::Subtract => Some((positive - negative.penalty, Some(negative))),
+    }
+}
+
+/// The negative exemplar of `heuristic` closest to `query`, if it reaches
+/// `threshold` (penalty = similarity × weight).
+pub fn negative_match(query: &[f32], heuristic: &CachedHeuristic, metric: SimilarityMetric, threshold: f32) -> Option<NegativeMatch> {
+    if query.is_empty() {
+        return None;
+    }
+    heuristic
+        .condition
+        .negatives
+        .iter()
+        .filter(|n| !n.embedding.is_empty())
+        .map(|n| (n, similarity(metric, query, &n.embedding)))
+        .filter(|(_, sim)| *sim >= threshold)
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(n, sim)| NegativeMatch { text: n.text.clone(), similarity: sim, penalty: sim * n.effective_weight() })
+}
+
+/// `query`'s similarity to `heuristic`'s condition embedding and exemplars,
+/// combined per `aggregation` (None if nothing is embedded).
+pub fn condition_similarity(
+    query: &[f32],
+    heuristic: &CachedHeuristic,
+    metric: SimilarityMetric,
+    aggregation: ExemplarAggregation,
+) -> Option<f32> {
+    if query.is_empty() {
+        return None;
+    }
+    let main = (!heuristic.condition_embedding.is_empty()).then(|| (heuristic.condition_embedding.as_slice(), 1.0));
+    let exemplars = heuristic
+        .condition
+        .exemplars
+        .iter()
+        .filter(|e| !e.embedding.is_empty())
+        .map(|e| (e.embedding.as_slice(), e.effective_weight()));
+    let scores = main.into_iter().chain(exemplars).map(|(embedding, weight)| (similarity(metric, query, embedding), weight));
+    aggregate(scores, aggregation)
+}
+
+/// Combine (similarity, weight) pairs (None without any).
+fn aggregate(scores: impl Iterator<Item = (f32, f32)>, aggregation: ExemplarAggregation) -> Option<f32> {
+    match aggregation {
+        ExemplarAggregation::Max => scores.map(|(similarity, weight)| similarity * weight).reduce(f32::max),
+        ExemplarAggregation::Mean => {
+            let (sum, weights) = scores.fold((0.0, 0.0), |(sum, weights), (similarity, weight)| {
+                (sum + similarity * weight, weights + weight)
+            });
+            (weights > 0.0).then(|| sum / weights)
+        }
+    }
+}
+
+/// Keep `previous`'s exemplar embeddings for exemplars (and negatives)
+/// `condition` lists without one (same text), as an update without
+/// embeddings keeps the condition embedding.
+pub fn keep_exemplar_embeddings(condition: &mut Condition, previous: &Condition) {
+    let keep = |exemplars: &mut [Exemplar], previous: &[Exemplar]| {
+        for exemplar in exemplars.iter_mut().filter(|e| e.embedding.is_empty()) {
+            if let Some(cached) = previous.iter().find(|p| p.text == exemplar.text) {
+                exemplar.embedding = cached.embedding.clone();
+            }
+        }
+    };
+    keep(&mut condition.exemplars, &previous.exemplars);
+    keep(&mut condition.negatives, &previous.negatives);
+}
+
+/// Drop every exemplar and negative embedding (their model version is no
+/// longer current).
+pub fn clear_exemplar_embeddings(condition: &mut Condition) {
+    for exemplar in condition.all_exemplars_mut() {
+        exemplar.embedding = Vec::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Heuristic;
+
+    fn heuristic(exemplars: Vec<Exemplar>) -> CachedHeuristic {
+        CachedHeuristic::from(Heuristic {
+            condition: Condition::text("watch out").with_exemplars(exemplars),
+            condition_embedding: vec![1.0, 0.0],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_aggregates_weighted_exemplars() {
+        let h = heuristic(vec![
+            Exemplar::new("behind you", 0.5, vec![0.0, 1.0]),
+            Exemplar::new("incoming", 0.0, Vec::new()),
+        ]);
+        let score = |aggregation| condition_similarity(&[0.0, 1.0], &h, SimilarityMetric::Cosine, aggregation).unwrap();
+        // Condition 0.0 (weight 1), exemplar 1.0 (weight 0.5); the unembedded one is skipped
+        assert!((score(ExemplarAggregation::Max) - 0.5).abs() < 1e-6);
+        assert!((score(ExemplarAggregation::Mean) - 0.5 / 1.5).abs() < 1e-6);
+
+        // Without exemplars: the plain condition similarity
+        let plain = heuristic(Vec::new());
+        let similarity = condition_similarity(&[1.0, 0.0], &plain, SimilarityMetric::Cosine, ExemplarAggregation::Mean);
+        assert!((similarity.unwrap() - 1.0).abs() < 1e-6);
+        assert!(condition_similarity(&[], &plain, SimilarityMetric::Cosine, ExemplarAggregation::Max).is_none());
+    }
+
+    #[test]
+    fn test_cache_matches_on_any_exemplar() {
+        let mut cache = crate::MemoryCache::new(crate::CacheConfig::default());
+        let mut h = heuristic(vec![Exemplar::new("behind you", 1.0, vec![0.0, 1.0])]);
+        h.confidence = 0.9;
+        let id = h.id;
+        cache.add_heuristic(h);
+        let matches = cache.find_matching_heuristics(&[0.0, 1.0], 0.9, 0.5, 5);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0, id);
+    }
+
+    #[test]
+    fn test_update_keeps_exemplar_embeddings() {
+        let previous = Condition::text("watch out").with_exemplars(vec![Exemplar::new("incoming", 1.0, vec![0.5; 2])]);
+        let mut updated = Condition::text("watch out").with_exemplars(vec![
+            Exemplar::new("incoming", 0.8, Vec::new()),
+            Exemplar::new("behind you", 1.0, Vec::new()),
+        ]);
+        keep_exemplar_embeddings(&mut updated, &previous);
+        assert_eq!(updated.exemplars[0].embedding, vec![0.5; 2]);
+        assert_eq!(updated.exemplars[0].weight, 0.8);
+        assert!(updated.exemplars[1].embedding.is_empty());
+    }
+
+    #[test]
+    fn test_negatives_penalize_or_veto() {
+        let mut h = heuristic(Vec::new());
+        h.condition.negatives = vec![
+            Exemplar::new("yeah right, a creeper", 0.5, vec![0.8, 0.6]),
+            Exemplar::new("far off", 1.0, vec![0.0, 1.0]),
+        ];
+        let config = CacheConfig { negative_threshold: 0.7, ..CacheConfig::default() };
+
+        // Similarity 1.0 to the condition, 0.8 to the first negative (0.0 to the other)
+        let (score, negative) = condition_score(&[1.0, 0.0], &h, &config).unwrap();
+        let negative = negative.unwrap();
+        assert_eq!(negative.text, "yeah right, a creeper");
+        assert!((negative.similarity - 0.8).abs() < 1e-6);
+        assert!((negative.penalty - 0.4).abs() < 1e-6);
+        assert!((score - 0.6).abs() < 1e-6);
+
+        let veto = CacheConfig { negative_policy: NegativePolicy::Veto, ..config.clone() };
+        assert!(condition_score(&[1.0, 0.0], &h, &veto).is_none());
+
+        // Below the threshold the negative doesn't count
+        let lenient = CacheConfig { negative_threshold: 0.9, ..config };
+        assert_eq!(condition_score(&[1.0, 0.0], &h, &lenient).unwrap().1, None);
+    }
+}
@@ -0,0 +1,277 @@
+//! A/B experiments over scorer configurations.
+//!
+//! An experiment splits EvaluateSalience traffic across variants, each with
+//! its own scorer and/or default thresholds. Assignment hashes the event (or
+//! source) so the same input always lands on the same variant, and every
+//! response is tagged with its variant (`experiment_variant`). Per-variant
+//! match rates and latency are exposed via GetExperimentStats and Prometheus.
+//!
+//! Configured as JSON in SALIENCE_EXPERIMENT, e.g.:
+//!
+//! ```text
+//! {"name": "strict-threshold", "assign_by": "source", "variants": [
+//!     {"name": "control", "weight": 90},
+//!     {"name": "strict", "weight": 10, "min_similarity": 0.85},
+//!     {"name": "overlap", "weight": 0, "scorer": "word_overlap"}]}
+//! ```
+//!
+//! Variant fields other than `name` are optional: `weight` defaults to 1,
+//! and unset scorer/thresholds fall back to the service's own. Per-request
+//! threshold overrides still take precedence over a variant's defaults.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::metrics::{experiment_metrics, Histogram};
+use crate::SalienceScorer;
+
+/// What the assignment hash is computed over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssignBy {
+    /// Source and event ID: each event is assigned independently (default)
+    #[default]
+    Event,
+    /// Source only: all events from a source share a variant
+    Source,
+}
+
+impl AssignBy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssignBy::Event => "event",
+            AssignBy::Source => "source",
+        }
+    }
+}
+
+/// One variant as configured.
+#[derive(Debug, Clone, Deserialize)]
+pub struct VariantConfig {
+    pub name: String,
+    /// Share of traffic relative to the other variants (0 = never assigned)
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    /// Scorer implementation (as in SALIENCE_SCORER); unset = the service's scorer
+    #[serde(default)]
+    pub scorer: Option<String>,
+    #[serde(default)]
+    pub min_similarity: Option<f32>,
+    #[serde(default)]
+    pub min_confidence: Option<f32>,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// An experiment definition (the SALIENCE_EXPERIMENT schema).
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExperimentConfig {
+    pub name: String,
+    #[serde(default)]
+    pub assign_by: AssignBy,
+    pub variants: Vec<VariantConfig>,
+}
+
+/// Errors in an experiment definition.
+#[derive(Debug, thiserror::Error)]
+pub enum ExperimentError {
+    #[error("Invalid experiment definition: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("Experiment {0} has no variants with a positive weight")]
+    NoWeight(String),
+    #[error("Experiment has duplicate variant name: {0}")]
+    DuplicateVariant(String),
+}
+
+/// Parse and validate an experiment definition.
+pub fn parse_experiment(json: &str) -> Result<ExperimentConfig, ExperimentError> {
+    let config: ExperimentConfig = serde_json::from_str(json)?;
+    if config.variants.iter().all(|v| v.weight == 0) {
+        return Err(ExperimentError::NoWeight(config.name));
+    }
+    let mut names = std::collections::HashSet::new();
+    if let Some(v) = config.variants.iter().find(|v| !names.insert(v.name.as_str())) {
+        return Err(ExperimentError::DuplicateVariant(v.name.clone()));
+    }
+    Ok(config)
+}
+
+/// A variant with its scorer built.
+pub struct Variant {
+    pub config: VariantConfig,
+    /// None = use the service's scorer
+    pub scorer: Option<Box<dyn SalienceScorer>>,
+}
+
+/// Running totals for one variant.
+#[derive(Debug, Clone, Default)]
+pub struct VariantStats {
+    pub requests: u64,
+    /// Requests where a heuristic matched
+    pub matches: u64,
+    /// Requests where scoring failed
+    pub errors: u64,
+    pub latency: Histogram,
+}
+
+impl VariantStats {
+    pub fn match_rate(&self) -> f32 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.matches as f32 / self.requests as f32
+        }
+    }
+}
+
+/// An active experiment: variants, assignment and per-variant stats.
+pub struct Experiment {
+    name: String,
+    assign_by: AssignBy,
+    variants: Vec<Variant>,
+    stats: Mutex<BTreeMap<String, VariantStats>>,
+}
+
+impl Experiment {
+    /// Build an experiment, creating a scorer for each variant that names one.
+    pub fn new(
+        config: ExperimentConfig,
+        mut make_scorer: impl FnMut(&str) -> Box<dyn SalienceScorer>,
+    ) -> Self {
+        let variants = config
+            .variants
+            .into_iter()
+            .map(|v| Variant { scorer: v.scorer.as_deref().map(&mut make_scorer), config: v })
+            .collect();
+        Self {
+            name: config.name,
+            assign_by: config.assign_by,
+            variants,
+            stats: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn assign_by(&self) -> AssignBy {
+        self.assign_by
+    }
+
+    pub fn variants(&self) -> &[Variant] {
+        &self.variants
+    }
+
+    /// Deterministically pick the variant for an event.
+    ///
+    /// The hash is salted with the experiment name, so renaming an
+    /// experiment reshuffles assignments.
+    pub fn assign(&self, event_id: &str, source: &str) -> &Variant {
+        let key = match self.assign_by {
+            AssignBy::Event => format!("{}\0{}\0{}", self.name, source, event_id),
+            AssignBy::Source => format!("{}\0{}", self.name, source),
+        };
+        let total: u64 = self.variants.iter().map(|v| v.config.weight as u64).sum();
+        let mut bucket = crate::seed::fnv1a(key.as_bytes(), 0xcbf29ce484222325) % total.max(1);
+        for variant in &self.variants {
+            let weight = variant.config.weight as u64;
+            if bucket < weight {
+                return variant;
+            }
+            bucket -= weight;
+        }
+        // Unreachable with a validated config (some positive weight)
+        &self.variants[0]
+    }
+
+    /// Record one evaluation's outcome for `variant`.
+    pub fn record(&self, variant: &str, matched: bool, error: bool, elapsed: Duration) {
+        {
+            let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+            let entry = stats.entry(variant.to_string()).or_default();
+            entry.requests += 1;
+            entry.matches += matched as u64;
+            entry.errors += error as u64;
+            entry.latency.observe(elapsed);
+        }
+        experiment_metrics().record(&self.name, variant, matched, elapsed);
+    }
+
+    /// Stats for every configured variant (zeroed if it hasn't been assigned yet).
+    pub fn stats(&self) -> Vec<(&VariantConfig, VariantStats)> {
+        let stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        self.variants
+            .iter()
+            .map(|v| (&v.config, stats.get(&v.config.name).cloned().unwrap_or_default()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn experiment(json: &str) -> Experiment {
+        Experiment::new(parse_experiment(json).unwrap(), |_| unreachable!("no variant names a scorer"))
+    }
+
+    #[test]
+    fn test_parse_experiment_validation() {
+        let config = parse_experiment(r#"{"name": "e", "variants": [{"name": "a"}, {"name": "b", "min_similarity": 0.9}]}"#).unwrap();
+        assert_eq!(config.assign_by, AssignBy::Event);
+        assert_eq!(config.variants[0].weight, 1);
+        assert_eq!(config.variants[1].min_similarity, Some(0.9));
+
+        assert!(matches!(
+            parse_experiment(r#"{"name": "e", "variants": [{"name": "a", "weight": 0}]}"#),
+            Err(ExperimentError::NoWeight(_))
+        ));
+        assert!(matches!(
+            parse_experiment(r#"{"name": "e", "variants": [{"name": "a"}, {"name": "a"}]}"#),
+            Err(ExperimentError::DuplicateVariant(_))
+        ));
+        assert!(parse_experiment("{").is_err());
+    }
+
+    #[test]
+    fn test_assignment_is_deterministic_and_weighted() {
+        let e = experiment(r#"{"name": "e", "variants": [{"name": "a", "weight": 3}, {"name": "b", "weight": 1}, {"name": "off", "weight": 0}]}"#);
+        let mut counts = BTreeMap::new();
+        for i in 0..4000 {
+            let id = format!("event-{}", i);
+            let name = &e.assign(&id, "game").config.name;
+            assert_eq!(name, &e.assign(&id, "game").config.name);
+            *counts.entry(name.clone()).or_insert(0) += 1;
+        }
+        assert!(!counts.contains_key("off"));
+        // ~3000 / ~1000
+        assert!((2700..3300).contains(&counts["a"]), "{:?}", counts);
+
+        // By source: every event from a source shares a variant
+        let e = experiment(r#"{"name": "e", "assign_by": "source", "variants": [{"name": "a"}, {"name": "b"}]}"#);
+        let first = &e.assign("1", "discord").config.name;
+        assert!((0..50).all(|i| &e.assign(&i.to_string(), "discord").config.name == first));
+    }
+
+    #[test]
+    fn test_variant_stats() {
+        let e = experiment(r#"{"name": "stats-test", "variants": [{"name": "a"}, {"name": "b"}]}"#);
+        e.record("a", true, false, Duration::from_millis(2));
+        e.record("a", false, true, Duration::from_millis(4));
+
+        let stats = e.stats();
+        assert_eq!(stats.len(), 2);
+        let (config, a) = &stats[0];
+        assert_eq!(config.name, "a");
+        assert_eq!((a.requests, a.matches, a.errors), (2, 1, 1));
+        assert!((a.match_rate() - 0.5).abs() < 0.001);
+        assert!((a.latency.mean_ms() - 3.0).abs() < 0.5);
+        assert_eq!(stats[1].1.requests, 0);
+    }
+}
@@ -0,0 +1,207 @@
+//! Explanations of why a heuristic matched.
+//!
+//! A match on its own says which heuristic fired, not what in the event set
+//! it off. EvaluateSalience attaches an explanation to every match:
+//! - Lexical matches (keyword pre-filter/fallback, word-overlap scorer): the
+//!   spans of the event text that matched, as byte offsets into the
+//!   evaluated (normalized) text
+//! - Embedding and storage matches: the condition sentence nearest the event
+//!   (the one sharing the most words with it; sentences aren't embedded
+//!   separately) and how far the match's similarity is ahead of the runner-up
+//!
+//! Both kinds carry the similarity and the runner-up, so a close call is
+//! visible at a glance. A negative exemplar that counted against the match
+//! (see exemplars module) is reported with its similarity and penalty.
+
+use aho_corasick::AhoCorasick;
+
+use crate::affect::on_word_boundaries;
+use crate::exemplars::NegativeMatch;
+use crate::keywords::heuristic_keywords_of;
+use crate::{MatchMethod, ScoredMatch};
+
+/// Words shorter than this don't count as shared (as in the word-overlap scorer).
+const MIN_WORD_LEN: usize = 3;
+
+/// Part of the event text that triggered a lexical match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedSpan {
+    /// The text as it appears in the event
+    pub term: String,
+    /// Byte offsets into the event text
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Why the winning match matched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchExplanation {
+    pub method: MatchMethod,
+    /// Lexical matches only
+    pub spans: Vec<MatchedSpan>,
+    /// Embedding and storage matches only (empty otherwise)
+    pub condition_sentence: String,
+    pub similarity: f32,
+    /// Next best match (None = the winner was the only one)
+    pub runner_up_heuristic_id: Option<String>,
+    /// Similarity minus the runner-up's (the full similarity when there is none)
+    pub similarity_margin: f32,
+    /// Calibrated probability the match is right (embedding matches under an
+    /// active calibration curve only; see calibration module)
+    pub match_probability: Option<f32>,
+    /// Negative exemplar whose penalty `similarity` is net of
+    pub negative: Option<NegativeMatch>,
+}
+
+/// Explain `matches[winner]` against the other matches for `event_text`.
+pub fn explain_match(event_text: &str, matches: &[ScoredMatch], winner: usize) -> MatchExplanation {
+    let best = &matches[winner];
+    let runner_up = matches
+        .iter()
+        .enumerate()
+        .filter(|(i, m)| *i != winner && m.heuristic_id != best.heuristic_id)
+        .map(|(_, m)| m)
+        .max_by(|a, b| a.similarity.partial_cmp(&b.similarity).unwrap_or(std::cmp::Ordering::Equal));
+
+    let (spans, condition_sentence) = match best.method {
+        MatchMethod::Keyword => (keyword_spans(event_text, &heuristic_keywords_of(&best.effects)), String::new()),
+        MatchMethod::WordOverlap => (shared_word_spans(event_text, &best.condition_text), String::new()),
+        MatchMethod::Embedding | MatchMethod::Storage => (Vec::new(), nearest_sentence(event_text, &best.condition_text)),
+    };
+    MatchExplanation {
+        method: best.method,
+        spans,
+        condition_sentence,
+        similarity: best.similarity,
+        runner_up_heuristic_id: runner_up.map(|m| m.heuristic_id.clone()),
+        similarity_margin: best.similarity - runner_up.map_or(0.0, |m| m.similarity),
+        match_probability: None,
+        negative: best.negative.clone(),
+    }
+}
+
+/// Whole-word, case-insensitive occurrences of `keywords` in `text`.
+fn keyword_spans(text: &str, keywords: &[&str]) -> Vec<MatchedSpan> {
+    let Ok(automaton) = AhoCorasick::builder()
+        .ascii_case_insensitive(true)
+        .match_kind(aho_corasick::MatchKind::LeftmostLongest)
+        .build(keywords.iter().map(|k| crate::normalize::fold_key(k)))
+    else {
+        return Vec::new();
+    };
+    automaton
+        .find_iter(text)
+        .filter(|m| on_word_boundaries(text, m.start(), m.end()))
+        .map(|m| MatchedSpan { term: text[m.start()..m.end()].to_string(), start: m.start(), end: m.end() })
+        .collect()
+}
+
+/// Byte ranges of the words in `text` (alphanumeric runs).
+fn word_ranges(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                ranges.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        ranges.push((s, text.len()));
+    }
+    ranges
+}
+
+/// Lowercased words of `text` long enough to count as shared.
+fn word_set(text: &str) -> std::collections::HashSet<String> {
+    word_ranges(text)
+        .into_iter()
+        .map(|(s, e)| &text[s..e])
+        .filter(|w| w.chars().count() >= MIN_WORD_LEN)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Words of `text` that also appear in `condition`.
+fn shared_word_spans(text: &str, condition: &str) -> Vec<MatchedSpan> {
+    let condition_words = word_set(condition);
+    word_ranges(text)
+        .into_iter()
+        .filter(|&(s, e)| {
+            let word = &text[s..e];
+            word.chars().count() >= MIN_WORD_LEN && condition_words.contains(&word.to_lowercase())
+        })
+        .map(|(start, end)| MatchedSpan { term: text[start..end].to_string(), start, end })
+        .collect()
+}
+
+/// The sentence of `condition` sharing the most words with `text` (the
+/// first sentence on a tie).
+fn nearest_sentence(text: &str, condition: &str) -> String {
+    let event_words = word_set(text);
+    condition
+        .split_inclusive(['.', '!', '?', '\n'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .enumerate()
+        .max_by_key(|(i, sentence)| (word_set(sentence).intersection(&event_words).count(), std::cmp::Reverse(*i)))
+        .map(|(_, sentence)| sentence.to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scored(id: &str, similarity: f32, method: MatchMethod, condition: &str, effects: serde_json::Value) -> ScoredMatch {
+        ScoredMatch {
+            heuristic_id: id.to_string(),
+            similarity,
+            confidence: 0.9,
+            origin: String::new(),
+            condition_text: condition.to_string(),
+            suggested_action: String::new(),
+            salience_boost: None,
+            effects: effects.into(),
+            method,
+            negative: None,
+        }
+    }
+
+    #[test]
+    fn test_lexical_matches_report_spans() {
+        let text = "a creeper is hissing nearby";
+        let keyword = scored("k", 0.8, MatchMethod::Keyword, "", serde_json::json!({"keywords": ["Creeper", "lava"]}));
+        let explanation = explain_match(text, &[keyword], 0);
+        assert_eq!(explanation.spans, vec![MatchedSpan { term: "creeper".to_string(), start: 2, end: 9 }]);
+        assert_eq!(explanation.runner_up_heuristic_id, None);
+        assert!((explanation.similarity_margin - 0.8).abs() < 1e-6);
+
+        let overlap = scored("w", 0.5, MatchMethod::WordOverlap, "Creeper hissing behind you", serde_json::json!({}));
+        let spans: Vec<String> = explain_match(text, &[overlap], 0).spans.into_iter().map(|s| s.term).collect();
+        assert_eq!(spans, vec!["creeper", "hissing"]);
+    }
+
+    #[test]
+    fn test_embedding_match_reports_sentence_and_margin() {
+        let condition = "Night is falling. A hostile mob is close to the player! Stay calm.";
+        let negative = NegativeMatch { text: "a friendly mob".to_string(), similarity: 0.8, penalty: 0.08 };
+        let matches = [
+            scored("runner", 0.78, MatchMethod::Embedding, "", serde_json::json!({})),
+            ScoredMatch {
+                negative: Some(negative.clone()),
+                ..scored("best", 0.86, MatchMethod::Embedding, condition, serde_json::json!({}))
+            },
+        ];
+        let explanation = explain_match("hostile mob near the player", &matches, 1);
+        assert_eq!(explanation.negative, Some(negative));
+        assert!(explanation.spans.is_empty());
+        assert_eq!(explanation.condition_sentence, "A hostile mob is close to the player!");
+        assert_eq!(explanation.runner_up_heuristic_id.as_deref(), Some("runner"));
+        assert!((explanation.similarity_margin - 0.08).abs() < 1e-6);
+    }
+}
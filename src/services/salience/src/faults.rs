@@ -0,0 +1,295 @@
+//! Fault injection for resilience tests (`fault-injection` feature).
+//!
+//! Degradation behavior (storage down, slow embeddings, partial responses)
+//! used to be tested by breaking the Python service by hand. With this
+//! feature the storage backend is wrapped in `FaultyStorage`, and the
+//! InjectFaults admin RPC sets what it does to each call:
+//! - Latency: a fixed delay before the call
+//! - Errors: a fraction of calls fail with UNAVAILABLE or a timeout, without
+//!   reaching storage
+//! - Partial responses: a fraction of successful calls return truncated
+//!   results (half the heuristics, half the embedding dimensions)
+//!
+//! Faults can be limited to named methods ("generate_embedding", ...) and
+//! last until replaced or cleared. Never enable the feature in production
+//! builds: the RPC is unauthenticated.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::proto::{self, InjectFaultsRequest};
+use crate::{CachedHeuristic, HeuristicChanges, HeuristicFeedback, StorageBackend, StorageError};
+
+/// How injected errors surface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FaultError {
+    #[default]
+    Unavailable,
+    Timeout,
+}
+
+/// Faults applied to storage calls.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FaultSpec {
+    /// Methods affected (empty = every method)
+    pub methods: Vec<String>,
+    pub latency: Duration,
+    /// Fraction of calls failing before reaching storage (0.0-1.0)
+    pub error_rate: f32,
+    pub error: FaultError,
+    /// Fraction of successful calls returning truncated results (0.0-1.0)
+    pub partial_rate: f32,
+}
+
+impl FaultSpec {
+    fn applies_to(&self, method: &str) -> bool {
+        self.methods.is_empty() || self.methods.iter().any(|m| m == method)
+    }
+}
+
+impl From<&InjectFaultsRequest> for FaultSpec {
+    fn from(req: &InjectFaultsRequest) -> Self {
+        Self {
+            methods: req.methods.iter().filter(|m| !m.is_empty()).cloned().collect(),
+            latency: Duration::from_millis(req.latency_ms.max(0) as u64),
+            error_rate: req.error_rate.clamp(0.0, 1.0),
+            error: if req.error_kind == "timeout" { FaultError::Timeout } else { FaultError::Unavailable },
+            partial_rate: req.partial_rate.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Injected faults since startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FaultCounts {
+    pub delays: u64,
+    pub errors: u64,
+    pub partials: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    spec: Mutex<Option<FaultSpec>>,
+    rng: AtomicU64,
+    delays: AtomicU64,
+    errors: AtomicU64,
+    partials: AtomicU64,
+}
+
+/// Shared handle: `FaultyStorage` reads it, the InjectFaults RPC sets it.
+#[derive(Clone, Default)]
+pub struct FaultInjector {
+    inner: Arc<Inner>,
+}
+
+impl FaultInjector {
+    /// Replace the active faults (None = clear).
+    pub fn set(&self, spec: Option<FaultSpec>) {
+        info!(faults = ?spec, "Storage fault injection updated");
+        *self.inner.spec.lock().unwrap_or_else(|e| e.into_inner()) = spec;
+    }
+
+    pub fn spec(&self) -> Option<FaultSpec> {
+        self.inner.spec.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn counts(&self) -> FaultCounts {
+        FaultCounts {
+            delays: self.inner.delays.load(Ordering::Relaxed),
+            errors: self.inner.errors.load(Ordering::Relaxed),
+            partials: self.inner.partials.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Uniform in [0, 1) from a splitmix64 sequence: no rand dependency, and
+    /// a fixed call order gets the same faults every run.
+    fn roll(&self) -> f32 {
+        const GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut z = self.inner.rng.fetch_add(GAMMA, Ordering::Relaxed).wrapping_add(GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Apply the faults for one call: delay, then maybe fail.
+    /// Returns whether a successful result should be truncated.
+    async fn before(&self, method: &'static str) -> Result<bool, StorageError> {
+        let Some(spec) = self.spec().filter(|s| s.applies_to(method)) else {
+            return Ok(false);
+        };
+        if !spec.latency.is_zero() {
+            self.inner.delays.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(spec.latency).await;
+        }
+        if spec.error_rate > 0.0 && self.roll() < spec.error_rate {
+            self.inner.errors.fetch_add(1, Ordering::Relaxed);
+            debug!(method, "Injecting storage error");
+            let message = format!("injected fault in {}", method);
+            return Err(match spec.error {
+                FaultError::Unavailable => StorageError::Unavailable(message),
+                FaultError::Timeout => StorageError::Timeout(message),
+            });
+        }
+        let partial = spec.partial_rate > 0.0 && self.roll() < spec.partial_rate;
+        if partial {
+            self.inner.partials.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(partial)
+    }
+}
+
+/// Storage backend wrapper applying the injector's faults.
+pub struct FaultyStorage {
+    inner: Arc<dyn StorageBackend>,
+    faults: FaultInjector,
+}
+
+impl FaultyStorage {
+    pub fn new(inner: Arc<dyn StorageBackend>, faults: FaultInjector) -> Self {
+        Self { inner, faults }
+    }
+}
+
+fn truncate_half<T>(items: &mut Vec<T>) {
+    items.truncate(items.len() / 2);
+}
+
+#[tonic::async_trait]
+impl StorageBackend for FaultyStorage {
+    async fn query_matching_heuristics(
+        &self,
+        event_text: &str,
+        min_confidence: f32,
+        limit: i32,
+        source_filter: Option<&str>,
+        trace_id: Option<&str>,
+    ) -> Result<Vec<CachedHeuristic>, StorageError> {
+        let partial = self.faults.before("query_matching_heuristics").await?;
+        let mut heuristics = self
+            .inner
+            .query_matching_heuristics(event_text, min_confidence, limit, source_filter, trace_id)
+            .await?;
+        if partial {
+            truncate_half(&mut heuristics);
+        }
+        Ok(heuristics)
+    }
+
+    async fn generate_embedding(&self, text: &str, trace_id: Option<&str>) -> Result<Vec<f32>, StorageError> {
+        let partial = self.faults.before("generate_embedding").await?;
+        let mut embedding = self.inner.generate_embedding(text, trace_id).await?;
+        if partial {
+            truncate_half(&mut embedding);
+        }
+        Ok(embedding)
+    }
+
+    fn embedding_model(&self) -> Option<String> {
+        self.inner.embedding_model()
+    }
+
+    async fn query_changed_heuristics(
+        &self,
+        updated_since_ms: i64,
+        limit: i32,
+        trace_id: Option<&str>,
+    ) -> Result<HeuristicChanges, StorageError> {
+        let partial = self.faults.before("query_changed_heuristics").await?;
+        let mut changes = self.inner.query_changed_heuristics(updated_since_ms, limit, trace_id).await?;
+        if partial {
+            truncate_half(&mut changes.heuristics);
+        }
+        Ok(changes)
+    }
+
+    async fn store_heuristic(&self, heuristic: &CachedHeuristic, trace_id: Option<&str>) -> Result<(), StorageError> {
+        self.faults.before("store_heuristic").await?;
+        self.inner.store_heuristic(heuristic, trace_id).await
+    }
+
+    async fn update_event_salience(
+        &self,
+        mut updates: Vec<proto::EventSalienceUpdate>,
+        trace_id: Option<&str>,
+    ) -> Result<usize, StorageError> {
+        if self.faults.before("update_event_salience").await? {
+            truncate_half(&mut updates);
+        }
+        self.inner.update_event_salience(updates, trace_id).await
+    }
+
+    async fn query_heuristic_feedback(
+        &self,
+        ids: &[Uuid],
+        trace_id: Option<&str>,
+    ) -> Result<HashMap<Uuid, HeuristicFeedback>, StorageError> {
+        let partial = self.faults.before("query_heuristic_feedback").await?;
+        let feedback = self.inner.query_heuristic_feedback(ids, trace_id).await?;
+        if partial {
+            let keep = feedback.len() / 2;
+            return Ok(feedback.into_iter().take(keep).collect());
+        }
+        Ok(feedback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedStorage;
+
+    #[tonic::async_trait]
+    impl StorageBackend for FixedStorage {
+        async fn query_matching_heuristics(
+            &self,
+            _event_text: &str,
+            _min_confidence: f32,
+            _limit: i32,
+            _source_filter: Option<&str>,
+            _trace_id: Option<&str>,
+        ) -> Result<Vec<CachedHeuristic>, StorageError> {
+            Ok(Vec::new())
+        }
+
+        async fn generate_embedding(&self, _text: &str, _trace_id: Option<&str>) -> Result<Vec<f32>, StorageError> {
+            Ok(vec![1.0; 8])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_faults_apply_to_selected_methods() {
+        let faults = FaultInjector::default();
+        let storage = FaultyStorage::new(Arc::new(FixedStorage), faults.clone());
+        assert_eq!(storage.generate_embedding("x", None).await.unwrap().len(), 8);
+
+        faults.set(Some(FaultSpec {
+            methods: vec!["generate_embedding".to_string()],
+            error_rate: 1.0,
+            error: FaultError::Timeout,
+            ..Default::default()
+        }));
+        assert!(matches!(storage.generate_embedding("x", None).await, Err(StorageError::Timeout(_))));
+        assert!(storage.query_matching_heuristics("x", 0.5, 10, None, None).await.is_ok());
+
+        faults.set(Some(FaultSpec { partial_rate: 1.0, latency: Duration::from_millis(5), ..Default::default() }));
+        assert_eq!(storage.generate_embedding("x", None).await.unwrap().len(), 4);
+        assert_eq!(faults.counts(), FaultCounts { delays: 1, errors: 1, partials: 1 });
+
+        faults.set(None);
+        assert_eq!(storage.generate_embedding("x", None).await.unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_roll_is_uniformish() {
+        let faults = FaultInjector::default();
+        let hits = (0..10_000).filter(|_| faults.roll() < 0.25).count();
+        assert!((2_000..3_000).contains(&hits), "{}", hits);
+    }
+}
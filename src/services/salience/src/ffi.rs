@@ -0,0 +1,401 @@
+//! C ABI over the cache and scoring core, for in-process use without gRPC
+//! (game-engine plugins, ctypes/cffi from Python; the python module wraps the
+//! same core natively).
+//!
+//! The declarations live in `include/gladys_memory.h`; build the shared
+//! library with `cargo build --release` (libgladys_memory.so / .dylib /
+//! gladys_memory.dll). Bump `GLADYS_ABI_VERSION` on any incompatible change.
+//!
+//! A cache holds heuristics (condition embedding, confidence, effects JSON);
+//! scoring finds the best match for an event embedding and applies its
+//! `salience` boost to a zero baseline, as EvaluateSalience does. Heuristics
+//! inserted without an embedding, and events scored by text, use the same
+//! hashed bag-of-words embedding as `salience-simulate` (see simulate
+//! module): fine for keyword-ish triggers, not the production model, so
+//! don't mix them with model embeddings in one cache.
+//!
+//! Functions returning `int32_t` return -1 on error; `gladys_last_error`
+//! then describes it. Caches are internally locked and may be shared
+//! between threads.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Mutex;
+
+use uuid::Uuid;
+
+use crate::proto::gladys::types::SalienceResult;
+use crate::server::apply_salience_boost;
+use crate::simulate::local_embedding;
+use crate::domain::{Condition, Effects};
+use crate::{CacheConfig, CachedHeuristic, MemoryCache, ScoringOutcome};
+
+/// Matches `GLADYS_ABI_VERSION` in the header.
+pub const GLADYS_ABI_VERSION: u32 = 1;
+
+/// An opaque cache handle.
+pub struct GladysCache {
+    cache: Mutex<MemoryCache>,
+}
+
+/// Result of scoring one event (`GladysScore` in the header).
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GladysScore {
+    /// 1 if a heuristic matched
+    pub matched: i32,
+    /// NUL-terminated UUID of the matched heuristic ("" if none)
+    pub heuristic_id: [c_char; 37],
+    pub similarity: f32,
+    pub confidence: f32,
+    pub threat: f32,
+    pub salience: f32,
+    pub novelty: f32,
+    pub goal_relevance: f32,
+    pub opportunity: f32,
+    pub actionability: f32,
+    pub social: f32,
+    pub emotional: f32,
+}
+
+impl Default for GladysScore {
+    fn default() -> Self {
+        Self {
+            matched: 0,
+            heuristic_id: [0; 37],
+            similarity: 0.0,
+            confidence: 0.0,
+            threat: 0.0,
+            salience: 0.0,
+            novelty: 0.0,
+            goal_relevance: 0.0,
+            opportunity: 0.0,
+            actionability: 0.0,
+            social: 0.0,
+            emotional: 0.0,
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+}
+
+/// Run `f`, turning errors and panics into `fallback` plus a last error.
+fn guard<T>(fallback: T, f: impl FnOnce() -> Result<T, String>) -> T {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(message)) => {
+            set_last_error(&message);
+            fallback
+        }
+        Err(_) => {
+            set_last_error("internal panic");
+            fallback
+        }
+    }
+}
+
+/// # Safety
+/// `ptr` must be NULL or a NUL-terminated string.
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err(format!("{} is NULL", name));
+    }
+    CStr::from_ptr(ptr).to_str().map_err(|_| format!("{} is not UTF-8", name))
+}
+
+/// # Safety
+/// `ptr` must be NULL (with `len` 0) or point to `len` floats.
+unsafe fn floats_arg<'a>(ptr: *const f32, len: usize) -> &'a [f32] {
+    if ptr.is_null() || len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(ptr, len)
+    }
+}
+
+fn lock(cache: &GladysCache) -> std::sync::MutexGuard<'_, MemoryCache> {
+    cache.cache.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// ABI version of the loaded library.
+#[no_mangle]
+pub extern "C" fn gladys_abi_version() -> u32 {
+    GLADYS_ABI_VERSION
+}
+
+/// Description of the last error on this thread ("" if none). Valid until
+/// the next call on this thread.
+#[no_mangle]
+pub extern "C" fn gladys_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ptr())
+}
+
+/// Create a cache holding up to `max_heuristics` (0 = default).
+#[no_mangle]
+pub extern "C" fn gladys_cache_new(max_heuristics: u32) -> *mut GladysCache {
+    guard(std::ptr::null_mut(), || {
+        let mut config = CacheConfig::default();
+        if max_heuristics > 0 {
+            config.max_heuristics = max_heuristics as usize;
+        }
+        Ok(Box::into_raw(Box::new(GladysCache { cache: Mutex::new(MemoryCache::new(config)) })))
+    })
+}
+
+/// Free a cache. NULL is ignored.
+///
+/// # Safety
+/// `cache` must be NULL or come from `gladys_cache_new`, and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn gladys_cache_free(cache: *mut GladysCache) {
+    if !cache.is_null() {
+        drop(Box::from_raw(cache));
+    }
+}
+
+/// Insert or replace a heuristic. `effects_json` may be NULL; `embedding`
+/// may be NULL to embed `condition_text` locally. Returns 0 or -1.
+///
+/// # Safety
+/// `cache` must be a live cache; strings NULL or NUL-terminated; `embedding`
+/// NULL or `embedding_len` floats.
+#[no_mangle]
+pub unsafe extern "C" fn gladys_cache_insert(
+    cache: *mut GladysCache,
+    id: *const c_char,
+    condition_text: *const c_char,
+    effects_json: *const c_char,
+    confidence: f32,
+    embedding: *const f32,
+    embedding_len: usize,
+) -> i32 {
+    guard(-1, || {
+        let cache = cache.as_ref().ok_or("cache is NULL")?;
+        let id = Uuid::parse_str(str_arg(id, "id")?).map_err(|e| format!("invalid id: {}", e))?;
+        let condition_text = str_arg(condition_text, "condition_text")?;
+        let effects = if effects_json.is_null() {
+            Effects::default()
+        } else {
+            Effects::parse(str_arg(effects_json, "effects_json")?).map_err(|e| format!("invalid effects_json: {}", e))?
+        };
+        lock(cache).add_heuristic(local_heuristic(id, condition_text, effects, confidence, floats_arg(embedding, embedding_len)));
+        Ok(0)
+    })
+}
+
+/// A heuristic inserted through a binding (here or the python module):
+/// `condition_text` embedded locally unless `embedding` is given.
+pub(crate) fn local_heuristic(
+    id: Uuid,
+    condition_text: &str,
+    effects: Effects,
+    confidence: f32,
+    embedding: &[f32],
+) -> CachedHeuristic {
+    let embedding = match embedding {
+        [] => local_embedding(condition_text),
+        embedding => embedding.to_vec(),
+    };
+    CachedHeuristic {
+        id,
+        name: condition_text.to_string(),
+        condition: Condition::text(condition_text),
+        effects,
+        confidence,
+        origin: String::new(),
+        source: String::new(),
+        condition_embedding: embedding.into(),
+        last_accessed_ms: 0,
+        cached_at_ms: 0,
+        hit_count: 0,
+        last_hit_ms: 0,
+        cooldown_until_ms: 0,
+        embedding_model: String::new(),
+    }
+}
+
+/// Remove a heuristic. Returns 1 if removed, 0 if absent, -1 on error.
+///
+/// # Safety
+/// `cache` must be a live cache and `id` NULL or NUL-terminated.
+#[no_mangle]
+pub unsafe extern "C" fn gladys_cache_remove(cache: *mut GladysCache, id: *const c_char) -> i32 {
+    guard(-1, || {
+        let cache = cache.as_ref().ok_or("cache is NULL")?;
+        let id = Uuid::parse_str(str_arg(id, "id")?).map_err(|e| format!("invalid id: {}", e))?;
+        Ok(lock(cache).remove_heuristic(&id) as i32)
+    })
+}
+
+/// Heuristics in the cache (0 for NULL).
+///
+/// # Safety
+/// `cache` must be NULL or a live cache.
+#[no_mangle]
+pub unsafe extern "C" fn gladys_cache_len(cache: *const GladysCache) -> usize {
+    cache.as_ref().map_or(0, |cache| lock(cache).stats().heuristic_count)
+}
+
+/// Best match for `embedding`, with its boost applied (`matched` = 0 if none).
+fn score(cache: &GladysCache, embedding: &[f32], min_similarity: f32, min_confidence: f32) -> GladysScore {
+    let mut cache = lock(cache);
+    let best = cache.find_matching_heuristics(embedding, min_similarity, min_confidence, 1).first().copied();
+    let Some((id, similarity)) = best else {
+        cache.apply_scoring_outcome(&ScoringOutcome { hit: Some(false), ..Default::default() });
+        return GladysScore::default();
+    };
+    cache.apply_scoring_outcome(&ScoringOutcome { matched: vec![(id, similarity)], hit: Some(true), ..Default::default() });
+    let Some(heuristic) = cache.get_heuristic(&id) else {
+        return GladysScore::default();
+    };
+
+    let mut salience = SalienceResult::default();
+    if let Some(boost) = &heuristic.effects.salience {
+        apply_salience_boost(&mut salience, boost);
+    }
+    let dimension = |name: &str| salience.vector.get(name).copied().unwrap_or(0.0);
+    let mut result = GladysScore {
+        matched: 1,
+        similarity,
+        confidence: heuristic.confidence,
+        threat: salience.threat,
+        salience: salience.salience,
+        novelty: dimension("novelty"),
+        goal_relevance: dimension("goal_relevance"),
+        opportunity: dimension("opportunity"),
+        actionability: dimension("actionability"),
+        social: dimension("social"),
+        emotional: dimension("emotional"),
+        ..Default::default()
+    };
+    for (out, byte) in result.heuristic_id.iter_mut().zip(id.hyphenated().to_string().bytes()) {
+        *out = byte as c_char;
+    }
+    result
+}
+
+/// Score an event embedding into `out`. Returns 1 if matched, 0 if not, -1 on error.
+///
+/// # Safety
+/// `cache` must be a live cache, `embedding` point to `embedding_len`
+/// floats, and `out` be writable.
+#[no_mangle]
+pub unsafe extern "C" fn gladys_score_embedding(
+    cache: *const GladysCache,
+    embedding: *const f32,
+    embedding_len: usize,
+    min_similarity: f32,
+    min_confidence: f32,
+    out: *mut GladysScore,
+) -> i32 {
+    guard(-1, || {
+        let cache = cache.as_ref().ok_or("cache is NULL")?;
+        let out = out.as_mut().ok_or("out is NULL")?;
+        let embedding = floats_arg(embedding, embedding_len);
+        if embedding.is_empty() {
+            return Err("embedding is empty".to_string());
+        }
+        *out = score(cache, embedding, min_similarity, min_confidence);
+        Ok(out.matched)
+    })
+}
+
+/// Score event text (embedded locally) into `out`. Returns 1 if matched,
+/// 0 if not, -1 on error.
+///
+/// # Safety
+/// `cache` must be a live cache, `text` NUL-terminated, and `out` writable.
+#[no_mangle]
+pub unsafe extern "C" fn gladys_score_text(
+    cache: *const GladysCache,
+    text: *const c_char,
+    min_similarity: f32,
+    min_confidence: f32,
+    out: *mut GladysScore,
+) -> i32 {
+    guard(-1, || {
+        let cache = cache.as_ref().ok_or("cache is NULL")?;
+        let out = out.as_mut().ok_or("out is NULL")?;
+        let embedding = local_embedding(str_arg(text, "text")?);
+        *out = score(cache, &embedding, min_similarity, min_confidence);
+        Ok(out.matched)
+    })
+}
+
+/// Cosine similarity of two `len`-float vectors (0 if either is NULL).
+///
+/// # Safety
+/// `a` and `b` must be NULL or point to `len` floats.
+#[no_mangle]
+pub unsafe extern "C" fn gladys_cosine_similarity(a: *const f32, b: *const f32, len: usize) -> f32 {
+    crate::cosine_similarity(floats_arg(a, len), floats_arg(b, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_declares_every_export() {
+        let header = include_str!("../include/gladys_memory.h");
+        let source = include_str!("ffi.rs");
+        let exports: Vec<&str> = source
+            .lines()
+            .filter_map(|l| l.split_once("extern \"C\" fn ").map(|(_, rest)| rest))
+            .filter_map(|rest| rest.split('(').next())
+            .collect();
+        assert!(exports.len() >= 9);
+        for name in exports {
+            assert!(header.contains(&format!("{}(", name)), "{} missing from header", name);
+        }
+        assert!(header.contains(&format!("#define GLADYS_ABI_VERSION {}", GLADYS_ABI_VERSION)));
+    }
+
+    #[test]
+    fn test_insert_and_score_through_the_abi() {
+        let id = Uuid::new_v4();
+        let c = |s: &str| CString::new(s).unwrap();
+        unsafe {
+            let cache = gladys_cache_new(0);
+            let effects = c(r#"{"salience": {"threat": 0.8, "social": 0.4}}"#);
+            let inserted = gladys_cache_insert(
+                cache,
+                c(&id.to_string()).as_ptr(),
+                c("creeper hissing nearby").as_ptr(),
+                effects.as_ptr(),
+                0.9,
+                std::ptr::null(),
+                0,
+            );
+            assert_eq!(inserted, 0);
+            assert_eq!(gladys_cache_len(cache), 1);
+
+            let mut out = GladysScore::default();
+            assert_eq!(gladys_score_text(cache, c("creeper hissing nearby").as_ptr(), 0.8, 0.5, &mut out), 1);
+            assert_eq!(CStr::from_ptr(out.heuristic_id.as_ptr()).to_str().unwrap(), id.to_string());
+            assert!((out.threat - 0.8).abs() < 1e-6);
+            assert!((out.social - 0.4).abs() < 1e-6);
+            assert_eq!(gladys_score_text(cache, c("quiet meadow").as_ptr(), 0.8, 0.5, &mut out), 0);
+            assert_eq!(out.matched, 0);
+
+            // Errors are reported, not panicked
+            assert_eq!(gladys_cache_insert(cache, c("not-a-uuid").as_ptr(), c("x").as_ptr(), std::ptr::null(), 0.9, std::ptr::null(), 0), -1);
+            assert!(CStr::from_ptr(gladys_last_error()).to_str().unwrap().contains("invalid id"));
+            assert_eq!(gladys_score_text(std::ptr::null(), c("x").as_ptr(), 0.8, 0.5, &mut out), -1);
+
+            assert_eq!(gladys_cache_remove(cache, c(&id.to_string()).as_ptr()), 1);
+            assert_eq!(gladys_cache_remove(cache, c(&id.to_string()).as_ptr()), 0);
+            gladys_cache_free(cache);
+        }
+        let v = [1.0f32, 0.0];
+        assert!((unsafe { gladys_cosine_similarity(v.as_ptr(), v.as_ptr(), 2) } - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,84 @@
+//! Idempotency keys for mutating admin RPCs.
+//!
+//! A caller that retries FlushCache/EvictFromCache with the same key within
+//! the window gets the first call's result back instead of running the
+//! mutation again (e.g., a retried flush reporting 0 entries flushed).
+
+use std::collections::HashMap;
+use std::future::Future;
+
+use tokio::sync::Mutex;
+
+/// Upper bound on remembered keys; the oldest are dropped first.
+const MAX_KEYS: usize = 1024;
+
+/// Remembers recent results by idempotency key, for one RPC.
+pub struct IdempotencyCache<R> {
+    window_ms: i64,
+    entries: Mutex<HashMap<String, (i64, R)>>,
+}
+
+impl<R: Clone> IdempotencyCache<R> {
+    pub fn new(window_ms: i64) -> Self {
+        Self { window_ms, entries: Mutex::new(HashMap::new()) }
+    }
+
+    /// Run `op` unless `key` was seen within the window.
+    ///
+    /// Returns the result and whether it was replayed from an earlier call.
+    /// An empty key always runs `op`. Calls sharing the cache are serialized,
+    /// so concurrent retries can't both execute.
+    pub async fn run<F, Fut>(&self, key: &str, op: F) -> (R, bool)
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = R>,
+    {
+        if key.is_empty() || self.window_ms <= 0 {
+            return (op().await, false);
+        }
+
+        let mut entries = self.entries.lock().await;
+        let now = crate::current_time_ms();
+        entries.retain(|_, (at, _)| now - *at < self.window_ms);
+        if let Some((_, result)) = entries.get(key) {
+            return (result.clone(), true);
+        }
+
+        let result = op().await;
+        if entries.len() >= MAX_KEYS {
+            if let Some(oldest) = entries.iter().min_by_key(|(_, (at, _))| *at).map(|(k, _)| k.clone()) {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key.to_string(), (now, result.clone()));
+        (result, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_idempotency_replays_within_window() {
+        let cache = IdempotencyCache::new(60_000);
+        let calls = AtomicU32::new(0);
+        let op = || async { calls.fetch_add(1, Ordering::SeqCst) };
+
+        assert_eq!(cache.run("k1", op).await, (0, false));
+        assert_eq!(cache.run("k1", op).await, (0, true));
+        assert_eq!(cache.run("k2", op).await, (1, false));
+        // Empty keys are never remembered
+        assert_eq!(cache.run("", op).await, (2, false));
+        assert_eq!(cache.run("", op).await, (3, false));
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_disabled_with_zero_window() {
+        let cache = IdempotencyCache::new(0);
+        assert_eq!(cache.run("k", || async { 1 }).await, (1, false));
+        assert_eq!(cache.run("k", || async { 2 }).await, (2, false));
+    }
+}
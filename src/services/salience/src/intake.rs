@@ -0,0 +1,119 @@
+//! Decision encoding shared by the message intakes (bus and mqtt modules)
+//! and the WebSocket feed (ws module).
+
+use crate::proto::{EvaluateSalienceResponse, RoutingHint};
+
+/// The decision published for an event, as JSON.
+pub fn decision_json(event_id: &str, response: &EvaluateSalienceResponse) -> serde_json::Value {
+    let salience = response.salience.clone().unwrap_or_default();
+    let routing_hint = RoutingHint::try_from(response.routing_hint).unwrap_or_default();
+    let mut decision = serde_json::json!({
+        "event_id": event_id,
+        "salience": {
+            "threat": salience.threat,
+            "salience": salience.salience,
+            "habituation": salience.habituation,
+            "vector": salience.vector,
+            "model_id": salience.model_id,
+        },
+        "from_cache": response.from_cache,
+        "matched_heuristic_id": response.matched_heuristic_id,
+        "dominant_dimension": response.dominant_dimension,
+        "composite_score": response.composite_score,
+        "routing_hint": routing_hint.as_str_name(),
+    });
+    if !response.error.is_empty() {
+        decision["error"] = response.error.clone().into();
+    }
+    if !response.experiment_variant.is_empty() {
+        decision["experiment_variant"] = response.experiment_variant.clone().into();
+    }
+    if !response.language.is_empty() {
+        decision["language"] = response.language.clone().into();
+    }
+    // Structured actions pass through as JSON, not a string to re-parse
+    if let Ok(effects @ serde_json::Value::Object(_)) = serde_json::from_str(&response.matched_effects_json) {
+        decision["effects"] = effects;
+    }
+    if let Some(run) = &response.executed_action {
+        decision["executed_action"] = serde_json::json!({
+            "action": run.action,
+            "success": run.success,
+            "output": run.output,
+        });
+    }
+    if let Some(explanation) = &response.explanation {
+        let spans: Vec<_> = explanation
+            .spans
+            .iter()
+            .map(|s| serde_json::json!({"term": s.term, "start": s.start, "end": s.end}))
+            .collect();
+        decision["explanation"] = serde_json::json!({
+            "method": explanation.method,
+            "spans": spans,
+            "condition_sentence": explanation.condition_sentence,
+            "similarity": explanation.similarity,
+            "runner_up_heuristic_id": explanation.runner_up_heuristic_id,
+            "similarity_margin": explanation.similarity_margin,
+        });
+    }
+    if !response.timings_ms.is_empty() {
+        decision["timings_ms"] = serde_json::json!(response.timings_ms);
+    }
+    decision
+}
+
+/// The decision published when evaluation itself failed.
+pub fn error_json(event_id: &str, status: &tonic::Status) -> serde_json::Value {
+    let error = format!("{:?}: {}", status.code(), status.message());
+    serde_json::json!({"event_id": event_id, "error": error})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decision_json_omits_empty_fields() {
+        let response = EvaluateSalienceResponse {
+            routing_hint: RoutingHint::Accumulate as i32,
+            matched_effects_json: "not json".to_string(),
+            ..Default::default()
+        };
+        let decision = decision_json("evt-1", &response);
+        assert_eq!(decision["event_id"], "evt-1");
+        assert_eq!(decision["routing_hint"], "ROUTING_HINT_ACCUMULATE");
+        assert_eq!(decision["salience"]["model_id"], "");
+        // Malformed effects are dropped rather than published as a string
+        for key in ["error", "experiment_variant", "language", "effects", "executed_action", "explanation", "timings_ms"] {
+            assert!(decision.get(key).is_none(), "unexpected {}", key);
+        }
+    }
+
+    #[test]
+    fn test_decision_json_reports_scoring_error() {
+        let response = EvaluateSalienceResponse {
+            error: "STORAGE_UNAVAILABLE: connection refused".to_string(),
+            routing_hint: 42,
+            matched_effects_json: r#"{"action": "notify"}"#.to_string(),
+            ..Default::default()
+        };
+        let decision = decision_json("evt-2", &response);
+        assert_eq!(decision["error"], "STORAGE_UNAVAILABLE: connection refused");
+        // An unknown hint falls back to unspecified
+        assert_eq!(decision["routing_hint"], "ROUTING_HINT_UNSPECIFIED");
+        assert_eq!(decision["effects"]["action"], "notify");
+    }
+
+    #[test]
+    fn test_error_json() {
+        let status = tonic::Status::invalid_argument("Event exceeds input limits: raw_text");
+        assert_eq!(
+            error_json("evt-3", &status),
+            serde_json::json!({
+                "event_id": "evt-3",
+                "error": "InvalidArgument: Event exceeds input limits: raw_text",
+            })
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Keyword pre-filter for heuristic matching.
+//!
+//! Many heuristics have distinctive trigger words ("creeper", "lava") that
+//! conclusively fire them. Listing those in the heuristic's effects JSON lets
+//! the scorer match on an Aho-Corasick scan of the event text and skip the
+//! embedding round trip entirely:
+//!
+//! ```text
+//! {"message": "Run!", "salience": {"threat": 0.9}, "keywords": ["creeper"]}
+//! ```
+//!
+//! Keywords match case-insensitively on word boundaries (indexed NFC-normalized
+//! and lowercased, like event text; see normalize module). Heuristics without
+//! keywords are unaffected and still go through embedding similarity.
+//!
+//! Configuration via environment variables:
+//!   SALIENCE_KEYWORD_PREFILTER: Check keywords before embedding (default: false)
+
+use std::collections::HashMap;
+
+use aho_corasick::{AhoCorasick, MatchKind};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::affect::on_word_boundaries;
+use crate::normalize::fold_key;
+use crate::{CachedHeuristic, Effects};
+
+/// Trigger keywords declared in a heuristic's effects (`"keywords": [...]`).
+pub fn heuristic_keywords(heuristic: &CachedHeuristic) -> Vec<&str> {
+    heuristic_keywords_of(&heuristic.effects)
+}
+
+/// Trigger keywords declared in `effects`.
+pub fn heuristic_keywords_of(effects: &Effects) -> Vec<&str> {
+    effects
+        .keywords
+        .iter()
+        .map(|k| k.trim())
+        .filter(|k| !k.is_empty())
+        .collect()
+}
+
+/// An automaton over the keywords of a set of heuristics.
+#[derive(Default)]
+pub struct KeywordIndex {
+    /// None if no heuristic declares keywords
+    automaton: Option<AhoCorasick>,
+    /// Heuristics owning each keyword, indexed by automaton pattern ID
+    owners: Vec<Vec<Uuid>>,
+}
+
+impl KeywordIndex {
+    pub fn build<'a>(heuristics: impl IntoIterator<Item = &'a CachedHeuristic>) -> Self {
+        // Keywords are shared case-insensitively, so key on the folded form
+        let mut patterns: Vec<String> = Vec::new();
+        let mut owners: Vec<Vec<Uuid>> = Vec::new();
+        let mut by_keyword: HashMap<String, usize> = HashMap::new();
+        for h in heuristics {
+            for keyword in heuristic_keywords(h) {
+                let keyword = fold_key(keyword);
+                let index = *by_keyword.entry(keyword.clone()).or_insert_with(|| {
+                    patterns.push(keyword);
+                    owners.push(Vec::new());
+                    patterns.len() - 1
+                });
+                owners[index].push(h.id);
+            }
+        }
+        if patterns.is_empty() {
+            return Self::default();
+        }
+        match AhoCorasick::builder()
+            .ascii_case_insensitive(true)
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(&patterns)
+        {
+            Ok(automaton) => Self { automaton: Some(automaton), owners },
+            Err(e) => {
+                warn!(error = %e, "Failed to build keyword index; keyword pre-filter disabled");
+                Self::default()
+            }
+        }
+    }
+
+    /// Distinct keywords indexed.
+    pub fn len(&self) -> usize {
+        self.owners.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.owners.is_empty()
+    }
+
+    /// Heuristics with a keyword in `text`, in order of first occurrence.
+    pub fn matches(&self, text: &str) -> Vec<Uuid> {
+        let Some(automaton) = &self.automaton else {
+            return Vec::new();
+        };
+        let mut ids: Vec<Uuid> = Vec::new();
+        for m in automaton.find_iter(text) {
+            if !on_word_boundaries(text, m.start(), m.end()) {
+                continue;
+            }
+            for id in &self.owners[m.pattern().as_usize()] {
+                if !ids.contains(id) {
+                    ids.push(*id);
+                }
+            }
+        }
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Condition;
+
+    fn heuristic(effects: serde_json::Value) -> CachedHeuristic {
+        CachedHeuristic {
+            id: Uuid::new_v4(),
+            name: "h".to_string(),
+            condition: Condition::text("h"),
+            effects: effects.into(),
+            confidence: 0.9,
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: Default::default(),
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_keyword_index_matches_whole_words() {
+        let creeper = heuristic(serde_json::json!({"keywords": ["creeper", "hissing sound"]}));
+        let lava = heuristic(serde_json::json!({"keywords": ["Lava", "", 3]}));
+        let plain = heuristic(serde_json::json!({"message": "no keywords"}));
+        assert_eq!(heuristic_keywords(&lava), vec!["Lava"]);
+
+        let index = KeywordIndex::build([&creeper, &lava, &plain]);
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.matches("A CREEPER! Also lava."), vec![creeper.id, lava.id]);
+        assert_eq!(index.matches("a hissing sound nearby"), vec![creeper.id]);
+        assert!(index.matches("creepers everywhere").is_empty());
+        assert!(index.matches("nothing here").is_empty());
+
+        assert!(KeywordIndex::build([&plain]).matches("creeper").is_empty());
+    }
+}
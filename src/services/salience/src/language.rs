@@ -0,0 +1,167 @@
+//! Language detection and per-language scoring profiles.
+//!
+//! Events arrive in several languages, and neither the embedding model nor
+//! the lexical scorers behave the same across them (similarities run lower
+//! for languages the model saw less of; word overlap needs whitespace-split
+//! words). Each event's text is tagged with its detected language (ISO 639-3,
+//! e.g. "eng", "deu"; via whatlang, behind the `language` feature) and the
+//! tag is returned as `language` in EvaluateSalienceResponse. GetCacheStats
+//! reports how many events were seen per language ("und" = undetermined).
+//!
+//! A language can have its own profile: a scorer (as in SALIENCE_SCORER)
+//! and/or default thresholds, configured as JSON in SALIENCE_LANGUAGE_PROFILES:
+//!
+//! ```text
+//! {"jpn": {"scorer": "embedding", "min_similarity": 0.6},
+//!  "deu": {"min_confidence": 0.6}}
+//! ```
+//!
+//! Profiles replace the service defaults for events in that language. An
+//! experiment variant's scorer and thresholds still take precedence, and
+//! per-request threshold overrides over both.
+//!
+//! Configuration via environment variables:
+//!   SALIENCE_LANGUAGE_DETECTION: Tag events with their language (default: false;
+//!     on whenever SALIENCE_LANGUAGE_PROFILES is set)
+//!   SALIENCE_LANGUAGE_PROFILES: Per-language profiles as JSON (default: none)
+//!   SALIENCE_LANGUAGE_MIN_CONFIDENCE: Detection confidence below which a
+//!     language is undetermined (default: 0.5)
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use serde::Deserialize;
+
+use crate::SalienceScorer;
+
+/// Tag counted for text whose language couldn't be determined.
+pub const UNDETERMINED: &str = "und";
+
+/// One language's profile as configured.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LanguageProfileConfig {
+    /// Scorer implementation (as in SALIENCE_SCORER); unset = the service's scorer
+    #[serde(default)]
+    pub scorer: Option<String>,
+    #[serde(default)]
+    pub min_similarity: Option<f32>,
+    #[serde(default)]
+    pub min_confidence: Option<f32>,
+}
+
+/// Parse SALIENCE_LANGUAGE_PROFILES (language code -> profile).
+pub fn parse_language_profiles(json: &str) -> Result<BTreeMap<String, LanguageProfileConfig>, serde_json::Error> {
+    let profiles: BTreeMap<String, LanguageProfileConfig> = serde_json::from_str(json)?;
+    Ok(profiles.into_iter().map(|(language, profile)| (language.to_ascii_lowercase(), profile)).collect())
+}
+
+/// A profile with its scorer built.
+pub struct LanguageProfile {
+    pub config: LanguageProfileConfig,
+    /// None = use the service's scorer
+    pub scorer: Option<Box<dyn SalienceScorer>>,
+}
+
+/// Tags events with their language and holds the per-language profiles.
+pub struct LanguageDetector {
+    profiles: BTreeMap<String, LanguageProfile>,
+    min_confidence: f64,
+    /// Events seen per language tag
+    counts: Mutex<BTreeMap<String, u64>>,
+}
+
+impl LanguageDetector {
+    /// Build a detector, creating a scorer for each profile that names one.
+    pub fn new(
+        profiles: BTreeMap<String, LanguageProfileConfig>,
+        min_confidence: f64,
+        mut make_scorer: impl FnMut(&str) -> Box<dyn SalienceScorer>,
+    ) -> Self {
+        let profiles = profiles
+            .into_iter()
+            .map(|(language, config)| {
+                let scorer = config.scorer.as_deref().map(&mut make_scorer);
+                (language, LanguageProfile { config, scorer })
+            })
+            .collect();
+        Self { profiles, min_confidence, counts: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// Languages with a profile.
+    pub fn profiled_languages(&self) -> Vec<&str> {
+        self.profiles.keys().map(String::as_str).collect()
+    }
+
+    /// ISO 639-3 code of `text`'s language (None = undetermined).
+    #[cfg(feature = "language")]
+    pub fn detect(&self, text: &str) -> Option<&'static str> {
+        whatlang::detect(text)
+            .filter(|info| info.confidence() >= self.min_confidence)
+            .map(|info| info.lang().code())
+    }
+
+    /// Without the `language` feature nothing is ever detected.
+    #[cfg(not(feature = "language"))]
+    pub fn detect(&self, _text: &str) -> Option<&'static str> {
+        let _ = self.min_confidence;
+        None
+    }
+
+    /// The profile for `language`, if one is configured.
+    pub fn profile(&self, language: Option<&str>) -> Option<&LanguageProfile> {
+        self.profiles.get(language?)
+    }
+
+    /// Count one evaluated event under its language tag.
+    pub fn record(&self, language: Option<&str>) {
+        let mut counts = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+        *counts.entry(language.unwrap_or(UNDETERMINED).to_string()).or_default() += 1;
+    }
+
+    /// Events seen per language tag since startup.
+    pub fn distribution(&self) -> BTreeMap<String, u64> {
+        self.counts.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector(json: &str) -> LanguageDetector {
+        LanguageDetector::new(parse_language_profiles(json).unwrap(), 0.5, |_| unreachable!("no profile names a scorer"))
+    }
+
+    #[test]
+    fn test_parse_profiles() {
+        let profiles = parse_language_profiles(r#"{"DEU": {"min_confidence": 0.6}, "jpn": {"scorer": "embedding"}}"#).unwrap();
+        assert_eq!(profiles["deu"].min_confidence, Some(0.6));
+        assert_eq!(profiles["jpn"].scorer.as_deref(), Some("embedding"));
+        assert!(parse_language_profiles("{").is_err());
+
+        let d = detector(r#"{"deu": {"min_similarity": 0.8}}"#);
+        assert_eq!(d.profile(Some("deu")).unwrap().config.min_similarity, Some(0.8));
+        assert!(d.profile(Some("eng")).is_none());
+        assert!(d.profile(None).is_none());
+    }
+
+    #[test]
+    fn test_distribution_counts_undetermined() {
+        let d = detector("{}");
+        d.record(Some("eng"));
+        d.record(Some("eng"));
+        d.record(None);
+        let distribution = d.distribution();
+        assert_eq!(distribution["eng"], 2);
+        assert_eq!(distribution[UNDETERMINED], 1);
+    }
+
+    #[cfg(feature = "language")]
+    #[test]
+    fn test_detect_language() {
+        let d = detector("{}");
+        assert_eq!(d.detect("The creeper is right behind you, run to the house now"), Some("eng"));
+        assert_eq!(d.detect("Der Creeper steht direkt hinter dir, lauf sofort zum Haus"), Some("deu"));
+        assert_eq!(d.detect(""), None);
+    }
+}
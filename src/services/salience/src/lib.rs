@@ -8,27 +8,106 @@
 //! - gRPC server for SalienceGateway service
 //! - gRPC client to Python storage backend
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::OnceLock;
 use uuid::Uuid;
 
+use config::ModelChangePolicy;
+use domain::SalienceBoost;
+use eviction_log::{EvictionReason, EvictionRecord};
+pub(crate) use gladys_salience_core::{cosine_similarity, similarity};
+
+pub mod actions;
+pub mod affect;
+pub mod arena;
+pub mod audit;
+#[cfg(feature = "nats")]
+pub mod bus;
+pub mod cache_handle;
+pub mod cache_sizing;
+pub mod calibration;
 pub mod client;
+pub mod compat;
 pub mod config;
+pub mod conflicts;
+pub mod daemon;
+pub mod degradation;
+pub mod diagnostics;
+pub mod domain;
+pub mod effectiveness;
+pub mod embedding_model;
+pub mod eviction_log;
+pub mod exemplars;
+pub mod explain;
+pub mod experiments;
+#[cfg(feature = "fault-injection")]
+pub mod faults;
+pub mod ffi;
+pub mod idempotency;
+#[cfg(any(feature = "nats", feature = "mqtt", feature = "ws"))]
+pub mod intake;
+pub mod keywords;
+pub mod language;
+pub mod limits;
+pub mod load_shed;
 pub mod logging;
+pub mod maintenance;
+pub mod metrics;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub mod normalize;
+pub mod peers;
+pub mod planes;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod postprocess;
+#[cfg(feature = "python-bindings")]
+pub mod python;
+pub mod qos;
+pub mod rate_limit;
+pub mod recording;
+pub mod refresh;
+pub mod router;
+pub mod routing;
+pub mod schema;
+pub mod seed;
 pub mod server;
+pub mod shared_cache;
+pub mod simulate;
+pub mod single_flight;
+pub mod slo;
+pub mod source_filter;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod standby;
+pub mod summary;
+pub mod version;
+pub mod warmup;
+#[cfg(feature = "word-overlap")]
+pub mod word_overlap;
+pub mod worker_pool;
+pub mod writeback;
+#[cfg(feature = "ws")]
+pub mod ws;
 /// Proto-generated types, organized by package.
 ///
 /// The module hierarchy matches the proto package hierarchy:
 /// - gladys.types -> proto::gladys::types
 /// - gladys.memory -> proto::gladys::memory
+///
+/// Messages and client stubs come from the `gladys-memory-client` crate; the
+/// service traits this crate implements are generated here against them.
 pub mod proto {
     /// Container module matching the `gladys.*` proto packages
     pub mod gladys {
         /// Shared types from types.proto (package gladys.types)
         pub mod types {
-            tonic::include_proto!("gladys.types");
+            pub use gladys_memory_client::proto::gladys::types::*;
         }
         /// Memory service from memory.proto (package gladys.memory)
         pub mod memory {
+            pub use gladys_memory_client::proto::gladys::memory::*;
             tonic::include_proto!("gladys.memory");
         }
     }
@@ -39,10 +118,40 @@ pub mod proto {
 }
 
 // Re-export types from modules
-pub use client::{ClientConfig, ClientError, StorageClient, EventBuilder, HeuristicBuilder};
-pub use config::{Config, ServerConfig, StorageConfig, SalienceConfig};
+pub use client::{BuildError, ClientConfig, ClientError, EmbeddingSource, RetryPolicy, StorageClient, EventBuilder, HeuristicBuilder};
+pub use arena::Embedding;
+pub use cache_handle::CacheHandle;
+pub use domain::{Condition, Effects, Event, Heuristic};
+pub use config::{Config, ServerConfig, StorageConfig, SalienceConfig, RefreshConfig, SimilarityMetric, VectorNormalization};
 pub use logging::{setup_logging, LogGuard, generate_trace_id, get_or_create_trace_id, TRACE_ID_HEADER};
-pub use server::{SalienceService, run_server, EmbeddingSimilarityScorer, GrpcStorageBackend};
+pub use server::{SalienceService, run_server, EmbeddingSimilarityScorer, GrpcStorageBackend, ServerOptions};
+#[cfg(feature = "word-overlap")]
+pub use word_overlap::WordOverlapScorer;
+
+/// How a match was found (reported in match explanations; see explain module).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchMethod {
+    /// Event embedding against cached condition embeddings
+    #[default]
+    Embedding,
+    /// A heuristic keyword in the event text
+    Keyword,
+    /// Condition words shared with the event text
+    WordOverlap,
+    /// Storage's semantic search (fallback on a cache miss)
+    Storage,
+}
+
+impl MatchMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Embedding => "embedding",
+            Self::Keyword => "keyword",
+            Self::WordOverlap => "word_overlap",
+            Self::Storage => "storage",
+        }
+    }
+}
 
 /// Result of scoring an event against known heuristics.
 #[derive(Debug, Clone)]
@@ -50,20 +159,176 @@ pub struct ScoredMatch {
     pub heuristic_id: String,
     pub similarity: f32,
     pub confidence: f32,
+    /// Where the heuristic came from ("user", "llm", "system"; empty if unknown)
+    pub origin: String,
     pub condition_text: String,
+    /// The effects' action message (empty if none)
     pub suggested_action: String,
-    pub salience_boost: Option<serde_json::Value>,
+    pub salience_boost: Option<SalienceBoost>,
+    /// The heuristic's full effects, including structured actions beyond the message
+    pub effects: Effects,
+    pub method: MatchMethod,
+    /// Negative exemplar already taken off `similarity` (see exemplars module)
+    pub negative: Option<exemplars::NegativeMatch>,
+}
+
+impl ScoredMatch {
+    /// A match of `heuristic` at `similarity`, with its effects already typed.
+    pub fn new(heuristic: &CachedHeuristic, similarity: f32) -> Self {
+        Self {
+            heuristic_id: heuristic.id.to_string(),
+            similarity,
+            confidence: heuristic.confidence,
+            origin: heuristic.origin.clone(),
+            condition_text: heuristic.condition.text.clone(),
+            suggested_action: heuristic.effects.message.clone().unwrap_or_default(),
+            salience_boost: heuristic.effects.salience.clone(),
+            effects: heuristic.effects.clone(),
+            method: MatchMethod::Embedding,
+            negative: None,
+        }
+    }
+
+    /// The same match, found by `method`.
+    pub fn with_method(mut self, method: MatchMethod) -> Self {
+        self.method = method;
+        self
+    }
+}
+
+/// Event-level signals a scorer computes alongside its matches.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventSignals {
+    /// Best similarity to an active goal (None if no goals or no embedding)
+    pub goal_relevance: Option<f32>,
+    /// Embedding failed; matches (if any) came from a fallback step
+    pub embedding_failed: bool,
+}
+
+/// Error type for storage backend operations.
+///
+/// Variants separate failures callers handle differently (retry later vs.
+/// give up vs. report a bug), and each has a stable machine-readable code.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum StorageError {
+    #[error("Not connected to storage service: {0}")]
+    NotConnected(String),
+    #[error("Storage request timed out: {0}")]
+    Timeout(String),
+    #[error("Storage service unavailable: {0}")]
+    Unavailable(String),
+    #[error("Storage RPC failed ({code:?}): {message}")]
+    Rpc { code: tonic::Code, message: String },
+    #[error("Storage service returned error: {0}")]
+    Storage(String),
+    #[error("Failed to decode storage response: {0}")]
+    Decode(String),
+}
+
+impl StorageError {
+    /// Stable machine-readable error code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            StorageError::NotConnected(_) => "STORAGE_NOT_CONNECTED",
+            StorageError::Timeout(_) => "STORAGE_TIMEOUT",
+            StorageError::Unavailable(_) => "STORAGE_UNAVAILABLE",
+            StorageError::Rpc { .. } => "STORAGE_RPC_FAILED",
+            StorageError::Storage(_) => "STORAGE_ERROR",
+            StorageError::Decode(_) => "STORAGE_DECODE_FAILED",
+        }
+    }
+
+    /// gRPC status code to surface to our own callers.
+    pub fn status_code(&self) -> tonic::Code {
+        match self {
+            StorageError::NotConnected(_) | StorageError::Unavailable(_) => tonic::Code::Unavailable,
+            StorageError::Timeout(_) => tonic::Code::DeadlineExceeded,
+            StorageError::Rpc { code, .. } => *code,
+            StorageError::Storage(_) => tonic::Code::Internal,
+            StorageError::Decode(_) => tonic::Code::DataLoss,
+        }
+    }
+}
+
+impl From<ClientError> for StorageError {
+    fn from(e: ClientError) -> Self {
+        match e {
+            ClientError::ConnectionFailed(e) => StorageError::NotConnected(e.to_string()),
+            ClientError::RpcFailed(status) => match status.code() {
+                tonic::Code::DeadlineExceeded => StorageError::Timeout(status.message().to_string()),
+                tonic::Code::Unavailable => StorageError::Unavailable(status.message().to_string()),
+                code => StorageError::Rpc { code, message: status.message().to_string() },
+            },
+            ClientError::StorageError(message) => StorageError::Storage(message),
+            ClientError::InvalidResponse => StorageError::Decode("invalid response from storage service".to_string()),
+        }
+    }
+}
+
+impl From<StorageError> for tonic::Status {
+    fn from(e: StorageError) -> Self {
+        tonic::Status::new(e.status_code(), format!("{}: {}", e.code(), e))
+    }
 }
 
 /// Error type for scoring operations.
 #[derive(Debug, thiserror::Error)]
 pub enum ScoringError {
     #[error("Embedding generation failed: {0}")]
-    EmbeddingError(String),
+    EmbeddingError(#[source] StorageError),
     #[error("Storage query failed: {0}")]
-    StorageError(String),
+    StorageError(#[source] StorageError),
     #[error("No matches found")]
     NoMatches,
+    /// Cache missed and the storage fallback budget is exhausted
+    #[error("Storage fallback rate limited")]
+    FallbackRateLimited,
+    #[error("Scoring worker pool unavailable: {0}")]
+    WorkerPool(#[from] worker_pool::PoolError),
+}
+
+impl ScoringError {
+    /// Stable machine-readable error code (the underlying storage code, if any).
+    pub fn code(&self) -> &'static str {
+        match self {
+            ScoringError::EmbeddingError(e) | ScoringError::StorageError(e) => e.code(),
+            ScoringError::NoMatches => "NO_MATCHES",
+            ScoringError::FallbackRateLimited => "FALLBACK_RATE_LIMITED",
+            ScoringError::WorkerPool(_) => "WORKER_POOL_UNAVAILABLE",
+        }
+    }
+
+    /// gRPC status code to surface to our own callers.
+    pub fn status_code(&self) -> tonic::Code {
+        match self {
+            ScoringError::EmbeddingError(e) | ScoringError::StorageError(e) => e.status_code(),
+            ScoringError::NoMatches => tonic::Code::NotFound,
+            ScoringError::FallbackRateLimited => tonic::Code::ResourceExhausted,
+            ScoringError::WorkerPool(_) => tonic::Code::Unavailable,
+        }
+    }
+}
+
+impl From<ScoringError> for tonic::Status {
+    fn from(e: ScoringError) -> Self {
+        tonic::Status::new(e.status_code(), format!("{}: {}", e.code(), e))
+    }
+}
+
+/// Match thresholds applied to a single scoring call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreThresholds {
+    pub min_similarity: f32,
+    pub min_confidence: f32,
+}
+
+/// Per-call scoring options, and where the call records its stage timings.
+pub struct ScoreOptions<'a> {
+    /// Thresholds for this call instead of the scorer's defaults
+    pub thresholds: ScoreThresholds,
+    pub trace_id: Option<&'a str>,
+    /// Per-stage latency (e.g., "embedding", "cache_lookup", "storage_fallback", "cache_warm")
+    pub timings: &'a mut metrics::StageTimings,
 }
 
 /// Interface for salience scoring algorithms.
@@ -79,8 +344,61 @@ pub trait SalienceScorer: Send + Sync {
         trace_id: Option<&str>,
     ) -> Result<Vec<ScoredMatch>, ScoringError>;
 
+    /// Score an event with per-call options, also returning event-level
+    /// signals (e.g., goal relevance).
+    ///
+    /// Scorers without tunable thresholds, internal stages or signals can rely
+    /// on the default, which ignores the thresholds, records no timings and
+    /// reports no signals.
+    async fn score_with(
+        &self,
+        event_text: &str,
+        source: &str,
+        options: ScoreOptions<'_>,
+    ) -> Result<(Vec<ScoredMatch>, EventSignals), ScoringError> {
+        let matches = self.score(event_text, source, options.trace_id).await?;
+        Ok((matches, EventSignals::default()))
+    }
+
     /// Return scorer configuration for logging.
     fn config(&self) -> serde_json::Value;
+
+    /// The storage fallback's negative cache, for GetCacheStats. Scorers
+    /// without one report zeros.
+    fn fallback_cache_stats(&self) -> ReadThroughStats {
+        ReadThroughStats::default()
+    }
+}
+
+/// Boxed scorers (e.g., the `Box<dyn SalienceScorer>` picked from config),
+/// so generic code accepts them alongside concrete scorer types.
+#[tonic::async_trait]
+impl<T: SalienceScorer + ?Sized> SalienceScorer for Box<T> {
+    async fn score(
+        &self,
+        event_text: &str,
+        source: &str,
+        trace_id: Option<&str>,
+    ) -> Result<Vec<ScoredMatch>, ScoringError> {
+        (**self).score(event_text, source, trace_id).await
+    }
+
+    async fn score_with(
+        &self,
+        event_text: &str,
+        source: &str,
+        options: ScoreOptions<'_>,
+    ) -> Result<(Vec<ScoredMatch>, EventSignals), ScoringError> {
+        (**self).score_with(event_text, source, options).await
+    }
+
+    fn config(&self) -> serde_json::Value {
+        (**self).config()
+    }
+
+    fn fallback_cache_stats(&self) -> ReadThroughStats {
+        (**self).fallback_cache_stats()
+    }
 }
 
 /// Abstraction for the storage backend to enable unit testing.
@@ -93,13 +411,219 @@ pub trait StorageBackend: Send + Sync {
         limit: i32,
         source_filter: Option<&str>,
         trace_id: Option<&str>,
-    ) -> Result<Vec<CachedHeuristic>, String>;
+    ) -> Result<Vec<CachedHeuristic>, StorageError>;
+
+    async fn generate_embedding(
+        &self,
+        text: &str,
+        trace_id: Option<&str>,
+    ) -> Result<Vec<f32>, StorageError>;
+
+    /// Version of the model behind the most recent embedding (None = unknown).
+    ///
+    /// Backends that can't tell keep the default (see embedding_model module).
+    fn embedding_model(&self) -> Option<String> {
+        None
+    }
+
+    /// Conditional fetch: heuristics updated after `updated_since_ms` (0 = all).
+    ///
+    /// Used by the background refresh loop. Backends without a change feed keep the default.
+    async fn query_changed_heuristics(
+        &self,
+        _updated_since_ms: i64,
+        _limit: i32,
+        _trace_id: Option<&str>,
+    ) -> Result<HeuristicChanges, StorageError> {
+        Err(StorageError::Rpc {
+            code: tonic::Code::Unimplemented,
+            message: "backend does not support conditional heuristic fetches".to_string(),
+        })
+    }
+
+    /// Persist a heuristic (insert or update by ID).
+    ///
+    /// Used when seeding; read-only backends keep the default.
+    async fn store_heuristic(
+        &self,
+        _heuristic: &CachedHeuristic,
+        _trace_id: Option<&str>,
+    ) -> Result<(), StorageError> {
+        Err(StorageError::Rpc {
+            code: tonic::Code::Unimplemented,
+            message: "backend does not support storing heuristics".to_string(),
+        })
+    }
+
+    /// Record salience decisions on stored events; returns how many were updated.
+    ///
+    /// Used by decision write-back; read-only backends keep the default.
+    async fn update_event_salience(
+        &self,
+        _updates: Vec<proto::EventSalienceUpdate>,
+        _trace_id: Option<&str>,
+    ) -> Result<usize, StorageError> {
+        Err(StorageError::Rpc {
+            code: tonic::Code::Unimplemented,
+            message: "backend does not support event salience updates".to_string(),
+        })
+    }
+
+    /// Recorded fire outcomes for these heuristics (IDs storage doesn't know are left out).
+    ///
+    /// Used by the effectiveness report; backends without feedback keep the default.
+    async fn query_heuristic_feedback(
+        &self,
+        _ids: &[Uuid],
+        _trace_id: Option<&str>,
+    ) -> Result<HashMap<Uuid, HeuristicFeedback>, StorageError> {
+        Err(StorageError::Rpc {
+            code: tonic::Code::Unimplemented,
+            message: "backend does not support heuristic feedback".to_string(),
+        })
+    }
+}
+
+/// Result of a conditional heuristic fetch.
+#[derive(Debug, Clone, Default)]
+pub struct HeuristicChanges {
+    /// Heuristics created or updated since the requested timestamp
+    pub heuristics: Vec<CachedHeuristic>,
+    /// Heuristics deleted since the requested timestamp
+    pub deleted_ids: Vec<Uuid>,
+    /// Latest `updated_at_ms` seen; the next fetch's `updated_since_ms`
+    pub latest_updated_ms: i64,
+}
+
+/// Fire outcomes storage has recorded for a heuristic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HeuristicFeedback {
+    pub fire_count: u32,
+    pub success_count: u32,
+}
+
+/// Shared backends (e.g., one `GrpcStorageBackend` used by both scorer and service).
+#[tonic::async_trait]
+impl<T: StorageBackend + ?Sized> StorageBackend for std::sync::Arc<T> {
+    async fn query_matching_heuristics(
+        &self,
+        event_text: &str,
+        min_confidence: f32,
+        limit: i32,
+        source_filter: Option<&str>,
+        trace_id: Option<&str>,
+    ) -> Result<Vec<CachedHeuristic>, StorageError> {
+        (**self)
+            .query_matching_heuristics(event_text, min_confidence, limit, source_filter, trace_id)
+            .await
+    }
 
     async fn generate_embedding(
         &self,
         text: &str,
         trace_id: Option<&str>,
-    ) -> Result<Vec<f32>, String>;
+    ) -> Result<Vec<f32>, StorageError> {
+        (**self).generate_embedding(text, trace_id).await
+    }
+
+    fn embedding_model(&self) -> Option<String> {
+        (**self).embedding_model()
+    }
+
+    async fn query_changed_heuristics(
+        &self,
+        updated_since_ms: i64,
+        limit: i32,
+        trace_id: Option<&str>,
+    ) -> Result<HeuristicChanges, StorageError> {
+        (**self).query_changed_heuristics(updated_since_ms, limit, trace_id).await
+    }
+
+    async fn store_heuristic(
+        &self,
+        heuristic: &CachedHeuristic,
+        trace_id: Option<&str>,
+    ) -> Result<(), StorageError> {
+        (**self).store_heuristic(heuristic, trace_id).await
+    }
+
+    async fn update_event_salience(
+        &self,
+        updates: Vec<proto::EventSalienceUpdate>,
+        trace_id: Option<&str>,
+    ) -> Result<usize, StorageError> {
+        (**self).update_event_salience(updates, trace_id).await
+    }
+
+    async fn query_heuristic_feedback(
+        &self,
+        ids: &[Uuid],
+        trace_id: Option<&str>,
+    ) -> Result<HashMap<Uuid, HeuristicFeedback>, StorageError> {
+        (**self).query_heuristic_feedback(ids, trace_id).await
+    }
+}
+
+/// Boxed backends (e.g., `Box<dyn StorageBackend>`), for generic scorers.
+#[tonic::async_trait]
+impl<T: StorageBackend + ?Sized> StorageBackend for Box<T> {
+    async fn query_matching_heuristics(
+        &self,
+        event_text: &str,
+        min_confidence: f32,
+        limit: i32,
+        source_filter: Option<&str>,
+        trace_id: Option<&str>,
+    ) -> Result<Vec<CachedHeuristic>, StorageError> {
+        (**self)
+            .query_matching_heuristics(event_text, min_confidence, limit, source_filter, trace_id)
+            .await
+    }
+
+    async fn generate_embedding(
+        &self,
+        text: &str,
+        trace_id: Option<&str>,
+    ) -> Result<Vec<f32>, StorageError> {
+        (**self).generate_embedding(text, trace_id).await
+    }
+
+    fn embedding_model(&self) -> Option<String> {
+        (**self).embedding_model()
+    }
+
+    async fn query_changed_heuristics(
+        &self,
+        updated_since_ms: i64,
+        limit: i32,
+        trace_id: Option<&str>,
+    ) -> Result<HeuristicChanges, StorageError> {
+        (**self).query_changed_heuristics(updated_since_ms, limit, trace_id).await
+    }
+
+    async fn store_heuristic(
+        &self,
+        heuristic: &CachedHeuristic,
+        trace_id: Option<&str>,
+    ) -> Result<(), StorageError> {
+        (**self).store_heuristic(heuristic, trace_id).await
+    }
+
+    async fn update_event_salience(
+        &self,
+        updates: Vec<proto::EventSalienceUpdate>,
+        trace_id: Option<&str>,
+    ) -> Result<usize, StorageError> {
+        (**self).update_event_salience(updates, trace_id).await
+    }
+
+    async fn query_heuristic_feedback(
+        &self,
+        ids: &[Uuid],
+        trace_id: Option<&str>,
+    ) -> Result<HashMap<Uuid, HeuristicFeedback>, StorageError> {
+        (**self).query_heuristic_feedback(ids, trace_id).await
+    }
 }
 
 // Note: CacheConfig, MemoryCache, CachedEvent, CachedHeuristic, CacheStats are already
@@ -117,6 +641,92 @@ pub struct MemoryCache {
     total_hits: u64,
     /// Statistics: total misses (not found in cache, requires storage query)
     total_misses: u64,
+    /// Recent matches for hysteresis: text hash -> (heuristic ID, matched at ms)
+    recent_matches: HashMap<u64, (Uuid, i64)>,
+    /// Dedup index: source + text hash -> (first event ID, first seen at ms)
+    events_by_text: HashMap<u64, (String, i64)>,
+    /// Cache-match similarity per heuristic: (sum, matches)
+    match_similarity: HashMap<Uuid, (f64, u64)>,
+    /// Churn tracking: heuristics evicted by LRU -> evicted at ms
+    evicted_heuristics: HashMap<Uuid, i64>,
+    /// Churn tracking: text hashes of events evicted at capacity -> evicted at ms
+    evicted_event_texts: HashMap<u64, i64>,
+    /// Statistics: evicted entries cached again within the churn window
+    refetches: RefetchCounts,
+    /// Statistics: removals by reason
+    evictions: EvictionCounts,
+    /// Heuristics deleted by notification -> deleted at ms (see `tombstone_heuristic`)
+    tombstones: HashMap<Uuid, i64>,
+    /// Statistics: inserts rejected because the heuristic was tombstoned
+    suppressed_reinserts: u64,
+    /// Recent heuristic evictions and why (see eviction_log module)
+    eviction_log: eviction_log::EvictionLog,
+    /// The Executive's current goals (replaced wholesale by SetActiveGoals)
+    active_goals: Vec<ActiveGoal>,
+    /// Heuristic groups switched off by DisableGroup (kept across flushes)
+    disabled_groups: HashSet<String>,
+    /// Keyword automaton over cached heuristics, built on first use and
+    /// reset whenever the heuristic set changes
+    keyword_index: OnceLock<keywords::KeywordIndex>,
+    /// Model version cached embeddings come from (None = not known yet; see
+    /// embedding_model module)
+    embedding_model: Option<String>,
+}
+
+/// Selects cached heuristics for a selective flush; every set field must match.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeuristicFilter {
+    pub group: Option<String>,
+    pub source: Option<String>,
+    pub origin: Option<String>,
+    /// Cached at least this long ago (0 = any age)
+    pub min_age_ms: i64,
+}
+
+impl HeuristicFilter {
+    /// No fields set: selects everything.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn matches(&self, h: &CachedHeuristic, now_ms: i64) -> bool {
+        self.group.as_ref().is_none_or(|g| h.effects.group.as_ref() == Some(g))
+            && self.source.as_ref().is_none_or(|s| h.source == *s)
+            && self.origin.as_ref().is_none_or(|o| h.origin == *o)
+            && now_ms - h.cached_at_ms >= self.min_age_ms
+    }
+}
+
+/// Cache bookkeeping for one scored event, applied under one lock by
+/// `MemoryCache::apply_scoring_outcome`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScoringOutcome {
+    /// Heuristics to touch and the similarity each matched at
+    pub matched: Vec<(Uuid, f32)>,
+    /// Matched from the cache (a hit) rather than storage or nothing (a
+    /// miss); `None` leaves the hit/miss counters alone
+    pub hit: Option<bool>,
+    /// Heuristics whose cooldown starts, and until when (Unix ms)
+    pub cooldowns: Vec<(Uuid, i64)>,
+    /// Event text hash and the heuristic it matched (for hysteresis)
+    pub text_match: Option<(u64, Uuid)>,
+    /// The event, to check against recently seen text (see `check_duplicate`)
+    pub seen: Option<SeenEvent>,
+}
+
+/// An evaluated event, as the dedup index sees it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SeenEvent {
+    pub event_id: String,
+    pub source: String,
+    pub raw_text: String,
+}
+
+/// A goal the Executive is pursuing, embedded for goal-relevance scoring.
+#[derive(Debug, Clone)]
+pub struct ActiveGoal {
+    pub text: String,
+    pub embedding: Vec<f32>,
 }
 
 /// Cached event in L0
@@ -125,8 +735,11 @@ pub struct CachedEvent {
     pub timestamp_ms: i64,
     pub source: String,
     pub raw_text: String,
-    pub embedding: Vec<f32>,
+    pub embedding: Embedding,
+    /// Times returned by a similarity check (`find_similar`, `is_novel`)
     pub access_count: u32,
+    /// Last such access (Unix ms, 0 = never)
+    pub last_accessed_ms: i64,
 }
 
 /// Cached heuristic for fast lookup (with LRU tracking)
@@ -134,11 +747,15 @@ pub struct CachedEvent {
 pub struct CachedHeuristic {
     pub id: Uuid,
     pub name: String,
-    pub condition: serde_json::Value,
-    pub action: serde_json::Value,
+    pub condition: Condition,
+    pub effects: Effects,
     pub confidence: f32,
+    /// Where the heuristic came from ("user", "llm", "system"; empty if unknown)
+    pub origin: String,
+    /// Event source this heuristic is scoped to (e.g., "game-sensor"; empty = any source)
+    pub source: String,
     /// Condition embedding for local cosine similarity matching (384-dim f32)
-    pub condition_embedding: Vec<f32>,
+    pub condition_embedding: Embedding,
     /// Last accessed time for LRU eviction
     pub last_accessed_ms: i64,
     /// Time when this heuristic was cached (for TTL-based invalidation)
@@ -147,6 +764,57 @@ pub struct CachedHeuristic {
     pub hit_count: u64,
     /// Last time this heuristic was matched
     pub last_hit_ms: i64,
+    /// Until when a match is held back by the heuristic's cooldown (0 = not cooling down)
+    pub cooldown_until_ms: i64,
+    /// Version of the model that produced condition_embedding (empty = unknown)
+    pub embedding_model: String,
+}
+
+impl CachedHeuristic {
+    /// Whether this heuristic applies to events from `source_filter`.
+    ///
+    /// Scoped heuristics only match their own source; unscoped ones (and
+    /// lookups without a filter) match everything.
+    pub fn matches_source(&self, source_filter: Option<&str>) -> bool {
+        self.source.is_empty() || source_filter.is_none_or(|source| self.source == source)
+    }
+
+    /// The storage-owned part of this entry.
+    pub fn to_heuristic(&self) -> Heuristic {
+        Heuristic {
+            id: self.id,
+            name: self.name.clone(),
+            condition: self.condition.clone(),
+            effects: self.effects.clone(),
+            confidence: self.confidence,
+            origin: self.origin.clone(),
+            source: self.source.clone(),
+            condition_embedding: self.condition_embedding.to_vec(),
+            embedding_model: self.embedding_model.clone(),
+        }
+    }
+}
+
+/// A fresh cache entry (no hits, not yet timestamped).
+impl From<Heuristic> for CachedHeuristic {
+    fn from(h: Heuristic) -> Self {
+        Self {
+            id: h.id,
+            name: h.name,
+            condition: h.condition,
+            effects: h.effects,
+            confidence: h.confidence,
+            origin: h.origin,
+            source: h.source,
+            condition_embedding: h.condition_embedding.into(),
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: h.embedding_model,
+        }
+    }
 }
 
 // Re-export CacheConfig from config module
@@ -154,33 +822,52 @@ pub use config::CacheConfig;
 
 impl MemoryCache {
     pub fn new(config: CacheConfig) -> Self {
+        let eviction_log = eviction_log::EvictionLog::new(config.eviction_log_size);
         Self {
             events_by_id: HashMap::new(),
             heuristics: HashMap::new(),
             config,
             total_hits: 0,
             total_misses: 0,
+            recent_matches: HashMap::new(),
+            events_by_text: HashMap::new(),
+            match_similarity: HashMap::new(),
+            evicted_heuristics: HashMap::new(),
+            evicted_event_texts: HashMap::new(),
+            refetches: RefetchCounts::default(),
+            evictions: EvictionCounts::default(),
+            tombstones: HashMap::new(),
+            suppressed_reinserts: 0,
+            eviction_log,
+            active_goals: Vec::new(),
+            disabled_groups: HashSet::new(),
+            keyword_index: OnceLock::new(),
+            embedding_model: None,
         }
     }
 
-    /// Check if an event is novel (not similar to anything in cache)
-    pub fn is_novel(&self, embedding: &[f32]) -> bool {
-        for event in self.events_by_id.values() {
-            let similarity = cosine_similarity(embedding, &event.embedding);
-            if similarity >= self.config.novelty_threshold {
-                return false; // Found similar event, not novel
-            }
+    /// Check if an event is novel (not similar to anything in cache).
+    /// The similar event found, if any, counts as accessed.
+    pub fn is_novel(&mut self, embedding: &[f32]) -> bool {
+        let threshold = self.config.novelty_threshold;
+        let similar = self
+            .events_by_id
+            .values()
+            .find(|event| similarity(self.config.similarity_metric, embedding, &event.embedding) >= threshold)
+            .map(|event| event.id);
+        if let Some(id) = similar {
+            self.record_event_access(&id);
         }
-        true // No similar events found
+        similar.is_none()
     }
 
     /// Find the most similar event in cache.
-    /// Returns (event_id, similarity) if found above threshold.
-    pub fn find_similar(&self, embedding: &[f32], threshold: f32) -> Option<(Uuid, f32)> {
+    /// Returns (event_id, similarity) if found above threshold; that event counts as accessed.
+    pub fn find_similar(&mut self, embedding: &[f32], threshold: f32) -> Option<(Uuid, f32)> {
         let mut best: Option<(Uuid, f32)> = None;
 
         for event in self.events_by_id.values() {
-            let similarity = cosine_similarity(embedding, &event.embedding);
+            let similarity = similarity(self.config.similarity_metric, embedding, &event.embedding);
             if similarity >= threshold {
                 match &best {
                     None => best = Some((event.id, similarity)),
@@ -192,27 +879,42 @@ impl MemoryCache {
             }
         }
 
+        if let Some((id, _)) = best {
+            self.record_event_access(&id);
+        }
         best
     }
 
+    /// Count a reference to a cached event (see `CacheConfig::event_access_weight_ms`).
+    fn record_event_access(&mut self, id: &Uuid) {
+        if let Some(event) = self.events_by_id.get_mut(id) {
+            event.access_count = event.access_count.saturating_add(1);
+            event.last_accessed_ms = current_time_ms();
+        }
+    }
+
+    /// When an event would be "as old as" for eviction: its timestamp, or with
+    /// an access weight, its last access pushed later by `weight` per access.
+    fn event_retention_ms(&self, event: &CachedEvent) -> i64 {
+        let weight = self.config.event_access_weight_ms;
+        if weight <= 0 {
+            return event.timestamp_ms;
+        }
+        event.timestamp_ms.max(event.last_accessed_ms).saturating_add(weight.saturating_mul(event.access_count as i64))
+    }
+
     /// Add an event to the cache.
     /// Evicts oldest events if cache is full.
     pub fn add_event(&mut self, event: CachedEvent) {
         // Evict if at capacity
-        while self.events_by_id.len() >= self.config.max_events {
-            // Find oldest event (lowest timestamp)
-            if let Some(oldest_id) = self
-                .events_by_id
-                .values()
-                .min_by_key(|e| e.timestamp_ms)
-                .map(|e| e.id)
-            {
-                self.events_by_id.remove(&oldest_id);
-            } else {
-                break;
-            }
-        }
+        self.evict_events_to(self.config.max_events.saturating_sub(1));
 
+        let now = current_time_ms();
+        let hash = text_hash(&event.raw_text);
+        if self.evicted_event_texts.remove(&hash).is_some_and(|at| now - at < self.churn_window_ms()) {
+            self.refetches.events += 1;
+        }
+        self.index_event_text(&event.id.to_string(), &event.source, &event.raw_text, event.timestamp_ms);
         self.events_by_id.insert(event.id, event);
     }
 
@@ -226,6 +928,67 @@ impl MemoryCache {
         self.events_by_id.get_mut(id)
     }
 
+    /// Iterate over all cached events (unordered).
+    pub fn events(&self) -> impl Iterator<Item = &CachedEvent> {
+        self.events_by_id.values()
+    }
+
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// Compare two embeddings with the cache's configured similarity metric.
+    pub fn compare(&self, a: &[f32], b: &[f32]) -> f32 {
+        similarity(self.config.similarity_metric, a, b)
+    }
+
+    /// Replace the active goal set.
+    pub fn set_active_goals(&mut self, goals: Vec<ActiveGoal>) {
+        self.active_goals = goals;
+    }
+
+    pub fn active_goals(&self) -> &[ActiveGoal] {
+        &self.active_goals
+    }
+
+    /// Switch a heuristic group on or off for scoring (nothing is evicted).
+    /// Returns whether its state changed.
+    pub fn set_group_enabled(&mut self, group: &str, enabled: bool) -> bool {
+        if enabled {
+            self.disabled_groups.remove(group)
+        } else {
+            self.disabled_groups.insert(group.to_string())
+        }
+    }
+
+    /// Whether heuristics in `group` take part in scoring (ungrouped ones always do).
+    pub fn is_group_enabled(&self, group: Option<&str>) -> bool {
+        group.is_none_or(|g| !self.disabled_groups.contains(g))
+    }
+
+    /// Every group that is cached or disabled -> (enabled, cached heuristics).
+    pub fn groups(&self) -> BTreeMap<String, (bool, usize)> {
+        let mut groups: BTreeMap<String, (bool, usize)> =
+            self.disabled_groups.iter().map(|g| (g.clone(), (false, 0))).collect();
+        for group in self.heuristics.values().filter_map(|h| h.effects.group.as_ref()) {
+            groups.entry(group.clone()).or_insert((true, 0)).1 += 1;
+        }
+        groups
+    }
+
+    /// Highest similarity between `embedding` and any active goal, clamped to [0, 1].
+    pub fn goal_relevance(&self, embedding: &[f32]) -> Option<f32> {
+        if embedding.is_empty() {
+            return None;
+        }
+        self.active_goals
+            .iter()
+            .filter(|g| !g.embedding.is_empty())
+            .map(|g| self.compare(embedding, &g.embedding))
+            .reduce(f32::max)
+            .map(|sim| sim.clamp(0.0, 1.0))
+    }
+
     /// Record a cache hit.
     pub fn record_hit(&mut self) {
         self.total_hits += 1;
@@ -238,8 +1001,17 @@ impl MemoryCache {
 
     /// Add a heuristic to the cache with LRU eviction.
     /// Evicts least-recently-accessed heuristics if cache is full.
-    pub fn add_heuristic(&mut self, mut heuristic: CachedHeuristic) {
+    ///
+    /// Returns false (caching nothing) if the heuristic was deleted within the
+    /// tombstone window, e.g., a storage fallback queued before the delete.
+    pub fn add_heuristic(&mut self, mut heuristic: CachedHeuristic) -> bool {
         let now = current_time_ms();
+        if self.is_tombstoned(&heuristic.id, now) {
+            self.suppressed_reinserts += 1;
+            tracing::debug!(heuristic_id = %heuristic.id, "Deleted heuristic not cached again");
+            return false;
+        }
+        self.drop_foreign_embedding(&mut heuristic);
 
         // Set last_accessed to now if not set
         if heuristic.last_accessed_ms == 0 {
@@ -252,21 +1024,163 @@ impl MemoryCache {
         }
 
         // Evict if at capacity
-        while self.heuristics.len() >= self.config.max_heuristics {
-            // Find least recently accessed heuristic
-            if let Some(oldest_id) = self
-                .heuristics
-                .values()
-                .min_by_key(|h| h.last_accessed_ms)
-                .map(|h| h.id)
-            {
-                self.heuristics.remove(&oldest_id);
-            } else {
+        self.evict_heuristics_to(self.config.max_heuristics.saturating_sub(1), EvictionReason::Lru);
+        if self.evicted_heuristics.remove(&heuristic.id).is_some_and(|at| now - at < self.churn_window_ms()) {
+            self.refetches.heuristics += 1;
+        }
+
+        lint_heuristic(&heuristic);
+        self.heuristics.insert(heuristic.id, heuristic);
+        self.keyword_index.take();
+        true
+    }
+
+    /// Remove a heuristic deleted in storage and keep it out of the cache for
+    /// `tombstone_ttl_ms`. Returns whether it was cached.
+    pub fn tombstone_heuristic(&mut self, id: &Uuid) -> bool {
+        let now = current_time_ms();
+        let ttl = self.config.tombstone_ttl_ms;
+        if ttl > 0 {
+            // Bound memory: drop expired tombstones once they outnumber the cache
+            if self.tombstones.len() >= self.config.max_heuristics.max(1) {
+                self.tombstones.retain(|_, deleted_at| now - *deleted_at < ttl);
+            }
+            self.tombstones.insert(*id, now);
+        }
+        self.remove_heuristic_because(id, EvictionReason::Notify)
+    }
+
+    /// Lift a tombstone (the heuristic was created again).
+    pub fn clear_tombstone(&mut self, id: &Uuid) {
+        self.tombstones.remove(id);
+    }
+
+    fn is_tombstoned(&self, id: &Uuid, now: i64) -> bool {
+        self.tombstones.get(id).is_some_and(|deleted_at| now - deleted_at < self.config.tombstone_ttl_ms)
+    }
+
+    /// Drop least recently accessed heuristics until at most `capacity` remain.
+    fn evict_heuristics_to(&mut self, capacity: usize, reason: EvictionReason) {
+        let now = current_time_ms();
+        while self.heuristics.len() > capacity {
+            let Some(oldest_id) = self.heuristics.values().min_by_key(|h| h.last_accessed_ms).map(|h| h.id) else {
                 break;
+            };
+            if let Some(evicted) = self.heuristics.remove(&oldest_id) {
+                self.eviction_log.record(&evicted, reason);
+            }
+            match reason {
+                EvictionReason::Resize => self.evictions.heuristic_resize += 1,
+                _ => self.evictions.heuristic_lru += 1,
             }
+            self.keyword_index.take();
+
+            // Bound memory: forget evictions older than the churn window
+            let window = self.churn_window_ms();
+            if self.evicted_heuristics.len() >= self.config.max_heuristics.max(1) * 2 {
+                self.evicted_heuristics.retain(|_, at| now - *at < window);
+                if self.evicted_heuristics.len() >= self.config.max_heuristics.max(1) * 2 {
+                    self.evicted_heuristics.clear();
+                }
+            }
+            self.evicted_heuristics.insert(oldest_id, now);
         }
+    }
 
-        self.heuristics.insert(heuristic.id, heuristic);
+    /// Drop the oldest events (by `event_retention_ms`) until at most `capacity` remain.
+    fn evict_events_to(&mut self, capacity: usize) {
+        let now = current_time_ms();
+        while self.events_by_id.len() > capacity {
+            let Some(oldest_id) = self.events_by_id.values().min_by_key(|e| self.event_retention_ms(e)).map(|e| e.id) else {
+                break;
+            };
+            let Some(evicted) = self.events_by_id.remove(&oldest_id) else {
+                break;
+            };
+            self.evictions.event_capacity += 1;
+
+            let window = self.churn_window_ms();
+            if self.evicted_event_texts.len() >= self.config.max_events.max(1) * 2 {
+                self.evicted_event_texts.retain(|_, at| now - *at < window);
+                if self.evicted_event_texts.len() >= self.config.max_events.max(1) * 2 {
+                    self.evicted_event_texts.clear();
+                }
+            }
+            self.evicted_event_texts.insert(text_hash(&evicted.raw_text), now);
+        }
+    }
+
+    /// How soon an evicted entry must come back to count as churn: the
+    /// heuristic TTL (entries are expected to be re-fetched after it anyway).
+    fn churn_window_ms(&self) -> i64 {
+        self.config.heuristic_ttl_ms
+    }
+
+    /// Change capacities (adaptive sizing), evicting down to a smaller one.
+    pub fn set_capacity(&mut self, max_heuristics: usize, max_events: usize) {
+        self.config.max_heuristics = max_heuristics.max(1);
+        self.config.max_events = max_events.max(1);
+        self.evict_heuristics_to(self.config.max_heuristics, EvictionReason::Resize);
+        self.evict_events_to(self.config.max_events);
+    }
+
+    /// How cached embeddings are laid out (see arena module).
+    pub fn embedding_layout(&self) -> arena::EmbeddingLayout {
+        let heuristics = self.heuristics.values().map(|h| &h.condition_embedding);
+        arena::EmbeddingLayout::of(heuristics.chain(self.events_by_id.values().map(|e| &e.embedding)))
+    }
+
+    /// Pack every cached embedding into fresh slabs; returns the slab count.
+    pub fn compact_embeddings(&mut self) -> usize {
+        let heuristics = self.heuristics.values_mut().map(|h| &mut h.condition_embedding);
+        arena::pack(heuristics.chain(self.events_by_id.values_mut().map(|e| &mut e.embedding)))
+    }
+
+    /// Insert a heuristic, or update an existing entry in place.
+    ///
+    /// Storage-owned fields (name, condition, effects, confidence, origin, source, embedding)
+    /// are replaced; cache-local statistics (hit_count, last_hit, LRU position)
+    /// are preserved. An empty embedding, or one from another model version,
+    /// keeps the cached one.
+    pub fn merge_heuristic(&mut self, mut heuristic: CachedHeuristic) {
+        self.drop_foreign_embedding(&mut heuristic);
+        match self.heuristics.get_mut(&heuristic.id) {
+            Some(existing) => {
+                lint_heuristic(&heuristic);
+                existing.name = heuristic.name;
+                exemplars::keep_exemplar_embeddings(&mut heuristic.condition, &existing.condition);
+                existing.condition = heuristic.condition;
+                existing.effects = heuristic.effects;
+                existing.confidence = heuristic.confidence;
+                existing.origin = heuristic.origin;
+                existing.source = heuristic.source;
+                if !heuristic.condition_embedding.is_empty() {
+                    existing.condition_embedding = heuristic.condition_embedding;
+                    existing.embedding_model = heuristic.embedding_model;
+                }
+                existing.cached_at_ms = current_time_ms();
+                self.keyword_index.take();
+            }
+            None => {
+                self.add_heuristic(heuristic);
+            }
+        }
+    }
+
+    /// Apply a delta from storage: merge updated heuristics, drop deleted ones.
+    ///
+    /// Returns (merged, removed).
+    pub fn apply_heuristic_changes(&mut self, changes: HeuristicChanges) -> (usize, usize) {
+        let merged = changes.heuristics.len();
+        for h in changes.heuristics {
+            self.merge_heuristic(h);
+        }
+        let removed = changes
+            .deleted_ids
+            .iter()
+            .filter(|id| self.remove_heuristic_because(id, EvictionReason::Refresh))
+            .count();
+        (merged, removed)
     }
 
     /// Touch a heuristic (update last_accessed for LRU and record a hit).
@@ -279,6 +1193,64 @@ impl MemoryCache {
         }
     }
 
+    /// Apply an event's hit/miss counter, touches, match similarities,
+    /// cooldowns, text match and dedup check in one call, so callers take the
+    /// write lock once per event. Returns the event this one duplicates, if any.
+    pub fn apply_scoring_outcome(&mut self, outcome: &ScoringOutcome) -> Option<String> {
+        match outcome.hit {
+            Some(true) => self.record_hit(),
+            Some(false) => self.record_miss(),
+            None => {}
+        }
+        for (id, similarity) in &outcome.matched {
+            self.touch_heuristic(id);
+            if outcome.hit == Some(true) {
+                self.record_match_similarity(id, *similarity);
+            }
+        }
+        for (id, until_ms) in &outcome.cooldowns {
+            self.start_cooldown(id, *until_ms);
+        }
+        if let Some((text_hash, id)) = outcome.text_match {
+            self.record_text_match(text_hash, id);
+        }
+        outcome
+            .seen
+            .as_ref()
+            .and_then(|event| self.check_duplicate(&event.event_id, &event.source, &event.raw_text))
+    }
+
+    /// Hold a heuristic back until `until_ms` (see `SalienceConfig::heuristic_cooldown_ms`).
+    pub fn start_cooldown(&mut self, id: &Uuid, until_ms: i64) {
+        if let Some(h) = self.heuristics.get_mut(id) {
+            h.cooldown_until_ms = until_ms;
+        }
+    }
+
+    /// Record the similarity of a cache match (for the effectiveness report).
+    pub fn record_match_similarity(&mut self, id: &Uuid, similarity: f32) {
+        if !self.heuristics.contains_key(id) {
+            return;
+        }
+        // Bound memory: forget heuristics that have left the cache
+        if self.match_similarity.len() >= self.config.max_heuristics.max(1) * 2 {
+            let heuristics = &self.heuristics;
+            self.match_similarity.retain(|id, _| heuristics.contains_key(id));
+        }
+        let (sum, matches) = self.match_similarity.entry(*id).or_default();
+        *sum += similarity as f64;
+        *matches += 1;
+    }
+
+    /// Mean similarity of a heuristic's cache matches (None if it has only
+    /// matched via storage, or not at all).
+    pub fn mean_match_similarity(&self, id: &Uuid) -> Option<f32> {
+        self.match_similarity
+            .get(id)
+            .filter(|(_, matches)| *matches > 0)
+            .map(|(sum, matches)| (sum / *matches as f64) as f32)
+    }
+
     /// Get a heuristic from cache.
     pub fn get_heuristic(&self, id: &Uuid) -> Option<&CachedHeuristic> {
         self.heuristics.get(id)
@@ -286,16 +1258,200 @@ impl MemoryCache {
 
     /// Remove a heuristic from cache.
     pub fn remove_heuristic(&mut self, id: &Uuid) -> bool {
-        self.heuristics.remove(id).is_some()
+        self.remove_heuristic_because(id, EvictionReason::Removed)
+    }
+
+    /// Remove a heuristic from cache, logging why (see eviction_log module).
+    pub fn remove_heuristic_because(&mut self, id: &Uuid, reason: EvictionReason) -> bool {
+        let Some(removed) = self.heuristics.remove(id) else {
+            return false;
+        };
+        self.eviction_log.record(&removed, reason);
+        self.evictions.removed += 1;
+        self.keyword_index.take();
+        true
+    }
+
+    /// Model version cached embeddings come from (None = not known yet).
+    pub fn embedding_model(&self) -> Option<&str> {
+        self.embedding_model.as_deref()
+    }
+
+    /// Switch to `model` as the version behind new embeddings.
+    ///
+    /// Nothing embedded by another version is compared again: cached events
+    /// and active goals are dropped, and heuristics embedded by another
+    /// version are evicted or lose their embedding, per the model change
+    /// policy. Heuristics without a version are assumed current until a
+    /// version is known, and stale after it changes. Returns the heuristics
+    /// affected (none if `model` is already current).
+    pub fn set_embedding_model(&mut self, model: &str) -> Vec<Uuid> {
+        if self.embedding_model.as_deref() == Some(model) {
+            return Vec::new();
+        }
+        let previous = self.embedding_model.replace(model.to_string());
+        let stale: Vec<Uuid> = self
+            .heuristics
+            .values()
+            .filter(|h| !h.condition_embedding.is_empty() && h.embedding_model != model)
+            .filter(|h| previous.is_some() || !h.embedding_model.is_empty())
+            .map(|h| h.id)
+            .collect();
+
+        if previous.is_some() {
+            self.events_by_id.clear();
+            self.active_goals.clear();
+        }
+        match self.config.model_change_policy {
+            ModelChangePolicy::Flush => {
+                for id in &stale {
+                    self.remove_heuristic_because(id, EvictionReason::ModelChange);
+                }
+            }
+            ModelChangePolicy::Reembed => {
+                for id in &stale {
+                    if let Some(h) = self.heuristics.get_mut(id) {
+                        h.condition_embedding = Embedding::default();
+                        exemplars::clear_exemplar_embeddings(&mut h.condition);
+                        if h.embedding_model.is_empty() {
+                            h.embedding_model = previous.clone().unwrap_or_default();
+                        }
+                    }
+                }
+            }
+        }
+        stale
+    }
+
+    /// Cached heuristics waiting for an embedding from the current model
+    /// (see `set_embedding_model`), with their condition text.
+    pub fn heuristics_to_reembed(&self, limit: usize) -> Vec<(Uuid, String)> {
+        let Some(model) = self.embedding_model.as_deref() else {
+            return Vec::new();
+        };
+        self.heuristics
+            .values()
+            .filter(|h| h.condition_embedding.is_empty() && !h.embedding_model.is_empty() && h.embedding_model != model)
+            .filter(|h| !h.condition.text.is_empty())
+            .take(limit)
+            .map(|h| (h.id, h.condition.text.clone()))
+            .collect()
+    }
+
+    /// Give a cached heuristic an embedding from `model`. Returns false if
+    /// it's no longer cached or `model` is no longer current.
+    pub fn set_heuristic_embedding(&mut self, id: &Uuid, embedding: Vec<f32>, model: &str) -> bool {
+        if self.embedding_model.as_deref() != Some(model) {
+            return false;
+        }
+        match self.heuristics.get_mut(id) {
+            Some(h) => {
+                h.condition_embedding = embedding.into();
+                h.embedding_model = model.to_string();
+                exemplars::clear_exemplar_embeddings(&mut h.condition);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cached heuristics and their condition text, for a bulk re-embed: all
+    /// of them, or (`only_stale`) those without an embedding from the
+    /// current model.
+    pub fn reembed_candidates(&self, only_stale: bool) -> Vec<(Uuid, String)> {
+        let current = self.embedding_model.as_deref();
+        self.heuristics
+            .values()
+            .filter(|h| !h.condition.text.is_empty())
+            .filter(|h| {
+                !only_stale
+                    || h.condition_embedding.is_empty()
+                    || current.is_some_and(|model| h.embedding_model != model)
+            })
+            .map(|h| (h.id, h.condition.text.clone()))
+            .collect()
+    }
+
+    /// Swap in embeddings from a bulk re-embed, all under the caller's one
+    /// lock, then make `model` current (see `set_embedding_model`; an empty
+    /// `model` leaves the current version alone). Entries that left the
+    /// cache or whose condition text changed meanwhile are skipped. Returns
+    /// how many embeddings were swapped in.
+    pub fn swap_embeddings(&mut self, model: &str, embeddings: Vec<(Uuid, String, Vec<f32>)>) -> usize {
+        let mut swapped = 0;
+        for (id, text, embedding) in embeddings {
+            if let Some(h) = self.heuristics.get_mut(&id).filter(|h| h.condition.text == text) {
+                if h.embedding_model != model {
+                    exemplars::clear_exemplar_embeddings(&mut h.condition);
+                }
+                h.condition_embedding = embedding.into();
+                h.embedding_model = model.to_string();
+                swapped += 1;
+            }
+        }
+        if !model.is_empty() {
+            self.set_embedding_model(model);
+        }
+        swapped
+    }
+
+    /// Clear `heuristic`'s embedding if another model version produced it.
+    fn drop_foreign_embedding(&self, heuristic: &mut CachedHeuristic) {
+        let Some(model) = self.embedding_model.as_deref() else {
+            return;
+        };
+        if !heuristic.embedding_model.is_empty() && heuristic.embedding_model != model && !heuristic.condition_embedding.is_empty() {
+            tracing::debug!(
+                heuristic_id = %heuristic.id,
+                embedding_model = %heuristic.embedding_model,
+                current = %model,
+                "Heuristic embedded by another model version; not comparing its embedding"
+            );
+            heuristic.condition_embedding = Embedding::default();
+            exemplars::clear_exemplar_embeddings(&mut heuristic.condition);
+        }
     }
 
     /// Clear all heuristics from cache.
     pub fn flush_heuristics(&mut self) -> usize {
         let count = self.heuristics.len();
-        self.heuristics.clear();
+        for (_, h) in self.heuristics.drain() {
+            self.eviction_log.record(&h, EvictionReason::Flush);
+        }
+        self.keyword_index.take();
+        self.evictions.flushed += count as u64;
         count
     }
 
+    /// Cached heuristics a selective flush would remove, most hit first.
+    pub fn heuristics_matching(&self, filter: &HeuristicFilter) -> Vec<&CachedHeuristic> {
+        let now = current_time_ms();
+        let mut matching: Vec<&CachedHeuristic> = self.heuristics.values().filter(|h| filter.matches(h, now)).collect();
+        matching.sort_by_key(|h| std::cmp::Reverse(h.hit_count));
+        matching
+    }
+
+    /// Remove the heuristics `filter` selects. Returns their IDs.
+    pub fn flush_matching(&mut self, filter: &HeuristicFilter) -> Vec<Uuid> {
+        let now = current_time_ms();
+        let ids: Vec<Uuid> = self.heuristics.values().filter(|h| filter.matches(h, now)).map(|h| h.id).collect();
+        for id in &ids {
+            if let Some(h) = self.heuristics.remove(id) {
+                self.eviction_log.record(&h, EvictionReason::Flush);
+            }
+        }
+        if !ids.is_empty() {
+            self.keyword_index.take();
+            self.evictions.flushed += ids.len() as u64;
+        }
+        ids
+    }
+
+    /// Recent evictions, newest first (see `EvictionLog::recent`).
+    pub fn eviction_log(&self, limit: usize, reason: Option<EvictionReason>, heuristic_id: Option<Uuid>) -> Vec<EvictionRecord> {
+        self.eviction_log.recent(limit, reason, heuristic_id)
+    }
+
     /// Get all heuristics in cache.
     pub fn list_heuristics(&self, limit: usize) -> Vec<&CachedHeuristic> {
         let mut h: Vec<&CachedHeuristic> = self.heuristics.values().collect();
@@ -316,64 +1472,226 @@ impl MemoryCache {
         self.heuristics
             .values()
             .filter(|h| {
-                h.confidence >= min_confidence
-                    && (ttl <= 0 || (now - h.cached_at_ms) < ttl)
+                h.confidence >= min_confidence
+                    && (ttl <= 0 || (now - h.cached_at_ms) < ttl)
+            })
+            .collect()
+    }
+
+    /// Find heuristics matching a query embedding via cosine similarity.
+    ///
+    /// Returns (heuristic_id, similarity) pairs sorted by similarity descending.
+    /// Filters by min_similarity, min_confidence, and TTL expiry.
+    pub fn find_matching_heuristics(
+        &self,
+        query_embedding: &[f32],
+        min_similarity: f32,
+        min_confidence: f32,
+        limit: usize,
+    ) -> Vec<(Uuid, f32)> {
+        self.find_matching_heuristics_in_source(query_embedding, None, min_similarity, min_confidence, limit)
+    }
+
+    /// Like `find_matching_heuristics`, restricted to heuristics scoped to
+    /// `source_filter` (see `CachedHeuristic::matches_source`).
+    pub fn find_matching_heuristics_in_source(
+        &self,
+        query_embedding: &[f32],
+        source_filter: Option<&str>,
+        min_similarity: f32,
+        min_confidence: f32,
+        limit: usize,
+    ) -> Vec<(Uuid, f32)> {
+        let now = current_time_ms();
+        let ttl = self.config.heuristic_ttl_ms;
+
+        let mut matches: Vec<(Uuid, f32)> = self.heuristics
+            .values()
+            .filter(|h| {
+                // Skip expired
+                if ttl > 0 && (now - h.cached_at_ms) >= ttl {
+                    return false;
+                }
+                // Skip low confidence
+                if h.confidence < min_confidence {
+                    return false;
+                }
+                // Skip heuristics scoped to other sources, and disabled groups
+                h.matches_source(source_filter) && self.is_group_enabled(h.effects.group.as_deref())
+            })
+            // Heuristics with nothing embedded have no similarity, and a negative exemplar can veto
+            .filter_map(|h| exemplars::condition_score(query_embedding, h, &self.config).map(|(sim, _)| (h.id, sim)))
+            .filter(|(_, sim)| *sim >= min_similarity)
+            .collect();
+
+        matches.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        if limit > 0 && matches.len() > limit {
+            matches.truncate(limit);
+        }
+        matches
+    }
+
+    /// `query`'s similarity to a cached heuristic's condition and exemplars
+    /// (None if nothing is embedded; see exemplars module).
+    pub fn condition_similarity(&self, query: &[f32], heuristic: &CachedHeuristic) -> Option<f32> {
+        exemplars::condition_similarity(query, heuristic, self.config.similarity_metric, self.config.exemplar_aggregation)
+    }
+
+    /// The negative exemplar of a cached heuristic that counts against its
+    /// match for `query`, if any (see exemplars module).
+    pub fn negative_match(&self, query: &[f32], heuristic: &CachedHeuristic) -> Option<exemplars::NegativeMatch> {
+        exemplars::negative_match(query, heuristic, self.config.similarity_metric, self.config.negative_threshold)
+    }
+
+    /// Heuristics whose keywords (see `keywords` module) appear in `text`,
+    /// highest confidence first. Filters by min_confidence, source and TTL expiry.
+    pub fn find_keyword_matches(
+        &self,
+        text: &str,
+        source_filter: Option<&str>,
+        min_confidence: f32,
+        limit: usize,
+    ) -> Vec<Uuid> {
+        let now = current_time_ms();
+        let ttl = self.config.heuristic_ttl_ms;
+        let index = self
+            .keyword_index
+            .get_or_init(|| keywords::KeywordIndex::build(self.heuristics.values()));
+
+        let mut matches: Vec<&CachedHeuristic> = index
+            .matches(text)
+            .iter()
+            .filter_map(|id| self.heuristics.get(id))
+            .filter(|h| {
+                (ttl <= 0 || (now - h.cached_at_ms) < ttl)
+                    && h.confidence >= min_confidence
+                    && h.matches_source(source_filter)
+                    && self.is_group_enabled(h.effects.group.as_deref())
             })
-            .collect()
+            .collect();
+        matches.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal));
+
+        if limit > 0 && matches.len() > limit {
+            matches.truncate(limit);
+        }
+        matches.into_iter().map(|h| h.id).collect()
     }
 
-    /// Find heuristics matching a query embedding via cosine similarity.
+    /// Embedding of a cached event with exactly this text, if any.
+    pub fn cached_event_embedding(&self, raw_text: &str) -> Option<&[f32]> {
+        self.events_by_id
+            .values()
+            .find(|e| e.raw_text == raw_text && !e.embedding.is_empty())
+            .map(|e| e.embedding.as_slice())
+    }
+
+    /// Like `find_matching_heuristics`, but with match hysteresis.
     ///
-    /// Returns (heuristic_id, similarity) pairs sorted by similarity descending.
-    /// Filters by min_similarity, min_confidence, and TTL expiry.
-    pub fn find_matching_heuristics(
+    /// A heuristic that matched the same text (by hash) within the hysteresis
+    /// window only needs to clear `min_similarity - match_hysteresis_margin`.
+    pub fn find_matching_heuristics_with_hysteresis(
         &self,
         query_embedding: &[f32],
+        text_hash: u64,
+        source_filter: Option<&str>,
         min_similarity: f32,
         min_confidence: f32,
         limit: usize,
     ) -> Vec<(Uuid, f32)> {
-        if query_embedding.is_empty() {
-            return Vec::new();
+        let sticky = self.recent_match(text_hash);
+        let Some(sticky_id) = sticky else {
+            return self.find_matching_heuristics_in_source(
+                query_embedding,
+                source_filter,
+                min_similarity,
+                min_confidence,
+                limit,
+            );
+        };
+
+        let lowered = min_similarity - self.config.match_hysteresis_margin;
+        let mut matches =
+            self.find_matching_heuristics_in_source(query_embedding, source_filter, lowered, min_confidence, 0);
+        matches.retain(|(id, sim)| *sim >= min_similarity || *id == sticky_id);
+
+        if limit > 0 && matches.len() > limit {
+            matches.truncate(limit);
+        }
+        matches
+    }
+
+    /// Heuristic that matched this text hash within the hysteresis window, if any.
+    pub fn recent_match(&self, text_hash: u64) -> Option<Uuid> {
+        let window = self.config.match_hysteresis_window_ms;
+        if window <= 0 {
+            return None;
         }
+        self.recent_matches
+            .get(&text_hash)
+            .filter(|(_, matched_at)| current_time_ms() - matched_at < window)
+            .map(|(id, _)| *id)
+    }
 
+    /// Remember that a heuristic matched this text hash (for hysteresis).
+    pub fn record_text_match(&mut self, text_hash: u64, heuristic_id: Uuid) {
         let now = current_time_ms();
-        let ttl = self.config.heuristic_ttl_ms;
+        let window = self.config.match_hysteresis_window_ms;
+        if window <= 0 {
+            return;
+        }
 
-        let mut matches: Vec<(Uuid, f32)> = self.heuristics
-            .values()
-            .filter(|h| {
-                // Skip expired
-                if ttl > 0 && (now - h.cached_at_ms) >= ttl {
-                    return false;
-                }
-                // Skip low confidence
-                if h.confidence < min_confidence {
-                    return false;
-                }
-                // Skip empty embeddings
-                !h.condition_embedding.is_empty()
-            })
-            .filter_map(|h| {
-                let sim = cosine_similarity(query_embedding, &h.condition_embedding);
-                if sim >= min_similarity {
-                    Some((h.id, sim))
-                } else {
-                    None
-                }
-            })
-            .collect();
+        // Bound memory: drop expired entries once the map outgrows the event cache
+        if self.recent_matches.len() >= self.config.max_events.max(1) {
+            self.recent_matches.retain(|_, (_, matched_at)| now - *matched_at < window);
+        }
+        self.recent_matches.insert(text_hash, (heuristic_id, now));
+    }
 
-        matches.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    /// An earlier event with the same source and text, first seen within the
+    /// dedup window. Otherwise this event is indexed, and repeats within the
+    /// window are reported as duplicates of it.
+    pub fn check_duplicate(&mut self, event_id: &str, source: &str, raw_text: &str) -> Option<String> {
+        let window = self.config.dedup_window_ms;
+        if window <= 0 || raw_text.is_empty() {
+            return None;
+        }
+        let now = current_time_ms();
+        let original = self
+            .events_by_text
+            .get(&dedup_key(source, raw_text))
+            .filter(|(id, seen_at)| now - seen_at < window && id != event_id)
+            .map(|(id, _)| id.clone());
+        if original.is_none() {
+            self.index_event_text(event_id, source, raw_text, now);
+        }
+        original
+    }
 
-        if limit > 0 && matches.len() > limit {
-            matches.truncate(limit);
+    /// Record an event as the first sighting of its text, unless an earlier
+    /// one is still within the dedup window.
+    fn index_event_text(&mut self, event_id: &str, source: &str, raw_text: &str, seen_at: i64) {
+        let window = self.config.dedup_window_ms;
+        if window <= 0 || event_id.is_empty() {
+            return;
         }
 
-        matches
+        // Bound memory: drop expired entries once the index outgrows the event cache
+        if self.events_by_text.len() >= self.config.max_events.max(1) {
+            self.events_by_text.retain(|_, (_, first_seen)| seen_at - *first_seen < window);
+        }
+        let entry = self
+            .events_by_text
+            .entry(dedup_key(source, raw_text))
+            .or_insert_with(|| (event_id.to_string(), seen_at));
+        if seen_at - entry.1 >= window {
+            *entry = (event_id.to_string(), seen_at);
+        }
     }
 
     /// Get cache statistics.
+    ///
+    /// Everything is read from one `&self` borrow, so callers holding a single
+    /// lock get mutually consistent numbers.
     pub fn stats(&self) -> CacheStats {
         CacheStats {
             event_count: self.events_by_id.len(),
@@ -382,8 +1700,89 @@ impl MemoryCache {
             max_heuristics: self.config.max_heuristics,
             total_hits: self.total_hits,
             total_misses: self.total_misses,
+            oldest_event_ms: self.events_by_id.values().map(|e| e.timestamp_ms).min().unwrap_or(0),
+            evictions: self.evictions.clone(),
+            recent_match_count: self.recent_matches.len(),
+            refetches: self.refetches.clone(),
+            suppressed_reinserts: self.suppressed_reinserts,
+            approx_memory_bytes: self.approx_memory_bytes(),
         }
     }
+
+    /// Rough heap + inline size of cached entries (ignores map overhead).
+    fn approx_memory_bytes(&self) -> usize {
+        let events: usize = self
+            .events_by_id
+            .values()
+            .map(|e| {
+                std::mem::size_of::<CachedEvent>()
+                    + e.source.len()
+                    + e.raw_text.len()
+                    + e.embedding.len() * std::mem::size_of::<f32>()
+            })
+            .sum();
+        let heuristics: usize = self
+            .heuristics
+            .values()
+            .map(|h| {
+                std::mem::size_of::<CachedHeuristic>()
+                    + h.name.len()
+                    + h.condition.text.len()
+                    + h.effects.to_json().len()
+                    + h.condition_embedding.len() * std::mem::size_of::<f32>()
+                    + h.condition
+                        .exemplars
+                        .iter()
+                        .chain(&h.condition.negatives)
+                        .map(|e| e.text.len() + e.embedding.len() * std::mem::size_of::<f32>())
+                        .sum::<usize>()
+            })
+            .sum();
+        let matches = self.recent_matches.len() * std::mem::size_of::<(u64, (Uuid, i64))>();
+        let dedup: usize = self
+            .events_by_text
+            .values()
+            .map(|(id, _)| std::mem::size_of::<(u64, (String, i64))>() + id.len())
+            .sum();
+        events + heuristics + matches + dedup
+    }
+}
+
+/// Cache removals by reason, since startup.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EvictionCounts {
+    /// Heuristics dropped by LRU because the cache was full
+    pub heuristic_lru: u64,
+    /// Heuristics dropped by LRU because the capacity was lowered
+    pub heuristic_resize: u64,
+    /// Events dropped (oldest first) because the event cache was full
+    pub event_capacity: u64,
+    /// Heuristics removed individually (EvictFromCache, change notifications, deletes)
+    pub removed: u64,
+    /// Heuristics cleared by FlushCache
+    pub flushed: u64,
+}
+
+impl EvictionCounts {
+    /// Counts keyed by reason name, for GetCacheStats.
+    pub fn by_reason(&self) -> HashMap<String, u64> {
+        HashMap::from([
+            ("heuristic_lru".to_string(), self.heuristic_lru),
+            ("heuristic_resize".to_string(), self.heuristic_resize),
+            ("event_capacity".to_string(), self.event_capacity),
+            ("removed".to_string(), self.removed),
+            ("flushed".to_string(), self.flushed),
+        ])
+    }
+}
+
+/// Evicted entries cached again within the churn window, since startup.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RefetchCounts {
+    /// Heuristics evicted by LRU and then re-fetched from storage
+    pub heuristics: u64,
+    /// Events evicted at capacity whose text came back
+    pub events: u64,
 }
 
 /// Cache statistics for monitoring.
@@ -395,6 +1794,16 @@ pub struct CacheStats {
     pub max_heuristics: usize,
     pub total_hits: u64,
     pub total_misses: u64,
+    /// Timestamp of the oldest cached event (Unix ms, 0 if none)
+    pub oldest_event_ms: i64,
+    pub evictions: EvictionCounts,
+    /// Entries in the match-hysteresis map
+    pub recent_match_count: usize,
+    pub refetches: RefetchCounts,
+    /// Inserts rejected because the heuristic was recently deleted
+    pub suppressed_reinserts: u64,
+    /// Approximate memory held by cached entries
+    pub approx_memory_bytes: usize,
 }
 
 impl CacheStats {
@@ -408,35 +1817,236 @@ impl CacheStats {
     }
 }
 
-/// Get current time in milliseconds since Unix epoch.
-fn current_time_ms() -> i64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as i64
+/// How a `ReadThroughCache` lookup was answered.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CacheRead<V> {
+    /// Cached within its TTL
+    Hit(V),
+    /// This caller fetched it (and cached it)
+    Fetched(V),
+    /// Shared from another caller's in-flight fetch (None = the source has none)
+    Coalesced(Option<V>),
+    /// The source has nothing for the key (fetched by this caller, or
+    /// remembered within the negative TTL)
+    Absent,
+}
+
+impl<V> CacheRead<V> {
+    pub fn into_value(self) -> Option<V> {
+        match self {
+            Self::Hit(v) | Self::Fetched(v) => Some(v),
+            Self::Coalesced(v) => v,
+            Self::Absent => None,
+        }
+    }
+}
+
+/// Size and hit count of a `ReadThroughCache`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadThroughStats {
+    /// Entries held, including expired ones not yet pruned
+    pub entries: usize,
+    /// Lookups answered from the cache (found or absent) since startup
+    pub hits: u64,
 }
 
-/// Compute cosine similarity between two vectors
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
-    if a.len() != b.len() || a.is_empty() {
-        return 0.0;
+struct ReadThroughEntry<V> {
+    /// None = negative entry
+    value: Option<V>,
+    expires_ms: i64,
+}
+
+/// Lookup, then fetch, then insert, for values kept outside `MemoryCache`.
+///
+/// A miss runs the caller's fetch; identical concurrent misses share one
+/// fetch (see single_flight module). Found values are kept for `ttl_ms`,
+/// "the source has none" for `negative_ttl_ms`, and fetch errors aren't
+/// cached. A TTL of 0 disables that kind of entry, leaving only coalescing.
+/// At capacity, expired entries go first, then the soonest to expire.
+pub struct ReadThroughCache<K, V, E> {
+    entries: std::sync::Mutex<HashMap<K, ReadThroughEntry<V>>>,
+    flights: single_flight::SingleFlight<K, Result<Option<V>, E>>,
+    ttl_ms: i64,
+    negative_ttl_ms: i64,
+    capacity: usize,
+    hits: std::sync::atomic::AtomicU64,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone, E: Clone> ReadThroughCache<K, V, E> {
+    pub fn new(ttl_ms: i64, negative_ttl_ms: i64, capacity: usize) -> Self {
+        Self {
+            entries: std::sync::Mutex::new(HashMap::new()),
+            flights: single_flight::SingleFlight::new(),
+            ttl_ms: ttl_ms.max(0),
+            negative_ttl_ms: negative_ttl_ms.max(0),
+            capacity: capacity.max(1),
+            hits: std::sync::atomic::AtomicU64::new(0),
+        }
+    }
+
+    /// The cached answer for `key`, without fetching (None = not cached;
+    /// `Some(None)` = cached as absent).
+    pub fn get(&self, key: &K) -> Option<Option<V>> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.get(key).filter(|e| e.expires_ms > current_time_ms()).map(|e| e.value.clone())
+    }
+
+    /// The cached answer, or `fetch`'s (`Ok(None)` = the source has none).
+    pub async fn get_or_fetch<F, Fut>(&self, key: K, fetch: F) -> Result<CacheRead<V>, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<Option<V>, E>>,
+    {
+        if let Some(cached) = self.get(&key) {
+            self.hits.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            return Ok(cached.map_or(CacheRead::Absent, CacheRead::Hit));
+        }
+        let (result, coalesced) = self.flights.run(key.clone(), fetch).await;
+        let value = result?;
+        if coalesced {
+            return Ok(CacheRead::Coalesced(value));
+        }
+        self.insert(key, value.clone());
+        Ok(value.map_or(CacheRead::Absent, CacheRead::Fetched))
+    }
+
+    /// Cache an answer directly (`None` = absent), subject to the TTLs.
+    pub fn insert(&self, key: K, value: Option<V>) {
+        let ttl_ms = if value.is_some() { self.ttl_ms } else { self.negative_ttl_ms };
+        if ttl_ms == 0 {
+            return;
+        }
+        let now = current_time_ms();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, e| e.expires_ms > now);
+            while entries.len() >= self.capacity {
+                let Some(soonest) = entries.iter().min_by_key(|(_, e)| e.expires_ms).map(|(k, _)| k.clone()) else {
+                    break;
+                };
+                entries.remove(&soonest);
+            }
+        }
+        entries.insert(key, ReadThroughEntry { value, expires_ms: now + ttl_ms });
+    }
+
+    /// Forget `key` (e.g., the source changed it).
+    pub fn invalidate(&self, key: &K) {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).remove(key);
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// Entries held, including expired ones not yet pruned.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
-    let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
-    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
-    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    pub fn stats(&self) -> ReadThroughStats {
+        ReadThroughStats { entries: self.len(), hits: self.hits.load(std::sync::atomic::Ordering::Relaxed) }
+    }
+}
 
-    if norm_a == 0.0 || norm_b == 0.0 {
-        return 0.0;
+/// Warn about each validation issue in a heuristic's effects (see
+/// `Effects::validate`) as it enters the cache, so bad LLM-extracted
+/// heuristics are visible. Advisory only: the heuristic is cached either
+/// way, and ListCachedHeuristics reports the issue count.
+fn lint_heuristic(heuristic: &CachedHeuristic) {
+    for issue in heuristic.effects.validate() {
+        tracing::warn!(heuristic_id = %heuristic.id, name = %heuristic.name, issue = %issue, "Heuristic effects failed validation");
     }
+}
+
+/// Stable-within-process hash of event text, used as a cache key.
+pub fn text_hash(text: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
 
-    dot / (norm_a * norm_b)
+/// Dedup index key: the same text from different sources isn't a duplicate.
+fn dedup_key(source: &str, raw_text: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (source, raw_text).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Get current time in milliseconds since Unix epoch.
+pub(crate) fn current_time_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_storage_error_taxonomy() {
+        let timeout: StorageError = ClientError::RpcFailed(tonic::Status::deadline_exceeded("slow")).into();
+        assert_eq!(timeout, StorageError::Timeout("slow".to_string()));
+        assert_eq!(timeout.code(), "STORAGE_TIMEOUT");
+        assert_eq!(tonic::Status::from(timeout).code(), tonic::Code::DeadlineExceeded);
+
+        let decode: StorageError = ClientError::InvalidResponse.into();
+        assert_eq!(decode.code(), "STORAGE_DECODE_FAILED");
+        assert_eq!(decode.status_code(), tonic::Code::DataLoss);
+
+        let rpc: StorageError = ClientError::RpcFailed(tonic::Status::permission_denied("no")).into();
+        assert_eq!(rpc.status_code(), tonic::Code::PermissionDenied);
+
+        let scoring = ScoringError::StorageError(StorageError::Unavailable("down".to_string()));
+        assert_eq!(scoring.code(), "STORAGE_UNAVAILABLE");
+        let status = tonic::Status::from(scoring);
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+        assert!(status.message().starts_with("STORAGE_UNAVAILABLE: "));
+    }
+
+    #[test]
+    fn test_scored_match_from_typed_effects() {
+        let heuristic = CachedHeuristic {
+            id: Uuid::new_v4(),
+            name: "creeper".to_string(),
+            condition: Condition { text: "creeper nearby".to_string(), ..Condition::default() },
+            effects: Effects::parse(r#"{"message": "Run!", "salience": {"threat": 0.8}, "sound": "alarm"}"#).unwrap(),
+            confidence: 0.9,
+            origin: "user".to_string(),
+            source: String::new(),
+            condition_embedding: Default::default(),
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        };
+
+        let scored = ScoredMatch::new(&heuristic, 0.85);
+        assert_eq!(scored.heuristic_id, heuristic.id.to_string());
+        assert_eq!((scored.similarity, scored.confidence), (0.85, 0.9));
+        assert_eq!(scored.origin, "user");
+        assert_eq!(scored.condition_text, "creeper nearby");
+        assert_eq!(scored.suggested_action, "Run!");
+        assert_eq!(scored.salience_boost, Some(SalienceBoost::from([("threat".to_string(), 0.8)])));
+        assert_eq!(scored.effects.extra["sound"], "alarm");
+        assert_eq!(scored.method, MatchMethod::Embedding);
+        assert_eq!(scored.with_method(MatchMethod::Keyword).method, MatchMethod::Keyword);
+
+        // No effects: no action and no boost
+        let bare = CachedHeuristic { effects: Effects::default(), ..heuristic };
+        let scored = ScoredMatch::new(&bare, 0.85);
+        assert_eq!(scored.suggested_action, "");
+        assert!(scored.salience_boost.is_none());
+    }
+
     #[test]
     fn test_cosine_similarity_identical() {
         let a = vec![1.0, 0.0, 0.0];
@@ -451,9 +2061,172 @@ mod tests {
         assert!((cosine_similarity(&a, &b)).abs() < 0.0001);
     }
 
+    #[test]
+    fn test_dot_product_similarity() {
+        let a = vec![2.0, 0.0, 1.0];
+        let b = vec![3.0, 5.0, 2.0];
+        assert!((similarity(SimilarityMetric::Dot, &a, &b) - 8.0).abs() < 0.0001);
+        assert_eq!(similarity(SimilarityMetric::Dot, &a, &[1.0]), 0.0);
+    }
+
+    #[test]
+    fn test_euclidean_similarity() {
+        let a = vec![1.0, 0.0];
+        assert!((similarity(SimilarityMetric::Euclidean, &a, &a) - 1.0).abs() < 0.0001);
+        // distance 1.0 -> 1 / (1 + 1) = 0.5
+        assert!((similarity(SimilarityMetric::Euclidean, &a, &[1.0, 1.0]) - 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_find_matching_heuristics_respects_metric() {
+        // Same direction, different magnitude: cosine says identical, euclidean does not
+        let query = vec![1.0; 4];
+        let condition = vec![3.0; 4];
+
+        for (metric, expect_match) in [
+            (SimilarityMetric::Cosine, true),
+            (SimilarityMetric::Dot, true),
+            (SimilarityMetric::Euclidean, false),
+        ] {
+            let mut cache = MemoryCache::new(CacheConfig {
+                heuristic_ttl_ms: 0,
+                similarity_metric: metric,
+                ..CacheConfig::default()
+            });
+            cache.add_heuristic(CachedHeuristic {
+                id: Uuid::new_v4(),
+                name: "scaled".to_string(),
+                condition: Condition::default(),
+                effects: Effects::default(),
+                origin: String::new(),
+                source: String::new(),
+                condition_embedding: condition.clone().into(),
+                confidence: 0.9,
+                last_accessed_ms: 0,
+                cached_at_ms: 0,
+                hit_count: 0,
+                last_hit_ms: 0,
+                cooldown_until_ms: 0,
+                embedding_model: String::new(),
+            });
+            let matches = cache.find_matching_heuristics(&query, 0.9, 0.0, 10);
+            assert_eq!(!matches.is_empty(), expect_match, "metric {:?}", metric);
+        }
+    }
+
+    #[test]
+    fn test_match_hysteresis_lowers_threshold_for_recent_text() {
+        let mut cache = MemoryCache::new(CacheConfig {
+            heuristic_ttl_ms: 0,
+            match_hysteresis_margin: 0.1,
+            match_hysteresis_window_ms: 60_000,
+            ..CacheConfig::default()
+        });
+
+        let id = Uuid::new_v4();
+        cache.add_heuristic(CachedHeuristic {
+            id,
+            name: "borderline".to_string(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: vec![1.0, 0.0].into(),
+            confidence: 0.9,
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        });
+
+        // cos(45deg) ~= 0.707: just below a 0.75 threshold
+        let query = vec![1.0, 1.0];
+        let hash = text_hash("enemy spotted nearby");
+        assert!(cache.find_matching_heuristics_with_hysteresis(&query, hash, None, 0.75, 0.0, 5).is_empty());
+
+        cache.record_text_match(hash, id);
+        let matches = cache.find_matching_heuristics_with_hysteresis(&query, hash, None, 0.75, 0.0, 5);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0, id);
+
+        // Other texts don't get the reduced threshold
+        let other = text_hash("something else");
+        assert!(cache.find_matching_heuristics_with_hysteresis(&query, other, None, 0.75, 0.0, 5).is_empty());
+    }
+
+    #[test]
+    fn test_match_hysteresis_disabled_with_zero_window() {
+        let mut cache = MemoryCache::new(CacheConfig {
+            match_hysteresis_window_ms: 0,
+            ..CacheConfig::default()
+        });
+        let hash = text_hash("text");
+        cache.record_text_match(hash, Uuid::new_v4());
+        assert!(cache.recent_match(hash).is_none());
+    }
+
+    #[test]
+    fn test_check_duplicate_within_window() {
+        let mut cache = MemoryCache::new(CacheConfig { dedup_window_ms: 60_000, ..CacheConfig::default() });
+        assert_eq!(cache.check_duplicate("e1", "game", "creeper nearby"), None);
+        assert_eq!(cache.check_duplicate("e2", "game", "creeper nearby").as_deref(), Some("e1"));
+        // Retries of the original and other sources aren't duplicates
+        assert_eq!(cache.check_duplicate("e1", "game", "creeper nearby"), None);
+        assert_eq!(cache.check_duplicate("e3", "chat", "creeper nearby"), None);
+
+        // Cached events are indexed too
+        let id = Uuid::new_v4();
+        cache.add_event(CachedEvent {
+            id,
+            timestamp_ms: current_time_ms(),
+            source: "game".to_string(),
+            raw_text: "lava ahead".to_string(),
+            embedding: Default::default(),
+            access_count: 0,
+            last_accessed_ms: 0,
+        });
+        assert_eq!(cache.check_duplicate("e4", "game", "lava ahead"), Some(id.to_string()));
+
+        let mut disabled = MemoryCache::new(CacheConfig { dedup_window_ms: 0, ..CacheConfig::default() });
+        disabled.check_duplicate("e1", "game", "creeper nearby");
+        assert_eq!(disabled.check_duplicate("e2", "game", "creeper nearby"), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_through_cache_fetches_once_and_caches_absence() {
+        let cache: ReadThroughCache<&str, u32, String> = ReadThroughCache::new(60_000, 60_000, 2);
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let fetch = |value: Result<Option<u32>, String>| {
+            calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async move { value }
+        };
+
+        assert_eq!(cache.get_or_fetch("a", || fetch(Ok(Some(1)))).await, Ok(CacheRead::Fetched(1)));
+        assert_eq!(cache.get_or_fetch("a", || fetch(Ok(Some(2)))).await, Ok(CacheRead::Hit(1)));
+        assert_eq!(cache.get_or_fetch("b", || fetch(Ok(None))).await, Ok(CacheRead::Absent));
+        assert_eq!(cache.get_or_fetch("b", || fetch(Ok(Some(3)))).await, Ok(CacheRead::Absent));
+        assert_eq!(cache.get(&"b"), Some(None));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(cache.stats(), ReadThroughStats { entries: 2, hits: 2 });
+
+        // Errors aren't cached; at capacity an older entry makes room
+        assert_eq!(cache.get_or_fetch("c", || fetch(Err("down".to_string()))).await, Err("down".to_string()));
+        assert_eq!(cache.get(&"c"), None);
+        cache.insert("c", Some(4));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&"c"), Some(Some(4)));
+
+        // TTL 0: nothing is kept, every miss fetches
+        let uncached: ReadThroughCache<&str, u32, String> = ReadThroughCache::new(0, 0, 8);
+        uncached.get_or_fetch("a", || fetch(Ok(None))).await.unwrap();
+        assert!(uncached.is_empty());
+    }
+
     #[test]
     fn test_novelty_empty_cache() {
-        let cache = MemoryCache::new(CacheConfig::default());
+        let mut cache = MemoryCache::new(CacheConfig::default());
         let embedding = vec![0.1; 384];
         assert!(cache.is_novel(&embedding));
     }
@@ -465,6 +2238,7 @@ mod tests {
             max_heuristics: 50,
             novelty_threshold: 0.9,
             heuristic_ttl_ms: 5000,
+            ..CacheConfig::default()
         });
 
         let embedding = vec![1.0; 384];
@@ -473,8 +2247,9 @@ mod tests {
             timestamp_ms: 1000,
             source: "test".to_string(),
             raw_text: "test event".to_string(),
-            embedding: embedding.clone(),
+            embedding: embedding.clone().into(),
             access_count: 0,
+            last_accessed_ms: 0,
         });
 
         // Identical embedding should not be novel
@@ -492,6 +2267,7 @@ mod tests {
             max_heuristics: 50,
             novelty_threshold: 0.9,
             heuristic_ttl_ms: 5000,
+            ..CacheConfig::default()
         });
 
         // Add 4 events to trigger eviction
@@ -501,13 +2277,18 @@ mod tests {
                 timestamp_ms: i * 1000,
                 source: "test".to_string(),
                 raw_text: format!("event {}", i),
-                embedding: vec![i as f32; 384],
+                embedding: vec![i as f32; 384].into(),
                 access_count: 0,
+                last_accessed_ms: 0,
             });
         }
 
         // Should only have 3 events (oldest evicted)
-        assert_eq!(cache.stats().event_count, 3);
+        let stats = cache.stats();
+        assert_eq!(stats.event_count, 3);
+        assert_eq!(stats.oldest_event_ms, 1000);
+        assert_eq!(stats.evictions.event_capacity, 1);
+        assert!(stats.approx_memory_bytes >= 3 * 384 * std::mem::size_of::<f32>());
     }
 
     #[test]
@@ -521,8 +2302,9 @@ mod tests {
             timestamp_ms: 1000,
             source: "test".to_string(),
             raw_text: "test event".to_string(),
-            embedding: embedding.clone(),
+            embedding: embedding.clone().into(),
             access_count: 0,
+            last_accessed_ms: 0,
         });
 
         // Should find the event with high similarity
@@ -537,6 +2319,38 @@ mod tests {
         assert!(cache.find_similar(&different, 0.9).is_none());
     }
 
+    #[test]
+    fn test_accessed_events_outlive_older_unreferenced_ones() {
+        let event = |timestamp_ms: i64, embedding: Vec<f32>| CachedEvent {
+            id: Uuid::new_v4(),
+            timestamp_ms,
+            source: "test".to_string(),
+            raw_text: format!("event {}", timestamp_ms),
+            embedding: embedding.into(),
+            access_count: 0,
+            last_accessed_ms: 0,
+        };
+        let context = vec![1.0; 384];
+        let mut other = vec![-1.0; 384];
+        other[0] = 1.0;
+
+        for weight in [0, 60_000] {
+            let mut cache = MemoryCache::new(CacheConfig { max_events: 2, event_access_weight_ms: weight, ..CacheConfig::default() });
+            let now = current_time_ms();
+            let referenced = event(now - 20_000, context.clone());
+            let referenced_id = referenced.id;
+            cache.add_event(referenced);
+            cache.add_event(event(now - 10_000, other.clone()));
+            assert_eq!(cache.find_similar(&context, 0.9).map(|(id, _)| id), Some(referenced_id));
+            assert!(!cache.is_novel(&context));
+            assert_eq!(cache.get_event(&referenced_id).unwrap().access_count, 2);
+
+            cache.add_event(event(now, other.clone()));
+            // Without a weight, the oldest goes regardless of how often it was referenced
+            assert_eq!(cache.get_event(&referenced_id).is_some(), weight > 0);
+        }
+    }
+
     #[test]
     fn test_heuristics_by_confidence() {
         let mut cache = MemoryCache::new(CacheConfig::default());
@@ -544,27 +2358,35 @@ mod tests {
         cache.add_heuristic(CachedHeuristic {
             id: Uuid::new_v4(),
             name: "low_confidence".to_string(),
-            condition: serde_json::json!({}),
-            action: serde_json::json!({}),
-            condition_embedding: Vec::new(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: Default::default(),
             confidence: 0.3,
             last_accessed_ms: 0,
             cached_at_ms: 0,
             hit_count: 0,
             last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
         });
 
         cache.add_heuristic(CachedHeuristic {
             id: Uuid::new_v4(),
             name: "high_confidence".to_string(),
-            condition: serde_json::json!({}),
-            action: serde_json::json!({}),
-            condition_embedding: Vec::new(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: Default::default(),
             confidence: 0.9,
             last_accessed_ms: 0,
             cached_at_ms: 0,
             hit_count: 0,
             last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
         });
 
         let high_conf = cache.get_heuristics_by_confidence(0.5);
@@ -583,6 +2405,7 @@ mod tests {
             max_heuristics: 3,
             novelty_threshold: 0.9,
             heuristic_ttl_ms: 5000,
+            ..CacheConfig::default()
         });
 
         // Add 3 heuristics with different last_accessed times
@@ -593,40 +2416,52 @@ mod tests {
         cache.add_heuristic(CachedHeuristic {
             id: id1,
             name: "first".to_string(),
-            condition: serde_json::json!({}),
-            action: serde_json::json!({}),
-            condition_embedding: Vec::new(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: Default::default(),
             confidence: 0.5,
             last_accessed_ms: 1000, // Oldest
             cached_at_ms: 0,
             hit_count: 0,
             last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
         });
 
         cache.add_heuristic(CachedHeuristic {
             id: id2,
             name: "second".to_string(),
-            condition: serde_json::json!({}),
-            action: serde_json::json!({}),
-            condition_embedding: Vec::new(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: Default::default(),
             confidence: 0.5,
             last_accessed_ms: 2000,
             cached_at_ms: 0,
             hit_count: 0,
             last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
         });
 
         cache.add_heuristic(CachedHeuristic {
             id: id3,
             name: "third".to_string(),
-            condition: serde_json::json!({}),
-            action: serde_json::json!({}),
-            condition_embedding: Vec::new(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: Default::default(),
             confidence: 0.5,
             last_accessed_ms: 3000, // Newest
             cached_at_ms: 0,
             hit_count: 0,
             last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
         });
 
         assert_eq!(cache.stats().heuristic_count, 3);
@@ -636,14 +2471,18 @@ mod tests {
         cache.add_heuristic(CachedHeuristic {
             id: id4,
             name: "fourth".to_string(),
-            condition: serde_json::json!({}),
-            action: serde_json::json!({}),
-            condition_embedding: Vec::new(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: Default::default(),
             confidence: 0.5,
             last_accessed_ms: 4000,
             cached_at_ms: 0,
             hit_count: 0,
             last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
         });
 
         assert_eq!(cache.stats().heuristic_count, 3);
@@ -651,6 +2490,13 @@ mod tests {
         assert!(cache.get_heuristic(&id2).is_some());
         assert!(cache.get_heuristic(&id3).is_some());
         assert!(cache.get_heuristic(&id4).is_some());
+        assert_eq!((cache.evictions.heuristic_lru, cache.evictions.heuristic_resize), (1, 0));
+
+        // Lowering the capacity evicts by LRU too, but is counted apart
+        cache.set_capacity(1, 100);
+        assert!(cache.get_heuristic(&id4).is_some());
+        assert_eq!((cache.evictions.heuristic_lru, cache.evictions.heuristic_resize), (1, 2));
+        assert_eq!(cache.evictions.by_reason()["heuristic_resize"], 2);
     }
 
     #[test]
@@ -660,6 +2506,7 @@ mod tests {
             max_heuristics: 3,
             novelty_threshold: 0.9,
             heuristic_ttl_ms: 5000,
+            ..CacheConfig::default()
         });
 
         let id1 = Uuid::new_v4();
@@ -670,40 +2517,52 @@ mod tests {
         cache.add_heuristic(CachedHeuristic {
             id: id1,
             name: "first".to_string(),
-            condition: serde_json::json!({}),
-            action: serde_json::json!({}),
-            condition_embedding: Vec::new(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: Default::default(),
             confidence: 0.5,
             last_accessed_ms: 1000,
             cached_at_ms: 0,
             hit_count: 0,
             last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
         });
 
         cache.add_heuristic(CachedHeuristic {
             id: id2,
             name: "second".to_string(),
-            condition: serde_json::json!({}),
-            action: serde_json::json!({}),
-            condition_embedding: Vec::new(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: Default::default(),
             confidence: 0.5,
             last_accessed_ms: 2000,
             cached_at_ms: 0,
             hit_count: 0,
             last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
         });
 
         cache.add_heuristic(CachedHeuristic {
             id: id3,
             name: "third".to_string(),
-            condition: serde_json::json!({}),
-            action: serde_json::json!({}),
-            condition_embedding: Vec::new(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: Default::default(),
             confidence: 0.5,
             last_accessed_ms: 3000,
             cached_at_ms: 0,
             hit_count: 0,
             last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
         });
 
         // Touch id1 - should update its last_accessed to now
@@ -715,14 +2574,18 @@ mod tests {
         cache.add_heuristic(CachedHeuristic {
             id: id4,
             name: "fourth".to_string(),
-            condition: serde_json::json!({}),
-            action: serde_json::json!({}),
-            condition_embedding: Vec::new(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: Default::default(),
             confidence: 0.5,
             last_accessed_ms: 0, // Will be set by add_heuristic
             cached_at_ms: 0,
             hit_count: 0,
             last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
         });
 
         assert!(cache.get_heuristic(&id1).is_some()); // id1 was touched, should survive
@@ -738,6 +2601,7 @@ mod tests {
             max_heuristics: 50,
             novelty_threshold: 0.9,
             heuristic_ttl_ms: 300_000, // 5 min
+            ..CacheConfig::default()
         });
 
         // Create two heuristics with different embeddings
@@ -752,27 +2616,35 @@ mod tests {
         cache.add_heuristic(CachedHeuristic {
             id: id1,
             name: "h1".to_string(),
-            condition: serde_json::json!({}),
-            action: serde_json::json!({}),
-            condition_embedding: emb1.clone(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: emb1.clone().into(),
             confidence: 0.8,
             last_accessed_ms: 0,
             cached_at_ms: 0,
             hit_count: 0,
             last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
         });
 
         cache.add_heuristic(CachedHeuristic {
             id: id2,
             name: "h2".to_string(),
-            condition: serde_json::json!({}),
-            action: serde_json::json!({}),
-            condition_embedding: emb2,
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: emb2.into(),
             confidence: 0.3, // Below threshold
             last_accessed_ms: 0,
             cached_at_ms: 0,
             hit_count: 0,
             last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
         });
 
         // Query with emb1 — should match h1 (high confidence), not h2 (low confidence)
@@ -795,6 +2667,70 @@ mod tests {
         assert!(matches.is_empty());
     }
 
+    #[test]
+    fn test_goal_relevance() {
+        let mut cache = MemoryCache::new(CacheConfig::default());
+        let mut embedding = vec![0.0; 384];
+        embedding[0] = 1.0;
+        assert_eq!(cache.goal_relevance(&embedding), None);
+
+        let mut other = vec![0.0; 384];
+        other[1] = 1.0;
+        cache.set_active_goals(vec![
+            ActiveGoal { text: "orthogonal".to_string(), embedding: other.clone() },
+            ActiveGoal { text: "aligned".to_string(), embedding: embedding.clone() },
+        ]);
+        assert!((cache.goal_relevance(&embedding).unwrap() - 1.0).abs() < 0.001);
+        assert_eq!(cache.goal_relevance(&[]), None);
+
+        // Opposite directions clamp to zero rather than going negative
+        let opposite: Vec<f32> = other.iter().map(|v| -v).collect();
+        cache.set_active_goals(vec![ActiveGoal { text: "away".to_string(), embedding: opposite }]);
+        assert_eq!(cache.goal_relevance(&other), Some(0.0));
+    }
+
+    #[test]
+    fn test_find_matching_heuristics_in_source() {
+        let mut cache = MemoryCache::new(CacheConfig::default());
+        let scoped = |source: &str| CachedHeuristic {
+            id: Uuid::new_v4(),
+            name: source.to_string(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            confidence: 0.9,
+            origin: String::new(),
+            source: source.to_string(),
+            condition_embedding: vec![1.0; 384].into(),
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        };
+        let (chat, game, global) = (scoped("chat"), scoped("game"), scoped(""));
+        let (chat_id, global_id) = (chat.id, global.id);
+        cache.add_heuristic(chat);
+        cache.add_heuristic(game);
+        cache.add_heuristic(global);
+
+        let emb = vec![1.0; 384];
+        let ids = |source| -> Vec<Uuid> {
+            let mut ids: Vec<Uuid> = cache
+                .find_matching_heuristics_in_source(&emb, source, 0.5, 0.0, 10)
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect();
+            ids.sort();
+            ids
+        };
+        let mut expected = vec![chat_id, global_id];
+        expected.sort();
+        assert_eq!(ids(Some("chat")), expected);
+        assert_eq!(ids(Some("email")), vec![global_id]);
+        assert_eq!(ids(None).len(), 3);
+    }
+
     #[test]
     fn test_find_matching_heuristics_ttl_expiry() {
         let mut cache = MemoryCache::new(CacheConfig {
@@ -802,20 +2738,25 @@ mod tests {
             max_heuristics: 50,
             novelty_threshold: 0.9,
             heuristic_ttl_ms: 1, // 1ms TTL — will expire immediately
+            ..CacheConfig::default()
         });
 
         let emb: Vec<f32> = vec![1.0; 384];
         cache.add_heuristic(CachedHeuristic {
             id: Uuid::new_v4(),
             name: "expired".to_string(),
-            condition: serde_json::json!({}),
-            action: serde_json::json!({}),
-            condition_embedding: emb.clone(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: emb.clone().into(),
             confidence: 0.9,
             last_accessed_ms: 0,
             cached_at_ms: 1, // Very old
             hit_count: 0,
             last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
         });
 
         // Wait a tiny bit for TTL to expire
@@ -833,14 +2774,18 @@ mod tests {
         cache.add_heuristic(CachedHeuristic {
             id,
             name: "to_remove".to_string(),
-            condition: serde_json::json!({}),
-            action: serde_json::json!({}),
-            condition_embedding: vec![1.0; 384],
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: vec![1.0; 384].into(),
             confidence: 0.9,
             last_accessed_ms: 0,
             cached_at_ms: 0,
             hit_count: 0,
             last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
         });
 
         assert!(cache.get_heuristic(&id).is_some());
@@ -852,4 +2797,189 @@ mod tests {
         let matches = cache.find_matching_heuristics(&emb, 0.5, 0.0, 10);
         assert!(matches.is_empty());
     }
+
+    #[test]
+    fn test_apply_scoring_outcome_batches_bookkeeping() {
+        let mut cache = MemoryCache::new(CacheConfig { dedup_window_ms: 60_000, ..CacheConfig::default() });
+        let id = Uuid::new_v4();
+        cache.add_heuristic(CachedHeuristic {
+            id,
+            name: "creeper".to_string(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: vec![1.0; 384].into(),
+            confidence: 0.9,
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        });
+
+        let seen = SeenEvent { event_id: "e1".to_string(), source: "game".to_string(), raw_text: "creeper".to_string() };
+        let duplicate_of = cache.apply_scoring_outcome(&ScoringOutcome {
+            matched: vec![(id, 0.8)],
+            hit: Some(true),
+            cooldowns: vec![(id, i64::MAX)],
+            text_match: Some((7, id)),
+            seen: Some(seen.clone()),
+        });
+        assert_eq!(duplicate_of, None);
+        assert_eq!(cache.recent_match(7), Some(id));
+        // A storage match counts as a miss and isn't a cache similarity sample
+        let repeat = SeenEvent { event_id: "e2".to_string(), ..seen };
+        let duplicate_of = cache.apply_scoring_outcome(&ScoringOutcome {
+            matched: vec![(id, 1.0)],
+            hit: Some(false),
+            seen: Some(repeat),
+            ..Default::default()
+        });
+        assert_eq!(duplicate_of.as_deref(), Some("e1"));
+        // No verdict: counters untouched
+        cache.apply_scoring_outcome(&ScoringOutcome::default());
+
+        let stats = cache.stats();
+        assert_eq!((stats.total_hits, stats.total_misses), (1, 1));
+        let h = cache.get_heuristic(&id).unwrap();
+        assert_eq!((h.hit_count, h.cooldown_until_ms), (2, i64::MAX));
+        assert_eq!(cache.mean_match_similarity(&id), Some(0.8));
+    }
+
+    #[test]
+    fn test_tombstone_blocks_reinsert_within_window() {
+        let mut cache = MemoryCache::new(CacheConfig { tombstone_ttl_ms: 60_000, ..CacheConfig::default() });
+        let id = Uuid::new_v4();
+        let heuristic = || CachedHeuristic {
+            id,
+            name: "deleted".to_string(),
+            condition: Condition::default(),
+            effects: Effects::default(),
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: vec![1.0; 384].into(),
+            confidence: 0.9,
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        };
+        assert!(cache.add_heuristic(heuristic()));
+        assert!(cache.tombstone_heuristic(&id));
+
+        // A storage fallback that raced the delete lands afterwards
+        assert!(!cache.add_heuristic(heuristic()));
+        cache.merge_heuristic(heuristic());
+        assert!(cache.get_heuristic(&id).is_none());
+        assert_eq!(cache.stats().suppressed_reinserts, 2);
+
+        // Created again in storage: cacheable again
+        cache.clear_tombstone(&id);
+        assert!(cache.add_heuristic(heuristic()));
+
+        let mut untracked = MemoryCache::new(CacheConfig { tombstone_ttl_ms: 0, ..CacheConfig::default() });
+        untracked.add_heuristic(heuristic());
+        untracked.tombstone_heuristic(&id);
+        assert!(untracked.add_heuristic(heuristic()));
+    }
+
+    #[test]
+    fn test_heuristic_delta_preserves_hit_stats() {
+        let mut cache = MemoryCache::new(CacheConfig::default());
+        let kept = Uuid::new_v4();
+        let deleted = Uuid::new_v4();
+        for (id, name) in [(kept, "kept"), (deleted, "deleted")] {
+            cache.add_heuristic(CachedHeuristic {
+                id,
+                name: name.to_string(),
+                condition: Condition::text("old"),
+                effects: Effects::default(),
+                origin: String::new(),
+                source: String::new(),
+                condition_embedding: vec![1.0; 384].into(),
+                confidence: 0.5,
+                last_accessed_ms: 0,
+                cached_at_ms: 0,
+                hit_count: 0,
+                last_hit_ms: 0,
+                cooldown_until_ms: 0,
+                embedding_model: String::new(),
+            });
+        }
+        cache.touch_heuristic(&kept);
+        cache.touch_heuristic(&kept);
+        let last_hit = cache.get_heuristic(&kept).unwrap().last_hit_ms;
+
+        let (merged, removed) = cache.apply_heuristic_changes(HeuristicChanges {
+            heuristics: vec![CachedHeuristic {
+                id: kept,
+                name: "kept".to_string(),
+                condition: Condition::text("new"),
+                effects: Effects::default(),
+                origin: String::new(),
+                source: String::new(),
+                condition_embedding: Default::default(),
+                confidence: 0.8,
+                last_accessed_ms: 0,
+                cached_at_ms: 0,
+                hit_count: 0,
+                last_hit_ms: 0,
+                cooldown_until_ms: 0,
+                embedding_model: String::new(),
+            }],
+            deleted_ids: vec![deleted, Uuid::new_v4()],
+            latest_updated_ms: 0,
+        });
+        assert_eq!((merged, removed), (1, 1));
+
+        let h = cache.get_heuristic(&kept).unwrap();
+        assert_eq!(h.condition.text, "new");
+        assert!((h.confidence - 0.8).abs() < 0.001);
+        assert_eq!(h.hit_count, 2);
+        assert_eq!(h.last_hit_ms, last_hit);
+        assert_eq!(h.condition_embedding.len(), 384); // empty update keeps embedding
+        assert!(cache.get_heuristic(&deleted).is_none());
+    }
+
+    #[test]
+    fn test_keyword_matches_track_heuristic_changes() {
+        let mut cache = MemoryCache::new(CacheConfig::default());
+        let keyword_heuristic = |id, keywords: serde_json::Value, confidence, source: &str| CachedHeuristic {
+            id,
+            name: "h".to_string(),
+            condition: Condition::text("h"),
+            effects: serde_json::json!({"keywords": keywords}).into(),
+            origin: String::new(),
+            source: source.to_string(),
+            condition_embedding: Default::default(),
+            confidence,
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        };
+        let (lava, scoped) = (Uuid::new_v4(), Uuid::new_v4());
+        cache.add_heuristic(keyword_heuristic(lava, serde_json::json!(["lava"]), 0.6, ""));
+        assert_eq!(cache.find_keyword_matches("lava ahead", None, 0.5, 5), vec![lava]);
+
+        // Added heuristics are picked up; higher confidence sorts first
+        cache.add_heuristic(keyword_heuristic(scoped, serde_json::json!(["lava"]), 0.9, "game"));
+        assert_eq!(cache.find_keyword_matches("lava ahead", Some("game"), 0.5, 5), vec![scoped, lava]);
+        assert_eq!(cache.find_keyword_matches("lava ahead", Some("chat"), 0.5, 5), vec![lava]);
+        assert_eq!(cache.find_keyword_matches("lava ahead", None, 0.7, 5), vec![scoped]);
+
+        // Keyword edits and removals too
+        cache.merge_heuristic(keyword_heuristic(lava, serde_json::json!(["magma"]), 0.6, ""));
+        assert_eq!(cache.find_keyword_matches("magma", None, 0.5, 5), vec![lava]);
+        cache.remove_heuristic(&lava);
+        assert!(cache.find_keyword_matches("magma", None, 0.5, 5).is_empty());
+        cache.flush_heuristics();
+        assert!(cache.find_keyword_matches("lava ahead", None, 0.0, 5).is_empty());
+    }
 }
@@ -0,0 +1,129 @@
+//! Input limits for EvaluateSalience.
+//!
+//! Adapters are trusted to send event-sized input, and one that doesn't (a
+//! multi-megabyte raw_text) stalls embedding for everyone behind it. Events
+//! over a limit are either truncated and evaluated, or rejected with
+//! INVALID_ARGUMENT, per `SALIENCE_OVERSIZE_POLICY`:
+//! - raw_text is cut at the last character boundary within the limit
+//! - entity_ids keeps the first entries
+//! - structured_json can't be cut and stay JSON, so it's dropped (the fast
+//!   path doesn't read it; see compat module)
+//!
+//! Both outcomes are counted (`gladys_salience_input_limited_total`).
+//!
+//! Configuration via environment variables (see `SalienceConfig`):
+//!   SALIENCE_MAX_TEXT_BYTES: raw_text limit (default: 65536, 0 = unlimited)
+//!   SALIENCE_MAX_ENTITY_IDS: entity_ids limit (default: 256, 0 = unlimited)
+//!   SALIENCE_MAX_STRUCTURED_JSON_BYTES: structured_json limit (default: 262144, 0 = unlimited)
+//!   SALIENCE_OVERSIZE_POLICY: "truncate" or "reject" (default: truncate)
+
+use tonic::Status;
+use tracing::warn;
+
+use crate::config::{OversizePolicy, SalienceConfig};
+use crate::metrics::record_input_limited;
+use crate::proto::EvaluateSalienceRequest;
+
+/// Fields of `request` over their configured limit, with the limit.
+fn oversized(request: &EvaluateSalienceRequest, config: &SalienceConfig) -> Vec<(&'static str, usize)> {
+    let over = |len: usize, limit: usize| limit > 0 && len > limit;
+    let mut fields = Vec::new();
+    if over(request.raw_text.len(), config.max_text_bytes) {
+        fields.push(("raw_text", config.max_text_bytes));
+    }
+    if over(request.entity_ids.len(), config.max_entity_ids) {
+        fields.push(("entity_ids", config.max_entity_ids));
+    }
+    if over(request.structured_json.len(), config.max_structured_json_bytes) {
+        fields.push(("structured_json", config.max_structured_json_bytes));
+    }
+    fields
+}
+
+/// Enforce the input limits on `request`, truncating it in place or
+/// returning INVALID_ARGUMENT (boxed; Status is large). Returns the
+/// truncated field names.
+pub fn enforce_limits(
+    request: &mut EvaluateSalienceRequest,
+    config: &SalienceConfig,
+) -> Result<Vec<&'static str>, Box<Status>> {
+    let fields = oversized(request, config);
+    if fields.is_empty() {
+        return Ok(Vec::new());
+    }
+    let described: Vec<String> = fields.iter().map(|(field, limit)| format!("{} (limit {})", field, limit)).collect();
+
+    if config.oversize_policy == OversizePolicy::Reject {
+        record_input_limited("rejected");
+        warn!(event_id = %request.event_id, source = %request.source, fields = ?described, "Rejecting oversized event");
+        return Err(Box::new(Status::invalid_argument(format!("Event exceeds input limits: {}", described.join(", ")))));
+    }
+
+    for (field, limit) in &fields {
+        match *field {
+            "raw_text" => truncate_at_char_boundary(&mut request.raw_text, *limit),
+            "entity_ids" => request.entity_ids.truncate(*limit),
+            _ => request.structured_json.clear(),
+        }
+    }
+    record_input_limited("truncated");
+    warn!(event_id = %request.event_id, source = %request.source, fields = ?described, "Truncated oversized event");
+    Ok(fields.into_iter().map(|(field, _)| field).collect())
+}
+
+/// Shorten `text` to at most `max_bytes` without splitting a character.
+fn truncate_at_char_boundary(text: &mut String, max_bytes: usize) {
+    if text.len() <= max_bytes {
+        return;
+    }
+    let end = (0..=max_bytes).rev().find(|&i| text.is_char_boundary(i)).unwrap_or(0);
+    text.truncate(end);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(policy: OversizePolicy) -> SalienceConfig {
+        SalienceConfig {
+            max_text_bytes: 8,
+            max_entity_ids: 2,
+            max_structured_json_bytes: 16,
+            oversize_policy: policy,
+            ..SalienceConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_truncates_oversized_fields() {
+        let mut request = EvaluateSalienceRequest {
+            // "é" is two bytes, so byte 8 falls inside the fourth one
+            raw_text: "aéééé".to_string(),
+            entity_ids: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            structured_json: r#"{"key": "a long value"}"#.to_string(),
+            ..Default::default()
+        };
+        let truncated = enforce_limits(&mut request, &limits(OversizePolicy::Truncate)).unwrap();
+        assert_eq!(truncated, vec!["raw_text", "entity_ids", "structured_json"]);
+        assert_eq!(request.raw_text, "aééé");
+        assert_eq!(request.entity_ids.len(), 2);
+        assert!(request.structured_json.is_empty());
+
+        // Within limits: untouched
+        let mut small = EvaluateSalienceRequest { raw_text: "hi".to_string(), ..Default::default() };
+        assert!(enforce_limits(&mut small, &limits(OversizePolicy::Reject)).unwrap().is_empty());
+        assert_eq!(small.raw_text, "hi");
+    }
+
+    #[test]
+    fn test_rejects_oversized_event() {
+        let mut request = EvaluateSalienceRequest { raw_text: "x".repeat(9), ..Default::default() };
+        let status = enforce_limits(&mut request, &limits(OversizePolicy::Reject)).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("raw_text (limit 8)"));
+
+        // 0 = unlimited
+        let unlimited = SalienceConfig { max_text_bytes: 0, ..limits(OversizePolicy::Reject) };
+        assert!(enforce_limits(&mut request, &unlimited).is_ok());
+    }
+}
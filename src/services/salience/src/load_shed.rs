@@ -0,0 +1,217 @@
+//! Adaptive sampling of low-value events under overload.
+//!
+//! When evaluations pile up faster than they finish, scoring every event
+//! from a source that never scores anything only adds to the backlog. With
+//! shedding on, EvaluateSalience skips a share of the events from such
+//! sources: they get baseline salience at once, with `shed` set.
+//!
+//! - Load is the number of evaluations in flight, smoothed over recent
+//!   arrivals so a brief burst doesn't trigger shedding
+//! - Past SALIENCE_SHED_QUEUE_START the share skipped ramps linearly with the
+//!   smoothed depth, reaching SALIENCE_SHED_MAX_RATE at
+//!   SALIENCE_SHED_QUEUE_FULL. The rest are still scored, so a source that
+//!   turns interesting is noticed
+//! - Only sources whose mean composite score (over their recent scored
+//!   events, at least SALIENCE_SHED_MIN_EVENTS of them) is at most
+//!   SALIENCE_SHED_MAX_SCORE are sampled; every other event is scored
+//!
+//! The smoothed depth, current rate and events shed are reported in health
+//! details.
+//!
+//! Configuration via environment variables (see `SalienceConfig`):
+//!   SALIENCE_SHED_QUEUE_START: In-flight evaluations at which shedding starts (default: 0 = off)
+//!   SALIENCE_SHED_QUEUE_FULL: In-flight evaluations at which the rate peaks (default: 2x start)
+//!   SALIENCE_SHED_MAX_RATE: Largest share of a low-value source's events skipped (default: 0.9)
+//!   SALIENCE_SHED_MAX_SCORE: Mean composite score at or below which a source is low-value (default: 0.1)
+//!   SALIENCE_SHED_MIN_EVENTS: Scored events before a source's mean counts (default: 100)
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::config::SalienceConfig;
+
+/// Weight of each arrival's depth in the smoothed depth.
+const DEPTH_SMOOTHING: f32 = 0.05;
+
+/// Events a source's mean is taken over (older ones fade out).
+const HISTORY_WINDOW: u64 = 1000;
+
+/// Sources tracked; new ones past this are never shed.
+const MAX_SOURCES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct SourceHistory {
+    mean: f32,
+    events: u64,
+}
+
+/// Decides which events to skip under load.
+pub struct LoadShedder {
+    start: f32,
+    full: f32,
+    max_rate: f32,
+    max_score: f32,
+    min_events: u64,
+    in_flight: AtomicUsize,
+    depth: Mutex<f32>,
+    history: Mutex<HashMap<String, SourceHistory>>,
+    rng: AtomicU64,
+    shed: AtomicU64,
+}
+
+/// Counts an evaluation as in flight until dropped.
+pub struct InFlight<'a> {
+    shedder: &'a LoadShedder,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.shedder.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl LoadShedder {
+    /// None when shedding is off (SALIENCE_SHED_QUEUE_START = 0).
+    pub fn from_config(config: &SalienceConfig) -> Option<Self> {
+        if config.shed_queue_start == 0 {
+            return None;
+        }
+        let full = if config.shed_queue_full > config.shed_queue_start {
+            config.shed_queue_full
+        } else {
+            config.shed_queue_start * 2
+        };
+        Some(Self {
+            start: config.shed_queue_start as f32,
+            full: full as f32,
+            max_rate: config.shed_max_rate.clamp(0.0, 1.0),
+            max_score: config.shed_max_score,
+            min_events: config.shed_min_events,
+            in_flight: AtomicUsize::new(0),
+            depth: Mutex::new(0.0),
+            history: Mutex::new(HashMap::new()),
+            rng: AtomicU64::new(0),
+            shed: AtomicU64::new(0),
+        })
+    }
+
+    /// Count a new evaluation in flight and fold the depth into the average.
+    pub fn enter(&self) -> InFlight<'_> {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut depth = self.depth.lock().unwrap_or_else(|e| e.into_inner());
+        *depth += DEPTH_SMOOTHING * (in_flight as f32 - *depth);
+        InFlight { shedder: self }
+    }
+
+    /// Share of a low-value source's events skipped at the current depth.
+    pub fn rate(&self) -> f32 {
+        let depth = *self.depth.lock().unwrap_or_else(|e| e.into_inner());
+        if depth < self.start {
+            return 0.0;
+        }
+        self.max_rate * ((depth - self.start) / (self.full - self.start)).min(1.0)
+    }
+
+    /// Whether to skip this event from `source` (counted when so).
+    pub fn should_shed(&self, source: &str) -> bool {
+        let rate = self.rate();
+        if rate <= 0.0 || !self.is_low_value(source) || self.roll() >= rate {
+            return false;
+        }
+        self.shed.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+    fn is_low_value(&self, source: &str) -> bool {
+        let history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        history.get(source).is_some_and(|h| h.events >= self.min_events && h.mean <= self.max_score)
+    }
+
+    /// Fold a scored event's composite score into its source's mean.
+    pub fn observe(&self, source: &str, composite_score: f32) {
+        let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        if !history.contains_key(source) && history.len() >= MAX_SOURCES {
+            return;
+        }
+        let h = history.entry(source.to_string()).or_default();
+        h.events += 1;
+        h.mean += (composite_score - h.mean) / h.events.min(HISTORY_WINDOW) as f32;
+    }
+
+    /// Events shed since startup.
+    pub fn shed_total(&self) -> u64 {
+        self.shed.load(Ordering::Relaxed)
+    }
+
+    /// Uniform in [0, 1) from a splitmix64 sequence (as in the faults module).
+    fn roll(&self) -> f32 {
+        const GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut z = self.rng.fetch_add(GAMMA, Ordering::Relaxed).wrapping_add(GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn health_details(&self) -> HashMap<String, String> {
+        let depth = *self.depth.lock().unwrap_or_else(|e| e.into_inner());
+        let mut details = HashMap::new();
+        details.insert("shed_in_flight".to_string(), self.in_flight.load(Ordering::Relaxed).to_string());
+        details.insert("shed_depth".to_string(), format!("{:.1}", depth));
+        details.insert("shed_rate".to_string(), format!("{:.2}", self.rate()));
+        details.insert("shed_total".to_string(), self.shed_total().to_string());
+        details
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shedder() -> LoadShedder {
+        let config = SalienceConfig {
+            shed_queue_start: 10,
+            shed_queue_full: 20,
+            shed_max_rate: 0.5,
+            shed_max_score: 0.1,
+            shed_min_events: 5,
+            ..SalienceConfig::default()
+        };
+        LoadShedder::from_config(&config).unwrap()
+    }
+
+    #[test]
+    fn test_rate_ramps_with_depth() {
+        assert!(LoadShedder::from_config(&SalienceConfig { shed_queue_start: 0, ..SalienceConfig::default() }).is_none());
+        let shedder = shedder();
+        assert_eq!(shedder.rate(), 0.0);
+        for (depth, rate) in [(5.0, 0.0), (15.0, 0.25), (40.0, 0.5)] {
+            *shedder.depth.lock().unwrap() = depth;
+            assert!((shedder.rate() - rate).abs() < 1e-6, "depth {}", depth);
+        }
+
+        // A brief burst barely moves the smoothed depth
+        *shedder.depth.lock().unwrap() = 0.0;
+        let burst: Vec<_> = (0..15).map(|_| shedder.enter()).collect();
+        assert_eq!(shedder.rate(), 0.0);
+        drop(burst);
+        assert_eq!(shedder.in_flight.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_only_low_value_sources_are_sampled() {
+        let shedder = shedder();
+        for _ in 0..5 {
+            shedder.observe("telemetry", 0.02);
+            shedder.observe("chat", 0.6);
+        }
+        shedder.observe("new", 0.0);
+        *shedder.depth.lock().unwrap() = 40.0;
+
+        let shed = (0..1000).filter(|_| shedder.should_shed("telemetry")).count();
+        assert!((400..600).contains(&shed), "shed {}", shed);
+        assert!((0..100).all(|_| !shedder.should_shed("chat") && !shedder.should_shed("new")));
+        assert_eq!(shedder.shed_total(), shed as u64);
+    }
+}
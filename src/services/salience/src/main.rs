@@ -8,38 +8,114 @@
 //! - Heuristic lookup for System 1 fast responses
 //! - gRPC server for SalienceGateway service
 //!
-//! On cache miss, queries Python storage via QueryMatchingHeuristics RPC.
+//! On cache miss, queries Python storage via QueryMatchingHeuristics RPC, or
+//! a local SQLite file in deployments without the Python service (see sqlite
+//! module, behind the `sqlite` feature), or reads matches straight from
+//! Postgres while writes still go through Python (see postgres module,
+//! behind the `postgres` feature).
+//! Optionally, a background loop keeps the cache in sync (see refresh module)
+//! and a seed file primes it at startup (see seed module). An A/B experiment
+//! can split scoring across variants (see experiments module), events can be
+//! scored per detected language (see language module), embedding matches
+//! can be gated on a similarity curve calibrated from labeled feedback (see
+//! calibration module), and replicas can share a Redis cache tier (see
+//! shared_cache module). Events can also
+//! arrive over NATS or MQTT instead of gRPC (see the bus and mqtt modules,
+//! behind the `nats` and `mqtt` features), and decisions can be written back
+//! onto the stored events (see writeback module). A latency SLO can be
+//! tracked with breach alerts (see slo module), and cache capacities can
+//! adapt to the observed hit rate and churn (see cache_sizing module).
+//! Cached embeddings can be packed into slabs while idle (see arena module)
+//! and are never compared across embedding model versions (see
+//! embedding_model module),
+//! health checks can hold off traffic until the cache warms up (see warmup
+//! module), and whitelisted local actions can run straight off a match (see
+//! actions module). Noisy sources can be silenced without scoring them (see
+//! source_filter module), sources can be given separate concurrency budgets
+//! so bulk traffic is shed first under overload (see qos module), and events
+//! from sources that rarely score can be sampled while load is high (see
+//! load_shed module). While the cache is re-embedded or storage migrated,
+//! maintenance mode answers every event with canned salience (see
+//! maintenance module).
+//! In front of several instances, one can act as a router that sends each
+//! source to the same backend (see router module). At
+//! startup, storage's schema version is checked against ours (see schema
+//! module), and a new instance can copy the cache of the one it replaces
+//! before reporting ready (see standby module). It can run under systemd or
+//! as a Windows service, shutting down gracefully when stopped (see daemon
+//! module). `--version` and the GetVersion RPC report exactly which build is
+//! running (see version module).
 //!
 //! Configuration is loaded from environment variables.
 //! See config module for available settings.
 
 use std::sync::Arc;
-use tokio::sync::RwLock;
 
 use gladys_memory::{
-    CacheConfig, Config, MemoryCache, run_server, setup_logging,
-    SalienceScorer, EmbeddingSimilarityScorer, GrpcStorageBackend
+    CacheHandle, Config, MemoryCache, run_server, setup_logging, ServerOptions,
+    SalienceScorer, EmbeddingSimilarityScorer, GrpcStorageBackend, StorageBackend
 };
-use tracing::info;
+use gladys_memory::actions::ActionRegistry;
+use gladys_memory::arena::{run_compaction_loop, CompactionStatusHandle};
+use gladys_memory::embedding_model::run_reembed_loop;
+use gladys_memory::config::{ModelChangePolicy, RefreshRole, StorageKind};
+use gladys_memory::cache_sizing::run_cache_sizing;
+use gladys_memory::experiments::{parse_experiment, Experiment};
+use gladys_memory::language::{parse_language_profiles, LanguageDetector};
+use gladys_memory::qos::{parse_qos_classes, QosScheduler};
+#[cfg(feature = "prometheus")]
+use gladys_memory::metrics::serve_metrics;
+use gladys_memory::rate_limit::TokenBucket;
+use gladys_memory::recording::{RecordingStorage, ReplayStorage};
+use gladys_memory::refresh::{run_refresh_loop, RefreshStatusHandle};
+use gladys_memory::peers::PeerSet;
+use gladys_memory::router::EvaluationRouter;
+use gladys_memory::schema::check_schema_version;
+use gladys_memory::seed::load_seed_file;
+use gladys_memory::standby::{run_standby, PRIMING_STEP};
+use gladys_memory::warmup::Warmup;
+use gladys_memory::shared_cache::{run_invalidation_listener, SharedCache};
+use gladys_memory::version;
+use gladys_memory::worker_pool::WorkerPool;
+use tracing::{info, warn};
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::args().nth(1).as_deref() == Some("--version") {
+        println!("{}", version::version_line());
+        return Ok(());
+    }
+
+    // Windows service hosting (see daemon module)
+    #[cfg(windows)]
+    if let Some(arg) = std::env::args().nth(1) {
+        use gladys_memory::daemon::windows;
+        match arg.as_str() {
+            "--service" => return Ok(windows::run_service(serve)?),
+            "--install-service" => return Ok(windows::install_service()?),
+            "--uninstall-service" => return Ok(windows::uninstall_service()?),
+            _ => {}
+        }
+    }
+    serve()
+}
+
+/// Run the service until it's asked to stop.
+fn serve() -> Result<(), Box<dyn std::error::Error>> {
+    tokio::runtime::Runtime::new()?.block_on(run())
+}
+
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize structured logging (must hold guard for app lifetime)
     let _log_guard = setup_logging("memory-rust");
 
-    info!("Starting GLADyS Memory Fast Path");
+    info!(version = version::VERSION, commit = version::GIT_COMMIT, "Starting GLADyS Memory Fast Path");
 
     // Load configuration from environment variables
     let config = Config::from_env();
     config.log_config();
 
     // Initialize empty LRU cache - heuristics are loaded on-demand from storage
-    let cache = MemoryCache::new(CacheConfig {
-        max_events: config.cache.max_events,
-        max_heuristics: config.cache.max_heuristics,
-        novelty_threshold: config.cache.novelty_threshold,
-        heuristic_ttl_ms: config.cache.heuristic_ttl_ms,
-    });
+    let cache = MemoryCache::new(config.cache.clone());
     info!(
         max_events = cache.stats().max_events,
         max_heuristics = config.cache.max_heuristics,
@@ -47,17 +123,197 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     // Wrap cache in Arc<RwLock> for shared access across async tasks
-    let cache = Arc::new(RwLock::new(cache));
+    let cache = CacheHandle::new(cache);
+
+    // Shared storage backend: used by the scorer and by RPCs that need embeddings
+    let storage: Arc<dyn StorageBackend> = match &config.storage.replay_path {
+        Some(path) => {
+            info!(path = %path, "Serving storage from a recording");
+            Arc::new(ReplayStorage::load(path)?)
+        }
+        None if config.storage.backend == StorageKind::Sqlite => open_sqlite(&config)?,
+        None if config.storage.backend == StorageKind::Postgres => open_postgres(&config)?,
+        None => Arc::new(GrpcStorageBackend::new(config.storage.clone())),
+    };
+
+    // Contract handshake with storage (nothing to check when replaying; a
+    // SQLite file's version is checked on open)
+    let schema = match (&config.storage.replay_path, config.storage.backend) {
+        (Some(_), _) | (None, StorageKind::Sqlite) => None,
+        (None, StorageKind::Grpc | StorageKind::Postgres) => {
+            let client_config = GrpcStorageBackend::new(config.storage.clone()).client_config();
+            let schema = check_schema_version(client_config).await;
+            schema.enforce(config.storage.schema_mismatch)?;
+            Some(schema)
+        }
+    };
+
+    // Record storage interactions for replay (see recording module)
+    let storage: Arc<dyn StorageBackend> = match &config.storage.record_path {
+        Some(path) => {
+            info!(path = %path, "Recording storage interactions");
+            Arc::new(RecordingStorage::create(storage, path)?)
+        }
+        None => storage,
+    };
+
+    // Storage fault injection for resilience tests (InjectFaults RPC)
+    #[cfg(feature = "fault-injection")]
+    let faults = gladys_memory::faults::FaultInjector::default();
+    #[cfg(feature = "fault-injection")]
+    let storage: Arc<dyn StorageBackend> = {
+        warn!("Built with fault-injection: InjectFaults can degrade storage calls; not for production");
+        Arc::new(gladys_memory::faults::FaultyStorage::new(storage, faults.clone()))
+    };
+
+    // Cold-start priming (optional). With a warm-up gate, seeding runs in the
+    // background and the health checks report progress until the gate opens.
+    let warmup = (config.warmup.enabled() || config.standby.enabled()).then(|| Arc::new(Warmup::new(config.warmup.clone())));
+    if let Some(path) = config.seed.path.clone() {
+        match &warmup {
+            Some(warmup) => {
+                warmup.begin_priming("seed");
+                let (warmup, cache, storage, persist) = (warmup.clone(), cache.clone(), storage.clone(), config.seed.persist);
+                tokio::spawn(async move {
+                    if let Err(e) = load_seed_file(&path, &cache, storage.as_ref(), persist).await {
+                        warn!(error = %e, "Seed heuristics not loaded");
+                    }
+                    warmup.end_priming("seed");
+                });
+            }
+            None => {
+                if let Err(e) = load_seed_file(&path, &cache, storage.as_ref(), config.seed.persist).await {
+                    warn!(error = %e, "Seed heuristics not loaded");
+                }
+            }
+        }
+    }
+
+    // Warm standby: copy the cache of the instance being replaced (optional);
+    // readiness waits for the snapshot
+    if config.standby.enabled() {
+        if let Some(warmup) = &warmup {
+            warmup.begin_priming(PRIMING_STEP);
+        }
+        tokio::spawn(run_standby(config.standby.clone(), cache.clone(), warmup.clone()));
+    }
+
+    // Cache scans off the runtime threads (optional), shared by all scorers
+    let pool = WorkerPool::for_scoring(&config.salience).map(Arc::new);
+
+    // Cross-replica shared cache tier (optional)
+    let shared_cache = SharedCache::from_config(&config.shared_cache).map(Arc::new);
+    if let Some(shared) = &shared_cache {
+        if let Err(e) = shared.sync_generation().await {
+            warn!(error = %e, "Shared cache unreachable at startup; continuing without it until it recovers");
+        }
+        tokio::spawn(run_invalidation_listener(shared.clone(), cache.clone()));
+    }
 
     // Create the scoring strategy
-    let scorer = create_scorer(&config, cache.clone());
+    let scorer = create_scorer(&config, cache.clone(), storage.clone(), pool.clone(), shared_cache.clone());
 
     info!(
+        storage_backend = config.storage.backend.as_str(),
         storage_address = %config.storage.address,
         scorer = %config.scorer,
         "Storage backend and scorer configured"
     );
 
+    // A/B experiment (optional); variants naming a scorer get their own instance
+    let experiment = config.experiment.as_deref().and_then(|spec| match parse_experiment(spec) {
+        Ok(experiment) => {
+            info!(experiment = %experiment.name, variants = experiment.variants.len(), "Experiment enabled");
+            Some(Experiment::new(experiment, |scorer| {
+                let variant_config = Config { scorer: scorer.to_string(), ..config.clone() };
+                create_scorer(&variant_config, cache.clone(), storage.clone(), pool.clone(), shared_cache.clone())
+            }))
+        }
+        Err(e) => {
+            warn!(error = %e, "Experiment disabled");
+            None
+        }
+    });
+
+    // Language tagging (optional); profiles naming a scorer get their own instance
+    let profiles = config.language_profiles.as_deref().map(parse_language_profiles).transpose().unwrap_or_else(|e| {
+        warn!(error = %e, "Invalid SALIENCE_LANGUAGE_PROFILES; no per-language profiles");
+        None
+    });
+    let languages = (config.salience.language_detection || profiles.is_some()).then(|| {
+        #[cfg(not(feature = "language"))]
+        warn!("Language detection is configured but this build lacks the language feature; every event is undetermined");
+        LanguageDetector::new(profiles.unwrap_or_default(), config.salience.language_min_confidence, |scorer| {
+            let profile_config = Config { scorer: scorer.to_string(), ..config.clone() };
+            create_scorer(&profile_config, cache.clone(), storage.clone(), pool.clone(), shared_cache.clone())
+        })
+    });
+
+    // Per-source QoS classes (optional)
+    let qos = config.qos_classes.as_deref().and_then(|json| match parse_qos_classes(json) {
+        Ok(classes) => Some(QosScheduler::new(classes, config.qos_default_class.as_deref())),
+        Err(e) => {
+            warn!(error = %e, "Invalid SALIENCE_QOS_CLASSES; no QoS budgets");
+            None
+        }
+    });
+
+    // Prometheus scrape endpoint (optional)
+    if config.server.metrics_port != 0 {
+        #[cfg(feature = "prometheus")]
+        {
+            let metrics_addr = format!("{}:{}", config.server.host, config.server.metrics_port).parse()?;
+            tokio::spawn(async move {
+                if let Err(e) = serve_metrics(metrics_addr).await {
+                    warn!(error = %e, "Metrics endpoint stopped");
+                }
+            });
+        }
+        #[cfg(not(feature = "prometheus"))]
+        warn!(metrics_port = config.server.metrics_port, "METRICS_PORT is set but this build lacks the prometheus feature; not serving");
+    }
+
+    // Peer replicas, for notification relay and refresh broadcasts (optional)
+    let peers = PeerSet::from_config(&config.peers).map(Arc::new);
+
+    // Background heuristic refresh (optional; default is on-demand loading)
+    let refresh_status = if config.refresh.enabled() {
+        if config.refresh.role == RefreshRole::Leader && peers.is_none() {
+            warn!("REFRESH_ROLE=leader without PEER_ADDRESSES; nothing to broadcast to");
+        }
+        let status = RefreshStatusHandle::default();
+        tokio::spawn(run_refresh_loop(
+            config.refresh.clone(),
+            cache.clone(),
+            storage.clone(),
+            status.clone(),
+            peers.clone(),
+        ));
+        Some(status)
+    } else {
+        None
+    };
+
+    // Adaptive cache capacities (optional; default is fixed sizes)
+    if config.cache_sizing.enabled() {
+        tokio::spawn(run_cache_sizing(config.cache_sizing.clone(), cache.clone()));
+    }
+
+    // Embedding compaction while idle (optional)
+    let compaction_status = (config.cache.compaction_interval_ms > 0).then(|| {
+        let status = CompactionStatusHandle::default();
+        tokio::spawn(run_compaction_loop(config.cache.compaction_interval_ms, cache.clone(), status.clone()));
+        status
+    });
+
+    // Re-embed cached heuristics after an embedding model change (optional; default is to flush them)
+    if config.cache.model_change_policy == ModelChangePolicy::Reembed {
+        tokio::spawn(run_reembed_loop(cache.clone(), storage.clone()));
+    }
+
+    // Whitelisted System-1 actions (optional)
+    let actions = config.actions.enabled().then(|| Arc::new(ActionRegistry::new(config.actions.clone()).with_builtins()));
+
     // Start the gRPC server
     info!(
         host = %config.server.host,
@@ -65,26 +321,103 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "Starting gRPC server"
     );
 
-    // This runs until the server is shut down (Ctrl+C)
+    // This runs until shutdown is requested (Ctrl+C, SIGTERM, or service stop)
     // The scorer handles heuristic matching (with cache-first logic)
-    run_server(config.server, config.salience, scorer, cache).await?;
+    let router = EvaluationRouter::from_config(&config.router)?;
+    let options = ServerOptions {
+        refresh_status,
+        compaction_status,
+        schema,
+        warmup,
+        actions,
+        experiment,
+        languages,
+        shared_cache,
+        peers,
+        router,
+        bus: Some(config.bus),
+        mqtt: Some(config.mqtt),
+        writeback: Some(config.writeback),
+        slo: Some(config.slo),
+        qos,
+        #[cfg(feature = "fault-injection")]
+        faults: Some(faults),
+    };
+    run_server(config.server, config.salience, scorer, cache, storage, options).await?;
 
     info!("Memory Fast Path shutdown complete");
     Ok(())
 }
 
+/// Local SQLite storage (STORAGE_BACKEND=sqlite; see sqlite module).
+fn open_sqlite(config: &Config) -> Result<Arc<dyn StorageBackend>, Box<dyn std::error::Error>> {
+    #[cfg(feature = "sqlite")]
+    {
+        let path = &config.storage.sqlite_path;
+        info!(path = %path, "Serving storage from a local SQLite database");
+        let storage = gladys_memory::sqlite::SqliteStorageBackend::open(path, config.salience.min_heuristic_similarity)?;
+        Ok(Arc::new(storage))
+    }
+    #[cfg(not(feature = "sqlite"))]
+    {
+        let _ = config;
+        Err("STORAGE_BACKEND=sqlite but this build lacks the sqlite feature".into())
+    }
+}
+
+/// Matching straight from Postgres, the rest through Python
+/// (STORAGE_BACKEND=postgres; see postgres module).
+fn open_postgres(config: &Config) -> Result<Arc<dyn StorageBackend>, Box<dyn std::error::Error>> {
+    #[cfg(feature = "postgres")]
+    {
+        info!("Reading heuristic matches straight from Postgres");
+        let storage = gladys_memory::postgres::PgStorageBackend::new(
+            config.storage.clone(),
+            config.salience.min_heuristic_similarity,
+        )?;
+        Ok(Arc::new(storage))
+    }
+    #[cfg(not(feature = "postgres"))]
+    {
+        let _ = config;
+        Err("STORAGE_BACKEND=postgres but this build lacks the postgres feature".into())
+    }
+}
+
 /// Factory function to create the requested salience scorer.
 fn create_scorer(
     config: &Config,
-    cache: Arc<RwLock<MemoryCache>>,
+    cache: CacheHandle,
+    storage: Arc<dyn StorageBackend>,
+    pool: Option<Arc<WorkerPool>>,
+    shared_cache: Option<Arc<SharedCache>>,
 ) -> Box<dyn SalienceScorer> {
     match config.scorer.as_str() {
         "embedding" | "" => {
-            let backend = Box::new(GrpcStorageBackend::new(config.storage.clone()));
-            Box::new(EmbeddingSimilarityScorer::new(
+            Box::new(
+                EmbeddingSimilarityScorer::new(
+                    cache,
+                    storage,
+                    config.salience.min_heuristic_similarity,
+                    config.salience.min_heuristic_confidence,
+                )
+                .with_fallback_limit(TokenBucket::for_fallback(&config.salience))
+                .with_fallback_negative_ttl(config.salience.fallback_negative_ttl_ms)
+                .with_keyword_prefilter(config.salience.keyword_prefilter)
+                .with_embedding_fallback(config.salience.embedding_fallback.clone())
+                .with_worker_pool(pool)
+                .with_shared_cache(shared_cache),
+            )
+        }
+        #[cfg(feature = "word-overlap")]
+        "word_overlap" => {
+            if !config.refresh.enabled() {
+                warn!("word_overlap scorer only sees cached heuristics; set REFRESH_INTERVAL_MS to populate the cache");
+            }
+            Box::new(gladys_memory::WordOverlapScorer::new(
                 cache,
-                backend,
-                config.salience.min_heuristic_similarity,
+                config.salience.word_overlap_min_ratio,
+                config.salience.word_overlap_min_words,
                 config.salience.min_heuristic_confidence,
             ))
         }
@@ -100,8 +433,19 @@ mod tests {
     #[test]
     fn test_create_scorer_default() {
         let config = Config::default();
-        let cache = Arc::new(RwLock::new(MemoryCache::new(CacheConfig::default())));
-        let scorer = create_scorer(&config, cache);
+        let cache = CacheHandle::new(MemoryCache::new(CacheConfig::default()));
+        let storage: Arc<dyn StorageBackend> = Arc::new(GrpcStorageBackend::new(config.storage.clone()));
+        let scorer = create_scorer(&config, cache, storage, None, None);
         assert_eq!(scorer.config()["scorer"], "embedding_similarity");
     }
+
+    #[cfg(feature = "word-overlap")]
+    #[test]
+    fn test_create_scorer_word_overlap() {
+        let config = Config { scorer: "word_overlap".to_string(), ..Config::default() };
+        let cache = CacheHandle::new(MemoryCache::new(CacheConfig::default()));
+        let storage: Arc<dyn StorageBackend> = Arc::new(GrpcStorageBackend::new(config.storage.clone()));
+        let scorer = create_scorer(&config, cache, storage, None, None);
+        assert_eq!(scorer.config()["scorer"], "word_overlap");
+    }
 }
@@ -0,0 +1,196 @@
+//! Maintenance mode: canned salience while the cache or storage is busy.
+//!
+//! Re-embedding the cache or migrating storage leaves scoring slow or
+//! wrong for a while. In maintenance mode EvaluateSalience answers every
+//! event at once with a fixed salience profile, with `maintenance` set: no
+//! embedding, cache lookup or storage call. Like filtered events, these
+//! aren't recorded as decisions or counted in the salience summary.
+//!
+//! - The profile is threat, habituation and salience vector as JSON, e.g.
+//!   `{"threat": 0.0, "vector": {"novelty": 0.3}}`; it goes through the usual
+//!   floors, ceilings and normalization, so the composite score and routing
+//!   hint are filled in. Without one, events get baseline salience
+//! - The SetMaintenanceMode admin RPC turns the mode on or off at runtime
+//!   (and can swap the profile); an empty request just reports it
+//! - Health checks keep answering as before. Health details report whether
+//!   the mode is on, since when and why
+//!
+//! Configuration via environment variables (see `SalienceConfig`):
+//!   SALIENCE_MAINTENANCE: Start in maintenance mode (default: false)
+//!   SALIENCE_MAINTENANCE_PROFILE: Salience profile as JSON (default: none = baseline)
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::SalienceConfig;
+use crate::current_time_ms;
+use crate::proto::SalienceResult;
+
+/// Model ID reported on canned salience.
+pub const MAINTENANCE_MODEL_ID: &str = "maintenance";
+
+/// The salience every event gets in maintenance mode.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MaintenanceProfile {
+    #[serde(default)]
+    pub threat: f32,
+    #[serde(default)]
+    pub habituation: f32,
+    #[serde(default)]
+    pub vector: HashMap<String, f32>,
+}
+
+impl MaintenanceProfile {
+    /// Baseline salience: novelty only.
+    pub fn baseline(novelty: f32) -> Self {
+        Self { vector: HashMap::from([("novelty".to_string(), novelty)]), ..Default::default() }
+    }
+
+    pub fn parse(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// The profile as unprocessed salience.
+    pub fn salience(&self) -> SalienceResult {
+        SalienceResult {
+            threat: self.threat,
+            salience: 0.0,
+            habituation: self.habituation,
+            vector: self.vector.clone(),
+            model_id: MAINTENANCE_MODEL_ID.to_string(),
+        }
+    }
+}
+
+/// Maintenance mode as of one moment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaintenanceState {
+    pub enabled: bool,
+    /// When the mode last changed (0 = never since startup)
+    pub since_ms: i64,
+    pub reason: String,
+    pub profile: MaintenanceProfile,
+}
+
+/// Whether events get canned salience, and which.
+#[derive(Debug)]
+pub struct Maintenance {
+    state: RwLock<MaintenanceState>,
+}
+
+impl Maintenance {
+    pub fn new(enabled: bool, profile: MaintenanceProfile) -> Self {
+        let since_ms = if enabled { current_time_ms() } else { 0 };
+        let reason = if enabled { "configured at startup".to_string() } else { String::new() };
+        Self { state: RwLock::new(MaintenanceState { enabled, since_ms, reason, profile }) }
+    }
+
+    /// As configured; an invalid profile falls back to baseline salience.
+    pub fn from_config(config: &SalienceConfig) -> Self {
+        let baseline = MaintenanceProfile::baseline(config.baseline_novelty);
+        let profile = match config.maintenance_profile.as_deref().map(MaintenanceProfile::parse) {
+            Some(Ok(profile)) => profile,
+            Some(Err(e)) => {
+                warn!(error = %e, "Invalid SALIENCE_MAINTENANCE_PROFILE; maintenance mode answers with baseline salience");
+                baseline
+            }
+            None => baseline,
+        };
+        Self::new(config.maintenance, profile)
+    }
+
+    /// The canned salience, when the mode is on.
+    pub fn active_profile(&self) -> Option<MaintenanceProfile> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        state.enabled.then(|| state.profile.clone())
+    }
+
+    pub fn state(&self) -> MaintenanceState {
+        self.state.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Turn the mode on or off and/or replace the profile; returns the new
+    /// state. `since_ms` only moves when the mode flips.
+    pub fn update(&self, enabled: Option<bool>, profile: Option<MaintenanceProfile>, reason: &str) -> MaintenanceState {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(enabled) = enabled {
+            if enabled != state.enabled {
+                state.enabled = enabled;
+                state.since_ms = current_time_ms();
+            }
+            state.reason = if enabled { reason.to_string() } else { String::new() };
+        }
+        if let Some(profile) = profile {
+            state.profile = profile;
+        }
+        state.clone()
+    }
+
+    pub fn health_details(&self) -> HashMap<String, String> {
+        let state = self.state();
+        let mut details = HashMap::new();
+        details.insert("maintenance".to_string(), state.enabled.to_string());
+        if state.enabled {
+            details.insert("maintenance_since_ms".to_string(), state.since_ms.to_string());
+            details.insert("maintenance_reason".to_string(), state.reason);
+        }
+        details
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_parsing() {
+        let profile = MaintenanceProfile::parse(r#"{"threat": 0.2, "vector": {"novelty": 0.3}}"#).unwrap();
+        assert_eq!(profile.threat, 0.2);
+        assert_eq!(profile.habituation, 0.0);
+        assert_eq!(profile.vector["novelty"], 0.3);
+        assert_eq!(MaintenanceProfile::parse(&profile.to_json()).unwrap(), profile);
+        assert!(MaintenanceProfile::parse(r#"{"threat": 0.2, "salience": 1.0}"#).is_err());
+        assert_eq!(profile.salience().model_id, MAINTENANCE_MODEL_ID);
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config = SalienceConfig {
+            maintenance: true,
+            maintenance_profile: Some("{".to_string()),
+            baseline_novelty: 0.2,
+            ..SalienceConfig::default()
+        };
+        assert_eq!(Maintenance::from_config(&config).active_profile(), Some(MaintenanceProfile::baseline(0.2)));
+        let off = SalienceConfig { maintenance: false, ..config };
+        assert!(Maintenance::from_config(&off).active_profile().is_none());
+    }
+
+    #[test]
+    fn test_toggle() {
+        let maintenance = Maintenance::new(false, MaintenanceProfile::baseline(0.1));
+        assert!(maintenance.active_profile().is_none());
+        assert_eq!(maintenance.health_details()["maintenance"], "false");
+
+        let state = maintenance.update(Some(true), None, "re-embedding");
+        assert!(state.enabled && state.since_ms > 0);
+        assert_eq!(maintenance.active_profile(), Some(MaintenanceProfile::baseline(0.1)));
+        assert_eq!(maintenance.health_details()["maintenance_reason"], "re-embedding");
+
+        // Swapping the profile doesn't restart the clock
+        let swapped = maintenance.update(None, Some(MaintenanceProfile::baseline(0.5)), "");
+        assert_eq!((swapped.since_ms, swapped.reason.as_str()), (state.since_ms, "re-embedding"));
+        assert_eq!(maintenance.active_profile().unwrap().vector["novelty"], 0.5);
+
+        assert!(!maintenance.update(Some(false), None, "").enabled);
+        assert!(maintenance.active_profile().is_none());
+    }
+}
@@ -0,0 +1,625 @@
+//! In-process metrics for the Memory fast path.
+//!
+//! Metrics are kept in process-wide registries so that short-lived objects
+//! (e.g., a `StorageClient` created per storage call) all report into the
+//! same place. They are surfaced two ways:
+//! - Prometheus text exposition via `render_prometheus()` / `serve_metrics()`
+//! - Summary key-value pairs in `GetHealthDetails` (storage client only)
+//!
+//! EvaluateSalience stage timings are also returned per request when the
+//! request sets `debug`.
+//!
+//! Configuration via environment variables:
+//!   METRICS_PORT: Port for the Prometheus scrape endpoint (default: 0 = disabled;
+//!     requires the `prometheus` feature)
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "prometheus")]
+use std::net::SocketAddr;
+#[cfg(feature = "prometheus")]
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+#[cfg(feature = "prometheus")]
+use tokio::net::TcpListener;
+#[cfg(feature = "prometheus")]
+use tracing::{debug, info, warn};
+
+/// Latency bucket upper bounds in seconds (Prometheus convention).
+pub const LATENCY_BUCKETS_SECS: &[f64] = &[
+    0.001, 0.002, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+];
+
+/// Fixed-bucket latency histogram.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    /// Non-cumulative count per bucket; the final slot is the +Inf bucket.
+    counts: Vec<u64>,
+    /// Sum of all observations in seconds
+    pub sum_secs: f64,
+    /// Number of observations
+    pub count: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            counts: vec![0; LATENCY_BUCKETS_SECS.len() + 1],
+            sum_secs: 0.0,
+            count: 0,
+        }
+    }
+}
+
+impl Histogram {
+    pub fn observe(&mut self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        let idx = LATENCY_BUCKETS_SECS
+            .iter()
+            .position(|&bound| secs <= bound)
+            .unwrap_or(LATENCY_BUCKETS_SECS.len());
+        self.counts[idx] += 1;
+        self.sum_secs += secs;
+        self.count += 1;
+    }
+
+    /// Mean latency in milliseconds (0 when empty).
+    pub fn mean_ms(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum_secs * 1000.0 / self.count as f64
+        }
+    }
+
+    /// Cumulative counts per bucket bound, ending with +Inf.
+    pub fn cumulative(&self) -> Vec<(String, u64)> {
+        let mut total = 0;
+        let mut out = Vec::with_capacity(self.counts.len());
+        for (i, count) in self.counts.iter().enumerate() {
+            total += count;
+            let le = LATENCY_BUCKETS_SECS
+                .get(i)
+                .map(|b| b.to_string())
+                .unwrap_or_else(|| "+Inf".to_string());
+            out.push((le, total));
+        }
+        out
+    }
+}
+
+/// Per-RPC statistics for calls made to the storage service.
+#[derive(Debug, Clone, Default)]
+pub struct RpcStats {
+    /// Total calls (successful or not)
+    pub calls: u64,
+    /// Calls that failed at the transport/status level or returned an error payload
+    pub errors: u64,
+    /// Count per outcome code ("OK", "Unavailable", "DeadlineExceeded", "StorageError", ...)
+    pub codes: BTreeMap<String, u64>,
+    /// Call latency
+    pub latency: Histogram,
+}
+
+/// Registry of storage client metrics, keyed by RPC method name.
+///
+/// Connection setup is recorded under the pseudo-method `"connect"`.
+pub struct ClientMetrics {
+    methods: Mutex<BTreeMap<&'static str, RpcStats>>,
+}
+
+impl ClientMetrics {
+    pub const fn new() -> Self {
+        Self { methods: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// Record one call outcome. `code` is "OK" on success.
+    pub fn record(&self, method: &'static str, elapsed: Duration, code: &str) {
+        let mut methods = self.methods.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = methods.entry(method).or_default();
+        stats.calls += 1;
+        if code != "OK" {
+            stats.errors += 1;
+        }
+        *stats.codes.entry(code.to_string()).or_insert(0) += 1;
+        stats.latency.observe(elapsed);
+    }
+
+    /// Copy of the current per-method statistics.
+    pub fn snapshot(&self) -> BTreeMap<&'static str, RpcStats> {
+        self.methods.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Summary key-value pairs for `GetHealthDetails`.
+    pub fn health_details(&self) -> HashMap<String, String> {
+        let mut details = HashMap::new();
+        for (method, stats) in self.snapshot() {
+            details.insert(format!("storage_rpc.{}.calls", method), stats.calls.to_string());
+            details.insert(format!("storage_rpc.{}.errors", method), stats.errors.to_string());
+            details.insert(
+                format!("storage_rpc.{}.mean_ms", method),
+                format!("{:.2}", stats.latency.mean_ms()),
+            );
+        }
+        details
+    }
+}
+
+impl Default for ClientMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static STORAGE_CLIENT_METRICS: ClientMetrics = ClientMetrics::new();
+
+/// Process-wide metrics for calls to the Python storage service.
+pub fn storage_client_metrics() -> &'static ClientMetrics {
+    &STORAGE_CLIENT_METRICS
+}
+
+/// Per-request stage timings collected during one evaluation.
+///
+/// A stage recorded more than once (e.g., two embedding calls) accumulates.
+#[derive(Debug, Clone, Default)]
+pub struct StageTimings {
+    stages: Vec<(&'static str, Duration)>,
+}
+
+impl StageTimings {
+    pub fn record(&mut self, stage: &'static str, elapsed: Duration) {
+        match self.stages.iter_mut().find(|(s, _)| *s == stage) {
+            Some((_, total)) => *total += elapsed,
+            None => self.stages.push((stage, elapsed)),
+        }
+    }
+
+    /// Record the time since `start` under `stage`.
+    pub fn record_since(&mut self, stage: &'static str, start: Instant) {
+        self.record(stage, start.elapsed());
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, Duration)> + '_ {
+        self.stages.iter().copied()
+    }
+
+    /// Stage -> milliseconds, for `EvaluateSalienceResponse.timings_ms`.
+    pub fn to_millis_map(&self) -> HashMap<String, f32> {
+        self.iter()
+            .map(|(stage, elapsed)| (stage.to_string(), elapsed.as_secs_f32() * 1000.0))
+            .collect()
+    }
+}
+
+/// Registry of EvaluateSalience stage latency histograms, keyed by stage name.
+pub struct StageMetrics {
+    stages: Mutex<BTreeMap<&'static str, Histogram>>,
+}
+
+impl StageMetrics {
+    pub const fn new() -> Self {
+        Self { stages: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// Fold one evaluation's timings into the histograms.
+    pub fn record(&self, timings: &StageTimings) {
+        let mut stages = self.stages.lock().unwrap_or_else(|e| e.into_inner());
+        for (stage, elapsed) in timings.iter() {
+            stages.entry(stage).or_default().observe(elapsed);
+        }
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<&'static str, Histogram> {
+        self.stages.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl Default for StageMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static SALIENCE_STAGE_METRICS: StageMetrics = StageMetrics::new();
+
+/// Process-wide EvaluateSalience stage latency histograms.
+pub fn salience_stage_metrics() -> &'static StageMetrics {
+    &SALIENCE_STAGE_METRICS
+}
+
+/// Per-variant request outcomes and latency for A/B experiments.
+#[derive(Debug, Clone, Default)]
+pub struct VariantMetrics {
+    pub requests: u64,
+    pub matches: u64,
+    pub latency: Histogram,
+}
+
+/// Registry of experiment metrics, keyed by (experiment, variant).
+pub struct ExperimentMetrics {
+    variants: Mutex<BTreeMap<(String, String), VariantMetrics>>,
+}
+
+impl ExperimentMetrics {
+    pub const fn new() -> Self {
+        Self { variants: Mutex::new(BTreeMap::new()) }
+    }
+
+    pub fn record(&self, experiment: &str, variant: &str, matched: bool, elapsed: Duration) {
+        let mut variants = self.variants.lock().unwrap_or_else(|e| e.into_inner());
+        let metrics = variants.entry((experiment.to_string(), variant.to_string())).or_default();
+        metrics.requests += 1;
+        metrics.matches += matched as u64;
+        metrics.latency.observe(elapsed);
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<(String, String), VariantMetrics> {
+        self.variants.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl Default for ExperimentMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static EXPERIMENT_METRICS: ExperimentMetrics = ExperimentMetrics::new();
+
+/// Process-wide per-variant experiment metrics.
+pub fn experiment_metrics() -> &'static ExperimentMetrics {
+    &EXPERIMENT_METRICS
+}
+
+static FALLBACK_RATE_LIMITED: AtomicU64 = AtomicU64::new(0);
+
+/// Count a storage fallback skipped by the rate limiter.
+pub fn record_fallback_rate_limited() {
+    FALLBACK_RATE_LIMITED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Storage fallbacks skipped by the rate limiter since startup.
+pub fn fallback_rate_limited_total() -> u64 {
+    FALLBACK_RATE_LIMITED.load(Ordering::Relaxed)
+}
+
+static FALLBACK_COALESCED: AtomicU64 = AtomicU64::new(0);
+
+/// Count a storage fallback that shared another caller's in-flight query.
+pub fn record_fallback_coalesced() {
+    FALLBACK_COALESCED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Storage fallbacks coalesced into an in-flight query since startup.
+pub fn fallback_coalesced_total() -> u64 {
+    FALLBACK_COALESCED.load(Ordering::Relaxed)
+}
+
+/// Shared cache tier lookups by outcome ("hit", "miss", "error").
+static SHARED_CACHE_LOOKUPS: [AtomicU64; 3] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+const SHARED_CACHE_OUTCOMES: [&str; 3] = ["hit", "miss", "error"];
+
+/// Count a shared cache tier lookup ("hit", "miss" or "error").
+pub fn record_shared_cache_lookup(outcome: &str) {
+    if let Some(i) = SHARED_CACHE_OUTCOMES.iter().position(|o| *o == outcome) {
+        SHARED_CACHE_LOOKUPS[i].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Shared cache tier lookups since startup, by outcome.
+pub fn shared_cache_lookups() -> Vec<(&'static str, u64)> {
+    SHARED_CACHE_OUTCOMES
+        .iter()
+        .zip(&SHARED_CACHE_LOOKUPS)
+        .map(|(outcome, count)| (*outcome, count.load(Ordering::Relaxed)))
+        .collect()
+}
+
+static BUS_MESSAGES: [[AtomicU64; 3]; 2] = [
+    [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)],
+    [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)],
+];
+const BUS_TRANSPORTS: [&str; 2] = ["nats", "mqtt"];
+const BUS_OUTCOMES: [&str; 3] = ["evaluated", "invalid", "failed"];
+
+/// Count a message consumed from an event intake ("nats" or "mqtt";
+/// "evaluated", "invalid" or "failed").
+pub fn record_bus_message(transport: &str, outcome: &str) {
+    let t = BUS_TRANSPORTS.iter().position(|t| *t == transport);
+    let o = BUS_OUTCOMES.iter().position(|o| *o == outcome);
+    if let (Some(t), Some(o)) = (t, o) {
+        BUS_MESSAGES[t][o].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Event intake messages since startup, by transport and outcome.
+pub fn bus_messages() -> Vec<(&'static str, &'static str, u64)> {
+    let mut counts = Vec::new();
+    for (transport, outcomes) in BUS_TRANSPORTS.iter().zip(&BUS_MESSAGES) {
+        for (outcome, count) in BUS_OUTCOMES.iter().zip(outcomes) {
+            counts.push((*transport, *outcome, count.load(Ordering::Relaxed)));
+        }
+    }
+    counts
+}
+
+/// Decision write-back updates by outcome ("written", "dropped", "failed").
+static WRITEBACK_UPDATES: [AtomicU64; 3] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+const WRITEBACK_OUTCOMES: [&str; 3] = ["written", "dropped", "failed"];
+
+/// Count decision write-back updates ("written", "dropped" or "failed").
+pub fn record_writeback(outcome: &str, count: u64) {
+    if let Some(i) = WRITEBACK_OUTCOMES.iter().position(|o| *o == outcome) {
+        WRITEBACK_UPDATES[i].fetch_add(count, Ordering::Relaxed);
+    }
+}
+
+/// Decision write-back updates since startup, by outcome.
+pub fn writeback_updates() -> Vec<(&'static str, u64)> {
+    WRITEBACK_OUTCOMES
+        .iter()
+        .zip(&WRITEBACK_UPDATES)
+        .map(|(outcome, count)| (*outcome, count.load(Ordering::Relaxed)))
+        .collect()
+}
+
+/// EvaluateSalience requests over an input limit, by outcome ("truncated", "rejected").
+static INPUT_LIMITED: [AtomicU64; 2] = [AtomicU64::new(0), AtomicU64::new(0)];
+const INPUT_LIMITED_OUTCOMES: [&str; 2] = ["truncated", "rejected"];
+
+/// Count a request over an input limit ("truncated" or "rejected").
+pub fn record_input_limited(outcome: &str) {
+    if let Some(i) = INPUT_LIMITED_OUTCOMES.iter().position(|o| *o == outcome) {
+        INPUT_LIMITED[i].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Requests over an input limit since startup, by outcome.
+pub fn input_limited() -> Vec<(&'static str, u64)> {
+    INPUT_LIMITED_OUTCOMES
+        .iter()
+        .zip(&INPUT_LIMITED)
+        .map(|(outcome, count)| (*outcome, count.load(Ordering::Relaxed)))
+        .collect()
+}
+
+static SCORING_QUEUE_DEPTH: AtomicU64 = AtomicU64::new(0);
+
+/// Count a job submitted to the scoring worker pool.
+pub fn scoring_queue_enqueued() {
+    SCORING_QUEUE_DEPTH.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Count a job picked up by a scoring worker (or rejected).
+pub fn scoring_queue_dequeued() {
+    SCORING_QUEUE_DEPTH.fetch_sub(1, Ordering::Relaxed);
+}
+
+/// Scoring jobs submitted but not yet picked up by a worker.
+pub fn scoring_queue_depth() -> u64 {
+    SCORING_QUEUE_DEPTH.load(Ordering::Relaxed)
+}
+
+/// Map a tonic status code to its metric label.
+pub fn code_label(code: tonic::Code) -> String {
+    format!("{:?}", code)
+}
+
+/// Render all registries in Prometheus text exposition format.
+pub fn render_prometheus() -> String {
+    let mut out = String::new();
+    let snapshot = storage_client_metrics().snapshot();
+
+    let _ = writeln!(out, "# HELP gladys_storage_client_requests_total Storage RPCs by method and outcome code.");
+    let _ = writeln!(out, "# TYPE gladys_storage_client_requests_total counter");
+    for (method, stats) in &snapshot {
+        for (code, count) in &stats.codes {
+            let _ = writeln!(
+                out,
+                "gladys_storage_client_requests_total{{method=\"{}\",code=\"{}\"}} {}",
+                method, code, count
+            );
+        }
+    }
+
+    let _ = writeln!(out, "# HELP gladys_storage_client_latency_seconds Storage RPC latency by method.");
+    let _ = writeln!(out, "# TYPE gladys_storage_client_latency_seconds histogram");
+    for (method, stats) in &snapshot {
+        write_histogram(&mut out, "gladys_storage_client_latency_seconds", &format!("method=\"{}\"", method), &stats.latency);
+    }
+
+    let _ = writeln!(out, "# HELP gladys_salience_stage_latency_seconds EvaluateSalience latency by stage.");
+    let _ = writeln!(out, "# TYPE gladys_salience_stage_latency_seconds histogram");
+    for (stage, histogram) in &salience_stage_metrics().snapshot() {
+        write_histogram(&mut out, "gladys_salience_stage_latency_seconds", &format!("stage=\"{}\"", stage), histogram);
+    }
+
+    let _ = writeln!(out, "# HELP gladys_salience_fallback_rate_limited_total Storage fallbacks skipped by the rate limiter.");
+    let _ = writeln!(out, "# TYPE gladys_salience_fallback_rate_limited_total counter");
+    let _ = writeln!(out, "gladys_salience_fallback_rate_limited_total {}", fallback_rate_limited_total());
+
+    let _ = writeln!(out, "# HELP gladys_salience_fallback_coalesced_total Storage fallbacks that shared an identical in-flight query.");
+    let _ = writeln!(out, "# TYPE gladys_salience_fallback_coalesced_total counter");
+    let _ = writeln!(out, "gladys_salience_fallback_coalesced_total {}", fallback_coalesced_total());
+
+    let _ = writeln!(out, "# HELP gladys_salience_shared_cache_lookups_total Shared cache tier lookups before storage fallback, by outcome.");
+    let _ = writeln!(out, "# TYPE gladys_salience_shared_cache_lookups_total counter");
+    for (outcome, count) in shared_cache_lookups() {
+        let _ = writeln!(out, "gladys_salience_shared_cache_lookups_total{{outcome=\"{}\"}} {}", outcome, count);
+    }
+
+    let _ = writeln!(out, "# HELP gladys_salience_bus_messages_total Events consumed from message intakes (NATS, MQTT), by outcome.");
+    let _ = writeln!(out, "# TYPE gladys_salience_bus_messages_total counter");
+    for (transport, outcome, count) in bus_messages() {
+        let _ = writeln!(out, "gladys_salience_bus_messages_total{{transport=\"{}\",outcome=\"{}\"}} {}", transport, outcome, count);
+    }
+
+    let _ = writeln!(out, "# HELP gladys_salience_writeback_updates_total Decisions written back to stored events, by outcome.");
+    let _ = writeln!(out, "# TYPE gladys_salience_writeback_updates_total counter");
+    for (outcome, count) in writeback_updates() {
+        let _ = writeln!(out, "gladys_salience_writeback_updates_total{{outcome=\"{}\"}} {}", outcome, count);
+    }
+
+    let _ = writeln!(out, "# HELP gladys_salience_input_limited_total EvaluateSalience requests over an input limit, by outcome.");
+    let _ = writeln!(out, "# TYPE gladys_salience_input_limited_total counter");
+    for (outcome, count) in input_limited() {
+        let _ = writeln!(out, "gladys_salience_input_limited_total{{outcome=\"{}\"}} {}", outcome, count);
+    }
+
+    if let Some(slo) = crate::slo::slo_tracker() {
+        let status = slo.status();
+        let labels = format!("scope=\"{}\",quantile=\"{}\"", slo.scope(), slo.quantile());
+        let _ = writeln!(out, "# HELP gladys_salience_slo_target_seconds Latency SLO target.");
+        let _ = writeln!(out, "# TYPE gladys_salience_slo_target_seconds gauge");
+        let _ = writeln!(out, "gladys_salience_slo_target_seconds{{{}}} {}", labels, slo.target_ms() / 1000.0);
+        if let Some(window) = status.last_window {
+            let _ = writeln!(out, "# HELP gladys_salience_slo_latency_seconds Evaluation latency percentile over the last closed SLO window.");
+            let _ = writeln!(out, "# TYPE gladys_salience_slo_latency_seconds gauge");
+            let _ = writeln!(out, "gladys_salience_slo_latency_seconds{{{}}} {}", labels, window.percentile_ms / 1000.0);
+        }
+        let _ = writeln!(out, "# HELP gladys_salience_slo_consecutive_breaches Consecutive SLO windows over target.");
+        let _ = writeln!(out, "# TYPE gladys_salience_slo_consecutive_breaches gauge");
+        let _ = writeln!(out, "gladys_salience_slo_consecutive_breaches{{{}}} {}", labels, status.consecutive_breaches);
+        let _ = writeln!(out, "# HELP gladys_salience_slo_alerts_total SLO breach alerts raised.");
+        let _ = writeln!(out, "# TYPE gladys_salience_slo_alerts_total counter");
+        let _ = writeln!(out, "gladys_salience_slo_alerts_total{{{}}} {}", labels, status.alerts_total);
+    }
+
+    let _ = writeln!(out, "# HELP gladys_salience_scoring_queue_depth Scoring jobs waiting for a worker thread.");
+    let _ = writeln!(out, "# TYPE gladys_salience_scoring_queue_depth gauge");
+    let _ = writeln!(out, "gladys_salience_scoring_queue_depth {}", scoring_queue_depth());
+
+    let experiments = experiment_metrics().snapshot();
+    if !experiments.is_empty() {
+        let _ = writeln!(out, "# HELP gladys_salience_experiment_requests_total EvaluateSalience requests by experiment variant.");
+        let _ = writeln!(out, "# TYPE gladys_salience_experiment_requests_total counter");
+        let _ = writeln!(out, "# HELP gladys_salience_experiment_matches_total Requests that matched a heuristic, by experiment variant.");
+        let _ = writeln!(out, "# TYPE gladys_salience_experiment_matches_total counter");
+        for ((experiment, variant), metrics) in &experiments {
+            let labels = format!("experiment=\"{}\",variant=\"{}\"", experiment, variant);
+            let _ = writeln!(out, "gladys_salience_experiment_requests_total{{{}}} {}", labels, metrics.requests);
+            let _ = writeln!(out, "gladys_salience_experiment_matches_total{{{}}} {}", labels, metrics.matches);
+        }
+        let _ = writeln!(out, "# HELP gladys_salience_experiment_latency_seconds EvaluateSalience latency by experiment variant.");
+        let _ = writeln!(out, "# TYPE gladys_salience_experiment_latency_seconds histogram");
+        for ((experiment, variant), metrics) in &experiments {
+            let labels = format!("experiment=\"{}\",variant=\"{}\"", experiment, variant);
+            write_histogram(&mut out, "gladys_salience_experiment_latency_seconds", &labels, &metrics.latency);
+        }
+    }
+
+    out
+}
+
+/// Append one histogram's bucket/sum/count series.
+pub fn write_histogram(out: &mut String, name: &str, labels: &str, histogram: &Histogram) {
+    for (le, count) in histogram.cumulative() {
+        let _ = writeln!(out, "{}_bucket{{{},le=\"{}\"}} {}", name, labels, le, count);
+    }
+    let _ = writeln!(out, "{}_sum{{{}}} {}", name, labels, histogram.sum_secs);
+    let _ = writeln!(out, "{}_count{{{}}} {}", name, labels, histogram.count);
+}
+
+/// Serve `render_prometheus()` over plain HTTP for Prometheus scrapes.
+///
+/// Deliberately minimal: every request gets the current metrics, regardless of path.
+#[cfg(feature = "prometheus")]
+pub async fn serve_metrics(addr: SocketAddr) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!("Serving Prometheus metrics on {}", addr);
+
+    loop {
+        let (mut stream, peer) = listener.accept().await?;
+        tokio::spawn(async move {
+            // Drain the request head; its contents don't change the response.
+            let mut buf = [0u8; 1024];
+            if let Err(e) = stream.read(&mut buf).await {
+                debug!(peer = %peer, error = %e, "Failed to read metrics request");
+                return;
+            }
+            let body = render_prometheus();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            if let Err(e) = stream.write_all(response.as_bytes()).await {
+                warn!(peer = %peer, error = %e, "Failed to write metrics response");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets() {
+        let mut h = Histogram::default();
+        h.observe(Duration::from_micros(500)); // <= 1ms
+        h.observe(Duration::from_millis(20)); // <= 25ms
+        h.observe(Duration::from_secs(10)); // +Inf
+
+        let buckets = h.cumulative();
+        assert_eq!(buckets[0], ("0.001".to_string(), 1));
+        assert_eq!(buckets.last().unwrap(), &("+Inf".to_string(), 3));
+        assert_eq!(h.count, 3);
+    }
+
+    #[test]
+    fn test_client_metrics_record() {
+        let metrics = ClientMetrics::new();
+        metrics.record("generate_embedding", Duration::from_millis(4), "OK");
+        metrics.record("generate_embedding", Duration::from_millis(6), "Unavailable");
+
+        let snapshot = metrics.snapshot();
+        let stats = &snapshot["generate_embedding"];
+        assert_eq!(stats.calls, 2);
+        assert_eq!(stats.errors, 1);
+        assert_eq!(stats.codes["Unavailable"], 1);
+        assert!((stats.latency.mean_ms() - 5.0).abs() < 0.5);
+
+        let details = metrics.health_details();
+        assert_eq!(details["storage_rpc.generate_embedding.calls"], "2");
+    }
+
+    #[test]
+    fn test_stage_timings_accumulate() {
+        let mut timings = StageTimings::default();
+        timings.record("embedding", Duration::from_millis(2));
+        timings.record("embedding", Duration::from_millis(3));
+        timings.record("total", Duration::from_millis(7));
+
+        let millis = timings.to_millis_map();
+        assert!((millis["embedding"] - 5.0).abs() < 0.01);
+
+        let metrics = StageMetrics::new();
+        metrics.record(&timings);
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot["embedding"].count, 1);
+        assert_eq!(snapshot["total"].count, 1);
+    }
+
+    #[cfg(feature = "prometheus")]
+    #[tokio::test]
+    async fn test_serve_metrics_answers_scrapes() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        tokio::spawn(serve_metrics(addr));
+
+        let mut stream = loop {
+            match tokio::net::TcpStream::connect(addr).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        };
+        stream.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.contains("# TYPE gladys_storage_client_requests_total counter"));
+    }
+}
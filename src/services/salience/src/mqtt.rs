@@ -0,0 +1,472 @@
+//! MQTT sensor intake (enabled with the `mqtt` feature).
+//!
+//! Home-automation sensors (motion, door contacts, ...) usually publish to an
+//! MQTT broker rather than calling gRPC. With MQTT_BROKER_ADDRESS and
+//! MQTT_TOPICS set, the service subscribes to the configured topic filters,
+//! turns each message into an event with that filter's template, and
+//! evaluates it exactly as EvaluateSalience would.
+//!
+//! MQTT_TOPICS maps topic filters (`+` and `#` wildcards allowed) to
+//! templates for the event's source and text:
+//!
+//! ```text
+//! {
+//!   "home/+/motion": {"source": "home_motion", "raw_text": "Motion detected in the {topic.1}"},
+//!   "home/door/+": {"source": "door", "raw_text": "The {topic.2} door is {payload.state}"}
+//! }
+//! ```
+//!
+//! Placeholders: `{payload}` (the raw payload), `{payload.<field>}` (a
+//! top-level field of a JSON payload), `{topic}` and `{topic.<n>}` (a
+//! 0-based topic level). Missing values render empty. `raw_text` defaults to
+//! `{payload}` and `source` to "mqtt". A JSON object payload is also passed
+//! on as the event's structured_json. If several filters match a topic, the
+//! lexically first wins.
+//!
+//! Messages are evaluated in arrival order, one at a time, so a sensor's
+//! "open" is never scored after its "closed". With MQTT_OUTPUT_TOPIC set,
+//! each decision is published there as JSON (see intake module).
+//!
+//! Only MQTT 3.1.1 at QoS 0 is spoken; no TLS.
+//!
+//! Configuration via environment variables:
+//!   MQTT_BROKER_ADDRESS: Broker host:port (default: none = disabled)
+//!   MQTT_TOPICS: Topic filter -> template JSON, as above (required)
+//!   MQTT_OUTPUT_TOPIC: Topic to publish decisions to (default: none)
+//!   MQTT_CLIENT_ID: Client identifier (default: "gladys-salience")
+//!   MQTT_USERNAME / MQTT_PASSWORD: Broker credentials (default: none)
+//!   MQTT_KEEP_ALIVE_SECS: Keep-alive interval (default: 30)
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tonic::Request;
+use tracing::{debug, info, warn};
+
+use crate::config::MqttConfig;
+use crate::intake::{decision_json, error_json};
+use crate::metrics::record_bus_message;
+use crate::proto::salience_gateway_server::SalienceGateway;
+use crate::proto::EvaluateSalienceRequest;
+
+/// Wait between reconnect attempts.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const SUBSCRIBE: u8 = 0x82;
+const SUBACK: u8 = 0x90;
+const PINGREQ: u8 = 0xC0;
+const PINGRESP: u8 = 0xD0;
+
+/// Errors talking to the broker or reading the topic configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum MqttError {
+    #[error("MQTT I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("MQTT protocol error: {0}")]
+    Protocol(String),
+    #[error("MQTT broker refused connection (return code {0})")]
+    Refused(u8),
+    #[error("Invalid MQTT_TOPICS: {0}")]
+    Topics(String),
+}
+
+/// How messages on one topic filter become events.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TopicTemplate {
+    #[serde(default = "default_source")]
+    pub source: String,
+    #[serde(default = "default_raw_text")]
+    pub raw_text: String,
+}
+
+fn default_source() -> String {
+    "mqtt".to_string()
+}
+
+fn default_raw_text() -> String {
+    "{payload}".to_string()
+}
+
+/// Parse MQTT_TOPICS into filter -> template.
+pub fn parse_topics(json: &str) -> Result<BTreeMap<String, TopicTemplate>, MqttError> {
+    let topics: BTreeMap<String, TopicTemplate> =
+        serde_json::from_str(json).map_err(|e| MqttError::Topics(e.to_string()))?;
+    if topics.is_empty() {
+        return Err(MqttError::Topics("no topics configured".to_string()));
+    }
+    if let Some(filter) = topics.keys().find(|f| !valid_filter(f)) {
+        return Err(MqttError::Topics(format!("bad topic filter: {}", filter)));
+    }
+    Ok(topics)
+}
+
+/// `#` only as the last level, wildcards only as whole levels.
+fn valid_filter(filter: &str) -> bool {
+    let levels: Vec<&str> = filter.split('/').collect();
+    !filter.is_empty()
+        && levels.iter().enumerate().all(|(i, level)| match *level {
+            "#" => i == levels.len() - 1,
+            "+" => true,
+            level => !level.contains(['+', '#']),
+        })
+}
+
+/// Whether `topic` matches `filter` (MQTT wildcard rules).
+pub fn topic_matches(filter: &str, topic: &str) -> bool {
+    let mut topic_levels = topic.split('/');
+    for level in filter.split('/') {
+        match (level, topic_levels.next()) {
+            ("#", _) => return true,
+            ("+", Some(_)) => {}
+            (level, Some(t)) if level == t => {}
+            _ => return false,
+        }
+    }
+    topic_levels.next().is_none()
+}
+
+/// Fill a template's placeholders from a message.
+pub fn render(template: &str, topic: &str, payload: &str) -> String {
+    let json: Option<serde_json::Value> = serde_json::from_str(payload).ok();
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let name = &rest[start + 1..start + len];
+        match name.split_once('.') {
+            None if name == "payload" => out.push_str(payload),
+            None if name == "topic" => out.push_str(topic),
+            Some(("topic", n)) => {
+                if let Some(level) = n.parse().ok().and_then(|n: usize| topic.split('/').nth(n)) {
+                    out.push_str(level);
+                }
+            }
+            Some(("payload", field)) => match json.as_ref().and_then(|j| j.get(field)) {
+                Some(serde_json::Value::String(s)) => out.push_str(s),
+                Some(value) => out.push_str(&value.to_string()),
+                None => {}
+            },
+            // Not a placeholder; keep it
+            _ => out.push_str(&rest[start..=start + len]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The event for a message on `topic`, if any configured filter matches.
+fn event_for(topics: &BTreeMap<String, TopicTemplate>, topic: &str, payload: &[u8]) -> Option<EvaluateSalienceRequest> {
+    let (_, template) = topics.iter().find(|(filter, _)| topic_matches(filter, topic))?;
+    let payload = String::from_utf8_lossy(payload);
+    let structured_json = match serde_json::from_str::<serde_json::Value>(&payload) {
+        Ok(serde_json::Value::Object(_)) => payload.to_string(),
+        _ => String::new(),
+    };
+    Some(EvaluateSalienceRequest {
+        event_id: uuid::Uuid::new_v4().to_string(),
+        source: render(&template.source, topic, &payload),
+        raw_text: render(&template.raw_text, topic, &payload),
+        structured_json,
+        ..Default::default()
+    })
+}
+
+fn push_str(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// Frame a packet: fixed header byte, remaining length, body.
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = vec![header];
+    let mut len = body.len();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+    out.extend_from_slice(body);
+    out
+}
+
+fn connect_packet(config: &MqttConfig) -> Vec<u8> {
+    let mut body = Vec::new();
+    push_str(&mut body, "MQTT");
+    body.push(4); // protocol level 3.1.1
+    let mut flags = 0x02; // clean session
+    if config.username.is_some() {
+        flags |= 0x80;
+    }
+    if config.password.is_some() {
+        flags |= 0x40;
+    }
+    body.push(flags);
+    body.extend_from_slice(&config.keep_alive_secs.to_be_bytes());
+    push_str(&mut body, &config.client_id);
+    if let Some(username) = &config.username {
+        push_str(&mut body, username);
+    }
+    if let Some(password) = &config.password {
+        push_str(&mut body, password);
+    }
+    packet(CONNECT, &body)
+}
+
+fn subscribe_packet<'a>(packet_id: u16, filters: impl IntoIterator<Item = &'a String>) -> Vec<u8> {
+    let mut body = packet_id.to_be_bytes().to_vec();
+    for filter in filters {
+        push_str(&mut body, filter);
+        body.push(0); // QoS 0
+    }
+    packet(SUBSCRIBE, &body)
+}
+
+fn publish_packet(topic: &str, payload: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    push_str(&mut body, topic);
+    body.extend_from_slice(payload);
+    packet(PUBLISH, &body)
+}
+
+/// Read one packet: (fixed header byte, body).
+async fn read_packet<R: AsyncRead + Unpin>(reader: &mut R) -> Result<(u8, Vec<u8>), MqttError> {
+    let header = reader.read_u8().await?;
+    let mut len = 0usize;
+    for shift in (0..4).map(|i| i * 7) {
+        let byte = reader.read_u8().await?;
+        len |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            let mut body = vec![0; len];
+            reader.read_exact(&mut body).await?;
+            return Ok((header, body));
+        }
+    }
+    Err(MqttError::Protocol("remaining length too long".to_string()))
+}
+
+/// Split a PUBLISH body into (topic, payload).
+fn parse_publish(header: u8, body: &[u8]) -> Result<(String, Vec<u8>), MqttError> {
+    let short = || MqttError::Protocol("truncated PUBLISH".to_string());
+    let len = u16::from_be_bytes(body.get(..2).ok_or_else(short)?.try_into().map_err(|_| short())?) as usize;
+    let topic = body.get(2..2 + len).ok_or_else(short)?;
+    let topic = String::from_utf8(topic.to_vec()).map_err(|_| MqttError::Protocol("topic is not UTF-8".to_string()))?;
+    // QoS > 0 carries a packet ID (we subscribe at QoS 0, but brokers may differ)
+    let offset = 2 + len + if header & 0x06 != 0 { 2 } else { 0 };
+    Ok((topic, body.get(offset..).ok_or_else(short)?.to_vec()))
+}
+
+async fn write(writer: &Mutex<OwnedWriteHalf>, data: &[u8]) -> Result<(), MqttError> {
+    writer.lock().await.write_all(data).await?;
+    Ok(())
+}
+
+/// Consume messages until the connection fails.
+async fn consume<G: SalienceGateway>(
+    address: &str,
+    config: &MqttConfig,
+    topics: &BTreeMap<String, TopicTemplate>,
+    gateway: &G,
+) -> Result<(), MqttError> {
+    let stream = TcpStream::connect(address).await?;
+    stream.set_nodelay(true)?;
+    let (mut reader, writer) = stream.into_split();
+    let writer = Arc::new(Mutex::new(writer));
+
+    write(&writer, &connect_packet(config)).await?;
+    match read_packet(&mut reader).await? {
+        (CONNACK, body) if body.len() == 2 => match body[1] {
+            0 => {}
+            code => return Err(MqttError::Refused(code)),
+        },
+        (header, _) => return Err(MqttError::Protocol(format!("expected CONNACK, got 0x{:02x}", header))),
+    }
+    write(&writer, &subscribe_packet(1, topics.keys())).await?;
+    info!(address = %address, topics = topics.len(), output_topic = ?config.output_topic, "Consuming events from MQTT");
+
+    // Keep-alive pings; stopped when this connection ends
+    let pinger = writer.clone();
+    let interval = Duration::from_secs(u64::from(config.keep_alive_secs.max(2)) / 2);
+    let keep_alive = tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            if write(&pinger, &[PINGREQ, 0]).await.is_err() {
+                return;
+            }
+        }
+    });
+    let _keep_alive = AbortOnDrop(keep_alive);
+
+    loop {
+        let (header, body) = read_packet(&mut reader).await?;
+        match header & 0xF0 {
+            PUBLISH => {
+                let (topic, payload) = parse_publish(header, &body)?;
+                let Some(event) = event_for(topics, &topic, &payload) else {
+                    record_bus_message("mqtt", "invalid");
+                    debug!(topic = %topic, "No template for MQTT topic");
+                    continue;
+                };
+                let event_id = event.event_id.clone();
+                let decision = match gateway.evaluate_salience(Request::new(event)).await {
+                    Ok(response) => {
+                        record_bus_message("mqtt", "evaluated");
+                        decision_json(&event_id, response.get_ref())
+                    }
+                    Err(status) => {
+                        record_bus_message("mqtt", "failed");
+                        error_json(&event_id, &status)
+                    }
+                };
+                if let Some(output_topic) = &config.output_topic {
+                    write(&writer, &publish_packet(output_topic, decision.to_string().as_bytes())).await?;
+                }
+            }
+            SUBACK => {
+                if body.iter().skip(2).any(|code| *code == 0x80) {
+                    warn!("MQTT broker rejected a topic subscription");
+                }
+            }
+            PINGRESP => {}
+            other => debug!(packet = other, "Ignoring MQTT packet"),
+        }
+    }
+}
+
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Consume sensor events for as long as the process runs, reconnecting on failure.
+pub async fn run_consumer<G: SalienceGateway>(config: MqttConfig, gateway: Arc<G>) {
+    let Some(address) = config.broker_address.clone() else {
+        return;
+    };
+    let topics = match config.topics.as_deref().map(parse_topics) {
+        Some(Ok(topics)) => topics,
+        Some(Err(e)) => {
+            warn!(error = %e, "MQTT intake disabled");
+            return;
+        }
+        None => {
+            warn!("MQTT_BROKER_ADDRESS is set without MQTT_TOPICS; MQTT intake disabled");
+            return;
+        }
+    };
+    loop {
+        if let Err(e) = consume(&address, &config, &topics, gateway.as_ref()).await {
+            warn!(address = %address, error = %e, "MQTT consumer disconnected; reconnecting");
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulate::local_service;
+    use crate::{CacheConfig, CachedHeuristic, Condition, SalienceConfig};
+    use tokio::net::TcpListener;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_topic_templates() {
+        assert!(topic_matches("home/+/motion", "home/kitchen/motion"));
+        assert!(!topic_matches("home/+/motion", "home/kitchen/motion/extra"));
+        assert!(topic_matches("home/#", "home/door/front"));
+        assert!(!topic_matches("home/door", "home/door/front"));
+        assert!(parse_topics(r#"{"home/#/x": {}}"#).is_err());
+        assert!(parse_topics("{}").is_err());
+
+        let topics = parse_topics(r#"{"home/door/+": {"source": "door", "raw_text": "The {topic.2} door is {payload.state} {x}"}}"#).unwrap();
+        let event = event_for(&topics, "home/door/front", br#"{"state": "open"}"#).unwrap();
+        assert_eq!(event.source, "door");
+        assert_eq!(event.raw_text, "The front door is open {x}");
+        assert_eq!(event.structured_json, r#"{"state": "open"}"#);
+        assert_eq!(render("{payload}/{topic.9} {", "a/b", "on"), "on/ {");
+        assert!(event_for(&topics, "home/window/front", b"open").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_consumer_evaluates_sensor_events() {
+        let heuristic = CachedHeuristic {
+            id: Uuid::new_v4(),
+            name: "kitchen motion".to_string(),
+            condition: Condition::text("motion detected in the kitchen"),
+            effects: serde_json::json!({"salience": {"threat": 0.4}}).into(),
+            confidence: 0.9,
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: Default::default(),
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        };
+        let (service, _cache) = local_service(vec![heuristic.clone()], CacheConfig::default(), SalienceConfig::default());
+
+        // Fake broker: accept the client, deliver two messages, return the first decision
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let broker = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            assert_eq!(read_packet(&mut stream).await.unwrap().0, CONNECT);
+            stream.write_all(&packet(CONNACK, &[0, 0])).await.unwrap();
+            let (header, body) = read_packet(&mut stream).await.unwrap();
+            assert_eq!(header, SUBSCRIBE);
+            stream.write_all(&packet(SUBACK, &[body[0], body[1], 0])).await.unwrap();
+            stream.write_all(&publish_packet("home/garage/unknown", b"on")).await.unwrap();
+            stream.write_all(&publish_packet("home/kitchen/motion", br#"{"occupancy": true}"#)).await.unwrap();
+            loop {
+                let (header, body) = read_packet(&mut stream).await.unwrap();
+                if header & 0xF0 == PUBLISH {
+                    return parse_publish(header, &body).unwrap();
+                }
+            }
+        });
+
+        let config = MqttConfig {
+            broker_address: Some(address),
+            client_id: "test".to_string(),
+            username: None,
+            password: None,
+            topics: Some(r#"{"home/+/motion": {"source": "home", "raw_text": "Motion detected in the {topic.1}"}}"#.to_string()),
+            output_topic: Some("gladys/salience".to_string()),
+            keep_alive_secs: 30,
+        };
+        let consumer = tokio::spawn(run_consumer(config, Arc::new(service)));
+
+        let (topic, payload) = tokio::time::timeout(Duration::from_secs(5), broker).await.unwrap().unwrap();
+        assert_eq!(topic, "gladys/salience");
+        let decision: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(decision["matched_heuristic_id"], heuristic.id.to_string());
+        assert!(decision["salience"]["threat"].as_f64().unwrap() > 0.3);
+
+        consumer.abort();
+    }
+}
@@ -0,0 +1,78 @@
+//! Text normalization applied to event text before any matching.
+//!
+//! Adapters don't agree on spelling: "Danger!" and "DANGER  !" were different
+//! strings to keyword matching, to the text hashes behind dedup, hysteresis
+//! and negative caching, and to the embedding model. EvaluateSalience now
+//! normalizes raw_text once, before scoring, so every stage sees one form:
+//! 1. Case folding (Unicode lowercase)
+//! 2. Emoji stripping, if enabled
+//! 3. Unicode NFC, so composed and decomposed accents compare equal
+//! 4. Whitespace collapse: runs become one space, ends are trimmed
+//!
+//! TestHeuristic sample texts go through the same stage, so dry runs match
+//! what live events will see. Heuristic keywords are NFC-normalized and
+//! lowercased when indexed (see keywords module).
+//!
+//! Configuration via environment variables (see `SalienceConfig`):
+//!   SALIENCE_NORMALIZE_TEXT: Normalize event text (default: true)
+//!   SALIENCE_CASE_FOLD: Fold case while normalizing (default: true)
+//!   SALIENCE_STRIP_EMOJI: Drop emoji while normalizing (default: false)
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::config::SalienceConfig;
+
+/// `text` in the configured normal form (unchanged if normalization is off).
+pub fn normalize_text(text: &str, config: &SalienceConfig) -> String {
+    if !config.normalize_text {
+        return text.to_string();
+    }
+    let folded = if config.case_fold { text.to_lowercase() } else { text.to_string() };
+    let stripped: String = if config.strip_emoji { folded.chars().filter(|c| !is_emoji(*c)).collect() } else { folded };
+    let composed: String = stripped.nfc().collect();
+    composed.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// NFC and lowercase, for matching keys such as heuristic keywords.
+pub fn fold_key(text: &str) -> String {
+    text.nfc().collect::<String>().to_lowercase()
+}
+
+/// Emoji, including the joiners and modifiers that build emoji sequences.
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF // pictographs, emoticons, transport, flags, skin tones
+            | 0x2600..=0x27BF // misc symbols, dingbats
+            | 0x2B00..=0x2BFF // stars, arrows
+            | 0xFE0F // emoji presentation selector
+            | 0x200D // zero-width joiner
+            | 0x20E3 // combining keycap
+            | 0xE0020..=0xE007F // tag sequences
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_text() {
+        let config = SalienceConfig { normalize_text: true, case_fold: true, strip_emoji: false, ..SalienceConfig::default() };
+        assert_eq!(normalize_text("  DANGER\t\n ahead ", &config), "danger ahead");
+        // Decomposed "é" (e + combining acute) composes to U+00E9
+        assert_eq!(normalize_text("Cafe\u{301}", &config), "caf\u{e9}");
+        assert_eq!(normalize_text("fire 🔥", &config), "fire 🔥");
+
+        let strip = SalienceConfig { strip_emoji: true, ..config.clone() };
+        assert_eq!(normalize_text("fire 🔥 👩\u{200d}🚒 now", &strip), "fire now");
+
+        let off = SalienceConfig { normalize_text: false, ..config };
+        assert_eq!(normalize_text("  DANGER ", &off), "  DANGER ");
+    }
+
+    #[test]
+    fn test_fold_key() {
+        assert_eq!(fold_key("CRE\u{301}EPER"), "cr\u{e9}eper");
+    }
+}
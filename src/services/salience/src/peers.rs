@@ -0,0 +1,221 @@
+//! Forwarding of heuristic change notifications between replicas.
+//!
+//! An alternative to the Redis tier (see shared_cache module) for small
+//! fleets: each replica is configured with the others' addresses, and a
+//! NotifyHeuristicChange it receives from storage is relayed to every peer,
+//! so one notification from Python invalidates the whole fleet. The same
+//! channel carries the refresh leader's broadcasts (see refresh module).
+//!
+//! Loops are suppressed two ways: relayed notifications carry
+//! `forwarded = true` and are never relayed again, and each notification
+//! gets a `notification_id` that replicas remember for the idempotency
+//! window, so a notification that reaches a replica twice is only relayed once.
+//!
+//! Per-peer health (consecutive failures, last success, last error) is
+//! reported in GetHealthDetails. A peer that has failed `PEER_DOWN_AFTER`
+//! times in a row is only retried every `PEER_RETRY_MS`; notifications it
+//! misses meanwhile are lost, so pair this with heuristic TTLs or the
+//! refresh loop.
+//!
+//! The leader's broadcasts (ApplyHeuristicChanges) are an admin RPC, so peer
+//! addresses are the other replicas' admin listeners (see planes module); a
+//! replica without one serves admin RPCs on its main port.
+//!
+//! Configuration via environment variables:
+//!   PEER_ADDRESSES: Comma-separated host:port of the other replicas' admin
+//!     listeners (default: none = disabled)
+//!   PEER_TIMEOUT_MS: Per-peer connect + call timeout (default: 500)
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::task::JoinSet;
+use tonic::transport::Channel;
+use tracing::{debug, warn};
+
+use crate::config::PeerConfig;
+use crate::proto::salience_gateway_client::SalienceGatewayClient;
+use crate::proto::{ApplyHeuristicChangesRequest, NotifyHeuristicChangeRequest};
+
+/// Consecutive failures after which a peer is considered down.
+pub(crate) const PEER_DOWN_AFTER: u32 = 3;
+
+/// How often a down peer is retried, in ms.
+const PEER_RETRY_MS: i64 = 10_000;
+
+/// Forwarding health for one peer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PeerHealth {
+    pub consecutive_failures: u32,
+    /// 0 = never reached
+    pub last_success_ms: i64,
+    pub last_attempt_ms: i64,
+    pub last_error: String,
+    pub forwarded: u64,
+}
+
+impl PeerHealth {
+    pub fn is_down(&self) -> bool {
+        self.consecutive_failures >= PEER_DOWN_AFTER
+    }
+
+    /// Whether to try this peer now (down peers are retried periodically).
+    pub(crate) fn should_attempt(&self, now: i64) -> bool {
+        !self.is_down() || now - self.last_attempt_ms >= PEER_RETRY_MS
+    }
+}
+
+struct Peer {
+    address: String,
+    health: Mutex<PeerHealth>,
+}
+
+/// The other replicas to relay notifications to.
+pub struct PeerSet {
+    peers: Vec<Arc<Peer>>,
+    timeout: Duration,
+}
+
+impl PeerSet {
+    pub fn new(addresses: Vec<String>, timeout: Duration) -> Self {
+        let peers = addresses
+            .into_iter()
+            .map(|address| Arc::new(Peer { address, health: Mutex::new(PeerHealth::default()) }))
+            .collect();
+        Self { peers, timeout }
+    }
+
+    /// The peers configured in `config`, if any.
+    pub fn from_config(config: &PeerConfig) -> Option<Self> {
+        (!config.addresses.is_empty())
+            .then(|| Self::new(config.addresses.clone(), Duration::from_millis(config.timeout_ms.max(1))))
+    }
+
+    /// Relay `request` to every peer that isn't down, marked as forwarded.
+    /// Returns how many peers accepted it.
+    pub async fn forward(&self, mut request: NotifyHeuristicChangeRequest) -> usize {
+        request.forwarded = true;
+        self.fan_out("heuristic change", move |mut client| {
+            let request = request.clone();
+            async move { client.notify_heuristic_change(request).await.map(|_| ()) }
+        })
+        .await
+    }
+
+    /// Send the refresh leader's changes to every peer that isn't down.
+    /// Returns how many peers accepted them.
+    pub async fn broadcast_changes(&self, request: ApplyHeuristicChangesRequest) -> usize {
+        self.fan_out("refresh broadcast", move |mut client| {
+            let request = request.clone();
+            async move { client.apply_heuristic_changes(request).await.map(|_| ()) }
+        })
+        .await
+    }
+
+    /// Make `call` against every peer that isn't down, tracking health.
+    async fn fan_out<F, Fut>(&self, what: &'static str, call: F) -> usize
+    where
+        F: Fn(SalienceGatewayClient<Channel>) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), tonic::Status>> + Send + 'static,
+    {
+        let now = crate::current_time_ms();
+        let mut calls = JoinSet::new();
+        for peer in &self.peers {
+            let mut health = peer.health.lock().unwrap_or_else(|e| e.into_inner());
+            if !health.should_attempt(now) {
+                continue;
+            }
+            health.last_attempt_ms = now;
+            drop(health);
+
+            let (peer, call, timeout) = (peer.clone(), call.clone(), self.timeout);
+            calls.spawn(async move {
+                let attempt = async {
+                    let client = SalienceGatewayClient::connect(format!("http://{}", peer.address))
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    call(client).await.map_err(|e| e.message().to_string())
+                };
+                let result = tokio::time::timeout(timeout, attempt)
+                    .await
+                    .unwrap_or_else(|_| Err("timed out".to_string()));
+
+                let mut health = peer.health.lock().unwrap_or_else(|e| e.into_inner());
+                match result {
+                    Ok(()) => {
+                        health.consecutive_failures = 0;
+                        health.last_success_ms = crate::current_time_ms();
+                        health.forwarded += 1;
+                        debug!(peer = %peer.address, what, "Forwarded to peer");
+                        true
+                    }
+                    Err(e) => {
+                        health.consecutive_failures += 1;
+                        if health.consecutive_failures == PEER_DOWN_AFTER {
+                            warn!(peer = %peer.address, what, error = %e, "Peer marked down after repeated forwarding failures");
+                        } else {
+                            warn!(peer = %peer.address, what, error = %e, "Failed to forward to peer");
+                        }
+                        health.last_error = e;
+                        false
+                    }
+                }
+            });
+        }
+        let mut delivered = 0;
+        while let Some(result) = calls.join_next().await {
+            delivered += matches!(result, Ok(true)) as usize;
+        }
+        delivered
+    }
+
+    /// Health of each peer, in configured order.
+    pub fn health(&self) -> Vec<(String, PeerHealth)> {
+        self.peers
+            .iter()
+            .map(|p| (p.address.clone(), p.health.lock().unwrap_or_else(|e| e.into_inner()).clone()))
+            .collect()
+    }
+
+    /// Summary for GetHealthDetails.
+    pub fn health_details(&self) -> HashMap<String, String> {
+        let health = self.health();
+        let mut details = HashMap::new();
+        details.insert("peers_configured".to_string(), health.len().to_string());
+        details.insert("peers_down".to_string(), health.iter().filter(|(_, h)| h.is_down()).count().to_string());
+        for (address, h) in &health {
+            details.insert(format!("peer_{}_failures", address), h.consecutive_failures.to_string());
+            details.insert(format!("peer_{}_forwarded", address), h.forwarded.to_string());
+            if !h.last_error.is_empty() {
+                details.insert(format!("peer_{}_last_error", address), h.last_error.clone());
+            }
+        }
+        details
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unreachable_peer_goes_down_and_backs_off() {
+        // Nothing listens on port 1
+        let peers = PeerSet::new(vec!["127.0.0.1:1".to_string()], Duration::from_millis(200));
+        for _ in 0..PEER_DOWN_AFTER {
+            assert_eq!(peers.forward(NotifyHeuristicChangeRequest::default()).await, 0);
+        }
+        let (_, health) = &peers.health()[0];
+        assert!(health.is_down());
+        assert!(!health.last_error.is_empty());
+        assert_eq!(peers.health_details()["peers_down"], "1");
+
+        // Down: skipped until the retry interval passes
+        assert!(!health.should_attempt(health.last_attempt_ms + 1));
+        assert!(health.should_attempt(health.last_attempt_ms + PEER_RETRY_MS));
+        peers.forward(NotifyHeuristicChangeRequest::default()).await;
+        assert_eq!(peers.health()[0].1.consecutive_failures, PEER_DOWN_AFTER);
+    }
+}
@@ -0,0 +1,102 @@
+//! Separate listeners for the data and admin planes.
+//!
+//! Every RPC is part of one SalienceGateway service, so by default they all
+//! share the gRPC port. With an admin listener configured, the main port
+//! becomes the data plane: admin RPCs there (cache management, heuristic
+//! import/export, replica sync, diagnostics, fault injection) fail with
+//! PERMISSION_DENIED, and only the admin listener, a second TCP port or a
+//! Unix socket, serves them. A firewall can then expose the data port alone. The admin listener
+//! serves every RPC, and everything else on the data plane is unchanged.
+//!
+//! Configuration via environment variables (see `ServerConfig`):
+//!   ADMIN_PORT: Port for admin RPCs (default: 0 = admin RPCs on the main port)
+//!   ADMIN_HOST: Host for ADMIN_PORT (default: 127.0.0.1)
+//!   ADMIN_SOCKET: Unix socket path for admin RPCs instead of a port (Unix only)
+
+use std::convert::Infallible;
+
+use tonic::body::BoxBody;
+use tonic::codegen::{http, BoxFuture, Context, Poll, Service};
+use tonic::server::NamedService;
+use tonic::Status;
+
+/// SalienceGateway methods only the admin listener serves.
+pub const ADMIN_METHODS: &[&str] = &[
+    "FlushCache",
+    "EvictFromCache",
+    "ListCachedHeuristics",
+    "GetEvictionLog",
+    "ReEmbedCache",
+    "EnableGroup",
+    "DisableGroup",
+    "TestHeuristic",
+    "ReplayDecisions",
+    "ExportHeuristics",
+    "ImportHeuristics",
+    "SubmitCalibrationFeedback",
+    "UpdateSourceFilter",
+    "SetMaintenanceMode",
+    "StreamCacheState",
+    "ApplyHeuristicChanges",
+    "GetAuditLog",
+    "RunDiagnostics",
+    "InjectFaults",
+];
+
+/// Whether `path` ("/package.Service/Method") names an admin RPC.
+pub fn is_admin_path(path: &str) -> bool {
+    path.rsplit('/').next().is_some_and(|method| ADMIN_METHODS.contains(&method))
+}
+
+/// Wraps the data-plane service, refusing admin RPCs.
+#[derive(Debug, Clone)]
+pub struct DataPlane<S> {
+    inner: S,
+}
+
+impl<S> DataPlane<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+impl<S, B> Service<http::Request<B>> for DataPlane<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>, Error = Infallible>,
+    S::Future: Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        if is_admin_path(request.uri().path()) {
+            let status = Status::permission_denied(format!("{} is only served on the admin listener", request.uri().path()));
+            return Box::pin(async move { Ok(status.into_http()) });
+        }
+        Box::pin(self.inner.call(request))
+    }
+}
+
+impl<S: NamedService> NamedService for DataPlane<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admin_paths() {
+        assert!(is_admin_path("/gladys.memory.SalienceGateway/FlushCache"));
+        assert!(is_admin_path("/gladys.memory.SalienceGateway/InjectFaults"));
+        assert!(is_admin_path("/gladys.memory.SalienceGateway/ApplyHeuristicChanges"));
+        assert!(!is_admin_path("/gladys.memory.SalienceGateway/EvaluateSalience"));
+        assert!(!is_admin_path("/gladys.memory.SalienceGateway/GetHealth"));
+        assert!(!is_admin_path(""));
+    }
+}
@@ -0,0 +1,243 @@
+//! Direct Postgres reads: storage fallbacks without the Python hop.
+//!
+//! On a cache miss the fast path asks Python's QueryMatchingHeuristics,
+//! which embeds the event text again and runs a pgvector search. In
+//! read-heavy deployments that hop dominates fallback latency. With
+//! STORAGE_BACKEND=postgres (in a build with the `postgres` feature),
+//! `PgStorageBackend` runs the same search against the shared schema itself:
+//! - The query embedding is the one just generated for the event (the last
+//!   few embeddings are remembered for this), or a fresh one from Python
+//!   when there isn't one
+//! - The search mirrors Python's: cosine similarity against
+//!   `condition_embedding`, the similarity and confidence floors, frozen
+//!   heuristics left out, and under a source filter only heuristics of
+//!   exactly that source
+//! - Everything else (embeddings, change fetches, stores, write-back,
+//!   feedback) still goes through Python, which keeps owning the schema and
+//!   all writes. Direct reads don't touch `last_accessed`
+//! - Heuristics without an embedding aren't matched; Python's deprecated
+//!   full-text fallback isn't carried over
+//!
+//! The pool connects lazily, so an unreachable database fails fallbacks
+//! (STORAGE_UNAVAILABLE or STORAGE_TIMEOUT) rather than startup.
+//!
+//! Configuration via environment variables (see `StorageConfig`):
+//!   STORAGE_BACKEND: grpc, sqlite or postgres (default: grpc)
+//!   STORAGE_POSTGRES_URL: Connection URL (default: postgres://gladys@localhost:5432/gladys)
+//!   STORAGE_POSTGRES_MAX_CONNECTIONS: Pool size (default: 4)
+//!   STORAGE_ADDRESS and the other STORAGE_* settings still apply to Python
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use pgvector::Vector;
+use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
+use sqlx::Row;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::client::embedding_to_bytes;
+use crate::compat::heuristic_from_proto;
+use crate::config::StorageConfig;
+use crate::proto::{self, EventSalienceUpdate};
+use crate::{CachedHeuristic, GrpcStorageBackend, HeuristicChanges, HeuristicFeedback, StorageBackend, StorageError};
+
+/// Event embeddings remembered for the fallback that may follow.
+const RECENT_EMBEDDINGS: usize = 64;
+
+/// Matches when the request doesn't set a limit (as in Python).
+const DEFAULT_LIMIT: i64 = 10;
+
+/// Python's semantic search (storage.py `query_matching_heuristics`).
+const MATCH_QUERY: &str = "
+SELECT id, name, condition->>'text' AS condition_text, action::text AS effects_json,
+       confidence, origin, origin_id, source, condition_embedding, fire_count, success_count,
+       (EXTRACT(EPOCH FROM updated_at) * 1000)::BIGINT AS updated_at_ms
+FROM heuristics
+WHERE condition_embedding IS NOT NULL
+  AND 1 - (condition_embedding <=> $1) >= $2
+  AND confidence >= $3
+  AND frozen = false
+  AND ($5::TEXT IS NULL OR source = $5)
+ORDER BY condition_embedding <=> $1
+LIMIT $4";
+
+/// `StorageBackend` reading matches from Postgres (see module docs).
+pub struct PgStorageBackend {
+    pool: PgPool,
+    python: GrpcStorageBackend,
+    min_similarity: f32,
+    recent: Mutex<VecDeque<(String, Vec<f32>)>>,
+}
+
+impl PgStorageBackend {
+    /// Matches are at or above `min_similarity`. Doesn't connect yet.
+    pub fn new(config: StorageConfig, min_similarity: f32) -> Result<Self, StorageError> {
+        let pool = PgPoolOptions::new()
+            .max_connections(config.postgres_max_connections.max(1))
+            .acquire_timeout(config.connect_timeout())
+            .connect_lazy(&config.postgres_url)
+            .map_err(pg_error)?;
+        Ok(Self {
+            pool,
+            python: GrpcStorageBackend::new(config),
+            min_similarity,
+            recent: Mutex::new(VecDeque::with_capacity(RECENT_EMBEDDINGS)),
+        })
+    }
+
+    fn remember(&self, text: &str, embedding: &[f32]) {
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        if recent.len() == RECENT_EMBEDDINGS {
+            recent.pop_front();
+        }
+        recent.push_back((text.to_string(), embedding.to_vec()));
+    }
+
+    fn recall(&self, text: &str) -> Option<Vec<f32>> {
+        let recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        recent.iter().rev().find(|(t, _)| t == text).map(|(_, embedding)| embedding.clone())
+    }
+}
+
+fn pg_error(e: sqlx::Error) -> StorageError {
+    match e {
+        sqlx::Error::PoolTimedOut => StorageError::Timeout("Postgres connection pool".to_string()),
+        sqlx::Error::Io(e) => StorageError::Unavailable(format!("Postgres: {}", e)),
+        e => StorageError::Storage(format!("Postgres: {}", e)),
+    }
+}
+
+/// The row as Python would have sent it.
+fn heuristic_from_row(row: &PgRow, embedding_model: &str) -> Result<proto::Heuristic, sqlx::Error> {
+    let embedding: Vector = row.try_get("condition_embedding")?;
+    Ok(proto::Heuristic {
+        id: row.try_get::<Uuid, _>("id")?.to_string(),
+        name: row.try_get("name")?,
+        condition_text: row.try_get::<Option<String>, _>("condition_text")?.unwrap_or_default(),
+        condition_embedding: embedding_to_bytes(embedding.as_slice()),
+        effects_json: row.try_get("effects_json")?,
+        confidence: row.try_get::<f64, _>("confidence")? as f32,
+        origin: row.try_get::<Option<String>, _>("origin")?.unwrap_or_else(|| "learned".to_string()),
+        origin_id: row.try_get::<Option<String>, _>("origin_id")?.unwrap_or_default(),
+        fire_count: row.try_get::<Option<i32>, _>("fire_count")?.unwrap_or(0),
+        success_count: row.try_get::<Option<i32>, _>("success_count")?.unwrap_or(0),
+        updated_at_ms: row.try_get::<Option<i64>, _>("updated_at_ms")?.unwrap_or(0),
+        source: row.try_get::<Option<String>, _>("source")?.unwrap_or_default(),
+        embedding_model: embedding_model.to_string(),
+        ..Default::default()
+    })
+}
+
+#[tonic::async_trait]
+impl StorageBackend for PgStorageBackend {
+    async fn query_matching_heuristics(
+        &self,
+        event_text: &str,
+        min_confidence: f32,
+        limit: i32,
+        source_filter: Option<&str>,
+        trace_id: Option<&str>,
+    ) -> Result<Vec<CachedHeuristic>, StorageError> {
+        let embedding = match self.recall(event_text) {
+            Some(embedding) => embedding,
+            None => self.generate_embedding(event_text, trace_id).await?,
+        };
+        let rows = sqlx::query(MATCH_QUERY)
+            .bind(Vector::from(embedding))
+            .bind(self.min_similarity as f64)
+            .bind(min_confidence as f64)
+            .bind(if limit > 0 { limit as i64 } else { DEFAULT_LIMIT })
+            .bind(source_filter)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(pg_error)?;
+        debug!(trace_id = ?trace_id, count = rows.len(), "Postgres returned matches");
+
+        let embedding_model = self.python.embedding_model().unwrap_or_default();
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                heuristic_from_row(row, &embedding_model)
+                    .map_err(|e| warn!(error = %e, "Skipping undecodable heuristic row"))
+                    .ok()
+            })
+            .filter_map(heuristic_from_proto)
+            .map(CachedHeuristic::from)
+            .collect())
+    }
+
+    async fn generate_embedding(&self, text: &str, trace_id: Option<&str>) -> Result<Vec<f32>, StorageError> {
+        let embedding = self.python.generate_embedding(text, trace_id).await?;
+        self.remember(text, &embedding);
+        Ok(embedding)
+    }
+
+    fn embedding_model(&self) -> Option<String> {
+        self.python.embedding_model()
+    }
+
+    async fn query_changed_heuristics(
+        &self,
+        updated_since_ms: i64,
+        limit: i32,
+        trace_id: Option<&str>,
+    ) -> Result<HeuristicChanges, StorageError> {
+        self.python.query_changed_heuristics(updated_since_ms, limit, trace_id).await
+    }
+
+    async fn store_heuristic(&self, heuristic: &CachedHeuristic, trace_id: Option<&str>) -> Result<(), StorageError> {
+        self.python.store_heuristic(heuristic, trace_id).await
+    }
+
+    async fn update_event_salience(
+        &self,
+        updates: Vec<EventSalienceUpdate>,
+        trace_id: Option<&str>,
+    ) -> Result<usize, StorageError> {
+        self.python.update_event_salience(updates, trace_id).await
+    }
+
+    async fn query_heuristic_feedback(
+        &self,
+        ids: &[Uuid],
+        trace_id: Option<&str>,
+    ) -> Result<HashMap<Uuid, HeuristicFeedback>, StorageError> {
+        self.python.query_heuristic_feedback(ids, trace_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unreachable() -> PgStorageBackend {
+        let config = StorageConfig {
+            address: "http://127.0.0.1:1".to_string(),
+            postgres_url: "postgres://gladys@127.0.0.1:1/gladys".to_string(),
+            connect_timeout_secs: 1,
+            ..StorageConfig::default()
+        };
+        PgStorageBackend::new(config, 0.7).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_recent_embeddings_are_bounded() {
+        let storage = unreachable();
+        for i in 0..=RECENT_EMBEDDINGS {
+            storage.remember(&format!("event {}", i), &[i as f32]);
+        }
+        storage.remember("event 1", &[-1.0]);
+        assert_eq!(storage.recall("event 0"), None);
+        assert_eq!(storage.recall("event 1"), Some(vec![-1.0]));
+        assert_eq!(storage.recall(&format!("event {}", RECENT_EMBEDDINGS)), Some(vec![RECENT_EMBEDDINGS as f32]));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_database_fails_the_fallback() {
+        let storage = unreachable();
+        storage.remember("creeper approaching", &[0.5; 4]);
+        let err = storage.query_matching_heuristics("creeper approaching", 0.5, 5, None, None).await.unwrap_err();
+        assert!(matches!(err, StorageError::Unavailable(_) | StorageError::Timeout(_)), "{:?}", err);
+    }
+}
@@ -0,0 +1,164 @@
+//! Salience vector post-processing.
+//!
+//! Runs after heuristic boosts, in order:
+//! 1. Clamp threat and every vector dimension to [0, 1]
+//! 2. Apply per-dimension floors/ceilings from `SalienceConfig`
+//! 3. Recompute the salience scalar (max over the vector) and the composite score
+//! 4. Optionally normalize the vector (max / sum / softmax)
+//! 5. Report the dominant dimension
+//!
+//! The scalar and composite are computed before normalization so that routing
+//! doesn't change with the normalization mode.
+
+use crate::config::{SalienceConfig, VectorNormalization};
+use crate::proto::SalienceResult;
+use crate::routing::composite_score;
+
+/// Name reported when the threat scalar dominates the vector.
+pub const THREAT_DIMENSION: &str = "threat";
+
+/// Summary values computed while post-processing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostProcessed {
+    /// Highest dimension ("threat" or a vector key; empty if all zero)
+    pub dominant_dimension: String,
+    /// Weighted composite from `routing::composite_score`
+    pub composite_score: f32,
+}
+
+/// Run the post-processing pipeline in place.
+pub fn post_process(salience: &mut SalienceResult, config: &SalienceConfig) -> PostProcessed {
+    salience.threat = bound(THREAT_DIMENSION, salience.threat, config);
+    for (dimension, value) in salience.vector.iter_mut() {
+        *value = bound(dimension, *value, config);
+    }
+    // Floors also introduce dimensions the boosts didn't set
+    for (dimension, floor) in &config.dimension_floors {
+        if dimension != THREAT_DIMENSION && !salience.vector.contains_key(dimension) {
+            let value = bound(dimension, floor.clamp(0.0, 1.0), config);
+            salience.vector.insert(dimension.clone(), value);
+        }
+    }
+
+    salience.salience = salience.vector.values().copied().fold(0.0, f32::max);
+    let processed = PostProcessed {
+        dominant_dimension: dominant_dimension(salience),
+        composite_score: composite_score(salience, config),
+    };
+    normalize(salience, config.vector_normalization);
+    processed
+}
+
+/// Clamp to [0, 1], then to the dimension's configured floor/ceiling.
+fn bound(dimension: &str, value: f32, config: &SalienceConfig) -> f32 {
+    let mut value = if value.is_nan() { 0.0 } else { value.clamp(0.0, 1.0) };
+    if let Some(floor) = config.dimension_floors.get(dimension) {
+        value = value.max(*floor);
+    }
+    if let Some(ceiling) = config.dimension_ceilings.get(dimension) {
+        value = value.min(*ceiling);
+    }
+    value
+}
+
+/// Highest-valued dimension, including threat. Ties go to threat, then alphabetical order.
+/// Empty when everything is zero.
+fn dominant_dimension(salience: &SalienceResult) -> String {
+    let mut best: Option<(&str, f32)> = None;
+    let mut dimensions: Vec<(&String, &f32)> = salience.vector.iter().collect();
+    dimensions.sort_by(|a, b| a.0.cmp(b.0));
+    for (dimension, value) in dimensions {
+        if *value > best.map_or(0.0, |(_, v)| v) {
+            best = Some((dimension, *value));
+        }
+    }
+    if salience.threat > 0.0 && salience.threat >= best.map_or(0.0, |(_, v)| v) {
+        return THREAT_DIMENSION.to_string();
+    }
+    best.map(|(d, _)| d.to_string()).unwrap_or_default()
+}
+
+fn normalize(salience: &mut SalienceResult, mode: VectorNormalization) {
+    let values = || salience.vector.values().copied();
+    let divisor = match mode {
+        VectorNormalization::None => return,
+        VectorNormalization::Max => values().fold(0.0, f32::max),
+        VectorNormalization::Sum => values().sum(),
+        VectorNormalization::Softmax => {
+            let denom: f32 = values().map(f32::exp).sum();
+            for value in salience.vector.values_mut() {
+                *value = value.exp() / denom;
+            }
+            return;
+        }
+    };
+    if divisor > 0.0 {
+        for value in salience.vector.values_mut() {
+            *value /= divisor;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn result(threat: f32, vector: &[(&str, f32)]) -> SalienceResult {
+        SalienceResult {
+            threat,
+            salience: 0.0,
+            habituation: 0.0,
+            vector: vector.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+            model_id: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_clamp_and_bounds() {
+        let config = SalienceConfig {
+            dimension_floors: HashMap::from([("goal_relevance".to_string(), 0.2)]),
+            dimension_ceilings: HashMap::from([("social".to_string(), 0.5)]),
+            ..SalienceConfig::default()
+        };
+        let mut salience = result(1.7, &[("novelty", -0.3), ("social", 0.9)]);
+        let dominant = post_process(&mut salience, &config).dominant_dimension;
+
+        assert_eq!(salience.threat, 1.0);
+        assert_eq!(salience.vector["novelty"], 0.0);
+        assert_eq!(salience.vector["social"], 0.5);
+        assert_eq!(salience.vector["goal_relevance"], 0.2);
+        assert!((salience.salience - 0.5).abs() < 0.001);
+        assert_eq!(dominant, THREAT_DIMENSION);
+    }
+
+    #[test]
+    fn test_normalization_keeps_scalar() {
+        let config = SalienceConfig {
+            vector_normalization: VectorNormalization::Sum,
+            ..SalienceConfig::default()
+        };
+        let mut salience = result(0.0, &[("novelty", 0.2), ("opportunity", 0.6)]);
+        let processed = post_process(&mut salience, &config);
+
+        assert_eq!(processed.dominant_dimension, "opportunity");
+        // composite uses pre-normalization values: 0.15 * 0.2 + 0.1 * 0.6
+        assert!((processed.composite_score - 0.09).abs() < 0.001);
+        assert!((salience.salience - 0.6).abs() < 0.001);
+        assert!((salience.vector["opportunity"] - 0.75).abs() < 0.001);
+
+        let config = SalienceConfig {
+            vector_normalization: VectorNormalization::Softmax,
+            ..SalienceConfig::default()
+        };
+        let mut salience = result(0.0, &[("novelty", 0.5), ("social", 0.5)]);
+        post_process(&mut salience, &config);
+        assert!((salience.vector["novelty"] - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_dominant_dimension_empty_when_zero() {
+        let mut salience = result(0.0, &[("novelty", 0.0)]);
+        assert_eq!(post_process(&mut salience, &SalienceConfig::default()).dominant_dimension, "");
+    }
+}
@@ -0,0 +1,296 @@
+//! Python bindings for the cache and scoring core (feature `python-bindings`).
+//!
+//! The Python side used to re-implement similarity for tests and analysis.
+//! This exposes the fast path's own matching as the `gladys_memory` extension
+//! module instead, so notebooks and the Python services get exactly the same
+//! semantics:
+//! - `MemoryCache`: insert/remove heuristics, `len()`, and `score`
+//! - `Score`: what `score` returns for a match (None = no match)
+//! - `cosine_similarity(a, b)` and `local_embedding(text)`
+//!
+//! `score` is the fast path's hybrid scoring: a heuristic keyword in the text
+//! is conclusive (see keywords module); otherwise the best embedding match.
+//! The matched heuristic's `salience` boost is applied to a zero baseline, as
+//! in the C ABI. Heuristics inserted without an embedding, and text scored
+//! without one, use the hashed bag-of-words embedding of `salience-simulate`
+//! (see ffi module for the caveats).
+//!
+//! Build and install the wheel with maturin from this directory (pyproject.toml
+//! turns on this feature and pyo3's extension-module):
+//!   maturin develop --release
+//!
+//! ```text
+//! >>> import gladys_memory
+//! >>> cache = gladys_memory.MemoryCache()
+//! >>> cache.insert(str(uuid4()), "creeper hissing", '{"salience": {"threat": 0.8}}', 0.9)
+//! >>> cache.score(text="a creeper hissing nearby").threat
+//! 0.8
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use uuid::Uuid;
+
+use crate::domain::Effects;
+use crate::ffi::local_heuristic;
+use crate::proto::gladys::types::SalienceResult;
+use crate::server::apply_salience_boost;
+use crate::{CacheConfig, MatchMethod, MemoryCache, ScoringOutcome};
+
+/// A match, as `MemoryCache.score` reports it.
+#[pyclass(name = "Score", module = "gladys_memory", get_all, frozen)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PyScore {
+    pub heuristic_id: String,
+    /// "keyword" or "embedding"
+    pub method: String,
+    pub similarity: f32,
+    pub confidence: f32,
+    pub threat: f32,
+    pub salience: f32,
+    /// Salience dimensions the boost set
+    pub vector: HashMap<String, f32>,
+}
+
+#[pymethods]
+impl PyScore {
+    fn __repr__(&self) -> String {
+        format!(
+            "Score(heuristic_id='{}', method='{}', similarity={}, threat={}, salience={})",
+            self.heuristic_id, self.method, self.similarity, self.threat, self.salience
+        )
+    }
+}
+
+/// Hybrid scoring of one event: keywords in `text`, then the best embedding
+/// match for `embedding` (or `text` embedded locally).
+fn score_event(
+    cache: &mut MemoryCache,
+    text: Option<&str>,
+    embedding: Option<&[f32]>,
+    min_similarity: f32,
+    min_confidence: f32,
+) -> Result<Option<PyScore>, String> {
+    let embedding = match (embedding.filter(|e| !e.is_empty()), text) {
+        (Some(embedding), _) => embedding.to_vec(),
+        (None, Some(text)) => crate::simulate::local_embedding(text),
+        (None, None) => return Err("score needs text or an embedding".to_string()),
+    };
+    let keyword = text.and_then(|t| cache.find_keyword_matches(t, None, min_confidence, 1).first().copied());
+    let best = match keyword {
+        // A keyword hit is conclusive; its similarity is never reported below the threshold
+        Some(id) => {
+            let similarity = cache
+                .get_heuristic(&id)
+                .and_then(|h| cache.condition_similarity(&embedding, h))
+                .unwrap_or(min_similarity)
+                .max(min_similarity);
+            Some((id, similarity, MatchMethod::Keyword))
+        }
+        None => cache
+            .find_matching_heuristics(&embedding, min_similarity, min_confidence, 1)
+            .first()
+            .map(|&(id, similarity)| (id, similarity, MatchMethod::Embedding)),
+    };
+    let Some((id, similarity, method)) = best else {
+        cache.apply_scoring_outcome(&ScoringOutcome { hit: Some(false), ..Default::default() });
+        return Ok(None);
+    };
+    cache.apply_scoring_outcome(&ScoringOutcome { matched: vec![(id, similarity)], hit: Some(true), ..Default::default() });
+    let Some(heuristic) = cache.get_heuristic(&id) else {
+        return Ok(None);
+    };
+
+    let mut salience = SalienceResult::default();
+    if let Some(boost) = &heuristic.effects.salience {
+        apply_salience_boost(&mut salience, boost);
+    }
+    Ok(Some(PyScore {
+        heuristic_id: id.to_string(),
+        method: method.as_str().to_string(),
+        similarity,
+        confidence: heuristic.confidence,
+        threat: salience.threat,
+        salience: salience.salience,
+        vector: salience.vector,
+    }))
+}
+
+/// The heuristic cache, internally locked (safe to share between threads).
+#[pyclass(name = "MemoryCache", module = "gladys_memory", frozen)]
+pub struct PyMemoryCache {
+    cache: Mutex<MemoryCache>,
+}
+
+impl PyMemoryCache {
+    fn lock(&self) -> std::sync::MutexGuard<'_, MemoryCache> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn parse_id(id: &str) -> PyResult<Uuid> {
+    Uuid::parse_str(id).map_err(|e| PyValueError::new_err(format!("invalid id: {}", e)))
+}
+
+#[pymethods]
+impl PyMemoryCache {
+    /// A cache holding up to `max_heuristics` (0 = default).
+    #[new]
+    #[pyo3(signature = (max_heuristics = 0))]
+    fn new(max_heuristics: usize) -> Self {
+        let mut config = CacheConfig::default();
+        if max_heuristics > 0 {
+            config.max_heuristics = max_heuristics;
+        }
+        Self { cache: Mutex::new(MemoryCache::new(config)) }
+    }
+
+    /// Insert or replace a heuristic; without `embedding`, `condition_text`
+    /// is embedded locally.
+    #[pyo3(signature = (id, condition_text, effects_json = None, confidence = 1.0, embedding = None))]
+    fn insert(
+        &self,
+        id: &str,
+        condition_text: &str,
+        effects_json: Option<&str>,
+        confidence: f32,
+        embedding: Option<Vec<f32>>,
+    ) -> PyResult<()> {
+        let id = parse_id(id)?;
+        let effects = match effects_json {
+            Some(json) => Effects::parse(json).map_err(|e| PyValueError::new_err(format!("invalid effects_json: {}", e)))?,
+            None => Effects::default(),
+        };
+        let heuristic = local_heuristic(id, condition_text, effects, confidence, embedding.as_deref().unwrap_or_default());
+        self.lock().add_heuristic(heuristic);
+        Ok(())
+    }
+
+    /// Remove a heuristic; False if it wasn't cached.
+    fn remove(&self, id: &str) -> PyResult<bool> {
+        Ok(self.lock().remove_heuristic(&parse_id(id)?))
+    }
+
+    fn __len__(&self) -> usize {
+        self.lock().stats().heuristic_count
+    }
+
+    /// Best match for an event, by `text` and/or `embedding` (None = no match).
+    #[pyo3(signature = (text = None, embedding = None, min_similarity = 0.7, min_confidence = 0.5))]
+    fn score(
+        &self,
+        text: Option<&str>,
+        embedding: Option<Vec<f32>>,
+        min_similarity: f32,
+        min_confidence: f32,
+    ) -> PyResult<Option<PyScore>> {
+        score_event(&mut self.lock(), text, embedding.as_deref(), min_similarity, min_confidence)
+            .map_err(PyValueError::new_err)
+    }
+}
+
+/// Cosine similarity of two vectors, as the cache computes it.
+#[pyfunction]
+fn cosine_similarity(a: Vec<f32>, b: Vec<f32>) -> f32 {
+    crate::cosine_similarity(&a, &b)
+}
+
+/// The hashed bag-of-words embedding used for text without an embedding.
+#[pyfunction]
+fn local_embedding(text: &str) -> Vec<f32> {
+    crate::simulate::local_embedding(text)
+}
+
+#[pymodule]
+fn gladys_memory(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyMemoryCache>()?;
+    module.add_class::<PyScore>()?;
+    module.add_function(wrap_pyfunction!(cosine_similarity, module)?)?;
+    module.add_function(wrap_pyfunction!(local_embedding, module)?)?;
+    module.add("__version__", crate::version::VERSION)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_with(effects: &str) -> (MemoryCache, Uuid) {
+        let mut cache = MemoryCache::new(CacheConfig::default());
+        let id = Uuid::new_v4();
+        cache.add_heuristic(local_heuristic(id, "creeper hissing nearby", Effects::parse(effects).unwrap(), 0.9, &[]));
+        (cache, id)
+    }
+
+    #[test]
+    fn test_score_embedding_then_miss() {
+        let (mut cache, id) = cache_with(r#"{"salience": {"threat": 0.8, "social": 0.4}}"#);
+        let score = score_event(&mut cache, Some("creeper hissing nearby"), None, 0.8, 0.5).unwrap().unwrap();
+        assert_eq!((score.heuristic_id, score.method.as_str()), (id.to_string(), "embedding"));
+        assert!((score.threat - 0.8).abs() < 1e-6);
+        assert!((score.vector["social"] - 0.4).abs() < 1e-6);
+
+        assert_eq!(score_event(&mut cache, Some("quiet meadow"), None, 0.8, 0.5).unwrap(), None);
+        let stats = cache.stats();
+        assert_eq!((stats.total_hits, stats.total_misses), (1, 1));
+    }
+
+    #[test]
+    fn test_score_keyword_is_conclusive() {
+        let (mut cache, id) = cache_with(r#"{"keywords": ["creeper"], "salience": {"threat": 0.9}}"#);
+        // Far from the condition by embedding, but the keyword fires it
+        let score = score_event(&mut cache, Some("a Creeper!"), Some(&[0.0; 384]), 0.8, 0.5).unwrap().unwrap();
+        assert_eq!((score.heuristic_id, score.method.as_str()), (id.to_string(), "keyword"));
+        assert!(score.similarity >= 0.8);
+    }
+
+    #[test]
+    fn test_score_needs_text_or_embedding() {
+        let (mut cache, _) = cache_with("{}");
+        assert!(score_event(&mut cache, None, None, 0.8, 0.5).is_err());
+        assert!(score_event(&mut cache, None, Some(&[]), 0.8, 0.5).is_err());
+    }
+
+    #[test]
+    fn test_module_round_trip() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let globals = pyo3::types::PyDict::new(py);
+            globals.set_item("gm", pyo3::wrap_pymodule!(gladys_memory)(py)).unwrap();
+            globals.set_item("hid", Uuid::new_v4().to_string()).unwrap();
+            py.run(
+                cr#"
+cache = gm.MemoryCache()
+cache.insert(hid, "creeper hissing nearby", '{"salience": {"threat": 0.8}}', 0.9)
+assert len(cache) == 1
+
+score = cache.score(text="creeper hissing nearby")
+assert (score.heuristic_id, score.method) == (hid, "embedding"), score
+assert abs(score.threat - 0.8) < 1e-6 and score.vector == {}
+assert cache.score(text="quiet meadow") is None
+
+embedding = gm.local_embedding("creeper hissing nearby")
+assert abs(gm.cosine_similarity(embedding, embedding) - 1.0) < 1e-6
+assert cache.score(embedding=embedding).heuristic_id == hid
+
+for bad in [lambda: cache.insert("not-a-uuid", "x"), lambda: cache.insert(hid, "x", "{"), lambda: cache.score()]:
+    try:
+        bad()
+        raise AssertionError("expected ValueError")
+    except ValueError:
+        pass
+
+assert cache.remove(hid) and not cache.remove(hid)
+assert len(cache) == 0
+assert gm.__version__
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+        });
+    }
+}
@@ -0,0 +1,207 @@
+//! Per-source QoS classes with separate concurrency budgets.
+//!
+//! Under overload every event competes for the same runtime, so a flood of
+//! bulk telemetry slows user-directed chat just as much as itself. QoS
+//! classes give groups of sources their own budget: each class has a limit
+//! on concurrent evaluations and on evaluations waiting for one. An event
+//! past both limits fails at once with RESOURCE_EXHAUSTED (the bus and MQTT
+//! consumers count it as failed), so a saturated bulk class sheds its own
+//! traffic while an interactive class with headroom keeps low latency.
+//!
+//! Classes are configured as JSON in SALIENCE_QOS_CLASSES, each listing the
+//! sources it covers:
+//!
+//! ```text
+//! {"interactive": {"sources": ["chat", "voice"], "max_concurrent": 32, "max_queued": 64},
+//!  "bulk": {"sources": ["telemetry"], "max_concurrent": 4, "max_queued": 8}}
+//! ```
+//!
+//! Sources no class lists fall into SALIENCE_QOS_DEFAULT_CLASS, or run
+//! unbudgeted when that's unset. Per-class in-flight, queued and rejected
+//! counts are reported in health details.
+//!
+//! Configuration via environment variables:
+//!   SALIENCE_QOS_CLASSES: QoS classes as JSON (default: none = no budgets)
+//!   SALIENCE_QOS_DEFAULT_CLASS: Class for sources no class lists (default: none)
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use serde::Deserialize;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+fn default_max_concurrent() -> usize {
+    16
+}
+
+fn default_max_queued() -> usize {
+    64
+}
+
+/// One QoS class as configured.
+#[derive(Debug, Clone, Deserialize)]
+pub struct QosClassConfig {
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Evaluations running at once (default: 16)
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: usize,
+    /// Evaluations waiting for a slot before new ones are rejected (default: 64)
+    #[serde(default = "default_max_queued")]
+    pub max_queued: usize,
+}
+
+/// Parse SALIENCE_QOS_CLASSES (class name -> class).
+pub fn parse_qos_classes(json: &str) -> Result<BTreeMap<String, QosClassConfig>, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+/// Errors admitting an evaluation.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum QosError {
+    #[error("QoS class {class} is saturated ({queued} evaluations queued)")]
+    Saturated { class: String, queued: usize },
+}
+
+struct QosClass {
+    name: String,
+    max_concurrent: usize,
+    max_queued: usize,
+    slots: Semaphore,
+    queued: AtomicUsize,
+    rejected: AtomicU64,
+}
+
+/// Admits evaluations against their source's class budget.
+pub struct QosScheduler {
+    classes: Vec<QosClass>,
+    /// Source -> index into `classes`
+    sources: HashMap<String, usize>,
+    default_class: Option<usize>,
+}
+
+impl QosScheduler {
+    /// Build the classes; a source listed by several classes goes to the
+    /// first by name, and an unknown default class is ignored.
+    pub fn new(classes: BTreeMap<String, QosClassConfig>, default_class: Option<&str>) -> Self {
+        let mut sources = HashMap::new();
+        let classes: Vec<QosClass> = classes
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, config))| {
+                for source in config.sources {
+                    sources.entry(source).or_insert(i);
+                }
+                let max_concurrent = config.max_concurrent.max(1);
+                QosClass {
+                    name,
+                    max_concurrent,
+                    max_queued: config.max_queued,
+                    slots: Semaphore::new(max_concurrent),
+                    queued: AtomicUsize::new(0),
+                    rejected: AtomicU64::new(0),
+                }
+            })
+            .collect();
+        let default_class = default_class.and_then(|name| classes.iter().position(|c| c.name == name));
+        Self { classes, sources, default_class }
+    }
+
+    /// Class names, sorted.
+    pub fn class_names(&self) -> Vec<&str> {
+        self.classes.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    /// The class `source` is budgeted under (None = unbudgeted).
+    pub fn class_of(&self, source: &str) -> Option<&str> {
+        self.class_index(source).map(|i| self.classes[i].name.as_str())
+    }
+
+    fn class_index(&self, source: &str) -> Option<usize> {
+        self.sources.get(source).copied().or(self.default_class)
+    }
+
+    /// Wait for a slot in `source`'s class, or fail at once if its queue is
+    /// full. The slot is held until the permit drops (None = unbudgeted).
+    pub async fn admit(&self, source: &str) -> Result<Option<SemaphorePermit<'_>>, QosError> {
+        let Some(index) = self.class_index(source) else {
+            return Ok(None);
+        };
+        let class = &self.classes[index];
+        if let Ok(permit) = class.slots.try_acquire() {
+            return Ok(Some(permit));
+        }
+        let queued = class.queued.fetch_add(1, Ordering::Relaxed);
+        if queued >= class.max_queued {
+            class.queued.fetch_sub(1, Ordering::Relaxed);
+            class.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(QosError::Saturated { class: class.name.clone(), queued });
+        }
+        let permit = class.slots.acquire().await;
+        class.queued.fetch_sub(1, Ordering::Relaxed);
+        // The semaphore is never closed
+        Ok(permit.ok())
+    }
+
+    pub fn health_details(&self) -> HashMap<String, String> {
+        let mut details = HashMap::new();
+        for class in &self.classes {
+            let in_flight = class.max_concurrent - class.slots.available_permits();
+            details.insert(format!("qos_{}_in_flight", class.name), in_flight.to_string());
+            details.insert(format!("qos_{}_queued", class.name), class.queued.load(Ordering::Relaxed).to_string());
+            details.insert(format!("qos_{}_rejected", class.name), class.rejected.load(Ordering::Relaxed).to_string());
+        }
+        details
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler() -> QosScheduler {
+        let classes = parse_qos_classes(
+            r#"{"interactive": {"sources": ["chat"], "max_concurrent": 2},
+                "bulk": {"sources": ["telemetry"], "max_concurrent": 1, "max_queued": 1}}"#,
+        )
+        .unwrap();
+        QosScheduler::new(classes, Some("bulk"))
+    }
+
+    #[test]
+    fn test_sources_map_to_classes() {
+        let qos = scheduler();
+        assert_eq!(qos.class_of("chat"), Some("interactive"));
+        assert_eq!(qos.class_of("telemetry"), Some("bulk"));
+        assert_eq!(qos.class_of("sensors"), Some("bulk"));
+        assert_eq!(QosScheduler::new(BTreeMap::new(), Some("missing")).class_of("sensors"), None);
+        assert!(parse_qos_classes("{").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_saturated_class_rejects_without_affecting_others() {
+        let qos = scheduler();
+        let running = qos.admit("telemetry").await.unwrap().unwrap();
+
+        // One may wait for the busy slot; the next is rejected
+        let mut waiting = tokio_test::task::spawn(qos.admit("telemetry"));
+        assert!(waiting.poll().is_pending());
+        assert_eq!(
+            qos.admit("sensors").await.unwrap_err(),
+            QosError::Saturated { class: "bulk".to_string(), queued: 1 }
+        );
+
+        // Interactive traffic has its own budget
+        let _chat = qos.admit("chat").await.unwrap().unwrap();
+        assert!(qos.admit("unknown").await.is_err());
+        let details = qos.health_details();
+        assert_eq!(details["qos_bulk_in_flight"], "1");
+        assert_eq!(details["qos_bulk_queued"], "1");
+        assert_eq!(details["qos_bulk_rejected"], "2");
+        assert_eq!(details["qos_interactive_in_flight"], "1");
+
+        drop(running);
+        assert!(waiting.is_woken());
+        assert!(matches!(waiting.poll(), std::task::Poll::Ready(Ok(Some(_)))));
+    }
+}
@@ -0,0 +1,83 @@
+//! Token bucket for storage fallback queries.
+//!
+//! On a cold cache every event misses and falls back to the Python storage
+//! service. The bucket caps that fallback rate; once it's empty, events get
+//! baseline salience with `degraded=true` instead of queueing more work
+//! against storage.
+//!
+//! Configuration via environment variables:
+//!   SALIENCE_FALLBACK_RATE: Fallback queries per second (default: 0 = unlimited)
+//!   SALIENCE_FALLBACK_BURST: Bucket capacity (default: 10)
+
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::config::SalienceConfig;
+
+/// A token bucket refilled continuously at `rate_per_sec`, holding at most `burst` tokens.
+pub struct TokenBucket {
+    rate_per_sec: f64,
+    burst: f64,
+    /// (tokens available, last refill)
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    /// A full bucket.
+    pub fn new(rate_per_sec: f64, burst: u32) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self { rate_per_sec, burst, state: Mutex::new((burst, Instant::now())) }
+    }
+
+    /// The fallback limiter configured in `config`, if any.
+    pub fn for_fallback(config: &SalienceConfig) -> Option<Self> {
+        (config.fallback_rate_per_sec > 0.0).then(|| Self::new(config.fallback_rate_per_sec, config.fallback_burst))
+    }
+
+    /// Take a token if one is available.
+    pub fn try_acquire(&self) -> bool {
+        self.try_acquire_at(Instant::now())
+    }
+
+    fn try_acquire_at(&self, now: Instant) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (tokens, last) = &mut *state;
+        let elapsed = now.saturating_duration_since(*last).as_secs_f64();
+        *tokens = (*tokens + elapsed * self.rate_per_sec).min(self.burst);
+        *last = now.max(*last);
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_bucket_burst_then_refill() {
+        let bucket = TokenBucket::new(2.0, 3);
+        let start = Instant::now();
+        assert!((0..3).all(|_| bucket.try_acquire_at(start)));
+        assert!(!bucket.try_acquire_at(start));
+
+        // 2/sec: one token after 500ms, never more than the burst
+        assert!(bucket.try_acquire_at(start + Duration::from_millis(500)));
+        assert!(!bucket.try_acquire_at(start + Duration::from_millis(600)));
+        let later = start + Duration::from_secs(60);
+        assert_eq!((0..10).filter(|_| bucket.try_acquire_at(later)).count(), 3);
+    }
+
+    #[test]
+    fn test_fallback_limiter_disabled_by_default() {
+        let config = SalienceConfig { fallback_rate_per_sec: 0.0, ..SalienceConfig::default() };
+        assert!(TokenBucket::for_fallback(&config).is_none());
+        let config = SalienceConfig { fallback_rate_per_sec: 5.0, ..SalienceConfig::default() };
+        assert!(TokenBucket::for_fallback(&config).is_some());
+    }
+}
@@ -0,0 +1,509 @@
+//! Record/replay of storage interactions for deterministic tests.
+//!
+//! Integration scenarios need the Python storage service, which CI doesn't
+//! run. `RecordingStorage` wraps a live backend and appends every call and
+//! its outcome to a JSON Lines file; `ReplayStorage` serves a recording back
+//! without any storage service:
+//! - Calls are matched on method and arguments (trace IDs are ignored)
+//! - Repeated identical calls replay their recorded outcomes in order; once
+//!   they run out, the last one repeats (e.g., for the refresh loop)
+//! - Errors are recorded and replayed as the same `StorageError` variant
+//! - A call with no recording fails with NOT_FOUND naming the call, so a
+//!   scenario that drifted from its recording is obvious
+//!
+//! Heuristics are stored in the heuristic file schema (see the seed module)
+//! plus their condition embedding, so recordings can be edited by hand.
+//!
+//! Configuration via environment variables (see `StorageConfig`):
+//!   STORAGE_RECORD_PATH: Append storage interactions to this file (default: none)
+//!   STORAGE_REPLAY_PATH: Serve storage from this recording instead of STORAGE_ADDRESS (default: none)
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::proto::EventSalienceUpdate;
+use crate::seed::SeedHeuristic;
+use crate::{CachedHeuristic, HeuristicChanges, HeuristicFeedback, StorageBackend, StorageError};
+
+/// A storage call's arguments (what replay matches on).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum StorageCall {
+    QueryMatchingHeuristics {
+        event_text: String,
+        min_confidence: f32,
+        limit: i32,
+        #[serde(default)]
+        source_filter: Option<String>,
+    },
+    GenerateEmbedding {
+        text: String,
+    },
+    QueryChangedHeuristics {
+        updated_since_ms: i64,
+        limit: i32,
+    },
+    StoreHeuristic {
+        heuristic: Box<RecordedHeuristic>,
+    },
+    UpdateEventSalience {
+        event_ids: Vec<String>,
+    },
+    QueryHeuristicFeedback {
+        ids: Vec<Uuid>,
+    },
+}
+
+impl StorageCall {
+    /// Replay lookup key: the call's canonical JSON.
+    fn key(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// What a storage call returned.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageOutcome {
+    Heuristics(Vec<RecordedHeuristic>),
+    Embedding(Vec<f32>),
+    Changes(RecordedChanges),
+    Stored,
+    Updated(usize),
+    Feedback(HashMap<Uuid, HeuristicFeedback>),
+    Error(RecordedError),
+}
+
+/// One line of a recording.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    #[serde(flatten)]
+    pub call: StorageCall,
+    pub outcome: StorageOutcome,
+}
+
+/// A heuristic in the heuristic file schema, plus its embedding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedHeuristic {
+    #[serde(flatten)]
+    pub heuristic: SeedHeuristic,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub condition_embedding: Vec<f32>,
+}
+
+impl From<&CachedHeuristic> for RecordedHeuristic {
+    fn from(h: &CachedHeuristic) -> Self {
+        Self { heuristic: SeedHeuristic::from(h), condition_embedding: h.condition_embedding.to_vec() }
+    }
+}
+
+impl RecordedHeuristic {
+    fn to_cached(&self) -> CachedHeuristic {
+        // An unset origin stays unset (seeding would default it to "system")
+        let origin = self.heuristic.origin.clone().unwrap_or_default();
+        let mut h = self.heuristic.clone().into_cached(self.condition_embedding.clone());
+        h.origin = origin;
+        h
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedChanges {
+    pub heuristics: Vec<RecordedHeuristic>,
+    #[serde(default)]
+    pub deleted_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub latest_updated_ms: i64,
+}
+
+/// A `StorageError`, by its stable code.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedError {
+    pub code: String,
+    /// gRPC status code (only used to rebuild STORAGE_RPC_FAILED)
+    #[serde(default)]
+    pub status: i32,
+    pub message: String,
+}
+
+impl From<&StorageError> for RecordedError {
+    fn from(e: &StorageError) -> Self {
+        let message = match e {
+            StorageError::NotConnected(m)
+            | StorageError::Timeout(m)
+            | StorageError::Unavailable(m)
+            | StorageError::Storage(m)
+            | StorageError::Decode(m) => m.clone(),
+            StorageError::Rpc { message, .. } => message.clone(),
+        };
+        Self { code: e.code().to_string(), status: e.status_code() as i32, message }
+    }
+}
+
+impl From<&RecordedError> for StorageError {
+    fn from(e: &RecordedError) -> Self {
+        let message = e.message.clone();
+        match e.code.as_str() {
+            "STORAGE_NOT_CONNECTED" => StorageError::NotConnected(message),
+            "STORAGE_TIMEOUT" => StorageError::Timeout(message),
+            "STORAGE_UNAVAILABLE" => StorageError::Unavailable(message),
+            "STORAGE_ERROR" => StorageError::Storage(message),
+            "STORAGE_DECODE_FAILED" => StorageError::Decode(message),
+            _ => StorageError::Rpc { code: tonic::Code::from_i32(e.status), message },
+        }
+    }
+}
+
+/// Decorator appending each call on `inner` to a recording file.
+pub struct RecordingStorage {
+    inner: Arc<dyn StorageBackend>,
+    file: Mutex<LineWriter<File>>,
+}
+
+impl RecordingStorage {
+    /// Record to `path` (appending if it exists).
+    pub fn create(inner: Arc<dyn StorageBackend>, path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { inner, file: Mutex::new(LineWriter::new(file)) })
+    }
+
+    fn record<T>(&self, call: StorageCall, result: &Result<T, StorageError>, outcome: impl FnOnce(&T) -> StorageOutcome) {
+        let outcome = match result {
+            Ok(value) => outcome(value),
+            Err(e) => StorageOutcome::Error(RecordedError::from(e)),
+        };
+        let line = match serde_json::to_string(&Interaction { call, outcome }) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!(error = %e, "Failed to serialize storage interaction");
+                return;
+            }
+        };
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(file, "{}", line) {
+            warn!(error = %e, "Failed to record storage interaction");
+        }
+    }
+}
+
+fn recorded_heuristics(heuristics: &[CachedHeuristic]) -> Vec<RecordedHeuristic> {
+    heuristics.iter().map(RecordedHeuristic::from).collect()
+}
+
+#[tonic::async_trait]
+impl StorageBackend for RecordingStorage {
+    async fn query_matching_heuristics(
+        &self,
+        event_text: &str,
+        min_confidence: f32,
+        limit: i32,
+        source_filter: Option<&str>,
+        trace_id: Option<&str>,
+    ) -> Result<Vec<CachedHeuristic>, StorageError> {
+        let result = self
+            .inner
+            .query_matching_heuristics(event_text, min_confidence, limit, source_filter, trace_id)
+            .await;
+        let call = StorageCall::QueryMatchingHeuristics {
+            event_text: event_text.to_string(),
+            min_confidence,
+            limit,
+            source_filter: source_filter.map(str::to_string),
+        };
+        self.record(call, &result, |h| StorageOutcome::Heuristics(recorded_heuristics(h)));
+        result
+    }
+
+    async fn generate_embedding(&self, text: &str, trace_id: Option<&str>) -> Result<Vec<f32>, StorageError> {
+        let result = self.inner.generate_embedding(text, trace_id).await;
+        let call = StorageCall::GenerateEmbedding { text: text.to_string() };
+        self.record(call, &result, |e| StorageOutcome::Embedding(e.clone()));
+        result
+    }
+
+    fn embedding_model(&self) -> Option<String> {
+        self.inner.embedding_model()
+    }
+
+    async fn query_changed_heuristics(
+        &self,
+        updated_since_ms: i64,
+        limit: i32,
+        trace_id: Option<&str>,
+    ) -> Result<HeuristicChanges, StorageError> {
+        let result = self.inner.query_changed_heuristics(updated_since_ms, limit, trace_id).await;
+        let call = StorageCall::QueryChangedHeuristics { updated_since_ms, limit };
+        self.record(call, &result, |c| {
+            StorageOutcome::Changes(RecordedChanges {
+                heuristics: recorded_heuristics(&c.heuristics),
+                deleted_ids: c.deleted_ids.clone(),
+                latest_updated_ms: c.latest_updated_ms,
+            })
+        });
+        result
+    }
+
+    async fn store_heuristic(&self, heuristic: &CachedHeuristic, trace_id: Option<&str>) -> Result<(), StorageError> {
+        let result = self.inner.store_heuristic(heuristic, trace_id).await;
+        let call = StorageCall::StoreHeuristic { heuristic: Box::new(RecordedHeuristic::from(heuristic)) };
+        self.record(call, &result, |_| StorageOutcome::Stored);
+        result
+    }
+
+    async fn update_event_salience(
+        &self,
+        updates: Vec<EventSalienceUpdate>,
+        trace_id: Option<&str>,
+    ) -> Result<usize, StorageError> {
+        let event_ids = updates.iter().map(|u| u.event_id.clone()).collect();
+        let result = self.inner.update_event_salience(updates, trace_id).await;
+        self.record(StorageCall::UpdateEventSalience { event_ids }, &result, |n| StorageOutcome::Updated(*n));
+        result
+    }
+
+    async fn query_heuristic_feedback(
+        &self,
+        ids: &[Uuid],
+        trace_id: Option<&str>,
+    ) -> Result<HashMap<Uuid, HeuristicFeedback>, StorageError> {
+        let result = self.inner.query_heuristic_feedback(ids, trace_id).await;
+        let call = StorageCall::QueryHeuristicFeedback { ids: ids.to_vec() };
+        self.record(call, &result, |f| StorageOutcome::Feedback(f.clone()));
+        result
+    }
+}
+
+/// Errors loading a recording.
+#[derive(Debug, thiserror::Error)]
+pub enum RecordingError {
+    #[error("Failed to read recording {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Invalid recording at line {line}: {source}")]
+    Parse {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+struct Recorded {
+    outcomes: VecDeque<StorageOutcome>,
+    /// Served once the queued outcomes run out
+    last: StorageOutcome,
+}
+
+/// Backend serving a recording made by `RecordingStorage`.
+pub struct ReplayStorage {
+    recorded: Mutex<HashMap<String, Recorded>>,
+}
+
+impl ReplayStorage {
+    pub fn new(interactions: impl IntoIterator<Item = Interaction>) -> Self {
+        let mut recorded: HashMap<String, Recorded> = HashMap::new();
+        for Interaction { call, outcome } in interactions {
+            let entry = recorded
+                .entry(call.key())
+                .or_insert_with(|| Recorded { outcomes: VecDeque::new(), last: outcome.clone() });
+            entry.last = outcome.clone();
+            entry.outcomes.push_back(outcome);
+        }
+        Self { recorded: Mutex::new(recorded) }
+    }
+
+    /// Parse a JSON Lines recording (blank lines are skipped).
+    pub fn parse(contents: &str) -> Result<Self, RecordingError> {
+        let interactions = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| serde_json::from_str(line).map_err(|source| RecordingError::Parse { line: i + 1, source }))
+            .collect::<Result<Vec<Interaction>, _>>()?;
+        Ok(Self::new(interactions))
+    }
+
+    pub fn load(path: &str) -> Result<Self, RecordingError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| RecordingError::Io { path: path.to_string(), source })?;
+        Self::parse(&contents)
+    }
+
+    fn replay(&self, call: StorageCall) -> Result<StorageOutcome, StorageError> {
+        let mut recorded = self.recorded.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(entry) = recorded.get_mut(&call.key()) else {
+            return Err(StorageError::Rpc {
+                code: tonic::Code::NotFound,
+                message: format!("no recorded response for {}", call.key()),
+            });
+        };
+        match entry.outcomes.pop_front().unwrap_or_else(|| entry.last.clone()) {
+            StorageOutcome::Error(e) => Err(StorageError::from(&e)),
+            outcome => Ok(outcome),
+        }
+    }
+}
+
+fn mismatched(method: &str) -> StorageError {
+    StorageError::Decode(format!("recorded outcome for {} has the wrong shape", method))
+}
+
+#[tonic::async_trait]
+impl StorageBackend for ReplayStorage {
+    async fn query_matching_heuristics(
+        &self,
+        event_text: &str,
+        min_confidence: f32,
+        limit: i32,
+        source_filter: Option<&str>,
+        _trace_id: Option<&str>,
+    ) -> Result<Vec<CachedHeuristic>, StorageError> {
+        let call = StorageCall::QueryMatchingHeuristics {
+            event_text: event_text.to_string(),
+            min_confidence,
+            limit,
+            source_filter: source_filter.map(str::to_string),
+        };
+        match self.replay(call)? {
+            StorageOutcome::Heuristics(h) => Ok(h.iter().map(RecordedHeuristic::to_cached).collect()),
+            _ => Err(mismatched("query_matching_heuristics")),
+        }
+    }
+
+    async fn generate_embedding(&self, text: &str, _trace_id: Option<&str>) -> Result<Vec<f32>, StorageError> {
+        match self.replay(StorageCall::GenerateEmbedding { text: text.to_string() })? {
+            StorageOutcome::Embedding(e) => Ok(e),
+            _ => Err(mismatched("generate_embedding")),
+        }
+    }
+
+    async fn query_changed_heuristics(
+        &self,
+        updated_since_ms: i64,
+        limit: i32,
+        _trace_id: Option<&str>,
+    ) -> Result<HeuristicChanges, StorageError> {
+        match self.replay(StorageCall::QueryChangedHeuristics { updated_since_ms, limit })? {
+            StorageOutcome::Changes(c) => Ok(HeuristicChanges {
+                heuristics: c.heuristics.iter().map(RecordedHeuristic::to_cached).collect(),
+                deleted_ids: c.deleted_ids,
+                latest_updated_ms: c.latest_updated_ms,
+            }),
+            _ => Err(mismatched("query_changed_heuristics")),
+        }
+    }
+
+    async fn store_heuristic(&self, heuristic: &CachedHeuristic, _trace_id: Option<&str>) -> Result<(), StorageError> {
+        match self.replay(StorageCall::StoreHeuristic { heuristic: Box::new(RecordedHeuristic::from(heuristic)) })? {
+            StorageOutcome::Stored => Ok(()),
+            _ => Err(mismatched("store_heuristic")),
+        }
+    }
+
+    async fn update_event_salience(
+        &self,
+        updates: Vec<EventSalienceUpdate>,
+        _trace_id: Option<&str>,
+    ) -> Result<usize, StorageError> {
+        let event_ids = updates.into_iter().map(|u| u.event_id).collect();
+        match self.replay(StorageCall::UpdateEventSalience { event_ids })? {
+            StorageOutcome::Updated(n) => Ok(n),
+            _ => Err(mismatched("update_event_salience")),
+        }
+    }
+
+    async fn query_heuristic_feedback(
+        &self,
+        ids: &[Uuid],
+        _trace_id: Option<&str>,
+    ) -> Result<HashMap<Uuid, HeuristicFeedback>, StorageError> {
+        match self.replay(StorageCall::QueryHeuristicFeedback { ids: ids.to_vec() })? {
+            StorageOutcome::Feedback(f) => Ok(f),
+            _ => Err(mismatched("query_heuristic_feedback")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Condition;
+
+    struct LiveStorage;
+
+    #[tonic::async_trait]
+    impl StorageBackend for LiveStorage {
+        async fn query_matching_heuristics(
+            &self,
+            event_text: &str,
+            _min_confidence: f32,
+            _limit: i32,
+            _source_filter: Option<&str>,
+            _trace_id: Option<&str>,
+        ) -> Result<Vec<CachedHeuristic>, StorageError> {
+            if event_text == "down" {
+                return Err(StorageError::Timeout("deadline exceeded".to_string()));
+            }
+            Ok(vec![CachedHeuristic {
+                id: Uuid::new_v4(),
+                name: "creeper".to_string(),
+                condition: Condition::text("creeper approaching"),
+                effects: serde_json::json!({"salience": {"threat": 0.8}}).into(),
+                confidence: 0.9,
+                origin: String::new(),
+                source: "minecraft".to_string(),
+                condition_embedding: vec![0.25, 0.5].into(),
+                last_accessed_ms: 0,
+                cached_at_ms: 0,
+                hit_count: 0,
+                last_hit_ms: 0,
+                cooldown_until_ms: 0,
+                embedding_model: String::new(),
+            }])
+        }
+
+        async fn generate_embedding(&self, text: &str, _trace_id: Option<&str>) -> Result<Vec<f32>, StorageError> {
+            Ok(vec![text.len() as f32])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_serves_recorded_interactions() {
+        let path = std::env::temp_dir().join(format!("gladys-recording-{}.jsonl", Uuid::new_v4()));
+        let recorder = RecordingStorage::create(Arc::new(LiveStorage), &path).unwrap();
+        let live = recorder.query_matching_heuristics("creeper", 0.5, 10, Some("minecraft"), Some("t1")).await.unwrap();
+        recorder.generate_embedding("abc", None).await.unwrap();
+        recorder.generate_embedding("abc", None).await.unwrap();
+        assert!(recorder.query_matching_heuristics("down", 0.5, 10, None, None).await.is_err());
+        drop(recorder);
+
+        let replay = ReplayStorage::load(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // Trace IDs aren't part of the match
+        let replayed = replay.query_matching_heuristics("creeper", 0.5, 10, Some("minecraft"), None).await.unwrap();
+        assert_eq!(replayed.len(), 1);
+        assert_eq!(replayed[0].id, live[0].id);
+        assert_eq!(replayed[0].effects, live[0].effects);
+        assert_eq!(replayed[0].condition_embedding, live[0].condition_embedding);
+        assert!(replayed[0].origin.is_empty());
+
+        // Repeated calls keep replaying after the recording runs out
+        for _ in 0..3 {
+            assert_eq!(replay.generate_embedding("abc", None).await.unwrap(), vec![3.0]);
+        }
+        let error = replay.query_matching_heuristics("down", 0.5, 10, None, None).await.unwrap_err();
+        assert_eq!(error, StorageError::Timeout("deadline exceeded".to_string()));
+
+        let unrecorded = replay.generate_embedding("zombie", None).await.unwrap_err();
+        assert!(matches!(unrecorded, StorageError::Rpc { code: tonic::Code::NotFound, .. }));
+    }
+}
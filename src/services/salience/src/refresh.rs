@@ -0,0 +1,398 @@
+//! Background heuristic refresh loop.
+//!
+//! Keeps the L0 heuristic cache warm without hammering storage:
+//! - Conditional fetches: only heuristics updated since the last sync
+//! - Quiet change feed: the interval doubles (up to the max) while nothing changes
+//! - Storage outages: exponential backoff (up to the max) until a fetch succeeds
+//!
+//! Any change resets the interval to the base. The latest status is shared
+//! with `SalienceService` and reported in `GetHealthDetails`.
+//!
+//! In a fleet, every replica polling storage multiplies the load. One replica
+//! can be configured as the leader: after each refresh it broadcasts what it
+//! fetched to its peers (see peers module) via ApplyHeuristicChanges, even
+//! when nothing changed. Followers apply the broadcasts and only query storage
+//! themselves once the leader has been silent for `REFRESH_LEADER_TIMEOUT_MS`,
+//! picking up from the leader's watermark.
+//!
+//! Configuration via environment variables (see `RefreshConfig`):
+//!   REFRESH_INTERVAL_MS: Base interval (default: 0 = disabled)
+//!   REFRESH_MAX_INTERVAL_MS: Adaptive interval cap (default: 60000)
+//!   REFRESH_BATCH_LIMIT: Heuristics per fetch (default: 50)
+//!   REFRESH_ROLE: independent, leader, or follower (default: independent)
+//!   REFRESH_LEADER_TIMEOUT_MS: Follower fallback after leader silence (default: 180000)
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tracing::{debug, info, warn};
+
+use crate::config::{RefreshConfig, RefreshRole};
+use crate::peers::PeerSet;
+use crate::proto::{self, ApplyHeuristicChangesRequest};
+use crate::{CacheHandle, HeuristicChanges, StorageBackend, StorageError};
+
+/// Exponentially growing interval that resets on activity.
+#[derive(Debug, Clone)]
+pub struct AdaptiveInterval {
+    base: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl AdaptiveInterval {
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self { base, max: max.max(base), current: base }
+    }
+
+    pub fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// Something changed: poll at the base rate again.
+    pub fn reset(&mut self) {
+        self.current = self.base;
+    }
+
+    /// Quiet feed or failure: double the interval, capped at the max.
+    pub fn grow(&mut self) {
+        self.current = self.current.saturating_mul(2).min(self.max);
+    }
+}
+
+/// Outcome of the most recent refreshes, for health reporting.
+#[derive(Debug, Clone, Default)]
+pub struct RefreshStatus {
+    /// When the last refresh was attempted (Unix ms, 0 = never)
+    pub last_attempt_ms: i64,
+    /// When the last refresh succeeded (Unix ms, 0 = never)
+    pub last_success_ms: i64,
+    /// Error from the last attempt, if it failed
+    pub last_error: Option<String>,
+    /// Failed attempts since the last success
+    pub consecutive_failures: u32,
+    /// Heuristics merged or removed by the last successful refresh
+    pub last_changed: usize,
+    /// Sync watermark: latest heuristic `updated_at_ms` seen
+    pub synced_until_ms: i64,
+    /// Current adaptive interval in milliseconds
+    pub interval_ms: u64,
+    /// This replica's part in fleet refresh
+    pub role: RefreshRole,
+    /// When the leader's last broadcast arrived (Unix ms, 0 = never)
+    pub last_leader_ms: i64,
+    /// Peers that accepted the last broadcast (leader only)
+    pub last_broadcast_delivered: usize,
+}
+
+impl RefreshStatus {
+    /// Summary key-value pairs for `GetHealthDetails`.
+    pub fn health_details(&self) -> HashMap<String, String> {
+        let mut details = HashMap::from([
+            ("refresh.last_attempt_ms".to_string(), self.last_attempt_ms.to_string()),
+            ("refresh.last_success_ms".to_string(), self.last_success_ms.to_string()),
+            ("refresh.consecutive_failures".to_string(), self.consecutive_failures.to_string()),
+            ("refresh.last_changed".to_string(), self.last_changed.to_string()),
+            ("refresh.synced_until_ms".to_string(), self.synced_until_ms.to_string()),
+            ("refresh.interval_ms".to_string(), self.interval_ms.to_string()),
+            ("refresh.role".to_string(), self.role.as_str().to_string()),
+        ]);
+        match self.role {
+            RefreshRole::Leader => {
+                details.insert(
+                    "refresh.last_broadcast_delivered".to_string(),
+                    self.last_broadcast_delivered.to_string(),
+                );
+            }
+            RefreshRole::Follower => {
+                details.insert("refresh.last_leader_ms".to_string(), self.last_leader_ms.to_string());
+            }
+            RefreshRole::Independent => {}
+        }
+        if let Some(error) = &self.last_error {
+            details.insert("refresh.last_error".to_string(), error.clone());
+        }
+        details
+    }
+
+    /// A leader broadcast was applied: it counts as a successful refresh.
+    pub fn record_leader_broadcast(&mut self, now: i64, changed: usize, latest_updated_ms: i64) {
+        self.last_leader_ms = now;
+        self.last_success_ms = now;
+        self.last_error = None;
+        self.consecutive_failures = 0;
+        self.last_changed = changed;
+        self.synced_until_ms = self.synced_until_ms.max(latest_updated_ms);
+    }
+}
+
+/// Shared handle to the refresh status.
+pub type RefreshStatusHandle = Arc<Mutex<RefreshStatus>>;
+
+/// Fetch heuristics changed since the last sync and apply them to the cache.
+///
+/// Updates are merged into existing entries (cache-local hit statistics are
+/// kept) and deleted IDs are evicted. Returns the number of heuristics merged
+/// or removed. The status is updated either way.
+pub async fn refresh_once(
+    cache: &CacheHandle,
+    storage: &dyn StorageBackend,
+    batch_limit: i32,
+    status: &Mutex<RefreshStatus>,
+) -> Result<usize, StorageError> {
+    fetch_and_apply(cache, storage, batch_limit, status).await.map(|(changed, _)| changed)
+}
+
+/// `refresh_once`, also returning the fetched changes for the leader to broadcast.
+async fn fetch_and_apply(
+    cache: &CacheHandle,
+    storage: &dyn StorageBackend,
+    batch_limit: i32,
+    status: &Mutex<RefreshStatus>,
+) -> Result<(usize, HeuristicChanges), StorageError> {
+    let since = status.lock().unwrap_or_else(|e| e.into_inner()).synced_until_ms;
+    let now = crate::current_time_ms();
+    let result = storage.query_changed_heuristics(since, batch_limit, None).await;
+
+    let result = match result {
+        Ok(changes) => {
+            let (merged, removed) = if changes.heuristics.is_empty() && changes.deleted_ids.is_empty() {
+                (0, 0)
+            } else {
+                cache.write().await.apply_heuristic_changes(changes.clone())
+            };
+            Ok((merged + removed, changes))
+        }
+        Err(e) => Err(e),
+    };
+
+    let mut status = status.lock().unwrap_or_else(|e| e.into_inner());
+    status.last_attempt_ms = now;
+    match result {
+        Ok((changed, changes)) => {
+            status.last_success_ms = now;
+            status.last_error = None;
+            status.consecutive_failures = 0;
+            status.last_changed = changed;
+            status.synced_until_ms = changes.latest_updated_ms.max(since);
+            Ok((changed, changes))
+        }
+        Err(e) => {
+            status.last_error = Some(format!("{}: {}", e.code(), e));
+            status.consecutive_failures += 1;
+            Err(e)
+        }
+    }
+}
+
+/// The broadcast a leader sends after fetching `changes`.
+pub fn changes_request(changes: &HeuristicChanges) -> ApplyHeuristicChangesRequest {
+    ApplyHeuristicChangesRequest {
+        heuristics: changes.heuristics.iter().map(|h| proto::Heuristic::from(&h.to_heuristic())).collect(),
+        deleted_ids: changes.deleted_ids.iter().map(|id| id.to_string()).collect(),
+        latest_updated_ms: changes.latest_updated_ms,
+    }
+}
+
+/// Whether a follower should defer to the leader: it has heard from it (or
+/// started) within the timeout.
+fn leader_alive(last_leader_ms: i64, started_ms: i64, now: i64, timeout_ms: u64) -> bool {
+    now - last_leader_ms.max(started_ms) < timeout_ms as i64
+}
+
+/// Run the refresh loop forever. Spawn it as a background task.
+///
+/// `peers` is only used by a leader, to broadcast what it fetched.
+pub async fn run_refresh_loop(
+    config: RefreshConfig,
+    cache: CacheHandle,
+    storage: Arc<dyn StorageBackend>,
+    status: RefreshStatusHandle,
+    peers: Option<Arc<PeerSet>>,
+) {
+    let mut interval = AdaptiveInterval::new(config.interval(), config.max_interval());
+    let started_ms = crate::current_time_ms();
+    let mut deferring = true;
+    status.lock().unwrap_or_else(|e| e.into_inner()).role = config.role;
+    info!(
+        interval_ms = config.interval_ms,
+        max_interval_ms = config.max_interval_ms,
+        role = config.role.as_str(),
+        "Heuristic refresh loop started"
+    );
+
+    loop {
+        if config.role == RefreshRole::Follower {
+            let last_leader_ms = status.lock().unwrap_or_else(|e| e.into_inner()).last_leader_ms;
+            let alive = leader_alive(last_leader_ms, started_ms, crate::current_time_ms(), config.leader_timeout_ms);
+            if alive != deferring {
+                deferring = alive;
+                if alive {
+                    info!("Refresh leader is broadcasting; deferring to it");
+                } else {
+                    warn!(timeout_ms = config.leader_timeout_ms, "Refresh leader silent; refreshing from storage");
+                }
+            }
+            if alive {
+                tokio::time::sleep(config.interval()).await;
+                continue;
+            }
+        }
+
+        match fetch_and_apply(&cache, storage.as_ref(), config.batch_limit, &status).await {
+            Ok((changed, changes)) => {
+                if changed == 0 {
+                    interval.grow();
+                    debug!(next_ms = interval.current().as_millis() as u64, "No heuristic changes");
+                } else {
+                    interval.reset();
+                    info!(changed, "Heuristic cache refreshed");
+                }
+                if let (RefreshRole::Leader, Some(peers)) = (config.role, &peers) {
+                    let delivered = peers.broadcast_changes(changes_request(&changes)).await;
+                    status.lock().unwrap_or_else(|e| e.into_inner()).last_broadcast_delivered = delivered;
+                    debug!(delivered, changed, "Broadcast refresh to peers");
+                }
+            }
+            Err(e) => {
+                interval.grow();
+                warn!(error = %e, next_ms = interval.current().as_millis() as u64, "Heuristic refresh failed");
+            }
+        }
+        status.lock().unwrap_or_else(|e| e.into_inner()).interval_ms = interval.current().as_millis() as u64;
+        tokio::time::sleep(interval.current()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CacheConfig, CachedHeuristic, Condition, Effects, HeuristicChanges, MemoryCache};
+    use uuid::Uuid;
+
+    struct ChangeFeed {
+        changes: Mutex<Vec<Result<HeuristicChanges, StorageError>>>,
+        seen_since: Mutex<Vec<i64>>,
+    }
+
+    #[tonic::async_trait]
+    impl StorageBackend for ChangeFeed {
+        async fn query_matching_heuristics(
+            &self,
+            _text: &str,
+            _min_conf: f32,
+            _limit: i32,
+            _source: Option<&str>,
+            _trace_id: Option<&str>,
+        ) -> Result<Vec<CachedHeuristic>, StorageError> {
+            Ok(vec![])
+        }
+
+        async fn generate_embedding(&self, _text: &str, _trace_id: Option<&str>) -> Result<Vec<f32>, StorageError> {
+            Ok(vec![])
+        }
+
+        async fn query_changed_heuristics(
+            &self,
+            updated_since_ms: i64,
+            _limit: i32,
+            _trace_id: Option<&str>,
+        ) -> Result<HeuristicChanges, StorageError> {
+            self.seen_since.lock().unwrap().push(updated_since_ms);
+            self.changes.lock().unwrap().remove(0)
+        }
+    }
+
+    fn heuristic() -> CachedHeuristic {
+        CachedHeuristic {
+            id: Uuid::new_v4(),
+            name: "h".to_string(),
+            condition: Condition::text("x"),
+            effects: Effects::default(),
+            confidence: 0.9,
+            origin: String::new(),
+            source: String::new(),
+            condition_embedding: Default::default(),
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_adaptive_interval() {
+        let mut interval = AdaptiveInterval::new(Duration::from_secs(5), Duration::from_secs(15));
+        interval.grow();
+        assert_eq!(interval.current(), Duration::from_secs(10));
+        interval.grow();
+        assert_eq!(interval.current(), Duration::from_secs(15));
+        interval.reset();
+        assert_eq!(interval.current(), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_once_tracks_watermark_and_failures() {
+        let cache = CacheHandle::new(MemoryCache::new(CacheConfig::default()));
+        let feed = ChangeFeed {
+            changes: Mutex::new(vec![
+                Ok(HeuristicChanges { heuristics: vec![heuristic(), heuristic()], latest_updated_ms: 500, ..Default::default() }),
+                Err(StorageError::Unavailable("down".to_string())),
+                Ok(HeuristicChanges { latest_updated_ms: 500, ..Default::default() }),
+            ]),
+            seen_since: Mutex::new(vec![]),
+        };
+        let status = Mutex::new(RefreshStatus::default());
+
+        assert_eq!(refresh_once(&cache, &feed, 10, &status).await.unwrap(), 2);
+        assert_eq!(cache.stats().await.heuristic_count, 2);
+
+        assert!(refresh_once(&cache, &feed, 10, &status).await.is_err());
+        {
+            let s = status.lock().unwrap();
+            assert_eq!(s.consecutive_failures, 1);
+            assert!(s.last_error.as_deref().unwrap().starts_with("STORAGE_UNAVAILABLE"));
+            assert_eq!(s.synced_until_ms, 500);
+        }
+
+        assert_eq!(refresh_once(&cache, &feed, 10, &status).await.unwrap(), 0);
+        assert_eq!(*feed.seen_since.lock().unwrap(), vec![0, 500, 500]);
+        let s = status.lock().unwrap();
+        assert_eq!(s.consecutive_failures, 0);
+        assert!(!s.health_details().contains_key("refresh.last_error"));
+    }
+
+    #[test]
+    fn test_follower_defers_until_leader_silent() {
+        let timeout_ms = 1_000;
+        // Grace period after startup, before the leader is first heard
+        assert!(leader_alive(0, 10_000, 10_500, timeout_ms));
+        assert!(!leader_alive(0, 10_000, 11_000, timeout_ms));
+        assert!(leader_alive(12_000, 10_000, 12_900, timeout_ms));
+
+        let mut status = RefreshStatus { role: RefreshRole::Follower, synced_until_ms: 700, ..Default::default() };
+        status.record_leader_broadcast(12_000, 3, 500);
+        assert_eq!(status.synced_until_ms, 700);
+        status.record_leader_broadcast(13_000, 0, 900);
+        assert_eq!(status.synced_until_ms, 900);
+        assert_eq!(status.health_details()["refresh.last_leader_ms"], "13000");
+    }
+
+    #[test]
+    fn test_changes_request_round_trips_heuristics() {
+        let (h, deleted) = (heuristic(), Uuid::new_v4());
+        let request = changes_request(&HeuristicChanges {
+            heuristics: vec![h.clone()],
+            deleted_ids: vec![deleted],
+            latest_updated_ms: 42,
+        });
+        assert_eq!(request.latest_updated_ms, 42);
+        assert_eq!(request.deleted_ids, vec![deleted.to_string()]);
+        let back = crate::compat::heuristic_from_proto(request.heuristics[0].clone()).unwrap();
+        assert_eq!(back.id, h.id);
+        assert_eq!(back.condition.text, "x");
+    }
+}
@@ -0,0 +1,243 @@
+//! Thin routing mode for multi-instance deployments.
+//!
+//! With several fast-path instances behind one address, sending every event
+//! from a source to the same instance keeps that source's heuristics hot in
+//! one cache instead of warming all of them. An instance configured with
+//! backends acts as a router: EvaluateSalience is not scored locally but
+//! forwarded to the backend that owns the event's `source` on a consistent
+//! hash ring, so adding or removing a backend only moves the sources that
+//! hashed to it.
+//!
+//! Failover is health-aware, using the same bookkeeping as peer relaying
+//! (see peers module): a failed call moves on to the next backend on the
+//! ring, and a backend that has failed repeatedly is skipped until its retry
+//! interval passes. Sources owned by a down backend temporarily land on its
+//! successor, then move back once it recovers. Per-backend health is
+//! reported in GetHealthDetails.
+//!
+//! Only EvaluateSalience is routed; admin and cache RPCs act on the router's
+//! own (unused) cache, so send those to the backends directly.
+//!
+//! Configuration via environment variables (see `RouterConfig`):
+//!   ROUTER_BACKENDS: Comma-separated host:port of the instances to route to (default: none = evaluate locally)
+//!   ROUTER_TIMEOUT_MS: Per-backend call timeout (default: 200)
+//!   ROUTER_VIRTUAL_NODES: Points per backend on the hash ring (default: 64)
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Status};
+use tracing::{debug, warn};
+
+use crate::config::RouterConfig;
+use crate::logging::TRACE_ID_HEADER;
+use crate::peers::{PeerHealth, PEER_DOWN_AFTER};
+use crate::proto::salience_gateway_client::SalienceGatewayClient;
+use crate::proto::{EvaluateSalienceRequest, EvaluateSalienceResponse};
+use crate::seed::fnv1a;
+
+/// Ring position for `bytes`: FNV-1a (stable across processes, so every
+/// router agrees) with a 64-bit finalizer, since FNV alone clusters similar
+/// short keys like "sensor-1" and "sensor-2" on one arc.
+fn ring_hash(bytes: &[u8]) -> u64 {
+    let mut h = fnv1a(bytes, 0xcbf29ce484222325);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51afd7ed558ccd);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xc4ceb9fe1a85ec53);
+    h ^ (h >> 33)
+}
+
+struct Backend {
+    address: String,
+    client: SalienceGatewayClient<Channel>,
+    health: Mutex<PeerHealth>,
+}
+
+/// Consistent-hash router over the backend instances.
+pub struct EvaluationRouter {
+    backends: Vec<Backend>,
+    /// (point, backend index), sorted by point
+    ring: Vec<(u64, usize)>,
+}
+
+impl EvaluationRouter {
+    /// Connections are made lazily, so unreachable backends don't fail startup;
+    /// only malformed addresses do.
+    pub fn new(addresses: Vec<String>, timeout: Duration, virtual_nodes: usize) -> Result<Self, String> {
+        let backends = addresses
+            .into_iter()
+            .map(|address| {
+                let channel = Endpoint::from_shared(format!("http://{}", address))
+                    .map_err(|e| format!("invalid router backend {}: {}", address, e))?
+                    .connect_timeout(timeout)
+                    .timeout(timeout)
+                    .connect_lazy();
+                Ok(Backend { address, client: SalienceGatewayClient::new(channel), health: Mutex::new(PeerHealth::default()) })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mut ring: Vec<(u64, usize)> = backends
+            .iter()
+            .enumerate()
+            .flat_map(|(index, backend)| {
+                (0..virtual_nodes.max(1))
+                    .map(move |node| (ring_hash(format!("{}#{}", backend.address, node).as_bytes()), index))
+            })
+            .collect();
+        ring.sort_unstable();
+        Ok(Self { backends, ring })
+    }
+
+    /// The router configured in `config`, if any backends are set.
+    pub fn from_config(config: &RouterConfig) -> Result<Option<Self>, String> {
+        if config.backends.is_empty() {
+            return Ok(None);
+        }
+        Self::new(config.backends.clone(), Duration::from_millis(config.timeout_ms.max(1)), config.virtual_nodes).map(Some)
+    }
+
+    /// Backend indices in the order `key` tries them: its owner on the ring,
+    /// then each distinct successor.
+    fn candidates(&self, key: &str) -> Vec<usize> {
+        if self.ring.is_empty() {
+            return Vec::new();
+        }
+        let hash = ring_hash(key.as_bytes());
+        let start = self.ring.partition_point(|(point, _)| *point < hash);
+        let mut order = Vec::with_capacity(self.backends.len());
+        for (_, index) in self.ring.iter().cycle().skip(start).take(self.ring.len()) {
+            if !order.contains(index) {
+                order.push(*index);
+                if order.len() == self.backends.len() {
+                    break;
+                }
+            }
+        }
+        order
+    }
+
+    /// Address of the backend that owns `source` while every backend is up.
+    pub fn owner(&self, source: &str) -> Option<&str> {
+        self.candidates(source).first().map(|&i| self.backends[i].address.as_str())
+    }
+
+    /// Evaluate `request` on the backend owning its source, failing over
+    /// along the ring. Down backends are skipped unless none are left to try.
+    pub async fn evaluate(&self, request: &EvaluateSalienceRequest, trace_id: &str) -> Result<EvaluateSalienceResponse, Status> {
+        let now = crate::current_time_ms();
+        let candidates = self.candidates(&request.source);
+        let mut attempts: Vec<usize> = candidates
+            .iter()
+            .copied()
+            .filter(|&i| self.backends[i].health.lock().unwrap_or_else(|e| e.into_inner()).should_attempt(now))
+            .collect();
+        if attempts.is_empty() {
+            attempts = candidates;
+        }
+
+        let mut last_error = "no backends configured".to_string();
+        for index in attempts {
+            let backend = &self.backends[index];
+            backend.health.lock().unwrap_or_else(|e| e.into_inner()).last_attempt_ms = crate::current_time_ms();
+
+            let mut outgoing = Request::new(request.clone());
+            if let Ok(value) = trace_id.parse() {
+                outgoing.metadata_mut().insert(TRACE_ID_HEADER, value);
+            }
+            let result = backend.client.clone().evaluate_salience(outgoing).await;
+
+            let mut health = backend.health.lock().unwrap_or_else(|e| e.into_inner());
+            match result {
+                Ok(response) => {
+                    health.consecutive_failures = 0;
+                    health.last_success_ms = crate::current_time_ms();
+                    health.forwarded += 1;
+                    debug!(backend = %backend.address, source = %request.source, "Routed evaluation");
+                    return Ok(response.into_inner());
+                }
+                Err(e) => {
+                    health.consecutive_failures += 1;
+                    if health.consecutive_failures == PEER_DOWN_AFTER {
+                        warn!(backend = %backend.address, error = %e.message(), "Router backend marked down after repeated failures");
+                    } else {
+                        warn!(backend = %backend.address, error = %e.message(), "Routed evaluation failed; trying next backend");
+                    }
+                    health.last_error = e.message().to_string();
+                    last_error = format!("{}: {}", backend.address, e.message());
+                }
+            }
+        }
+        Err(Status::unavailable(format!("no router backend accepted the evaluation (last error: {})", last_error)))
+    }
+
+    /// Health of each backend, in configured order.
+    pub fn health(&self) -> Vec<(String, PeerHealth)> {
+        self.backends
+            .iter()
+            .map(|b| (b.address.clone(), b.health.lock().unwrap_or_else(|e| e.into_inner()).clone()))
+            .collect()
+    }
+
+    /// Summary for GetHealthDetails.
+    pub fn health_details(&self) -> HashMap<String, String> {
+        let health = self.health();
+        let mut details = HashMap::new();
+        details.insert("router_backends".to_string(), health.len().to_string());
+        details.insert("router_backends_down".to_string(), health.iter().filter(|(_, h)| h.is_down()).count().to_string());
+        for (address, h) in &health {
+            details.insert(format!("router_{}_failures", address), h.consecutive_failures.to_string());
+            details.insert(format!("router_{}_forwarded", address), h.forwarded.to_string());
+            if !h.last_error.is_empty() {
+                details.insert(format!("router_{}_last_error", address), h.last_error.clone());
+            }
+        }
+        details
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn router(addresses: &[&str]) -> EvaluationRouter {
+        let addresses = addresses.iter().map(|a| a.to_string()).collect();
+        EvaluationRouter::new(addresses, Duration::from_millis(200), 64).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ring_is_stable_and_moves_few_sources() {
+        let three = router(&["10.0.0.1:50052", "10.0.0.2:50052", "10.0.0.3:50052"]);
+        let sources: Vec<String> = (0..300).map(|i| format!("sensor-{}", i)).collect();
+        assert_eq!(three.owner("minecraft"), three.owner("minecraft"));
+        assert_eq!(three.candidates("minecraft").len(), 3, "every backend is a fallback");
+
+        // All backends get a share
+        let owners: std::collections::HashSet<_> = sources.iter().filter_map(|s| three.owner(s)).collect();
+        assert_eq!(owners.len(), 3);
+
+        // Dropping a backend only moves the sources it owned
+        let two = router(&["10.0.0.1:50052", "10.0.0.2:50052"]);
+        for source in &sources {
+            if three.owner(source) != Some("10.0.0.3:50052") {
+                assert_eq!(three.owner(source), two.owner(source), "{} moved", source);
+            }
+        }
+        assert!(EvaluationRouter::new(vec!["not a host:port".to_string()], Duration::from_millis(1), 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_backends_fail_over_then_error() {
+        // Nothing listens on port 1
+        let router = router(&["127.0.0.1:1"]);
+        let request = EvaluateSalienceRequest { source: "minecraft".to_string(), ..Default::default() };
+        let err = router.evaluate(&request, "t").await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unavailable);
+        let (_, health) = &router.health()[0];
+        assert_eq!(health.consecutive_failures, 1);
+        assert!(!health.last_error.is_empty());
+        assert_eq!(router.health_details()["router_backends"], "1");
+    }
+}
@@ -0,0 +1,78 @@
+//! Composite salience score and routing hint.
+//!
+//! Consumers shouldn't each decide "is this worth interrupting for?" from the
+//! raw vector. The composite is a weighted sum of the threat scalar and vector
+//! dimensions (weights from `SalienceConfig`, clamped to [0, 1]); the routing
+//! hint buckets it using the configured thresholds.
+
+use crate::config::SalienceConfig;
+use crate::postprocess::THREAT_DIMENSION;
+use crate::proto::{RoutingHint, SalienceResult};
+
+/// Weighted sum of threat and vector dimensions, clamped to [0, 1].
+/// Dimensions without a weight don't contribute.
+pub fn composite_score(salience: &SalienceResult, config: &SalienceConfig) -> f32 {
+    let score: f32 = config
+        .composite_weights
+        .iter()
+        .map(|(dimension, weight)| {
+            let value = if dimension == THREAT_DIMENSION {
+                salience.threat
+            } else {
+                salience.vector.get(dimension).copied().unwrap_or(0.0)
+            };
+            weight * value
+        })
+        .sum();
+    score.clamp(0.0, 1.0)
+}
+
+/// Bucket an evaluation: high threat or composite routes immediately,
+/// a composite under the discard threshold may be dropped, the rest accumulate.
+pub fn routing_hint(composite: f32, threat: f32, config: &SalienceConfig) -> RoutingHint {
+    if threat >= config.immediate_threat_threshold || composite >= config.immediate_threshold {
+        RoutingHint::Immediate
+    } else if composite < config.discard_threshold {
+        RoutingHint::Discard
+    } else {
+        RoutingHint::Accumulate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn result(threat: f32, vector: &[(&str, f32)]) -> SalienceResult {
+        SalienceResult {
+            threat,
+            vector: vector.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_composite_score_weights() {
+        let config = SalienceConfig {
+            composite_weights: HashMap::from([("threat".to_string(), 0.5), ("novelty".to_string(), 0.5)]),
+            ..SalienceConfig::default()
+        };
+        let salience = result(0.4, &[("novelty", 0.8), ("social", 1.0)]);
+        assert!((composite_score(&salience, &config) - 0.6).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_routing_hint_thresholds() {
+        let config = SalienceConfig {
+            immediate_threshold: 0.6,
+            immediate_threat_threshold: 0.8,
+            discard_threshold: 0.1,
+            ..SalienceConfig::default()
+        };
+        assert_eq!(routing_hint(0.7, 0.0, &config), RoutingHint::Immediate);
+        assert_eq!(routing_hint(0.2, 0.9, &config), RoutingHint::Immediate);
+        assert_eq!(routing_hint(0.3, 0.5, &config), RoutingHint::Accumulate);
+        assert_eq!(routing_hint(0.05, 0.0, &config), RoutingHint::Discard);
+    }
+}
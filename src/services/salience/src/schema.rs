@@ -0,0 +1,192 @@
+//! Startup handshake against the storage service's schema version.
+//!
+//! The fast path and Python storage are built from the same memory.proto, but
+//! deployed separately. When one runs against a stale copy, the drift shows up
+//! as confusing runtime errors (fields silently read as defaults, RPCs failing
+//! UNIMPLEMENTED). At startup the fast path asks storage for its version via
+//! GetSchemaVersion; versions are "major.minor":
+//! - Same major: compatible (a minor difference is logged)
+//! - Different major: startup fails, or with `STORAGE_SCHEMA_MISMATCH=degraded`
+//!   the service serves anyway and GetHealth reports DEGRADED
+//! - No answer (storage down, or too old to know the RPC): logged, not fatal
+//!
+//! Both versions are reported in GetHealthDetails, along with the fingerprint
+//! of the protos this binary was compiled from (checked by build.rs against
+//! the committed `proto.fingerprint`).
+
+use std::collections::HashMap;
+
+use tracing::{error, info, warn};
+
+use crate::client::{ClientConfig, StorageClient};
+use crate::config::SchemaMismatchPolicy;
+
+/// The memory.proto contract version this build speaks.
+pub const SCHEMA_VERSION: &str = "1.0";
+
+/// Fingerprint of the compiled memory.proto/types.proto descriptors.
+pub const PROTO_FINGERPRINT: &str = env!("GLADYS_PROTO_FINGERPRINT");
+
+/// How storage's version compares to ours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaCompatibility {
+    Compatible,
+    MinorMismatch,
+    MajorMismatch,
+    /// Storage didn't say (unreachable, too old, or unparseable version)
+    Unknown,
+}
+
+impl SchemaCompatibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Compatible => "compatible",
+            Self::MinorMismatch => "minor_mismatch",
+            Self::MajorMismatch => "major_mismatch",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Outcome of the startup handshake.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaStatus {
+    pub local: String,
+    /// Storage's version (None = not known)
+    pub storage: Option<String>,
+    pub compatibility: SchemaCompatibility,
+    /// Why the version isn't known, if it isn't
+    pub error: Option<String>,
+}
+
+impl SchemaStatus {
+    /// Compare storage's reported version with ours.
+    pub fn compare(storage: &str) -> Self {
+        let compatibility = match (parse_version(SCHEMA_VERSION), parse_version(storage)) {
+            (Some(local), Some(remote)) if local == remote => SchemaCompatibility::Compatible,
+            (Some(local), Some(remote)) if local.0 == remote.0 => SchemaCompatibility::MinorMismatch,
+            (Some(_), Some(_)) => SchemaCompatibility::MajorMismatch,
+            _ => SchemaCompatibility::Unknown,
+        };
+        Self {
+            local: SCHEMA_VERSION.to_string(),
+            storage: Some(storage.to_string()),
+            compatibility,
+            error: (compatibility == SchemaCompatibility::Unknown).then(|| format!("Unparseable version: {:?}", storage)),
+        }
+    }
+
+    /// The handshake got no answer.
+    pub fn unknown(error: String) -> Self {
+        Self {
+            local: SCHEMA_VERSION.to_string(),
+            storage: None,
+            compatibility: SchemaCompatibility::Unknown,
+            error: Some(error),
+        }
+    }
+
+    /// Whether to serve with DEGRADED health.
+    pub fn is_degraded(&self) -> bool {
+        self.compatibility == SchemaCompatibility::MajorMismatch
+    }
+
+    /// Log the outcome; `Err` when `policy` says not to serve.
+    pub fn enforce(&self, policy: SchemaMismatchPolicy) -> Result<(), String> {
+        let storage = self.storage.as_deref().unwrap_or("unknown");
+        match self.compatibility {
+            SchemaCompatibility::Compatible => {
+                info!(version = %self.local, "Storage schema version matches");
+            }
+            SchemaCompatibility::MinorMismatch => {
+                info!(local = %self.local, storage, "Storage schema minor version differs; compatible");
+            }
+            SchemaCompatibility::Unknown => {
+                warn!(local = %self.local, error = ?self.error, "Could not verify storage schema version");
+            }
+            SchemaCompatibility::MajorMismatch => {
+                let message = format!(
+                    "Storage schema version {} is incompatible with {} (major versions differ)",
+                    storage, self.local
+                );
+                if policy == SchemaMismatchPolicy::Refuse {
+                    return Err(format!("{}; set STORAGE_SCHEMA_MISMATCH=degraded to serve anyway", message));
+                }
+                error!(local = %self.local, storage, "{}; serving DEGRADED", message);
+            }
+        }
+        Ok(())
+    }
+
+    /// Summary for GetHealthDetails.
+    pub fn health_details(&self) -> HashMap<String, String> {
+        let mut details = HashMap::from([
+            ("schema.local".to_string(), self.local.clone()),
+            ("schema.storage".to_string(), self.storage.clone().unwrap_or_default()),
+            ("schema.compatibility".to_string(), self.compatibility.as_str().to_string()),
+            ("schema.proto_fingerprint".to_string(), PROTO_FINGERPRINT.to_string()),
+        ]);
+        if let Some(error) = &self.error {
+            details.insert("schema.error".to_string(), error.clone());
+        }
+        details
+    }
+}
+
+/// "major.minor" (a missing minor reads as 0).
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let version = version.trim();
+    let (major, minor) = version.split_once('.').unwrap_or((version, "0"));
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// Ask storage for its schema version.
+pub async fn check_schema_version(config: ClientConfig) -> SchemaStatus {
+    let result = async {
+        let mut client = StorageClient::connect(config).await?;
+        client.get_schema_version(SCHEMA_VERSION).await
+    }
+    .await;
+    match result {
+        Ok(version) => SchemaStatus::compare(&version),
+        Err(e) => SchemaStatus::unknown(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(SchemaStatus::compare(SCHEMA_VERSION).compatibility, SchemaCompatibility::Compatible);
+        assert_eq!(SchemaStatus::compare("1.7").compatibility, SchemaCompatibility::MinorMismatch);
+        assert_eq!(SchemaStatus::compare("1").compatibility, SchemaCompatibility::Compatible);
+        assert_eq!(SchemaStatus::compare("2.0").compatibility, SchemaCompatibility::MajorMismatch);
+        assert_eq!(SchemaStatus::compare("v2").compatibility, SchemaCompatibility::Unknown);
+    }
+
+    #[test]
+    fn test_enforce_policy() {
+        let mismatch = SchemaStatus::compare("2.0");
+        assert!(mismatch.enforce(SchemaMismatchPolicy::Refuse).is_err());
+        assert!(mismatch.enforce(SchemaMismatchPolicy::Degraded).is_ok());
+        assert!(mismatch.is_degraded());
+        assert_eq!(mismatch.health_details()["schema.storage"], "2.0");
+
+        // Unknown never blocks startup
+        let unknown = SchemaStatus::unknown("connection refused".to_string());
+        assert!(unknown.enforce(SchemaMismatchPolicy::Refuse).is_ok());
+        assert!(!unknown.is_degraded());
+        assert_eq!(unknown.health_details()["schema.error"], "connection refused");
+    }
+
+    #[test]
+    fn test_proto_fingerprint_matches_committed_contract() {
+        // build.rs refuses to build on drift, so this holds for any binary that exists
+        assert_eq!(PROTO_FINGERPRINT, include_str!("../proto.fingerprint").trim());
+        assert_eq!(PROTO_FINGERPRINT.len(), 16);
+        assert!(PROTO_FINGERPRINT.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(SchemaStatus::compare(SCHEMA_VERSION).health_details()["schema.proto_fingerprint"], PROTO_FINGERPRINT);
+    }
+}
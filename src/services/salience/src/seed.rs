@@ -0,0 +1,542 @@
+//! Heuristic files: cold-start seeding, import and export.
+//!
+//! A fresh install has no heuristics, so every event scores at baseline until
+//! learning kicks in. A seed file gives the fast path something to match from
+//! the first event. The same format is used by ExportHeuristics and
+//! ImportHeuristics, so an export from one deployment can seed another.
+//!
+//! Schema (stable; new fields are optional and older readers ignore them):
+//!
+//! ```json
+//! [
+//!   {"id": "...", "name": "creeper", "condition_text": "creeper approaching player",
+//!    "effects": {"salience": {"threat": 0.8}}, "confidence": 0.8,
+//!    "origin": "user", "source": "minecraft",
+//!    "exemplars": [{"text": "hissing behind you", "weight": 0.8}],
+//!    "negatives": [{"text": "creeper plushie for sale"}]}
+//! ]
+//! ```
+//!
+//! Only `condition_text` is required; exemplars and negatives (see exemplars
+//! module) are embedded along with it when seeding, and their weight
+//! defaults to 1. `confidence` defaults to 0.5 and
+//! `origin` to "system". Entries without an `id` get one derived from the
+//! condition text, so reloading the same file (or persisting it on every
+//! start) updates the same heuristics instead of duplicating them.
+//!
+//! `diff_snapshots` (and `memctl diff`) compares two files by those IDs, to
+//! inspect drift between replicas' exports or across a deploy.
+//!
+//! Exports are written in a versioned, checksummed form (version 2): a JSON
+//! header line, then one record per line, prefixed with the CRC-32 of its
+//! JSON in hex:
+//!
+//! ```text
+//! {"format":"gladys-heuristics","version":2,"records":2}
+//! <crc32> {"id":"...","condition_text":"creeper approaching player",...}
+//! <crc32> {"id":"...","condition_text":"lava nearby",...}
+//! ```
+//!
+//! A record whose checksum doesn't match (or that doesn't parse) is skipped
+//! rather than failing the load, and records the header counts but the file
+//! lacks are counted as skipped too, so a torn write loses only its tail.
+//! Loads log how many records were loaded and skipped, and ImportHeuristics
+//! reports the skipped count. A plain JSON list (version 1, as above) is
+//! still read, whole or not at all; a newer version is refused.
+//!
+//! Configuration via environment variables (see `SeedConfig`):
+//!   SEED_HEURISTICS_PATH: JSON seed file (default: none)
+//!   SEED_HEURISTICS_PERSIST: Also store seeds in storage (default: false)
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::domain::{Condition, Effects, Exemplar};
+use crate::{CacheHandle, CachedHeuristic, StorageBackend};
+
+/// Origin recorded for seeded heuristics that don't specify one.
+pub const SEED_ORIGIN: &str = "system";
+
+/// One entry in a heuristic file.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SeedHeuristic {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub condition_text: String,
+    /// Same shape as a heuristic's effects_json (e.g. {"salience": {...}})
+    #[serde(default)]
+    pub effects: Effects,
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exemplars: Vec<Exemplar>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub negatives: Vec<Exemplar>,
+}
+
+fn default_confidence() -> f32 {
+    0.5
+}
+
+impl SeedHeuristic {
+    /// Explicit ID, or one derived from the condition text.
+    pub fn id(&self) -> Uuid {
+        self.id.unwrap_or_else(|| {
+            let text = self.condition_text.as_bytes();
+            Uuid::from_u64_pair(fnv1a(text, 0xcbf29ce484222325), fnv1a(text, 0x84222325cbf29ce4))
+        })
+    }
+
+    /// Cache entry for this record (origin defaults to `SEED_ORIGIN`).
+    pub fn into_cached(self, condition_embedding: Vec<f32>) -> CachedHeuristic {
+        let id = self.id();
+        CachedHeuristic {
+            id,
+            name: self.name.unwrap_or_else(|| self.condition_text.clone()),
+            condition: Condition::text(self.condition_text).with_exemplars(self.exemplars).with_negatives(self.negatives),
+            effects: self.effects,
+            confidence: self.confidence.clamp(0.0, 1.0),
+            origin: self.origin.unwrap_or_else(|| SEED_ORIGIN.to_string()),
+            source: self.source,
+            condition_embedding: condition_embedding.into(),
+            last_accessed_ms: 0,
+            cached_at_ms: 0,
+            hit_count: 0,
+            last_hit_ms: 0,
+            cooldown_until_ms: 0,
+            embedding_model: String::new(),
+        }
+    }
+}
+
+impl From<&CachedHeuristic> for SeedHeuristic {
+    fn from(h: &CachedHeuristic) -> Self {
+        Self {
+            id: Some(h.id),
+            name: Some(h.name.clone()),
+            condition_text: h.condition.text.clone(),
+            effects: h.effects.clone(),
+            confidence: h.confidence,
+            origin: (!h.origin.is_empty()).then(|| h.origin.clone()),
+            source: h.source.clone(),
+            exemplars: h.condition.exemplars.clone(),
+            negatives: h.condition.negatives.clone(),
+        }
+    }
+}
+
+/// FNV-1a: stable across processes and Rust versions, unlike `DefaultHasher`.
+pub(crate) fn fnv1a(bytes: &[u8], offset_basis: u64) -> u64 {
+    bytes.iter().fold(offset_basis, |hash, b| (hash ^ *b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Errors loading a seed file.
+#[derive(Debug, thiserror::Error)]
+pub enum SeedError {
+    #[error("Failed to read seed file {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Invalid seed file: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("Unsupported heuristic file: {format} version {version}")]
+    Unsupported { format: String, version: u32 },
+}
+
+/// Format name in a heuristic file's header.
+pub const FORMAT_NAME: &str = "gladys-heuristics";
+
+/// Heuristic file version written (1 = a plain JSON list).
+pub const FORMAT_VERSION: u32 = 2;
+
+/// First line of a version 2 heuristic file.
+#[derive(Debug, Deserialize, Serialize)]
+struct FileHeader {
+    format: String,
+    version: u32,
+    /// Records written after the header
+    records: usize,
+}
+
+/// What loading a heuristic file recovered.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoadStats {
+    pub version: u32,
+    pub loaded: usize,
+    /// Corrupt records, plus records the header counts but the file lacks
+    pub skipped: usize,
+}
+
+/// Parse a heuristic file, skipping corrupt records (see module docs).
+pub fn parse_heuristic_file(contents: &str) -> Result<(Vec<SeedHeuristic>, LoadStats), SeedError> {
+    let contents = contents.trim_start();
+    if contents.starts_with('[') {
+        let records: Vec<SeedHeuristic> = serde_json::from_str(contents)?;
+        let stats = LoadStats { version: 1, loaded: records.len(), skipped: 0 };
+        return Ok((records, stats));
+    }
+    let mut lines = contents.lines();
+    let header: FileHeader = serde_json::from_str(lines.next().unwrap_or_default())?;
+    if header.format != FORMAT_NAME || header.version > FORMAT_VERSION {
+        return Err(SeedError::Unsupported { format: header.format, version: header.version });
+    }
+    let mut records = Vec::new();
+    let mut skipped = 0;
+    for (i, line) in lines.enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_record(line) {
+            Some(record) => records.push(record),
+            None => {
+                warn!(line = i + 2, "Skipping corrupt heuristic record");
+                skipped += 1;
+            }
+        }
+    }
+    skipped += header.records.saturating_sub(records.len() + skipped);
+    let stats = LoadStats { version: header.version, loaded: records.len(), skipped };
+    Ok((records, stats))
+}
+
+/// One "<crc32> <json>" record (None if the checksum or JSON is bad).
+fn parse_record(line: &str) -> Option<SeedHeuristic> {
+    let (checksum, json) = line.split_once(' ')?;
+    if u32::from_str_radix(checksum, 16).ok()? != crc32(json.as_bytes()) {
+        return None;
+    }
+    serde_json::from_str(json).ok()
+}
+
+/// Parse a heuristic file, dropping the load statistics.
+pub fn parse_seed_file(contents: &str) -> Result<Vec<SeedHeuristic>, SeedError> {
+    Ok(parse_heuristic_file(contents)?.0)
+}
+
+/// Serialize heuristics in the current heuristic file format.
+pub fn to_seed_file(heuristics: &[CachedHeuristic]) -> String {
+    let records: Vec<String> = heuristics
+        .iter()
+        .map(SeedHeuristic::from)
+        .filter_map(|record| serde_json::to_string(&record).ok())
+        .collect();
+    let header = FileHeader { format: FORMAT_NAME.to_string(), version: FORMAT_VERSION, records: records.len() };
+    let mut file = serde_json::to_string(&header).unwrap_or_default();
+    file.push('\n');
+    for record in records {
+        file.push_str(&format!("{:08x} {}\n", crc32(record.as_bytes()), record));
+    }
+    file
+}
+
+/// CRC-32 (IEEE, as in zlib and PNG).
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 })
+    })
+}
+
+/// Confidence changes smaller than this aren't reported by `diff_snapshots`.
+pub const SNAPSHOT_CONFIDENCE_EPSILON: f32 = 1e-4;
+
+/// A heuristic whose confidence differs between two snapshots.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfidenceChange {
+    pub id: Uuid,
+    pub name: String,
+    pub before: f32,
+    pub after: f32,
+}
+
+/// How one heuristic file (e.g., an export) differs from another, ordered by ID.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SnapshotDiff {
+    /// In the second snapshot only
+    pub added: Vec<SeedHeuristic>,
+    /// In the first snapshot only
+    pub removed: Vec<SeedHeuristic>,
+    pub confidence_changed: Vec<ConfidenceChange>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.confidence_changed.is_empty()
+    }
+}
+
+/// Compare two heuristic files, matching entries by ID (explicit or derived
+/// from the condition text, as when seeding).
+pub fn diff_snapshots(before: &[SeedHeuristic], after: &[SeedHeuristic]) -> SnapshotDiff {
+    let index = |heuristics: &[SeedHeuristic]| -> BTreeMap<Uuid, SeedHeuristic> {
+        heuristics.iter().map(|h| (h.id(), h.clone())).collect()
+    };
+    let (before, mut after) = (index(before), index(after));
+    let mut diff = SnapshotDiff::default();
+    for (id, old) in before {
+        match after.remove(&id) {
+            None => diff.removed.push(old),
+            Some(new) if (new.confidence - old.confidence).abs() >= SNAPSHOT_CONFIDENCE_EPSILON => {
+                diff.confidence_changed.push(ConfidenceChange {
+                    id,
+                    name: new.name.clone().unwrap_or_else(|| new.condition_text.clone()),
+                    before: old.confidence,
+                    after: new.confidence,
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    diff.added = after.into_values().collect();
+    diff
+}
+
+/// What `seed_cache` did.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SeedSummary {
+    /// Heuristics embedded and added to the cache
+    pub cached: usize,
+    /// Heuristics stored in storage (0 unless persisting)
+    pub persisted: usize,
+}
+
+/// Embed and cache each seed, optionally persisting it.
+///
+/// Seeds whose embedding fails are skipped (they couldn't match from the
+/// cache anyway), unless persisting, where storage embeds them instead.
+/// Persist failures are logged and don't stop seeding.
+pub async fn seed_cache(
+    seeds: Vec<SeedHeuristic>,
+    cache: &CacheHandle,
+    storage: &dyn StorageBackend,
+    persist: bool,
+) -> SeedSummary {
+    let mut summary = SeedSummary::default();
+    for mut seed in seeds {
+        for exemplar in seed.exemplars.iter_mut().chain(seed.negatives.iter_mut()) {
+            match storage.generate_embedding(&exemplar.text, None).await {
+                Ok(embedding) => exemplar.embedding = embedding,
+                Err(e) => warn!(exemplar = %exemplar.text, error = %e, "Failed to embed seed exemplar"),
+            }
+        }
+        let embedding = match storage.generate_embedding(&seed.condition_text, None).await {
+            Ok(embedding) => embedding,
+            Err(e) => {
+                warn!(condition = %seed.condition_text, error = %e, "Failed to embed seed heuristic");
+                Vec::new()
+            }
+        };
+        let heuristic = seed.into_cached(embedding);
+
+        if persist {
+            match storage.store_heuristic(&heuristic, None).await {
+                Ok(()) => summary.persisted += 1,
+                Err(e) => warn!(heuristic_id = %heuristic.id, error = %e, "Failed to persist seed heuristic"),
+            }
+        }
+        if !heuristic.condition_embedding.is_empty() {
+            cache.write().await.merge_heuristic(heuristic);
+            summary.cached += 1;
+        }
+    }
+    summary
+}
+
+/// Load `path` and seed the cache. Returns the number of heuristics cached.
+pub async fn load_seed_file(
+    path: &str,
+    cache: &CacheHandle,
+    storage: &dyn StorageBackend,
+    persist: bool,
+) -> Result<usize, SeedError> {
+    let contents = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|source| SeedError::Io { path: path.to_string(), source })?;
+    let (seeds, stats) = parse_heuristic_file(&contents)?;
+    if stats.skipped > 0 {
+        warn!(path, loaded = stats.loaded, skipped = stats.skipped, "Seed file partly recovered; corrupt records skipped");
+    }
+    let total = seeds.len();
+    let summary = seed_cache(seeds, cache, storage, persist).await;
+    info!(
+        path,
+        total,
+        skipped = stats.skipped,
+        cached = summary.cached,
+        persisted = summary.persisted,
+        "Seed heuristics loaded"
+    );
+    Ok(summary.cached)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CacheConfig, MemoryCache, StorageError};
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_diff_snapshots_by_id() {
+        let before = parse_seed_file(
+            r#"[{"condition_text": "creeper approaching", "confidence": 0.5},
+                {"condition_text": "lava nearby", "confidence": 0.9},
+                {"name": "sunset", "condition_text": "sun going down", "confidence": 0.3}]"#,
+        )
+        .unwrap();
+        let after = parse_seed_file(
+            r#"[{"condition_text": "creeper approaching", "confidence": 0.5},
+                {"name": "sunset", "condition_text": "sun going down", "confidence": 0.6},
+                {"condition_text": "zombie at night"}]"#,
+        )
+        .unwrap();
+
+        let diff = diff_snapshots(&before, &after);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].condition_text, "zombie at night");
+        assert_eq!(diff.removed[0].condition_text, "lava nearby");
+        assert_eq!(diff.confidence_changed.len(), 1);
+        let change = &diff.confidence_changed[0];
+        assert_eq!((change.name.as_str(), change.before, change.after), ("sunset", 0.3, 0.6));
+        assert!(diff_snapshots(&after, &after).is_empty());
+    }
+
+    struct SeedStorage {
+        fail_embedding_for: &'static str,
+        stored: Mutex<Vec<CachedHeuristic>>,
+    }
+
+    #[tonic::async_trait]
+    impl StorageBackend for SeedStorage {
+        async fn query_matching_heuristics(
+            &self,
+            _text: &str,
+            _min_conf: f32,
+            _limit: i32,
+            _source: Option<&str>,
+            _trace_id: Option<&str>,
+        ) -> Result<Vec<CachedHeuristic>, StorageError> {
+            Ok(vec![])
+        }
+
+        async fn generate_embedding(&self, text: &str, _trace_id: Option<&str>) -> Result<Vec<f32>, StorageError> {
+            if text == self.fail_embedding_for {
+                return Err(StorageError::Unavailable("embedder down".to_string()));
+            }
+            Ok(vec![1.0; 384])
+        }
+
+        async fn store_heuristic(&self, heuristic: &CachedHeuristic, _trace_id: Option<&str>) -> Result<(), StorageError> {
+            self.stored.lock().unwrap().push(heuristic.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_parse_seed_file_defaults() {
+        let seeds = parse_seed_file(r#"[
+            {"condition_text": "creeper approaching", "effects": {"salience": {"threat": 0.8}}},
+            {"id": "7f1c8a2e-8b7a-4c55-9a51-1f7a2b3c4d5e", "condition_text": "x", "confidence": 0.9}
+        ]"#).unwrap();
+        assert_eq!(seeds.len(), 2);
+        assert_eq!(seeds[0].confidence, 0.5);
+        assert_eq!(seeds[0].id(), seeds[0].id(), "derived IDs are stable");
+        assert_eq!(seeds[1].id().to_string(), "7f1c8a2e-8b7a-4c55-9a51-1f7a2b3c4d5e");
+        assert!(parse_seed_file(r#"[{"effects": {}}]"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_seed_cache_embeds_and_persists() {
+        let cache = CacheHandle::new(MemoryCache::new(CacheConfig::default()));
+        let storage = SeedStorage { fail_embedding_for: "broken", stored: Mutex::new(vec![]) };
+        let seeds = parse_seed_file(r#"[
+            {"condition_text": "creeper approaching", "source": "minecraft"},
+            {"condition_text": "broken"}
+        ]"#).unwrap();
+        let id = seeds[0].id();
+
+        let summary = seed_cache(seeds, &cache, &storage, true).await;
+        assert_eq!(summary, SeedSummary { cached: 1, persisted: 2 });
+        let cache = cache.read().await;
+        let seeded = cache.get_heuristic(&id).unwrap();
+        assert_eq!(seeded.origin, SEED_ORIGIN);
+        assert_eq!(seeded.source, "minecraft");
+        assert_eq!(seeded.condition_embedding.len(), 384);
+        // Both are persisted; storage embeds the one we couldn't
+        assert!(storage.stored.lock().unwrap()[1].condition_embedding.is_empty());
+    }
+
+    #[test]
+    fn test_export_round_trips() {
+        let original = SeedHeuristic {
+            id: Some(Uuid::new_v4()),
+            name: Some("creeper".to_string()),
+            condition_text: "creeper approaching".to_string(),
+            effects: serde_json::json!({"salience": {"threat": 0.8}}).into(),
+            confidence: 0.7,
+            origin: Some("llm".to_string()),
+            source: "minecraft".to_string(),
+            exemplars: vec![Exemplar::new("hissing behind you", 0.8, vec![1.0; 4])],
+            negatives: Vec::new(),
+        }
+        .into_cached(vec![1.0; 4]);
+
+        let exported = to_seed_file(std::slice::from_ref(&original));
+        assert!(!exported.contains("condition_embedding"));
+        let reimported = parse_seed_file(&exported).unwrap().remove(0).into_cached(vec![]);
+        assert_eq!(reimported.id, original.id);
+        assert_eq!(reimported.name, original.name);
+        assert_eq!(reimported.effects, original.effects);
+        assert_eq!(reimported.origin, "llm");
+        assert_eq!(reimported.source, "minecraft");
+        // Exemplars keep their text and weight, not their embedding
+        assert_eq!(reimported.condition.exemplars, vec![Exemplar::new("hissing behind you", 0.8, Vec::new())]);
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn test_corrupt_records_are_skipped() {
+        let heuristics: Vec<CachedHeuristic> = parse_seed_file(
+            r#"[{"condition_text": "creeper approaching"},
+                {"condition_text": "lava nearby"},
+                {"condition_text": "zombie at night"}]"#,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|seed| seed.into_cached(vec![]))
+        .collect();
+        let exported = to_seed_file(&heuristics);
+        let (records, stats) = parse_heuristic_file(&exported).unwrap();
+        assert_eq!(stats, LoadStats { version: FORMAT_VERSION, loaded: 3, skipped: 0 });
+        assert_eq!(records[2].condition_text, "zombie at night");
+
+        // A flipped byte loses that record; a torn write loses the tail
+        let corrupted = exported.replacen("lava nearby", "lava nearbz", 1);
+        let torn = &corrupted[..corrupted.find("zombie").unwrap()];
+        let (records, stats) = parse_heuristic_file(torn).unwrap();
+        assert_eq!(stats, LoadStats { version: FORMAT_VERSION, loaded: 1, skipped: 2 });
+        assert_eq!(records[0].condition_text, "creeper approaching");
+
+        let legacy = parse_heuristic_file(r#"[{"condition_text": "lava nearby"}]"#).unwrap().1;
+        assert_eq!(legacy, LoadStats { version: 1, loaded: 1, skipped: 0 });
+        assert!(matches!(
+            parse_heuristic_file(r#"{"format":"gladys-heuristics","version":3,"records":0}"#),
+            Err(SeedError::Unsupported { version: 3, .. })
+        ));
+    }
+}
@@ -0,0 +1,553 @@
+//! Redis-backed shared cache tier (L0.5) for horizontally scaled replicas.
+//!
+//! Each fast-path replica has its own L0 cache, so behind a load balancer
+//! every replica pays its own cold-start storage fallbacks and only hears
+//! about invalidations sent to it. With a shared tier configured:
+//!
+//! - Storage fallback results (including "no match") are written to Redis,
+//!   and a replica checks Redis before querying storage itself.
+//! - FlushCache / EvictFromCache / NotifyHeuristicChange are published on a
+//!   pub/sub channel and applied by every replica's listener.
+//!
+//! Shared entries are keyed by a generation counter (`{prefix}generation`)
+//! that every invalidation increments, so results cached before the change
+//! are never served again; they simply expire. The event text in a key is
+//! hashed with 64-bit FNV-1a rather than the in-process `text_hash`, so
+//! replicas built with different Rust releases agree on keys.
+//!
+//! Only the handful of commands used here are spoken (GET, SET PX, INCR,
+//! PUBLISH, SUBSCRIBE), over a single connection per tier.
+//!
+//! Configuration via environment variables:
+//!   SHARED_CACHE_REDIS_ADDRESS: Redis host:port (default: none = disabled)
+//!   SHARED_CACHE_KEY_PREFIX: Key and channel prefix (default: "gladys:salience:")
+//!   SHARED_CACHE_TTL_MS: Lifetime of shared match results (default: 300000)
+//!   SHARED_CACHE_NEGATIVE_TTL_MS: Lifetime of shared "no match" results (default: 30000)
+//!   SHARED_CACHE_TIMEOUT_MS: Per-command timeout (default: 100)
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::config::SharedCacheConfig;
+use crate::eviction_log::EvictionReason;
+use crate::seed::SeedHeuristic;
+use crate::{CacheHandle, CachedHeuristic};
+
+/// Errors talking to the shared tier.
+#[derive(Debug, thiserror::Error)]
+pub enum SharedCacheError {
+    #[error("Redis I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Redis command timed out")]
+    Timeout,
+    #[error("Redis protocol error: {0}")]
+    Protocol(String),
+    #[error("Redis error: {0}")]
+    Redis(String),
+    #[error("Invalid shared cache entry: {0}")]
+    Decode(#[from] serde_json::Error),
+}
+
+/// 64-bit FNV-1a of the event text: shared keys need a hash that's the
+/// same on every replica and build.
+fn shared_text_hash(text: &str) -> u64 {
+    text.bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// A RESP reply (or request; commands are arrays of bulk strings).
+#[derive(Debug, Clone, PartialEq)]
+pub enum RespValue {
+    Simple(String),
+    Error(String),
+    Integer(i64),
+    /// None = nil
+    Bulk(Option<Vec<u8>>),
+    /// None = nil
+    Array(Option<Vec<RespValue>>),
+}
+
+/// Encode a command as a RESP array of bulk strings.
+pub fn encode_command(args: &[&[u8]]) -> Vec<u8> {
+    let mut out = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        out.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+        out.extend_from_slice(arg);
+        out.extend_from_slice(b"\r\n");
+    }
+    out
+}
+
+type ReadFuture<'a> = Pin<Box<dyn Future<Output = Result<RespValue, SharedCacheError>> + Send + 'a>>;
+
+/// Read one RESP value.
+pub fn read_value<R: AsyncBufReadExt + Unpin + Send>(reader: &mut R) -> ReadFuture<'_> {
+    Box::pin(async move {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Err(SharedCacheError::Io(std::io::ErrorKind::UnexpectedEof.into()));
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (kind, rest) = line.split_at_checked(1).ok_or_else(|| SharedCacheError::Protocol("empty line".to_string()))?;
+        let length = || rest.parse::<i64>().map_err(|_| SharedCacheError::Protocol(format!("bad length: {}", rest)));
+        match kind {
+            "+" => Ok(RespValue::Simple(rest.to_string())),
+            "-" => Ok(RespValue::Error(rest.to_string())),
+            ":" => Ok(RespValue::Integer(length()?)),
+            "$" => {
+                let Ok(len) = usize::try_from(length()?) else {
+                    return Ok(RespValue::Bulk(None));
+                };
+                let mut data = vec![0; len + 2];
+                reader.read_exact(&mut data).await?;
+                data.truncate(len);
+                Ok(RespValue::Bulk(Some(data)))
+            }
+            "*" => {
+                let Ok(len) = usize::try_from(length()?) else {
+                    return Ok(RespValue::Array(None));
+                };
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(read_value(reader).await?);
+                }
+                Ok(RespValue::Array(Some(items)))
+            }
+            other => Err(SharedCacheError::Protocol(format!("unknown reply type: {}", other))),
+        }
+    })
+}
+
+/// One Redis connection.
+pub struct RedisConnection {
+    stream: BufReader<TcpStream>,
+}
+
+impl RedisConnection {
+    pub async fn connect(address: &str) -> Result<Self, SharedCacheError> {
+        let stream = TcpStream::connect(address).await?;
+        stream.set_nodelay(true)?;
+        Ok(Self { stream: BufReader::new(stream) })
+    }
+
+    /// Send a command and read its reply (Redis errors become `SharedCacheError::Redis`).
+    pub async fn command(&mut self, args: &[&[u8]]) -> Result<RespValue, SharedCacheError> {
+        self.stream.get_mut().write_all(&encode_command(args)).await?;
+        match self.read().await? {
+            RespValue::Error(message) => Err(SharedCacheError::Redis(message)),
+            value => Ok(value),
+        }
+    }
+
+    /// Read the next value (e.g., a pub/sub message).
+    pub async fn read(&mut self) -> Result<RespValue, SharedCacheError> {
+        read_value(&mut self.stream).await
+    }
+}
+
+/// A heuristic as stored in the shared tier.
+#[derive(Debug, Serialize, Deserialize)]
+struct SharedHeuristic {
+    #[serde(flatten)]
+    heuristic: SeedHeuristic,
+    #[serde(default)]
+    embedding: Vec<f32>,
+}
+
+/// A cache invalidation published to every replica.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Invalidation {
+    /// Generation after this change; shared entries from earlier ones are stale
+    pub generation: u64,
+    /// Heuristic to evict (None = flush every heuristic)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heuristic_id: Option<Uuid>,
+}
+
+/// Client for the shared tier.
+pub struct SharedCache {
+    config: SharedCacheConfig,
+    address: String,
+    /// Lazily (re)connected; dropped after any error
+    connection: Mutex<Option<RedisConnection>>,
+    /// Latest invalidation generation seen
+    generation: AtomicU64,
+}
+
+impl SharedCache {
+    /// The shared tier configured in `config`, if any.
+    pub fn from_config(config: &SharedCacheConfig) -> Option<Self> {
+        let address = config.redis_address.clone()?;
+        Some(Self { config: config.clone(), address, connection: Mutex::new(None), generation: AtomicU64::new(0) })
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}{}", self.config.key_prefix, name)
+    }
+
+    fn channel(&self) -> String {
+        self.key("invalidate")
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    fn observe_generation(&self, generation: u64) {
+        self.generation.fetch_max(generation, Ordering::Relaxed);
+    }
+
+    /// Run a command on the shared connection, reconnecting if needed.
+    async fn command(&self, args: &[&[u8]]) -> Result<RespValue, SharedCacheError> {
+        let timeout = Duration::from_millis(self.config.timeout_ms.max(1));
+        let mut connection = self.connection.lock().await;
+        let result = tokio::time::timeout(timeout, async {
+            if connection.is_none() {
+                *connection = Some(RedisConnection::connect(&self.address).await?);
+            }
+            match connection.as_mut() {
+                Some(c) => c.command(args).await,
+                None => unreachable!("connected above"),
+            }
+        })
+        .await
+        .unwrap_or(Err(SharedCacheError::Timeout));
+        if result.is_err() {
+            // The reply stream may be out of step; start fresh next time
+            *connection = None;
+        }
+        result
+    }
+
+    /// Fetch the current generation (e.g., at startup, before any invalidation is heard).
+    pub async fn sync_generation(&self) -> Result<u64, SharedCacheError> {
+        match self.command(&[b"GET", self.key("generation").as_bytes()]).await? {
+            RespValue::Bulk(Some(data)) => {
+                let generation = String::from_utf8_lossy(&data).parse().unwrap_or(0);
+                self.observe_generation(generation);
+            }
+            RespValue::Bulk(None) => {}
+            other => return Err(SharedCacheError::Protocol(format!("unexpected GET reply: {:?}", other))),
+        }
+        Ok(self.generation())
+    }
+
+    fn match_key(&self, event_text: &str, min_confidence: f32, source: &str) -> String {
+        self.key(&format!(
+            "match:{}:{:016x}:{:08x}:{}",
+            self.generation(),
+            shared_text_hash(event_text),
+            min_confidence.to_bits(),
+            source
+        ))
+    }
+
+    /// Shared storage fallback result for this lookup (Some(empty) = known no-match).
+    pub async fn get_matches(
+        &self,
+        event_text: &str,
+        min_confidence: f32,
+        source: &str,
+    ) -> Result<Option<Vec<CachedHeuristic>>, SharedCacheError> {
+        let key = self.match_key(event_text, min_confidence, source);
+        match self.command(&[b"GET", key.as_bytes()]).await? {
+            RespValue::Bulk(Some(data)) => {
+                let records: Vec<SharedHeuristic> = serde_json::from_slice(&data)?;
+                Ok(Some(records.into_iter().map(|r| r.heuristic.into_cached(r.embedding)).collect()))
+            }
+            RespValue::Bulk(None) => Ok(None),
+            other => Err(SharedCacheError::Protocol(format!("unexpected GET reply: {:?}", other))),
+        }
+    }
+
+    /// Share a storage fallback result (empty = no match, kept for the negative TTL).
+    pub async fn put_matches(
+        &self,
+        event_text: &str,
+        min_confidence: f32,
+        source: &str,
+        heuristics: &[CachedHeuristic],
+    ) -> Result<(), SharedCacheError> {
+        let key = self.match_key(event_text, min_confidence, source);
+        let records: Vec<SharedHeuristic> = heuristics
+            .iter()
+            .map(|h| SharedHeuristic { heuristic: SeedHeuristic::from(h), embedding: h.condition_embedding.to_vec() })
+            .collect();
+        let value = serde_json::to_vec(&records)?;
+        let ttl = if heuristics.is_empty() { self.config.negative_ttl_ms } else { self.config.ttl_ms };
+        let ttl = ttl.max(1).to_string();
+        self.command(&[b"SET", key.as_bytes(), &value, b"PX", ttl.as_bytes()]).await?;
+        Ok(())
+    }
+
+    /// Start a new generation and tell every replica to evict `heuristic_id`
+    /// (None = flush). Returns the new generation.
+    pub async fn invalidate(&self, heuristic_id: Option<Uuid>) -> Result<u64, SharedCacheError> {
+        let generation = match self.command(&[b"INCR", self.key("generation").as_bytes()]).await? {
+            RespValue::Integer(n) => u64::try_from(n).unwrap_or(0),
+            other => return Err(SharedCacheError::Protocol(format!("unexpected INCR reply: {:?}", other))),
+        };
+        self.observe_generation(generation);
+        let message = serde_json::to_vec(&Invalidation { generation, heuristic_id })?;
+        self.command(&[b"PUBLISH", self.channel().as_bytes(), &message]).await?;
+        Ok(generation)
+    }
+
+    /// Subscribe to invalidations and apply each one to `cache`. Only returns
+    /// once the subscription fails.
+    pub async fn listen_once(&self, cache: &CacheHandle) -> Result<(), SharedCacheError> {
+        let mut connection = RedisConnection::connect(&self.address).await?;
+        let channel = self.channel();
+        connection.command(&[b"SUBSCRIBE", channel.as_bytes()]).await?;
+        info!(channel = %channel, "Subscribed to shared cache invalidations");
+        // A change published while we were disconnected is only reflected in the generation
+        self.sync_generation().await?;
+
+        loop {
+            let RespValue::Array(Some(parts)) = connection.read().await? else {
+                continue;
+            };
+            let [RespValue::Bulk(Some(kind)), _, RespValue::Bulk(Some(payload))] = &parts[..] else {
+                continue;
+            };
+            if kind != b"message" {
+                continue;
+            }
+            let invalidation: Invalidation = match serde_json::from_slice(payload) {
+                Ok(invalidation) => invalidation,
+                Err(e) => {
+                    warn!(error = %e, "Ignoring malformed shared cache invalidation");
+                    continue;
+                }
+            };
+            self.observe_generation(invalidation.generation);
+            let mut cache = cache.write().await;
+            match invalidation.heuristic_id {
+                Some(id) => {
+                    cache.remove_heuristic_because(&id, EvictionReason::Notify);
+                }
+                None => {
+                    cache.flush_heuristics();
+                }
+            }
+            debug!(generation = invalidation.generation, heuristic_id = ?invalidation.heuristic_id, "Applied shared cache invalidation");
+        }
+    }
+}
+
+/// Keep a subscription to invalidations open for the life of the process.
+pub async fn run_invalidation_listener(shared: Arc<SharedCache>, cache: CacheHandle) {
+    loop {
+        if let Err(e) = shared.listen_once(&cache).await {
+            warn!(error = %e, "Shared cache invalidation subscription lost; reconnecting");
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tokio::net::TcpListener;
+    use tokio::sync::broadcast;
+
+    /// A tiny in-memory Redis speaking just the commands used above.
+    /// Returns its address.
+    pub(crate) async fn spawn_fake_redis() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let store: Arc<std::sync::Mutex<HashMap<Vec<u8>, Vec<u8>>>> = Arc::default();
+        let (published, _) = broadcast::channel::<(Vec<u8>, Vec<u8>)>(16);
+        tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else { return };
+                let (store, published) = (store.clone(), published.clone());
+                tokio::spawn(async move {
+                    let mut stream = BufReader::new(stream);
+                    while let Ok(RespValue::Array(Some(args))) = read_value(&mut stream).await {
+                        let args: Vec<Vec<u8>> = args
+                            .into_iter()
+                            .filter_map(|a| if let RespValue::Bulk(Some(a)) = a { Some(a) } else { None })
+                            .collect();
+                        let reply: Vec<u8> = match args[0].to_ascii_uppercase().as_slice() {
+                            b"GET" => match store.lock().unwrap().get(&args[1]) {
+                                Some(v) => [format!("${}\r\n", v.len()).as_bytes(), v, b"\r\n"].concat(),
+                                None => b"$-1\r\n".to_vec(),
+                            },
+                            b"SET" => {
+                                store.lock().unwrap().insert(args[1].clone(), args[2].clone());
+                                b"+OK\r\n".to_vec()
+                            }
+                            b"INCR" => {
+                                let mut store = store.lock().unwrap();
+                                let n = store
+                                    .get(&args[1])
+                                    .and_then(|v| String::from_utf8_lossy(v).parse::<i64>().ok())
+                                    .unwrap_or(0)
+                                    + 1;
+                                store.insert(args[1].clone(), n.to_string().into_bytes());
+                                format!(":{}\r\n", n).into_bytes()
+                            }
+                            b"PUBLISH" => {
+                                let receivers = published.send((args[1].clone(), args[2].clone())).unwrap_or(0);
+                                format!(":{}\r\n", receivers).into_bytes()
+                            }
+                            b"SUBSCRIBE" => {
+                                let channel = args[1].clone();
+                                let mut messages = published.subscribe();
+                                let confirm = [&b"*3\r\n$9\r\nsubscribe\r\n"[..], &encode_command(&[&channel])[4..], b":1\r\n"].concat();
+                                let stream = stream.get_mut();
+                                if stream.write_all(&confirm).await.is_err() {
+                                    return;
+                                }
+                                while let Ok((ch, payload)) = messages.recv().await {
+                                    if ch == channel {
+                                        let message = encode_command(&[b"message", &ch, &payload]);
+                                        if stream.write_all(&message).await.is_err() {
+                                            return;
+                                        }
+                                    }
+                                }
+                                return;
+                            }
+                            _ => b"-ERR unknown command\r\n".to_vec(),
+                        };
+                        if stream.get_mut().write_all(&reply).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        address
+    }
+
+    pub(crate) fn shared_cache(address: &str) -> SharedCache {
+        let config = SharedCacheConfig { redis_address: Some(address.to_string()), ..SharedCacheConfig::default() };
+        SharedCache::from_config(&config).unwrap()
+    }
+
+    fn heuristic(name: &str) -> CachedHeuristic {
+        SeedHeuristic {
+            id: None,
+            name: None,
+            condition_text: name.to_string(),
+            effects: serde_json::json!({"salience": {"threat": 0.7}}).into(),
+            confidence: 0.8,
+            origin: Some("user".to_string()),
+            source: "game".to_string(),
+            exemplars: Vec::new(),
+            negatives: Vec::new(),
+        }
+        .into_cached(vec![0.5; 4])
+    }
+
+    #[tokio::test]
+    async fn test_resp_round_trip() {
+        let encoded = encode_command(&[b"SET", b"k", b"v"]);
+        assert_eq!(encoded, b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n");
+        let mut reader = BufReader::new(&encoded[..]);
+        assert_eq!(
+            read_value(&mut reader).await.unwrap(),
+            RespValue::Array(Some(vec![
+                RespValue::Bulk(Some(b"SET".to_vec())),
+                RespValue::Bulk(Some(b"k".to_vec())),
+                RespValue::Bulk(Some(b"v".to_vec())),
+            ]))
+        );
+        let mut reader = BufReader::new(&b"+OK\r\n-ERR no\r\n:42\r\n$-1\r\n*-1\r\n"[..]);
+        assert_eq!(read_value(&mut reader).await.unwrap(), RespValue::Simple("OK".to_string()));
+        assert_eq!(read_value(&mut reader).await.unwrap(), RespValue::Error("ERR no".to_string()));
+        assert_eq!(read_value(&mut reader).await.unwrap(), RespValue::Integer(42));
+        assert_eq!(read_value(&mut reader).await.unwrap(), RespValue::Bulk(None));
+        assert_eq!(read_value(&mut reader).await.unwrap(), RespValue::Array(None));
+        assert!(read_value(&mut reader).await.is_err());
+    }
+
+    #[test]
+    fn test_shared_keys_are_stable() {
+        // Published FNV-1a test vectors: keys never depend on the build
+        assert_eq!(shared_text_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(shared_text_hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(shared_text_hash("foobar"), 0x8594_4171_f739_67e8);
+        let shared = shared_cache("127.0.0.1:1");
+        let expected = format!("{}match:0:af63dc4c8601ec8c:3f000000:game", shared.config.key_prefix);
+        assert_eq!(shared.match_key("a", 0.5, "game"), expected);
+    }
+
+    #[tokio::test]
+    async fn test_shared_matches_and_generations() {
+        let address = spawn_fake_redis().await;
+        let shared = shared_cache(&address);
+        let creeper = heuristic("creeper nearby");
+
+        assert!(shared.get_matches("creeper", 0.5, "game").await.unwrap().is_none());
+        shared.put_matches("creeper", 0.5, "game", std::slice::from_ref(&creeper)).await.unwrap();
+        shared.put_matches("zombie", 0.5, "game", &[]).await.unwrap();
+
+        let found = shared.get_matches("creeper", 0.5, "game").await.unwrap().unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, creeper.id);
+        assert_eq!(found[0].origin, "user");
+        assert_eq!(found[0].condition_embedding, vec![0.5; 4]);
+        // Negative results are shared too; other thresholds/sources are separate entries
+        assert_eq!(shared.get_matches("zombie", 0.5, "game").await.unwrap().map(|h| h.len()), Some(0));
+        assert!(shared.get_matches("creeper", 0.6, "game").await.unwrap().is_none());
+
+        // An invalidation starts a new generation: earlier results are no longer served
+        assert_eq!(shared.invalidate(Some(creeper.id)).await.unwrap(), 1);
+        assert!(shared.get_matches("creeper", 0.5, "game").await.unwrap().is_none());
+
+        // Another replica picks up the generation at startup
+        let other = shared_cache(&address);
+        assert_eq!(other.sync_generation().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_invalidations_reach_other_replicas() {
+        let address = spawn_fake_redis().await;
+        let cache = CacheHandle::new(crate::MemoryCache::new(crate::config::CacheConfig::default()));
+        let (creeper, lava) = (heuristic("creeper"), heuristic("lava"));
+        {
+            let mut cache = cache.write().await;
+            cache.add_heuristic(creeper.clone());
+            cache.add_heuristic(lava.clone());
+        }
+        let listener = Arc::new(shared_cache(&address));
+        tokio::spawn(run_invalidation_listener(listener.clone(), cache.clone()));
+        // Give the listener time to subscribe
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let publisher = shared_cache(&address);
+        publisher.invalidate(Some(creeper.id)).await.unwrap();
+        for _ in 0..100 {
+            if cache.read().await.get_heuristic(&creeper.id).is_none() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(cache.read().await.get_heuristic(&creeper.id).is_none());
+        assert!(cache.read().await.get_heuristic(&lava.id).is_some());
+        assert_eq!(listener.generation(), 1);
+
+        publisher.invalidate(None).await.unwrap();
+        for _ in 0..100 {
+            if cache.read().await.get_heuristic(&lava.id).is_none() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(cache.read().await.get_heuristic(&lava.id).is_none());
+    }
+}
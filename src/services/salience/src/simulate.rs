@@ -0,0 +1,369 @@
+//! Scenario simulator: replay a transcript and report a salience timeline.
+//!
+//! Feeds timestamped events through an in-process `SalienceService` and
+//! records, per event, the salience vector, the matched heuristic, routing,
+//! and cache counters. This is the main tool for regression-testing a tuned
+//! heuristic set (see the `salience-simulate` binary).
+//!
+//! Transcripts are JSON lines, one event per line (blank lines and lines
+//! starting with `#` are skipped):
+//!
+//! ```text
+//! {"t_ms": 0, "source": "minecraft", "text": "A creeper is approaching the player"}
+//! ```
+//!
+//! `t_ms` is reported as-is; events are replayed back-to-back, so TTL and
+//! hysteresis windows see wall-clock time, not transcript time.
+//!
+//! Timelines double as golden files: `diff_timelines` compares a run against
+//! a committed timeline, exact on decisions and within a tolerance on scores
+//! (see `tests/golden_test.rs`).
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tonic::Request;
+
+use crate::proto::salience_gateway_server::SalienceGateway;
+use crate::proto::{EvaluateSalienceRequest, RoutingHint};
+use crate::{CacheHandle, CachedHeuristic, MemoryCache, SalienceService, StorageBackend, StorageError};
+
+/// Dimensions of `LocalStorage` embeddings (matches the production model).
+pub const LOCAL_EMBEDDING_DIMS: usize = 384;
+
+/// One transcript event.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TranscriptEvent {
+    #[serde(default)]
+    pub t_ms: i64,
+    #[serde(default)]
+    pub source: String,
+    pub text: String,
+}
+
+/// One timeline row: what the service decided for an event.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TimelineEntry {
+    pub t_ms: i64,
+    pub source: String,
+    pub text: String,
+    pub salience: f32,
+    pub threat: f32,
+    /// Ordered so golden files diff cleanly
+    pub vector: BTreeMap<String, f32>,
+    /// Empty when no heuristic matched
+    pub matched_heuristic_id: String,
+    pub from_cache: bool,
+    pub dominant_dimension: String,
+    pub composite_score: f32,
+    pub routing_hint: String,
+    /// Storage fallback was rate limited
+    #[serde(default)]
+    pub degraded: bool,
+    /// Cache counters after the event
+    pub cache_size: usize,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// Scoring error (`<CODE>: <message>`), empty on success
+    pub error: String,
+}
+
+/// Errors reading a transcript.
+#[derive(Debug, thiserror::Error)]
+pub enum SimulateError {
+    #[error("Transcript line {line}: {source}")]
+    Parse {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// Parse a JSON-lines transcript.
+pub fn parse_transcript(contents: &str) -> Result<Vec<TranscriptEvent>, SimulateError> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .map(|(i, l)| serde_json::from_str(l).map_err(|source| SimulateError::Parse { line: i + 1, source }))
+        .collect()
+}
+
+/// Replay events through `service` in order.
+pub async fn run_simulation(
+    service: &SalienceService,
+    cache: &CacheHandle,
+    events: &[TranscriptEvent],
+) -> Vec<TimelineEntry> {
+    let mut timeline = Vec::with_capacity(events.len());
+    for (i, event) in events.iter().enumerate() {
+        let request = EvaluateSalienceRequest {
+            event_id: format!("sim-{}", i),
+            source: event.source.clone(),
+            raw_text: event.text.clone(),
+            ..Default::default()
+        };
+        let response = match service.evaluate_salience(Request::new(request)).await {
+            Ok(r) => r.into_inner(),
+            Err(status) => crate::proto::EvaluateSalienceResponse {
+                error: status.message().to_string(),
+                ..Default::default()
+            },
+        };
+        let salience = response.salience.unwrap_or_default();
+        let stats = cache.stats().await;
+        timeline.push(TimelineEntry {
+            t_ms: event.t_ms,
+            source: event.source.clone(),
+            text: event.text.clone(),
+            salience: salience.salience,
+            threat: salience.threat,
+            vector: salience.vector.into_iter().collect(),
+            matched_heuristic_id: response.matched_heuristic_id,
+            from_cache: response.from_cache,
+            dominant_dimension: response.dominant_dimension,
+            composite_score: response.composite_score,
+            routing_hint: RoutingHint::try_from(response.routing_hint)
+                .map(|h| h.as_str_name().to_string())
+                .unwrap_or_default(),
+            degraded: response.degraded,
+            cache_size: stats.heuristic_count,
+            cache_hits: stats.total_hits,
+            cache_misses: stats.total_misses,
+            error: response.error,
+        });
+    }
+    timeline
+}
+
+/// Default score tolerance for `diff_timelines`.
+pub const DEFAULT_GOLDEN_TOLERANCE: f32 = 1e-3;
+
+/// Compare a run against a golden timeline.
+///
+/// Matches, routing, dominant dimension and cache counters must be equal;
+/// salience, threat, composite and vector values may differ by `tolerance`.
+/// Returns one message per difference (empty when the run matches).
+pub fn diff_timelines(expected: &[TimelineEntry], actual: &[TimelineEntry], tolerance: f32) -> Vec<String> {
+    let mut diffs = Vec::new();
+    if expected.len() != actual.len() {
+        diffs.push(format!("expected {} events, got {}", expected.len(), actual.len()));
+    }
+    for (i, (e, a)) in expected.iter().zip(actual).enumerate() {
+        let mut exact = |field: &str, e: &dyn std::fmt::Debug, a: &dyn std::fmt::Debug| {
+            let (e, a) = (format!("{:?}", e), format!("{:?}", a));
+            if e != a {
+                diffs.push(format!("event {} ({:?}): {} expected {}, got {}", i, expected[i].text, field, e, a));
+            }
+        };
+        exact("text", &e.text, &a.text);
+        exact("matched_heuristic_id", &e.matched_heuristic_id, &a.matched_heuristic_id);
+        exact("from_cache", &e.from_cache, &a.from_cache);
+        exact("degraded", &e.degraded, &a.degraded);
+        exact("routing_hint", &e.routing_hint, &a.routing_hint);
+        exact("dominant_dimension", &e.dominant_dimension, &a.dominant_dimension);
+        exact("cache_size", &e.cache_size, &a.cache_size);
+        exact("cache_hits", &e.cache_hits, &a.cache_hits);
+        exact("cache_misses", &e.cache_misses, &a.cache_misses);
+        exact("error", &e.error, &a.error);
+        exact("vector dimensions", &e.vector.keys().collect::<Vec<_>>(), &a.vector.keys().collect::<Vec<_>>());
+
+        let mut scores = vec![
+            ("salience".to_string(), e.salience, a.salience),
+            ("threat".to_string(), e.threat, a.threat),
+            ("composite_score".to_string(), e.composite_score, a.composite_score),
+        ];
+        scores.extend(
+            e.vector
+                .iter()
+                .filter_map(|(k, v)| a.vector.get(k).map(|actual| (format!("vector.{}", k), *v, *actual))),
+        );
+        for (field, e, a) in scores {
+            if (e - a).abs() > tolerance {
+                diffs.push(format!("event {} ({:?}): {} expected {}, got {}", i, expected[i].text, field, e, a));
+            }
+        }
+    }
+    diffs
+}
+
+/// In-process storage for simulations and tests: no Python, no model.
+///
+/// Embeddings are hashed bags of words, so texts sharing words are similar
+/// and unrelated texts are near-orthogonal. Good enough to exercise matching,
+/// not a stand-in for the real model's semantics.
+pub struct LocalStorage {
+    heuristics: Vec<CachedHeuristic>,
+    /// Minimum similarity for `query_matching_heuristics`
+    min_similarity: f32,
+}
+
+impl LocalStorage {
+    /// Heuristics without an embedding are embedded locally.
+    pub fn new(heuristics: Vec<CachedHeuristic>, min_similarity: f32) -> Self {
+        let heuristics = heuristics
+            .into_iter()
+            .map(|mut h| {
+                if h.condition_embedding.is_empty() {
+                    let text = h.condition.text.as_str();
+                    h.condition_embedding = local_embedding(text).into();
+                }
+                h
+            })
+            .collect();
+        Self { heuristics, min_similarity }
+    }
+}
+
+/// Hashed bag-of-words embedding, L2-normalized.
+pub fn local_embedding(text: &str) -> Vec<f32> {
+    let mut embedding = vec![0.0; LOCAL_EMBEDDING_DIMS];
+    for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+        // FNV-1a so embeddings are stable across runs
+        let hash = word
+            .to_lowercase()
+            .bytes()
+            .fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+        embedding[(hash % LOCAL_EMBEDDING_DIMS as u64) as usize] += 1.0;
+    }
+    let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        embedding.iter_mut().for_each(|v| *v /= norm);
+    }
+    embedding
+}
+
+#[tonic::async_trait]
+impl StorageBackend for LocalStorage {
+    async fn query_matching_heuristics(
+        &self,
+        event_text: &str,
+        min_confidence: f32,
+        limit: i32,
+        source_filter: Option<&str>,
+        _trace_id: Option<&str>,
+    ) -> Result<Vec<CachedHeuristic>, StorageError> {
+        let query = local_embedding(event_text);
+        let mut scored: Vec<(f32, &CachedHeuristic)> = self
+            .heuristics
+            .iter()
+            .filter(|h| h.confidence >= min_confidence && h.matches_source(source_filter))
+            .map(|h| (crate::cosine_similarity(&query, &h.condition_embedding), h))
+            .filter(|(sim, _)| *sim >= self.min_similarity)
+            .collect();
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        if limit > 0 {
+            scored.truncate(limit as usize);
+        }
+        Ok(scored.into_iter().map(|(_, h)| h.clone()).collect())
+    }
+
+    async fn generate_embedding(&self, text: &str, _trace_id: Option<&str>) -> Result<Vec<f32>, StorageError> {
+        Ok(local_embedding(text))
+    }
+}
+
+/// Build a service over `LocalStorage` with the production scorer.
+pub fn local_service(
+    heuristics: Vec<CachedHeuristic>,
+    cache_config: crate::CacheConfig,
+    salience_config: crate::SalienceConfig,
+) -> (SalienceService, CacheHandle) {
+    let cache = CacheHandle::new(MemoryCache::new(cache_config));
+    let storage: Arc<dyn StorageBackend> =
+        Arc::new(LocalStorage::new(heuristics, salience_config.min_heuristic_similarity));
+    let scorer: Box<dyn crate::SalienceScorer> = Box::new(
+        crate::EmbeddingSimilarityScorer::new(
+            cache.clone(),
+            storage.clone(),
+            salience_config.min_heuristic_similarity,
+            salience_config.min_heuristic_confidence,
+        )
+        .with_fallback_limit(crate::rate_limit::TokenBucket::for_fallback(&salience_config))
+        .with_keyword_prefilter(salience_config.keyword_prefilter),
+    );
+    let service = SalienceService::with_scorer(cache.clone(), scorer, salience_config).with_storage(storage);
+    (service, cache)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seed::parse_seed_file;
+
+    #[test]
+    fn test_parse_transcript_skips_comments() {
+        let events = parse_transcript("# warmup\n{\"t_ms\": 5, \"text\": \"hello\"}\n\n{\"text\": \"bye\"}\n").unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].t_ms, 5);
+        assert_eq!(events[1].source, "");
+
+        let err = parse_transcript("{\"text\": \"ok\"}\n{oops}").unwrap_err();
+        assert!(err.to_string().starts_with("Transcript line 2"));
+    }
+
+    #[tokio::test]
+    async fn test_simulation_timeline_shows_cache_warming() {
+        let heuristics = parse_seed_file(
+            r#"[{"condition_text": "creeper approaching player", "effects": {"salience": {"threat": 0.9}}, "confidence": 0.9}]"#,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|s| crate::seed::SeedHeuristic::into_cached(s, vec![]))
+        .collect();
+        let (service, cache) = local_service(heuristics, crate::CacheConfig::default(), crate::SalienceConfig::default());
+        let events = parse_transcript(
+            "{\"t_ms\": 0, \"text\": \"creeper approaching player\"}\n\
+             {\"t_ms\": 100, \"text\": \"creeper approaching player\"}\n\
+             {\"t_ms\": 200, \"text\": \"sunny meadow\"}\n",
+        )
+        .unwrap();
+
+        let timeline = run_simulation(&service, &cache, &events).await;
+        assert_eq!(timeline.len(), 3);
+        // First hit comes from storage and warms the cache; the repeat is served from it
+        assert!(!timeline[0].matched_heuristic_id.is_empty());
+        assert_eq!(timeline[0].cache_misses, 1);
+        assert_eq!(timeline[1].matched_heuristic_id, timeline[0].matched_heuristic_id);
+        assert_eq!(timeline[1].cache_size, 1);
+        assert!(timeline[1].threat >= 0.9);
+        assert!(timeline[2].matched_heuristic_id.is_empty());
+        assert_eq!(timeline[2].threat, 0.0);
+    }
+
+    #[test]
+    fn test_diff_timelines_tolerance() {
+        let entry = TimelineEntry {
+            t_ms: 0,
+            source: String::new(),
+            text: "creeper".to_string(),
+            salience: 0.5,
+            threat: 0.9,
+            vector: BTreeMap::from([("novelty".to_string(), 0.5)]),
+            matched_heuristic_id: "h1".to_string(),
+            from_cache: true,
+            dominant_dimension: "threat".to_string(),
+            composite_score: 0.4,
+            routing_hint: "ROUTING_HINT_IMMEDIATE".to_string(),
+            degraded: false,
+            cache_size: 1,
+            cache_hits: 1,
+            cache_misses: 0,
+            error: String::new(),
+        };
+        let mut drifted = entry.clone();
+        drifted.threat += 0.0005;
+        drifted.vector.insert("novelty".to_string(), 0.5004);
+        assert!(diff_timelines(std::slice::from_ref(&entry), std::slice::from_ref(&drifted), DEFAULT_GOLDEN_TOLERANCE).is_empty());
+
+        drifted.composite_score = 0.5;
+        drifted.matched_heuristic_id = "h2".to_string();
+        let diffs = diff_timelines(std::slice::from_ref(&entry), &[drifted], DEFAULT_GOLDEN_TOLERANCE);
+        assert_eq!(diffs.len(), 2);
+        assert!(diffs.iter().any(|d| d.contains("matched_heuristic_id")));
+        assert!(diffs.iter().any(|d| d.contains("composite_score")));
+
+        assert_eq!(diff_timelines(&[entry.clone(), entry.clone()], &[entry], 0.0).len(), 1);
+    }
+}
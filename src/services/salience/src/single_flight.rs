@@ -0,0 +1,177 @@
+//! Request coalescing for identical concurrent calls.
+//!
+//! A burst of duplicate events (e.g., a sensor repeating itself) would
+//! otherwise issue one identical storage query per event. `SingleFlight`
+//! lets the first caller for a key run the call while concurrent callers
+//! with the same key wait for and share its result. Entries last only while
+//! the call is in flight; nothing is cached afterwards.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::OnceCell;
+
+/// Coalesces concurrent calls by key.
+pub struct SingleFlight<K, V> {
+    in_flight: Mutex<HashMap<K, Arc<OnceCell<V>>>>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> SingleFlight<K, V> {
+    pub fn new() -> Self {
+        Self { in_flight: Mutex::new(HashMap::new()) }
+    }
+
+    /// Run `call` unless an identical call is already in flight, in which
+    /// case wait for that one. Returns the result and whether it was shared.
+    ///
+    /// If the running caller is cancelled, a waiting caller runs its own `call`.
+    pub async fn run<F, Fut>(&self, key: K, call: F) -> (V, bool)
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let cell = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            in_flight.entry(key.clone()).or_default().clone()
+        };
+        let mut ran = false;
+        let value = cell
+            .get_or_init(|| {
+                ran = true;
+                call()
+            })
+            .await
+            .clone();
+        if ran {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            // Only drop our own entry; a later flight may have replaced it
+            if in_flight.get(&key).is_some_and(|c| Arc::ptr_eq(c, &cell)) {
+                in_flight.remove(&key);
+            }
+        }
+        (value, !ran)
+    }
+
+    /// Calls currently in flight.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Default for SingleFlight<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_calls_share_one_result() {
+        let flights: Arc<SingleFlight<&str, usize>> = Arc::new(SingleFlight::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let (flights, calls) = (flights.clone(), calls.clone());
+                tokio::spawn(async move {
+                    flights
+                        .run("creeper", || async {
+                            tokio::time::sleep(Duration::from_millis(20)).await;
+                            calls.fetch_add(1, Ordering::SeqCst) + 1
+                        })
+                        .await
+                })
+            })
+            .collect();
+        let mut shared = 0;
+        for task in tasks {
+            let (value, was_shared) = task.await.unwrap();
+            assert_eq!(value, 1);
+            shared += was_shared as usize;
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(shared, 7);
+        assert_eq!(flights.in_flight(), 0);
+
+        // Once finished, the next call runs again
+        let (value, was_shared) = flights.run("creeper", || async { 42 }).await;
+        assert_eq!((value, was_shared), (42, false));
+    }
+
+    #[tokio::test]
+    async fn test_distinct_keys_run_separately() {
+        let flights: Arc<SingleFlight<&str, &str>> = Arc::new(SingleFlight::new());
+        let slow = |value| async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            value
+        };
+        let (creeper, zombie) = tokio::join!(
+            flights.run("creeper", || slow("creeper")),
+            flights.run("zombie", || slow("zombie")),
+        );
+        assert_eq!(creeper, ("creeper", false));
+        assert_eq!(zombie, ("zombie", false));
+        assert_eq!(flights.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_shared_but_not_kept() {
+        let flights: Arc<SingleFlight<&str, Result<usize, String>>> = Arc::new(SingleFlight::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let failing = |calls: Arc<AtomicUsize>| async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err("storage unavailable".to_string())
+        };
+
+        let (a, b) = tokio::join!(
+            flights.run("creeper", || failing(calls.clone())),
+            flights.run("creeper", || failing(calls.clone())),
+        );
+        assert_eq!(a.0, Err("storage unavailable".to_string()));
+        assert_eq!(a.0, b.0);
+        assert!(a.1 != b.1, "exactly one caller shares the failure");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // The failure isn't remembered: the next caller retries
+        let (value, was_shared) = flights.run("creeper", || async { Ok(1) }).await;
+        assert_eq!((value, was_shared), (Ok(1), false));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_leader_hands_off_to_waiter() {
+        let flights: Arc<SingleFlight<&str, usize>> = Arc::new(SingleFlight::new());
+
+        let leader = {
+            let flights = flights.clone();
+            tokio::spawn(async move {
+                flights
+                    .run("creeper", || async {
+                        tokio::time::sleep(Duration::from_secs(60)).await;
+                        1
+                    })
+                    .await
+            })
+        };
+        while flights.in_flight() == 0 {
+            tokio::task::yield_now().await;
+        }
+        let waiter = {
+            let flights = flights.clone();
+            tokio::spawn(async move { flights.run("creeper", || async { 2 }).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        leader.abort();
+
+        let (value, was_shared) = tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        assert_eq!((value, was_shared), (2, false));
+        assert_eq!(flights.in_flight(), 0);
+    }
+}
@@ -0,0 +1,264 @@
+//! Latency SLO tracking and breach alerts.
+//!
+//! The stage histograms show how latency is distributed, but not whether the
+//! fast path is holding its latency budget. With a target configured (e.g.,
+//! p99 < 20ms for cache hits), evaluations are timed into fixed windows:
+//! - Each closed window's percentile is exported as
+//!   `gladys_salience_slo_latency_seconds` and in health details
+//! - A window whose percentile exceeds the target is a breach
+//! - After N consecutive breached windows, a structured alert event
+//!   (`alert = "slo_breach"`) is logged once; the first window back within
+//!   target logs `alert = "slo_recovered"`
+//!
+//! Windows close on the next evaluation after they end, so an idle service
+//! neither breaches nor recovers. Windows without samples are skipped.
+//!
+//! Configuration via environment variables (see `SloConfig`):
+//!   SLO_TARGET_MS: Latency target in milliseconds (default: disabled)
+//!   SLO_PERCENTILE: Percentile held to the target (default: 99)
+//!   SLO_SCOPE: "cache_hit" or "all" evaluations (default: cache_hit)
+//!   SLO_WINDOW_SECS: Window length in seconds (default: 60)
+//!   SLO_BREACH_WINDOWS: Consecutive breached windows before alerting (default: 3)
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+use crate::config::SloConfig;
+
+/// Samples kept per window; beyond this the oldest are overwritten.
+const MAX_WINDOW_SAMPLES: usize = 10_000;
+
+/// Summary of the last closed window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowSummary {
+    pub percentile_ms: f64,
+    pub samples: u64,
+    pub breached: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SloStatus {
+    pub last_window: Option<WindowSummary>,
+    pub consecutive_breaches: u32,
+    /// Breach alert raised and not yet recovered
+    pub alerting: bool,
+    pub alerts_total: u64,
+}
+
+struct Window {
+    started: Instant,
+    samples: Vec<f64>,
+    seen: u64,
+}
+
+impl Window {
+    fn new(started: Instant) -> Self {
+        Self { started, samples: Vec::new(), seen: 0 }
+    }
+}
+
+/// Rolling-window latency percentile against a target.
+pub struct SloTracker {
+    target_ms: f64,
+    /// Percentile held to the target (0-100)
+    percentile: f64,
+    cache_hits_only: bool,
+    window: Duration,
+    breach_windows: u32,
+    state: Mutex<(Window, SloStatus)>,
+}
+
+impl SloTracker {
+    /// None if no target is configured.
+    pub fn new(config: &SloConfig) -> Option<Self> {
+        let target_ms = config.target_ms?;
+        Some(Self {
+            target_ms,
+            percentile: config.percentile.clamp(0.0, 100.0),
+            cache_hits_only: config.scope != "all",
+            window: Duration::from_secs(config.window_secs.max(1)),
+            breach_windows: config.breach_windows.max(1),
+            state: Mutex::new((Window::new(Instant::now()), SloStatus::default())),
+        })
+    }
+
+    /// Record one evaluation's latency.
+    pub fn record(&self, latency: Duration, cache_hit: bool) {
+        self.record_at(Instant::now(), latency, cache_hit);
+    }
+
+    fn record_at(&self, now: Instant, latency: Duration, cache_hit: bool) {
+        if self.cache_hits_only && !cache_hit {
+            return;
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (window, status) = &mut *state;
+        if now.duration_since(window.started) >= self.window {
+            let closed = std::mem::replace(window, Window::new(now));
+            self.close(closed, status);
+        }
+
+        let ms = latency.as_secs_f64() * 1000.0;
+        if window.samples.len() < MAX_WINDOW_SAMPLES {
+            window.samples.push(ms);
+        } else {
+            window.samples[window.seen as usize % MAX_WINDOW_SAMPLES] = ms;
+        }
+        window.seen += 1;
+    }
+
+    fn close(&self, mut window: Window, status: &mut SloStatus) {
+        if window.samples.is_empty() {
+            return;
+        }
+        window.samples.sort_by(f64::total_cmp);
+        let rank = ((self.quantile() * window.samples.len() as f64).ceil() as usize).clamp(1, window.samples.len());
+        let percentile_ms = window.samples[rank - 1];
+        let breached = percentile_ms > self.target_ms;
+        status.last_window = Some(WindowSummary { percentile_ms, samples: window.seen, breached });
+
+        if !breached {
+            if status.alerting {
+                info!(
+                    alert = "slo_recovered",
+                    percentile = self.percentile,
+                    observed_ms = percentile_ms,
+                    target_ms = self.target_ms,
+                    "Latency SLO recovered"
+                );
+            }
+            status.consecutive_breaches = 0;
+            status.alerting = false;
+            return;
+        }
+
+        status.consecutive_breaches += 1;
+        if status.consecutive_breaches == self.breach_windows {
+            status.alerting = true;
+            status.alerts_total += 1;
+            warn!(
+                alert = "slo_breach",
+                scope = self.scope(),
+                percentile = self.percentile,
+                observed_ms = percentile_ms,
+                target_ms = self.target_ms,
+                consecutive_windows = status.consecutive_breaches,
+                window_secs = self.window.as_secs(),
+                samples = window.seen,
+                "Latency SLO breached"
+            );
+        }
+    }
+
+    pub fn status(&self) -> SloStatus {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).1
+    }
+
+    pub fn target_ms(&self) -> f64 {
+        self.target_ms
+    }
+
+    /// Percentile as a fraction (0.99 for p99)
+    pub fn quantile(&self) -> f64 {
+        self.percentile / 100.0
+    }
+
+    pub fn scope(&self) -> &'static str {
+        if self.cache_hits_only { "cache_hit" } else { "all" }
+    }
+
+    pub fn health_details(&self) -> HashMap<String, String> {
+        let status = self.status();
+        let mut details = HashMap::from([
+            ("slo.target_ms".to_string(), format!("{:.2}", self.target_ms)),
+            ("slo.scope".to_string(), self.scope().to_string()),
+            ("slo.consecutive_breaches".to_string(), status.consecutive_breaches.to_string()),
+            ("slo.alerting".to_string(), status.alerting.to_string()),
+        ]);
+        if let Some(window) = status.last_window {
+            let key = format!("slo.p{}_ms", self.percentile);
+            details.insert(key, format!("{:.2}", window.percentile_ms));
+            details.insert("slo.window_samples".to_string(), window.samples.to_string());
+        }
+        details
+    }
+}
+
+static SLO_TRACKER: OnceLock<SloTracker> = OnceLock::new();
+
+/// Install the process-wide tracker (no-op without a target, or if one is
+/// already installed).
+pub fn install(config: &SloConfig) {
+    if let Some(tracker) = SloTracker::new(config) {
+        let _ = SLO_TRACKER.set(tracker);
+    }
+}
+
+/// The process-wide tracker, if an SLO is configured.
+pub fn slo_tracker() -> Option<&'static SloTracker> {
+    SLO_TRACKER.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(breach_windows: u32) -> SloTracker {
+        SloTracker::new(&SloConfig {
+            target_ms: Some(20.0),
+            percentile: 99.0,
+            scope: "cache_hit".to_string(),
+            window_secs: 60,
+            breach_windows,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_alert_after_consecutive_breached_windows() {
+        let tracker = tracker(2);
+        let start = Instant::now();
+        let window = |n: u64| start + Duration::from_secs(60 * n);
+        let slow = Duration::from_millis(30);
+        let fast = Duration::from_millis(5);
+
+        // Misses don't count toward a cache-hit SLO
+        tracker.record_at(window(0), slow, false);
+        for n in 0..2 {
+            tracker.record_at(window(n), slow, true);
+        }
+        // Closing window 1 is the second breach in a row
+        tracker.record_at(window(2), fast, true);
+        let status = tracker.status();
+        assert_eq!(status.consecutive_breaches, 2);
+        assert!(status.alerting);
+        assert_eq!(status.alerts_total, 1);
+        assert_eq!(status.last_window.unwrap().percentile_ms, 30.0);
+
+        tracker.record_at(window(3), fast, true);
+        let status = tracker.status();
+        assert!(!status.alerting && status.consecutive_breaches == 0);
+        assert_eq!(tracker.health_details().get("slo.p99_ms").map(String::as_str), Some("5.00"));
+    }
+
+    #[test]
+    fn test_percentile_ignores_tail_below_rank() {
+        let tracker = tracker(1);
+        let start = Instant::now();
+        // 1 slow sample in 200 is under the p99 rank
+        for i in 0..200 {
+            let ms = if i == 0 { 100 } else { 10 };
+            tracker.record_at(start, Duration::from_millis(ms), true);
+        }
+        tracker.record_at(start + Duration::from_secs(60), Duration::from_millis(10), true);
+        let window = tracker.status().last_window.unwrap();
+        assert_eq!(window.samples, 200);
+        assert!(!window.breached);
+
+        let disabled = SloConfig { target_ms: None, scope: "all".to_string(), ..SloConfig::default() };
+        assert!(SloTracker::new(&disabled).is_none());
+    }
+}
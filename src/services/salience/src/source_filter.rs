@@ -0,0 +1,128 @@
+//! Source allow- and deny-lists.
+//!
+//! A noisy source (a chat relay gone chatty, a sensor stuck repeating) can be
+//! silenced here without touching whatever publishes it. EvaluateSalience
+//! checks the event's source before any scoring: a filtered event gets
+//! baseline salience at once, with `filtered` set, and costs no embedding,
+//! cache lookup or storage call. Filtered events aren't recorded as decisions
+//! or counted in the salience summary.
+//!
+//! A source is filtered when it's on the deny-list, or when the allow-list
+//! isn't empty and it isn't on it. Sources match exactly. The UpdateSourceFilter
+//! admin RPC edits both lists at runtime (changes last until restart) and
+//! reports them; an empty update just reports them.
+//!
+//! Configuration via environment variables (see `SalienceConfig`):
+//!   SALIENCE_SOURCE_ALLOW: Comma-separated sources to evaluate (default: empty = all)
+//!   SALIENCE_SOURCE_DENY: Comma-separated sources never evaluated (default: none)
+
+use std::collections::BTreeSet;
+use std::sync::RwLock;
+
+/// The allow- and deny-lists, as of one moment.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceLists {
+    pub allow: BTreeSet<String>,
+    pub deny: BTreeSet<String>,
+}
+
+/// Edits to the lists (additions apply after removals).
+#[derive(Debug, Clone, Default)]
+pub struct SourceFilterUpdate {
+    pub allow_add: Vec<String>,
+    pub allow_remove: Vec<String>,
+    pub deny_add: Vec<String>,
+    pub deny_remove: Vec<String>,
+}
+
+impl SourceFilterUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.allow_add.is_empty() && self.allow_remove.is_empty() && self.deny_add.is_empty() && self.deny_remove.is_empty()
+    }
+}
+
+/// Decides which sources are evaluated.
+#[derive(Debug, Default)]
+pub struct SourceFilter {
+    lists: RwLock<SourceLists>,
+}
+
+impl SourceFilter {
+    pub fn new(allow: impl IntoIterator<Item = String>, deny: impl IntoIterator<Item = String>) -> Self {
+        let lists = SourceLists { allow: clean(allow), deny: clean(deny) };
+        Self { lists: RwLock::new(lists) }
+    }
+
+    /// Whether events from `source` skip scoring.
+    pub fn is_filtered(&self, source: &str) -> bool {
+        let lists = self.lists.read().unwrap_or_else(|e| e.into_inner());
+        lists.deny.contains(source) || (!lists.allow.is_empty() && !lists.allow.contains(source))
+    }
+
+    /// Apply `update` and return the lists as they now stand.
+    pub fn update(&self, update: SourceFilterUpdate) -> SourceLists {
+        let mut lists = self.lists.write().unwrap_or_else(|e| e.into_inner());
+        for source in clean(update.allow_remove) {
+            lists.allow.remove(&source);
+        }
+        for source in clean(update.deny_remove) {
+            lists.deny.remove(&source);
+        }
+        lists.allow.extend(clean(update.allow_add));
+        lists.deny.extend(clean(update.deny_add));
+        lists.clone()
+    }
+
+    pub fn lists(&self) -> SourceLists {
+        self.lists.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Trimmed, non-empty source names.
+fn clean(sources: impl IntoIterator<Item = String>) -> BTreeSet<String> {
+    sources.into_iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_deny_and_allow_lists() {
+        let open = SourceFilter::default();
+        assert!(!open.is_filtered("minecraft"));
+
+        let filter = SourceFilter::new(Vec::new(), strings(&["chat_relay", " "]));
+        assert!(filter.is_filtered("chat_relay"));
+        assert!(!filter.is_filtered("minecraft"));
+        assert_eq!(filter.lists().deny.len(), 1);
+
+        // A non-empty allow-list filters everything else; deny still wins
+        let lists = filter.update(SourceFilterUpdate {
+            allow_add: strings(&["minecraft", "chat_relay"]),
+            ..Default::default()
+        });
+        assert_eq!(lists.allow.len(), 2);
+        assert!(!filter.is_filtered("minecraft"));
+        assert!(filter.is_filtered("chat_relay"));
+        assert!(filter.is_filtered("sensors"));
+    }
+
+    #[test]
+    fn test_update_removes_before_adding() {
+        let filter = SourceFilter::new(strings(&["minecraft"]), strings(&["chat_relay"]));
+        let lists = filter.update(SourceFilterUpdate {
+            allow_remove: strings(&["minecraft"]),
+            deny_remove: strings(&["chat_relay"]),
+            deny_add: strings(&["chat_relay"]),
+            ..Default::default()
+        });
+        assert!(lists.allow.is_empty());
+        assert_eq!(lists.deny, clean(strings(&["chat_relay"])));
+        assert!(SourceFilterUpdate::default().is_empty());
+    }
+}
@@ -0,0 +1,363 @@
+//! SQLite storage: the fast path without the Python service.
+//!
+//! A small deployment (one box, a few thousand heuristics at most) doesn't
+//! need PostgreSQL and an embedding model behind a second service. With
+//! STORAGE_BACKEND=sqlite (in a build with the `sqlite` feature), storage is
+//! a local SQLite file instead:
+//! - Heuristics live in one table, condition embeddings as little-endian f32
+//!   blobs. QueryMatchingHeuristics is a brute-force cosine scan over the
+//!   rows, which is plenty fast at this size
+//! - Embeddings are the hashed bag-of-words ones the simulator uses (see
+//!   `simulate::local_embedding`), so matching is closer to word overlap than
+//!   to a sentence model. They're reported as LOCAL_EMBEDDING_MODEL, so the
+//!   cache never compares them with a real model's (see embedding_model module)
+//! - Seeding and ImportHeuristics store heuristics, decision write-back keeps
+//!   the latest decision per event, and the refresh loop's conditional fetch
+//!   goes by each row's update time. Rows deleted behind the service's back
+//!   aren't reported as deleted; they drop out of the cache by TTL
+//! - Instead of the storage schema handshake, the file's schema version
+//!   (`PRAGMA user_version`) is checked on open: a newer file is refused
+//!
+//! Feedback counts aren't kept, so the effectiveness report shows feedback
+//! as unavailable.
+//!
+//! Configuration via environment variables (see `StorageConfig`):
+//!   STORAGE_BACKEND: grpc, sqlite or postgres (default: grpc)
+//!   STORAGE_SQLITE_PATH: Database file, created if missing (default: gladys-memory.db)
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use prost::Message;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::client::{bytes_to_embedding, embedding_to_bytes};
+use crate::proto::EventSalienceUpdate;
+use crate::simulate::local_embedding;
+use crate::{CachedHeuristic, Condition, Effects, Heuristic, HeuristicChanges, StorageBackend, StorageError};
+
+/// Version of the tables below (stored as `PRAGMA user_version`).
+pub const SCHEMA_VERSION: i32 = 1;
+
+/// Model version reported for local embeddings.
+pub const LOCAL_EMBEDDING_MODEL: &str = "local-bow-384";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS heuristics (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    condition_json TEXT NOT NULL,
+    effects_json TEXT NOT NULL,
+    confidence REAL NOT NULL,
+    origin TEXT NOT NULL,
+    source TEXT NOT NULL,
+    condition_embedding BLOB NOT NULL,
+    embedding_model TEXT NOT NULL,
+    updated_at_ms INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS heuristics_updated_at ON heuristics (updated_at_ms);
+CREATE TABLE IF NOT EXISTS decisions (
+    event_id TEXT PRIMARY KEY,
+    salience BLOB NOT NULL,
+    matched_heuristic_id TEXT NOT NULL,
+    composite_score REAL NOT NULL,
+    updated_at_ms INTEGER NOT NULL
+);
+";
+
+const COLUMNS: &str = "id, name, condition_json, effects_json, confidence, origin, source, \
+                       condition_embedding, embedding_model, updated_at_ms";
+
+/// `StorageBackend` over a local SQLite file (see module docs).
+pub struct SqliteStorageBackend {
+    conn: Arc<Mutex<Connection>>,
+    min_similarity: f32,
+}
+
+/// A heuristic row as stored, before decoding.
+struct Row {
+    id: String,
+    name: String,
+    condition_json: String,
+    effects_json: String,
+    confidence: f32,
+    origin: String,
+    source: String,
+    condition_embedding: Vec<u8>,
+    embedding_model: String,
+    updated_at_ms: i64,
+}
+
+impl Row {
+    fn read(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            condition_json: row.get(2)?,
+            effects_json: row.get(3)?,
+            confidence: row.get(4)?,
+            origin: row.get(5)?,
+            source: row.get(6)?,
+            condition_embedding: row.get(7)?,
+            embedding_model: row.get(8)?,
+            updated_at_ms: row.get(9)?,
+        })
+    }
+
+    /// The heuristic, with exemplars embedded (None if the row doesn't decode).
+    fn decode(self) -> Option<CachedHeuristic> {
+        let decoded = Uuid::parse_str(&self.id).ok().and_then(|id| {
+            let condition: Condition = serde_json::from_str(&self.condition_json).ok()?;
+            let effects = Effects::parse(&self.effects_json).ok()?;
+            Some((id, condition, effects))
+        });
+        let Some((id, mut condition, effects)) = decoded else {
+            warn!(heuristic_id = %self.id, "Skipping undecodable heuristic row");
+            return None;
+        };
+        for exemplar in condition.exemplars.iter_mut().chain(condition.negatives.iter_mut()) {
+            exemplar.embedding = local_embedding(&exemplar.text);
+        }
+        Some(CachedHeuristic::from(Heuristic {
+            id,
+            name: self.name,
+            condition,
+            effects,
+            confidence: self.confidence,
+            origin: self.origin,
+            source: self.source,
+            condition_embedding: bytes_to_embedding(&self.condition_embedding),
+            embedding_model: self.embedding_model,
+        }))
+    }
+}
+
+fn sqlite_error(e: rusqlite::Error) -> StorageError {
+    StorageError::Storage(format!("SQLite: {}", e))
+}
+
+impl SqliteStorageBackend {
+    /// Open (or create) the database at `path`; ":memory:" is a private
+    /// in-memory database. Storage fallbacks return matches at or above
+    /// `min_similarity`.
+    pub fn open(path: impl AsRef<Path>, min_similarity: f32) -> Result<Self, StorageError> {
+        let conn = Connection::open(path).map_err(sqlite_error)?;
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).map_err(sqlite_error)?;
+        if version > SCHEMA_VERSION {
+            return Err(StorageError::Storage(format!(
+                "SQLite database has schema version {}, newer than this build's {}",
+                version, SCHEMA_VERSION
+            )));
+        }
+        conn.execute_batch(SCHEMA).map_err(sqlite_error)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION).map_err(sqlite_error)?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)), min_similarity })
+    }
+
+    /// Run `f` on the connection off the async runtime.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T, StorageError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || f(&mut conn.lock().unwrap_or_else(|e| e.into_inner())))
+            .await
+            .map_err(|e| StorageError::Storage(e.to_string()))?
+            .map_err(sqlite_error)
+    }
+
+    /// Rows matching `filter` (a WHERE/ORDER/LIMIT tail taking `args`),
+    /// decoded, with their update times.
+    async fn load(&self, filter: &'static str, args: Vec<Value>) -> Result<Vec<(CachedHeuristic, i64)>, StorageError> {
+        let rows = self
+            .with_conn(move |conn| {
+                let mut statement = conn.prepare(&format!("SELECT {} FROM heuristics {}", COLUMNS, filter))?;
+                let rows = statement.query_map(params_from_iter(args), Row::read)?;
+                rows.collect::<rusqlite::Result<Vec<Row>>>()
+            })
+            .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let updated_at_ms = row.updated_at_ms;
+                row.decode().map(|h| (h, updated_at_ms))
+            })
+            .collect())
+    }
+}
+
+#[tonic::async_trait]
+impl StorageBackend for SqliteStorageBackend {
+    async fn query_matching_heuristics(
+        &self,
+        event_text: &str,
+        min_confidence: f32,
+        limit: i32,
+        source_filter: Option<&str>,
+        _trace_id: Option<&str>,
+    ) -> Result<Vec<CachedHeuristic>, StorageError> {
+        let query = local_embedding(event_text);
+        let candidates = self.load("WHERE confidence >= ?1", vec![Value::Real(min_confidence as f64)]).await?;
+        let mut scored: Vec<(f32, CachedHeuristic)> = candidates
+            .into_iter()
+            .map(|(h, _)| h)
+            .filter(|h| h.matches_source(source_filter))
+            .map(|h| (crate::cosine_similarity(&query, &h.condition_embedding), h))
+            .filter(|(similarity, _)| *similarity >= self.min_similarity)
+            .collect();
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        if limit > 0 {
+            scored.truncate(limit as usize);
+        }
+        Ok(scored.into_iter().map(|(_, h)| h).collect())
+    }
+
+    async fn generate_embedding(&self, text: &str, _trace_id: Option<&str>) -> Result<Vec<f32>, StorageError> {
+        Ok(local_embedding(text))
+    }
+
+    fn embedding_model(&self) -> Option<String> {
+        Some(LOCAL_EMBEDDING_MODEL.to_string())
+    }
+
+    async fn query_changed_heuristics(
+        &self,
+        updated_since_ms: i64,
+        limit: i32,
+        _trace_id: Option<&str>,
+    ) -> Result<HeuristicChanges, StorageError> {
+        // LIMIT -1 = no limit
+        let limit = if limit > 0 { limit as i64 } else { -1 };
+        let args = vec![Value::Integer(updated_since_ms), Value::Integer(limit)];
+        let rows = self.load("WHERE updated_at_ms > ?1 ORDER BY updated_at_ms LIMIT ?2", args).await?;
+        let latest_updated_ms = rows.iter().map(|(_, updated)| *updated).max().unwrap_or(0).max(updated_since_ms);
+        Ok(HeuristicChanges {
+            heuristics: rows.into_iter().map(|(h, _)| h).collect(),
+            deleted_ids: Vec::new(),
+            latest_updated_ms,
+        })
+    }
+
+    async fn store_heuristic(&self, heuristic: &CachedHeuristic, _trace_id: Option<&str>) -> Result<(), StorageError> {
+        let h = heuristic.to_heuristic();
+        let condition_json = serde_json::to_string(&h.condition).map_err(|e| StorageError::Storage(e.to_string()))?;
+        // Always re-embed: matching here only works against local embeddings
+        let embedding = embedding_to_bytes(&local_embedding(&h.condition.text));
+        self.with_conn(move |conn| {
+            conn.execute(
+                &format!("INSERT OR REPLACE INTO heuristics ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)", COLUMNS),
+                params![
+                    h.id.to_string(),
+                    h.name,
+                    condition_json,
+                    h.effects.to_json(),
+                    h.confidence,
+                    h.origin,
+                    h.source,
+                    embedding,
+                    LOCAL_EMBEDDING_MODEL,
+                    crate::current_time_ms(),
+                ],
+            )
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn update_event_salience(
+        &self,
+        updates: Vec<EventSalienceUpdate>,
+        _trace_id: Option<&str>,
+    ) -> Result<usize, StorageError> {
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let now = crate::current_time_ms();
+            for update in &updates {
+                tx.execute(
+                    "INSERT OR REPLACE INTO decisions (event_id, salience, matched_heuristic_id, composite_score, updated_at_ms)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        update.event_id,
+                        update.salience.as_ref().map(Message::encode_to_vec).unwrap_or_default(),
+                        update.matched_heuristic_id,
+                        update.composite_score,
+                        now,
+                    ],
+                )?;
+            }
+            tx.commit()?;
+            Ok(updates.len())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Exemplar;
+
+    fn heuristic(text: &str, source: &str) -> CachedHeuristic {
+        CachedHeuristic::from(Heuristic {
+            id: Uuid::new_v4(),
+            name: text.to_string(),
+            condition: Condition::text(text).with_exemplars(vec![Exemplar::new("hissing behind you", 0.8, Vec::new())]),
+            effects: Effects::parse(r#"{"salience": {"threat": 0.8}}"#).unwrap(),
+            confidence: 0.7,
+            source: source.to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_stores_and_matches_heuristics() {
+        let storage = SqliteStorageBackend::open(":memory:", 0.3).unwrap();
+        let creeper = heuristic("creeper approaching the player", "minecraft");
+        storage.store_heuristic(&creeper, None).await.unwrap();
+        storage.store_heuristic(&heuristic("lava flowing nearby", ""), None).await.unwrap();
+
+        let matches = storage.query_matching_heuristics("a creeper is approaching", 0.5, 5, Some("minecraft"), None).await.unwrap();
+        assert_eq!(matches.len(), 1);
+        let matched = &matches[0];
+        assert_eq!(matched.id, creeper.id);
+        assert_eq!(matched.effects, creeper.effects);
+        assert_eq!(matched.embedding_model, LOCAL_EMBEDDING_MODEL);
+        assert_eq!(matched.condition.exemplars[0].embedding, local_embedding("hissing behind you"));
+
+        // Scoped to another source, or below the confidence floor
+        assert!(storage.query_matching_heuristics("a creeper is approaching", 0.5, 5, Some("chat"), None).await.unwrap().is_empty());
+        assert!(storage.query_matching_heuristics("a creeper is approaching", 0.9, 5, None, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_changes_and_write_back() {
+        let storage = SqliteStorageBackend::open(":memory:", 0.3).unwrap();
+        storage.store_heuristic(&heuristic("creeper approaching", ""), None).await.unwrap();
+        let all = storage.query_changed_heuristics(0, 0, None).await.unwrap();
+        assert_eq!(all.heuristics.len(), 1);
+        assert!(all.latest_updated_ms > 0);
+        let none = storage.query_changed_heuristics(all.latest_updated_ms, 0, None).await.unwrap();
+        assert!(none.heuristics.is_empty());
+        assert_eq!(none.latest_updated_ms, all.latest_updated_ms);
+
+        let update = EventSalienceUpdate { event_id: "e1".to_string(), composite_score: 0.4, ..Default::default() };
+        assert_eq!(storage.update_event_salience(vec![update.clone(), update], None).await.unwrap(), 2);
+        let decisions: i64 = storage
+            .with_conn(|conn| conn.query_row("SELECT COUNT(*) FROM decisions", [], |row| row.get(0)))
+            .await
+            .unwrap();
+        assert_eq!(decisions, 1);
+    }
+
+    #[test]
+    fn test_newer_schema_is_refused() {
+        let path = std::env::temp_dir().join(format!("gladys-sqlite-{}.db", Uuid::new_v4()));
+        Connection::open(&path).unwrap().pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+        assert!(SqliteStorageBackend::open(&path, 0.3).is_err());
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,314 @@
+//! Warm standby: copy a running instance's cache before taking over.
+//!
+//! A new instance deployed next to the one it replaces would start with an
+//! empty cache and score at baseline until live misses refill it. With
+//! STANDBY_SOURCE_ADDRESS set, it instead calls StreamCacheState on the old
+//! instance (an admin RPC, so the address is the old one's admin listener):
+//! - The old instance sends every cached heuristic, with embeddings, in
+//!   batches; the last batch is marked `snapshot_complete`
+//! - It then sends what changed every sync interval: heuristics cached or
+//!   changed since (by any path: refresh, misses, notifications) and the IDs
+//!   of those no longer cached
+//! - The standby holds its health checks at UNHEALTHY (warm-up phase
+//!   "priming:standby") until the snapshot is in, so traffic only cuts over
+//!   to a warm cache; WARMUP_MAX_WAIT_MS still bounds the wait
+//! - Until the snapshot completes, a failed or closed stream is retried with
+//!   backoff. After that, the stream ending means the old instance is gone,
+//!   and the standby carries on by itself
+//!
+//! Configuration via environment variables (see `StandbyConfig`):
+//!   STANDBY_SOURCE_ADDRESS: host:port of the instance to copy (default: none)
+//!   STANDBY_SYNC_INTERVAL_MS: How often the source sends changes (default: 1000)
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
+use tonic::Status;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::compat::heuristic_from_proto;
+use crate::config::StandbyConfig;
+use crate::embedding_model::observe_model;
+use crate::eviction_log::EvictionReason;
+use crate::proto::salience_gateway_client::SalienceGatewayClient;
+use crate::proto::{self, CacheStateUpdate, StreamCacheStateRequest};
+use crate::warmup::Warmup;
+use crate::{CacheHandle, CachedHeuristic, MemoryCache};
+
+/// Sync interval when the request doesn't set one.
+pub const DEFAULT_SYNC_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Heuristics per update message.
+const BATCH_SIZE: usize = 256;
+
+/// Warm-up priming step held open until the snapshot is in.
+pub const PRIMING_STEP: &str = "standby";
+
+const RETRY_INITIAL: Duration = Duration::from_millis(500);
+const RETRY_MAX: Duration = Duration::from_secs(10);
+
+/// What identifies a version of a cached heuristic: re-caching (a merge or
+/// re-embed) moves cached_at_ms, feedback moves the confidence.
+#[derive(Debug, Clone, PartialEq)]
+struct Fingerprint {
+    cached_at_ms: i64,
+    confidence: u32,
+    embedding_model: String,
+}
+
+fn fingerprints(cache: &MemoryCache) -> HashMap<Uuid, Fingerprint> {
+    cache
+        .list_heuristics(0)
+        .into_iter()
+        .map(|h| {
+            let fingerprint = Fingerprint {
+                cached_at_ms: h.cached_at_ms,
+                confidence: h.confidence.to_bits(),
+                embedding_model: h.embedding_model.clone(),
+            };
+            (h.id, fingerprint)
+        })
+        .collect()
+}
+
+/// Send `cache` to a standby: a snapshot, then changes every `interval`,
+/// until `updates` is closed.
+pub async fn publish_cache_state(cache: CacheHandle, interval: Duration, updates: mpsc::Sender<CacheStateUpdate>) {
+    let mut sent: HashMap<Uuid, Fingerprint> = HashMap::new();
+    let mut snapshot = true;
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        if updates.is_closed() {
+            debug!("Standby disconnected from the cache stream");
+            return;
+        }
+        let (changed, mut removed_ids, embedding_model) = {
+            let cache = cache.read().await;
+            let current = fingerprints(&cache);
+            let changed: Vec<proto::Heuristic> = current
+                .iter()
+                .filter(|(id, fingerprint)| sent.get(id) != Some(fingerprint))
+                .filter_map(|(id, _)| cache.get_heuristic(id))
+                .map(|h| proto::Heuristic::from(&h.to_heuristic()))
+                .collect();
+            let removed: Vec<String> = sent.keys().filter(|id| !current.contains_key(id)).map(Uuid::to_string).collect();
+            sent = current;
+            (changed, removed, cache.embedding_model().unwrap_or_default().to_string())
+        };
+        if changed.is_empty() && removed_ids.is_empty() && !snapshot {
+            continue;
+        }
+        if snapshot {
+            info!(heuristics = changed.len(), "Sending cache snapshot to a standby");
+        }
+
+        let mut batches: Vec<Vec<proto::Heuristic>> = Vec::new();
+        let mut changed = changed.into_iter().peekable();
+        while changed.peek().is_some() {
+            batches.push(changed.by_ref().take(BATCH_SIZE).collect());
+        }
+        if batches.is_empty() {
+            batches.push(Vec::new());
+        }
+        let last = batches.len() - 1;
+        for (i, heuristics) in batches.into_iter().enumerate() {
+            let update = CacheStateUpdate {
+                heuristics,
+                removed_ids: if i == last { std::mem::take(&mut removed_ids) } else { Vec::new() },
+                snapshot_complete: snapshot && i == last,
+                embedding_model: embedding_model.clone(),
+            };
+            if updates.send(update).await.is_err() {
+                debug!("Standby disconnected from the cache stream");
+                return;
+            }
+        }
+        snapshot = false;
+    }
+}
+
+/// Apply one update from the source. Returns (merged, removed).
+pub async fn apply_cache_update(cache: &CacheHandle, update: CacheStateUpdate) -> (usize, usize) {
+    if !update.embedding_model.is_empty() {
+        observe_model(cache, &update.embedding_model).await;
+    }
+    let heuristics: Vec<CachedHeuristic> =
+        update.heuristics.into_iter().filter_map(heuristic_from_proto).map(CachedHeuristic::from).collect();
+    let merged = heuristics.len();
+    let mut cache = cache.write().await;
+    for heuristic in heuristics {
+        cache.merge_heuristic(heuristic);
+    }
+    let removed = update
+        .removed_ids
+        .iter()
+        .filter_map(|id| Uuid::parse_str(id).ok())
+        .filter(|id| cache.remove_heuristic_because(id, EvictionReason::Notify))
+        .count();
+    (merged, removed)
+}
+
+/// Follow the source instance's cache until it goes away after the snapshot
+/// (see module docs). Ends the warm-up priming step once the snapshot is in.
+pub async fn run_standby(config: StandbyConfig, cache: CacheHandle, warmup: Option<Arc<Warmup>>) {
+    let Some(address) = config.source_address.clone() else {
+        return;
+    };
+    info!(address = %address, "Warm standby: copying the cache before reporting ready");
+    let mut synced = false;
+    let mut backoff = RETRY_INITIAL;
+    loop {
+        let result = follow(&address, &config, &cache, warmup.as_deref(), &mut synced).await;
+        match (result, synced) {
+            (Ok(()), true) => {
+                info!(address = %address, "Standby source closed the cache stream; serving on our own");
+                return;
+            }
+            (Err(e), true) => {
+                info!(address = %address, error = %e, "Lost the standby source; serving on our own");
+                return;
+            }
+            (Ok(()), false) => warn!(address = %address, "Standby source closed the stream before the snapshot completed; retrying"),
+            (Err(e), false) => warn!(address = %address, error = %e, "Standby source unreachable; retrying"),
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(RETRY_MAX);
+    }
+}
+
+async fn follow(
+    address: &str,
+    config: &StandbyConfig,
+    cache: &CacheHandle,
+    warmup: Option<&Warmup>,
+    synced: &mut bool,
+) -> Result<(), Status> {
+    let mut client = SalienceGatewayClient::connect(format!("http://{}", address))
+        .await
+        .map_err(|e| Status::unavailable(e.to_string()))?;
+    let request = StreamCacheStateRequest { sync_interval_ms: config.sync_interval_ms as i32 };
+    let mut stream = client.stream_cache_state(request).await?.into_inner();
+    while let Some(update) = stream.message().await? {
+        let complete = update.snapshot_complete;
+        let (merged, removed) = apply_cache_update(cache, update).await;
+        if complete && !*synced {
+            *synced = true;
+            let cached = cache.stats().await.heuristic_count;
+            info!(address = %address, cached, "Standby cache snapshot applied; reporting ready");
+            if let Some(warmup) = warmup {
+                warmup.end_priming(PRIMING_STEP);
+            }
+        } else if merged + removed > 0 {
+            debug!(merged, removed, "Applied cache changes from the standby source");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WarmupConfig;
+    use crate::{CacheConfig, Condition, Heuristic};
+
+    fn heuristic(name: &str) -> CachedHeuristic {
+        CachedHeuristic::from(Heuristic {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            condition: Condition::text(name),
+            condition_embedding: vec![0.5; 4],
+            confidence: 0.8,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_standby_follows_snapshot_and_changes() {
+        let source = CacheHandle::new(MemoryCache::new(CacheConfig::default()));
+        let (kept, dropped) = (heuristic("creeper"), heuristic("lava"));
+        let ids = (kept.id, dropped.id);
+        source.write().await.add_heuristic(kept);
+        source.write().await.add_heuristic(dropped);
+
+        let (tx, mut rx) = mpsc::channel(8);
+        let publisher = tokio::spawn(publish_cache_state(source.clone(), Duration::from_millis(10), tx));
+        let standby = CacheHandle::new(MemoryCache::new(CacheConfig::default()));
+        let snapshot = rx.recv().await.unwrap();
+        assert!(snapshot.snapshot_complete);
+        assert_eq!(apply_cache_update(&standby, snapshot).await, (2, 0));
+        assert!(standby.read().await.get_heuristic(&ids.0).unwrap().condition_embedding.len() == 4);
+
+        {
+            let mut source = source.write().await;
+            source.remove_heuristic(&ids.1);
+            let mut changed = source.get_heuristic(&ids.0).unwrap().clone();
+            changed.confidence = 0.95;
+            source.merge_heuristic(changed);
+        }
+        let update = rx.recv().await.unwrap();
+        assert!(!update.snapshot_complete);
+        assert_eq!(update.removed_ids, vec![ids.1.to_string()]);
+        assert_eq!(apply_cache_update(&standby, update).await, (1, 1));
+        let standby = standby.read().await;
+        assert_eq!(standby.get_heuristic(&ids.0).unwrap().confidence, 0.95);
+        assert!(standby.get_heuristic(&ids.1).is_none());
+
+        drop(rx);
+        publisher.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_completes_on_last_batch() {
+        // An empty cache still sends one (empty) complete snapshot
+        let source = CacheHandle::new(MemoryCache::new(CacheConfig::default()));
+        let (tx, mut rx) = mpsc::channel(8);
+        tokio::spawn(publish_cache_state(source, Duration::from_millis(10), tx));
+        let snapshot = rx.recv().await.unwrap();
+        assert!(snapshot.snapshot_complete && snapshot.heuristics.is_empty());
+
+        let source = CacheHandle::new(MemoryCache::new(CacheConfig { max_heuristics: BATCH_SIZE + 1, ..Default::default() }));
+        for i in 0..=BATCH_SIZE {
+            source.write().await.add_heuristic(heuristic(&format!("heuristic {}", i)));
+        }
+        let (tx, mut rx) = mpsc::channel(8);
+        tokio::spawn(publish_cache_state(source, Duration::from_millis(10), tx));
+        let first = rx.recv().await.unwrap();
+        assert_eq!((first.heuristics.len(), first.snapshot_complete), (BATCH_SIZE, false));
+        let last = rx.recv().await.unwrap();
+        assert_eq!((last.heuristics.len(), last.snapshot_complete), (1, true));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_update_is_skipped() {
+        let standby = CacheHandle::new(MemoryCache::new(CacheConfig::default()));
+        let update = CacheStateUpdate {
+            heuristics: vec![proto::Heuristic { id: "not-a-uuid".to_string(), ..Default::default() }],
+            removed_ids: vec!["not-a-uuid".to_string(), Uuid::new_v4().to_string()],
+            ..Default::default()
+        };
+        assert_eq!(apply_cache_update(&standby, update).await, (0, 0));
+        assert_eq!(standby.stats().await.heuristic_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_source_keeps_priming() {
+        let cache = CacheHandle::new(MemoryCache::new(CacheConfig::default()));
+        let warmup = Arc::new(Warmup::new(WarmupConfig { min_heuristics: 0, max_wait_ms: 0 }));
+        warmup.begin_priming(PRIMING_STEP);
+
+        // No source configured: nothing to wait for
+        run_standby(StandbyConfig::default(), cache.clone(), Some(warmup.clone())).await;
+
+        // Nothing listens on port 1: the standby retries and stays unready
+        let config = StandbyConfig { source_address: Some("127.0.0.1:1".to_string()), sync_interval_ms: 10 };
+        let following = tokio::time::timeout(Duration::from_millis(300), run_standby(config, cache, Some(warmup.clone())));
+        assert!(following.await.is_err());
+        assert_eq!(warmup.check(0).phase, format!("priming:{}", PRIMING_STEP));
+    }
+}
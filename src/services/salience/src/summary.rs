@@ -0,0 +1,224 @@
+//! Rolling salience aggregates for the dashboard.
+//!
+//! Live decisions are folded into one-minute buckets per source: event and
+//! match counts, and each dimension's sum and sampled values.
+//! `GetSalienceSummary` merges the buckets covering the last N minutes into
+//! per-source, per-dimension figures (mean, p95, match rate), so trends like
+//! "threat is rising for the game sensor" show without reading raw logs:
+//! - Buckets older than the retention are dropped as new ones open
+//! - A bucket keeps at most `MAX_BUCKET_SAMPLES` values per dimension for the
+//!   p95; means use every value
+//! - "threat" is reported alongside the vector dimensions
+//! - Failed evaluations aren't counted (their salience is a fallback, not a reading)
+//!
+//! Configuration via environment variables (see `SalienceConfig`):
+//!   SALIENCE_SUMMARY_RETENTION_MINUTES: Longest window kept (default: 60, 0 = disabled)
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use crate::postprocess::THREAT_DIMENSION;
+use crate::proto::SalienceResult;
+
+/// Values kept per dimension per bucket for the p95.
+const MAX_BUCKET_SAMPLES: usize = 512;
+
+const BUCKET_MS: i64 = 60_000;
+
+#[derive(Debug, Default)]
+struct DimensionAccumulator {
+    sum: f64,
+    count: u64,
+    samples: Vec<f32>,
+}
+
+impl DimensionAccumulator {
+    fn add(&mut self, value: f32) {
+        self.sum += value as f64;
+        self.count += 1;
+        if self.samples.len() < MAX_BUCKET_SAMPLES {
+            self.samples.push(value);
+        }
+    }
+
+    /// Fold in another bucket's values (samples aren't capped when merging).
+    fn merge(&mut self, other: &DimensionAccumulator) {
+        self.sum += other.sum;
+        self.count += other.count;
+        self.samples.extend_from_slice(&other.samples);
+    }
+}
+
+#[derive(Debug, Default)]
+struct SourceBucket {
+    events: u64,
+    matched: u64,
+    dimensions: HashMap<String, DimensionAccumulator>,
+}
+
+/// Aggregate for one dimension over the window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimensionStats {
+    pub dimension: String,
+    pub mean: f32,
+    pub p95: f32,
+}
+
+/// Aggregates for one source over the window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceStats {
+    pub source: String,
+    pub events: u64,
+    pub match_rate: f32,
+    /// Sorted by dimension
+    pub dimensions: Vec<DimensionStats>,
+}
+
+/// One-minute buckets of live decisions, keyed by minute then source.
+pub struct SalienceSummary {
+    retention_minutes: i64,
+    buckets: Mutex<BTreeMap<i64, HashMap<String, SourceBucket>>>,
+}
+
+impl SalienceSummary {
+    /// `retention_minutes` = 0 records nothing.
+    pub fn new(retention_minutes: i64) -> Self {
+        Self { retention_minutes: retention_minutes.max(0), buckets: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// Longest window that can be summarized.
+    pub fn retention_minutes(&self) -> i64 {
+        self.retention_minutes
+    }
+
+    /// Fold one decision into the current minute's bucket.
+    pub fn record(&self, source: &str, salience: &SalienceResult, matched: bool) {
+        self.record_at(crate::current_time_ms(), source, salience, matched);
+    }
+
+    fn record_at(&self, now_ms: i64, source: &str, salience: &SalienceResult, matched: bool) {
+        if self.retention_minutes == 0 {
+            return;
+        }
+        let minute = now_ms.div_euclid(BUCKET_MS);
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket = buckets.entry(minute).or_default().entry(source.to_string()).or_default();
+        bucket.events += 1;
+        bucket.matched += matched as u64;
+        bucket.dimensions.entry(THREAT_DIMENSION.to_string()).or_default().add(salience.threat);
+        for (dimension, value) in &salience.vector {
+            bucket.dimensions.entry(dimension.clone()).or_default().add(*value);
+        }
+
+        // Buckets wholly outside the retention can't be asked for again
+        let oldest = minute - self.retention_minutes + 1;
+        while buckets.first_key_value().is_some_and(|(m, _)| *m < oldest) {
+            buckets.pop_first();
+        }
+    }
+
+    /// Per-source aggregates over the last `window_minutes` (including the
+    /// current, partial minute), sorted by source.
+    pub fn summarize(&self, window_minutes: i64, source_filter: Option<&str>) -> Vec<SourceStats> {
+        self.summarize_at(crate::current_time_ms(), window_minutes, source_filter)
+    }
+
+    fn summarize_at(&self, now_ms: i64, window_minutes: i64, source_filter: Option<&str>) -> Vec<SourceStats> {
+        let current = now_ms.div_euclid(BUCKET_MS);
+        let window = window_minutes.clamp(0, self.retention_minutes);
+        if window == 0 {
+            return Vec::new();
+        }
+        let buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut merged: BTreeMap<&str, SourceBucket> = BTreeMap::new();
+        for (_, sources) in buckets.range(current - window + 1..=current) {
+            for (source, bucket) in sources {
+                if source_filter.is_some_and(|f| f != source) {
+                    continue;
+                }
+                let total = merged.entry(source.as_str()).or_default();
+                total.events += bucket.events;
+                total.matched += bucket.matched;
+                for (dimension, acc) in &bucket.dimensions {
+                    total.dimensions.entry(dimension.clone()).or_default().merge(acc);
+                }
+            }
+        }
+
+        merged
+            .into_iter()
+            .map(|(source, total)| {
+                let mut dimensions: Vec<DimensionStats> = total
+                    .dimensions
+                    .into_iter()
+                    .map(|(dimension, mut acc)| DimensionStats {
+                        dimension,
+                        mean: (acc.sum / acc.count.max(1) as f64) as f32,
+                        p95: p95(&mut acc.samples),
+                    })
+                    .collect();
+                dimensions.sort_by(|a, b| a.dimension.cmp(&b.dimension));
+                SourceStats {
+                    source: source.to_string(),
+                    events: total.events,
+                    match_rate: total.matched as f32 / total.events.max(1) as f32,
+                    dimensions,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Nearest-rank 95th percentile (0 if empty).
+fn p95(samples: &mut [f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    samples.sort_by(f32::total_cmp);
+    let rank = ((0.95 * samples.len() as f64).ceil() as usize).clamp(1, samples.len());
+    samples[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn salience(threat: f32, novelty: f32) -> SalienceResult {
+        SalienceResult {
+            threat,
+            vector: HashMap::from([("novelty".to_string(), novelty)]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_summary_aggregates_window_per_source() {
+        let summary = SalienceSummary::new(10);
+        let now = 100 * BUCKET_MS;
+        // An hour ago: outside any window
+        summary.record_at(now - 60 * BUCKET_MS, "game", &salience(1.0, 1.0), true);
+        for i in 0..20 {
+            summary.record_at(now - BUCKET_MS, "game", &salience(i as f32 / 20.0, 0.2), i % 2 == 0);
+        }
+        summary.record_at(now, "chat", &salience(0.0, 0.6), false);
+
+        let stats = summary.summarize_at(now, 5, None);
+        assert_eq!(stats.iter().map(|s| s.source.as_str()).collect::<Vec<_>>(), vec!["chat", "game"]);
+        let game = &stats[1];
+        assert_eq!(game.events, 20);
+        assert!((game.match_rate - 0.5).abs() < 0.001);
+        assert_eq!(game.dimensions.iter().map(|d| d.dimension.as_str()).collect::<Vec<_>>(), vec!["novelty", "threat"]);
+        let threat = &game.dimensions[1];
+        assert!((threat.mean - 0.475).abs() < 0.001);
+        assert!((threat.p95 - 0.9).abs() < 0.001);
+
+        // The current minute only, and a source filter
+        assert_eq!(summary.summarize_at(now, 1, None).len(), 1);
+        assert_eq!(summary.summarize_at(now, 5, Some("game")).len(), 1);
+
+        let disabled = SalienceSummary::new(0);
+        disabled.record_at(now, "game", &salience(1.0, 1.0), true);
+        assert!(disabled.summarize_at(now, 5, None).is_empty());
+    }
+}
@@ -0,0 +1,77 @@
+//! What's running: build and version information.
+//!
+//! Support tickets and multi-agent workspaces need to say exactly which
+//! build they talk to. The GetVersion RPC (and `memory-fast-path --version`)
+//! report:
+//! - The crate version and the git commit it was built from (GLADYS_GIT_COMMIT
+//!   at build time where there's no checkout, e.g. Docker builds; else
+//!   "unknown")
+//! - When it was built: SOURCE_DATE_EPOCH for reproducible builds, else when
+//!   the build script last ran
+//! - The cargo features compiled in
+//! - The proto contract: its schema version and the fingerprint of the
+//!   descriptors compiled in (see schema module)
+//! - The embedding model the cache expects (over RPC only; see
+//!   embedding_model module)
+
+use crate::schema::{PROTO_FINGERPRINT, SCHEMA_VERSION};
+
+/// Crate version.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Commit this binary was built from.
+pub const GIT_COMMIT: &str = env!("GLADYS_GIT_COMMIT");
+
+/// Build time, in seconds since the epoch.
+pub const BUILD_TIMESTAMP: &str = env!("GLADYS_BUILD_TIMESTAMP");
+
+/// Optional features compiled in.
+pub fn features() -> Vec<&'static str> {
+    [
+        ("word-overlap", cfg!(feature = "word-overlap")),
+        ("prometheus", cfg!(feature = "prometheus")),
+        ("language", cfg!(feature = "language")),
+        ("nats", cfg!(feature = "nats")),
+        ("mqtt", cfg!(feature = "mqtt")),
+        ("ws", cfg!(feature = "ws")),
+        ("fault-injection", cfg!(feature = "fault-injection")),
+        ("sqlite", cfg!(feature = "sqlite")),
+        ("postgres", cfg!(feature = "postgres")),
+        ("python-bindings", cfg!(feature = "python-bindings")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
+pub fn build_timestamp() -> i64 {
+    BUILD_TIMESTAMP.parse().unwrap_or(0)
+}
+
+/// One line for `--version`.
+pub fn version_line() -> String {
+    format!(
+        "memory-fast-path {} (commit {}, built {}, features [{}], schema {}, proto {})",
+        VERSION,
+        GIT_COMMIT,
+        build_timestamp(),
+        features().join(","),
+        SCHEMA_VERSION,
+        PROTO_FINGERPRINT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_line() {
+        assert!(!GIT_COMMIT.is_empty());
+        assert!(build_timestamp() > 0);
+        let line = version_line();
+        assert!(line.starts_with(&format!("memory-fast-path {} (commit ", VERSION)));
+        assert!(line.ends_with(&format!("proto {})", PROTO_FINGERPRINT)));
+        assert_eq!(features().contains(&"language"), cfg!(feature = "language"));
+    }
+}
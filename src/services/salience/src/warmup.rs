@@ -0,0 +1,142 @@
+//! Cache warm-up tracking and readiness gating.
+//!
+//! A replica that takes traffic before its cache is primed scores most events
+//! at baseline. With a warm-up criterion set, `GetHealth` and
+//! `GetHealthDetails` report UNHEALTHY until it is met, so orchestrators and
+//! load balancers hold traffic off:
+//! - Startup priming (the seed file, a standby's initial sync) runs in the
+//!   background and every step must finish
+//! - At least `min_heuristics` heuristics must be cached (from seeding, the
+//!   refresh loop, PrefetchHeuristics, or live misses)
+//! - After `max_wait_ms` the replica reports ready anyway, so an empty store
+//!   can't hold it out forever
+//!
+//! Readiness latches: evictions after warm-up don't flip it back. Progress
+//! (phase, heuristics cached / target, elapsed) is in `GetHealthDetails`.
+//!
+//! Configuration via environment variables (see `WarmupConfig`):
+//!   WARMUP_MIN_HEURISTICS: Cached heuristics required for readiness (default: 0 = no gating)
+//!   WARMUP_MAX_WAIT_MS: Report ready after this long regardless (default: 60000, 0 = no limit)
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use tracing::{info, warn};
+
+use crate::config::WarmupConfig;
+
+/// Warm-up progress at one readiness check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarmupProgress {
+    pub ready: bool,
+    /// "priming:<step>", "waiting", "ready" or "timed_out"
+    pub phase: String,
+    /// Heuristics cached / required
+    pub loaded: usize,
+    pub target: usize,
+    pub elapsed_ms: u64,
+}
+
+impl WarmupProgress {
+    /// Summary key-value pairs for `GetHealthDetails`.
+    pub fn health_details(&self) -> HashMap<String, String> {
+        HashMap::from([
+            ("warmup.ready".to_string(), self.ready.to_string()),
+            ("warmup.phase".to_string(), self.phase.clone()),
+            ("warmup.loaded".to_string(), self.loaded.to_string()),
+            ("warmup.target".to_string(), self.target.to_string()),
+            ("warmup.elapsed_ms".to_string(), self.elapsed_ms.to_string()),
+        ])
+    }
+
+    /// Reason reported by `GetHealth` while not ready.
+    pub fn message(&self) -> String {
+        format!("warming up ({}): {}/{} heuristics cached", self.phase, self.loaded, self.target)
+    }
+}
+
+#[derive(Debug, Default)]
+struct WarmupState {
+    /// Startup priming steps in progress
+    priming: Vec<String>,
+    /// Phase readiness latched in ("ready" or "timed_out")
+    settled: Option<&'static str>,
+}
+
+/// Warm-up phase tracker shared by startup priming and the health checks.
+pub struct Warmup {
+    config: WarmupConfig,
+    started: Instant,
+    state: Mutex<WarmupState>,
+}
+
+impl Warmup {
+    pub fn new(config: WarmupConfig) -> Self {
+        Self { config, started: Instant::now(), state: Mutex::new(WarmupState::default()) }
+    }
+
+    /// A startup priming step (e.g., "seed") started; readiness waits for it.
+    pub fn begin_priming(&self, step: &str) {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).priming.push(step.to_string());
+    }
+
+    /// The priming step finished (successfully or not).
+    pub fn end_priming(&self, step: &str) {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).priming.retain(|s| s != step);
+    }
+
+    /// Check readiness against the number of cached heuristics.
+    pub fn check(&self, cached_heuristics: usize) -> WarmupProgress {
+        let elapsed_ms = self.started.elapsed().as_millis() as u64;
+        let target = self.config.min_heuristics;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.settled.is_none() {
+            if state.priming.is_empty() && cached_heuristics >= target {
+                info!(cached_heuristics, elapsed_ms, "Cache warm-up complete; reporting ready");
+                state.settled = Some("ready");
+            } else if self.config.max_wait_ms > 0 && elapsed_ms >= self.config.max_wait_ms {
+                warn!(cached_heuristics, target, elapsed_ms, "Cache warm-up timed out; reporting ready anyway");
+                state.settled = Some("timed_out");
+            }
+        }
+        let phase = match (&state.settled, state.priming.first()) {
+            (Some(settled), _) => settled.to_string(),
+            (None, Some(step)) => format!("priming:{}", step),
+            (None, None) => "waiting".to_string(),
+        };
+        WarmupProgress { ready: state.settled.is_some(), phase, loaded: cached_heuristics, target, elapsed_ms }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warmup_waits_for_priming_and_target() {
+        let warmup = Warmup::new(WarmupConfig { min_heuristics: 3, max_wait_ms: 0 });
+        warmup.begin_priming("seed");
+        warmup.begin_priming("standby");
+        let progress = warmup.check(5);
+        assert!(!progress.ready);
+        assert_eq!(progress.phase, "priming:seed");
+
+        warmup.end_priming("seed");
+        assert_eq!(warmup.check(5).phase, "priming:standby");
+        warmup.end_priming("standby");
+        let progress = warmup.check(2);
+        assert_eq!((progress.ready, progress.phase.as_str(), progress.loaded, progress.target), (false, "waiting", 2, 3));
+        assert_eq!(progress.message(), "warming up (waiting): 2/3 heuristics cached");
+
+        assert!(warmup.check(3).ready);
+        // Latched: evictions afterwards don't make the replica unready
+        assert_eq!(warmup.check(0).phase, "ready");
+
+        let impatient = Warmup::new(WarmupConfig { min_heuristics: 100, max_wait_ms: 1 });
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let progress = impatient.check(0);
+        assert!(progress.ready);
+        assert_eq!(progress.health_details()["warmup.phase"], "timed_out");
+    }
+}
@@ -0,0 +1,182 @@
+//! Legacy word-overlap scorer.
+//!
+//! Matches event text against cached heuristic condition text by shared
+//! words, with no embedding model or storage round-trip. Useful as a
+//! zero-dependency fallback when the embedding path is unavailable.
+//!
+//! Only heuristics already in the L0 cache are considered, so pair it with
+//! the background refresh loop (REFRESH_INTERVAL_MS) to keep the cache populated.
+//!
+//! Enabled by the `word-overlap` feature (on by default); select it with
+//! SALIENCE_SCORER=word_overlap.
+
+use std::collections::HashSet;
+
+use crate::metrics::StageTimings;
+use crate::{CacheHandle, EventSignals, MatchMethod, SalienceScorer, ScoreOptions, ScoreThresholds, ScoredMatch, ScoringError};
+
+/// Words shorter than this carry too little signal to count as overlap.
+const MIN_WORD_LEN: usize = 3;
+
+/// Scores events by the fraction of a heuristic's condition words they contain.
+pub struct WordOverlapScorer {
+    cache: CacheHandle,
+    /// Fraction of condition words that must appear in the event (0.0-1.0)
+    min_overlap_ratio: f32,
+    /// Minimum number of shared words, regardless of ratio
+    min_words: usize,
+    min_confidence: f32,
+}
+
+impl WordOverlapScorer {
+    pub fn new(
+        cache: CacheHandle,
+        min_overlap_ratio: f32,
+        min_words: usize,
+        min_confidence: f32,
+    ) -> Self {
+        Self { cache, min_overlap_ratio, min_words, min_confidence }
+    }
+
+    /// Fraction of `condition` words present in `event`, if at least `min_words` are shared.
+    fn overlap(&self, event_words: &HashSet<String>, condition: &str) -> Option<f32> {
+        let condition_words = words(condition);
+        if condition_words.is_empty() {
+            return None;
+        }
+        let shared = condition_words.intersection(event_words).count();
+        let ratio = shared as f32 / condition_words.len() as f32;
+        (shared >= self.min_words && ratio >= self.min_overlap_ratio).then_some(ratio)
+    }
+}
+
+/// Lowercased alphanumeric words of at least `MIN_WORD_LEN` characters.
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= MIN_WORD_LEN)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[tonic::async_trait]
+impl SalienceScorer for WordOverlapScorer {
+    async fn score(
+        &self,
+        event_text: &str,
+        source: &str,
+        trace_id: Option<&str>,
+    ) -> Result<Vec<ScoredMatch>, ScoringError> {
+        let thresholds = ScoreThresholds {
+            min_similarity: self.min_overlap_ratio,
+            min_confidence: self.min_confidence,
+        };
+        let mut timings = StageTimings::default();
+        let options = ScoreOptions { thresholds, trace_id, timings: &mut timings };
+        Ok(self.score_with(event_text, source, options).await?.0)
+    }
+
+    /// Per-request `min_similarity` is an embedding threshold and doesn't
+    /// apply here; only `min_confidence` is honored.
+    async fn score_with(
+        &self,
+        event_text: &str,
+        source: &str,
+        options: ScoreOptions<'_>,
+    ) -> Result<(Vec<ScoredMatch>, EventSignals), ScoringError> {
+        let thresholds = options.thresholds;
+        let event_words = words(event_text);
+        if event_words.is_empty() {
+            return Ok((vec![], EventSignals::default()));
+        }
+
+        let source_filter = (!source.is_empty()).then_some(source);
+        let cache = self.cache.read().await;
+        let mut matches: Vec<ScoredMatch> = cache
+            .get_heuristics_by_confidence(thresholds.min_confidence)
+            .into_iter()
+            .filter(|h| h.matches_source(source_filter))
+            .filter_map(|h| self.overlap(&event_words, &h.condition.text).map(|ratio| ScoredMatch::new(h, ratio).with_method(MatchMethod::WordOverlap)))
+            .collect();
+
+        // Best overlap first; confidence breaks ties
+        matches.sort_by(|a, b| {
+            (b.similarity, b.confidence)
+                .partial_cmp(&(a.similarity, a.confidence))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        matches.truncate(5);
+        Ok((matches, EventSignals::default()))
+    }
+
+    fn config(&self) -> serde_json::Value {
+        serde_json::json!({
+            "scorer": "word_overlap",
+            "min_overlap_ratio": self.min_overlap_ratio,
+            "min_words": self.min_words,
+            "min_confidence": self.min_confidence,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CacheConfig, CachedHeuristic, Condition, MemoryCache};
+    use uuid::Uuid;
+
+    fn cache_with(conditions: &[(&str, f32)]) -> (CacheHandle, Vec<Uuid>) {
+        let mut cache = MemoryCache::new(CacheConfig::default());
+        let mut ids = Vec::new();
+        for (text, confidence) in conditions {
+            let id = Uuid::new_v4();
+            cache.add_heuristic(CachedHeuristic {
+                id,
+                name: text.to_string(),
+                condition: Condition::text(*text),
+                effects: serde_json::json!({"salience": {"threat": 0.7}}).into(),
+                confidence: *confidence,
+                origin: String::new(),
+                source: String::new(),
+                condition_embedding: Default::default(),
+                last_accessed_ms: 0,
+                cached_at_ms: 0,
+                hit_count: 0,
+                last_hit_ms: 0,
+                cooldown_until_ms: 0,
+                embedding_model: String::new(),
+            });
+            ids.push(id);
+        }
+        (CacheHandle::new(cache), ids)
+    }
+
+    #[tokio::test]
+    async fn test_word_overlap_matches_best_first() {
+        let (cache, ids) = cache_with(&[
+            ("creeper approaching player", 0.9),
+            ("creeper explosion nearby", 0.9),
+            ("sunrise over the village", 0.9),
+        ]);
+        let scorer = WordOverlapScorer::new(cache, 0.5, 2, 0.5);
+
+        let matches = scorer.score("A creeper is approaching the player!", "game", None).await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].heuristic_id, ids[0].to_string());
+        assert!((matches[0].similarity - 1.0).abs() < 0.001);
+        assert!(matches[0].salience_boost.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_word_overlap_thresholds() {
+        let (cache, _) = cache_with(&[("creeper approaching player", 0.3)]);
+
+        // Below min confidence
+        let scorer = WordOverlapScorer::new(cache.clone(), 0.5, 2, 0.5);
+        assert!(scorer.score("creeper approaching player", "game", None).await.unwrap().is_empty());
+
+        // One shared word is below min_words even though the ratio would pass
+        let scorer = WordOverlapScorer::new(cache, 0.3, 2, 0.0);
+        assert!(scorer.score("creeper", "game", None).await.unwrap().is_empty());
+        assert_eq!(scorer.score("creeper player", "game", None).await.unwrap().len(), 1);
+    }
+}
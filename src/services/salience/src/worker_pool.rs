@@ -0,0 +1,172 @@
+//! Dedicated worker threads for CPU-heavy scoring work.
+//!
+//! Cache similarity scans are pure CPU and grow with the heuristic count; run
+//! on the tokio runtime they hold a reactor thread for the whole scan and
+//! delay unrelated I/O. `WorkerPool` runs such jobs on its own threads
+//! instead, fed by a bounded queue: when the queue is full, submitters wait
+//! (asynchronously) rather than piling up unbounded work. Queue depth is
+//! exported as the `gladys_salience_scoring_queue_depth` gauge.
+//!
+//! Configuration via environment variables:
+//!   SALIENCE_SCORING_WORKERS: Worker threads (default: 0 = scan inline on the runtime)
+//!   SALIENCE_SCORING_QUEUE: Jobs that can wait for a worker (default: 256)
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, info};
+
+use crate::config::SalienceConfig;
+use crate::metrics::{scoring_queue_dequeued, scoring_queue_enqueued};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Errors submitting work to the pool.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum PoolError {
+    #[error("Worker pool is shut down")]
+    Closed,
+    #[error("Worker pool job panicked")]
+    Panicked,
+}
+
+/// A fixed set of worker threads consuming a bounded job queue.
+///
+/// Threads exit once the pool is dropped and the queue drains.
+pub struct WorkerPool {
+    sender: mpsc::Sender<Job>,
+    workers: usize,
+}
+
+impl WorkerPool {
+    pub fn new(workers: usize, queue_size: usize) -> Self {
+        let workers = workers.max(1);
+        let (sender, receiver) = mpsc::channel::<Job>(queue_size.max(1));
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..workers {
+            let receiver = receiver.clone();
+            let spawned = thread::Builder::new().name(format!("salience-worker-{}", i)).spawn(move || loop {
+                // Only one idle worker waits on the queue at a time; the rest wait on the lock
+                let job = receiver.lock().unwrap_or_else(|e| e.into_inner()).blocking_recv();
+                let Some(job) = job else { break };
+                scoring_queue_dequeued();
+                job();
+            });
+            if let Err(e) = spawned {
+                error!(worker = i, error = %e, "Failed to spawn scoring worker");
+            }
+        }
+        info!(workers, queue_size, "Scoring worker pool started");
+        Self { sender, workers }
+    }
+
+    /// The scoring pool configured in `config`, if any.
+    pub fn for_scoring(config: &SalienceConfig) -> Option<Self> {
+        (config.scoring_workers > 0).then(|| Self::new(config.scoring_workers, config.scoring_queue_size))
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// Run `job` on a worker thread, waiting for queue space if it's full.
+    pub async fn run<F, R>(&self, job: F) -> Result<R, PoolError>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (reply, result) = oneshot::channel();
+        let job: Job = Box::new(move || {
+            // Keep the worker alive if the job panics; the caller sees PoolError::Panicked
+            if let Ok(value) = catch_unwind(AssertUnwindSafe(job)) {
+                let _ = reply.send(value);
+            }
+        });
+        scoring_queue_enqueued();
+        if self.sender.send(job).await.is_err() {
+            scoring_queue_dequeued();
+            return Err(PoolError::Closed);
+        }
+        result.await.map_err(|_| PoolError::Panicked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_jobs_run_off_the_runtime() {
+        let pool = WorkerPool::new(2, 1);
+        let name = pool.run(|| thread::current().name().map(str::to_string)).await.unwrap();
+        assert!(name.unwrap().starts_with("salience-worker-"));
+
+        // More jobs than workers + queue slots: submitters wait for space
+        let pool = Arc::new(pool);
+        let tasks: Vec<_> = (0..8u64)
+            .map(|i| {
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    pool.run(move || {
+                        thread::sleep(Duration::from_millis(5));
+                        i * 2
+                    })
+                    .await
+                })
+            })
+            .collect();
+        for (i, task) in tasks.into_iter().enumerate() {
+            assert_eq!(task.await.unwrap().unwrap(), i as u64 * 2);
+        }
+
+        // A panicking job doesn't take its worker down
+        let pool = WorkerPool::new(1, 1);
+        assert!(matches!(pool.run(|| panic!("boom")).await, Err(PoolError::Panicked)));
+        assert_eq!(pool.run(|| 7).await.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_makes_submitters_wait() {
+        let pool = Arc::new(WorkerPool::new(1, 1));
+        let (started_tx, started) = oneshot::channel();
+        let (release, release_rx) = std::sync::mpsc::channel::<()>();
+
+        // Occupy the only worker, then the only queue slot
+        let busy = {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                pool.run(move || {
+                    let _ = started_tx.send(());
+                    let _ = release_rx.recv();
+                    1
+                })
+                .await
+            })
+        };
+        started.await.unwrap();
+        let queued = {
+            let pool = pool.clone();
+            tokio::spawn(async move { pool.run(|| 2).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        // A third submitter can't get in while both are taken
+        assert!(tokio::time::timeout(Duration::from_millis(50), pool.run(|| 3)).await.is_err());
+
+        release.send(()).unwrap();
+        assert_eq!(busy.await.unwrap().unwrap(), 1);
+        assert_eq!(queued.await.unwrap().unwrap(), 2);
+        assert_eq!(pool.run(|| 3).await.unwrap(), 3);
+    }
+
+    #[test]
+    fn test_for_scoring() {
+        let inline = SalienceConfig { scoring_workers: 0, ..SalienceConfig::default() };
+        assert!(WorkerPool::for_scoring(&inline).is_none());
+        let pooled = SalienceConfig { scoring_workers: 3, scoring_queue_size: 0, ..SalienceConfig::default() };
+        assert_eq!(WorkerPool::for_scoring(&pooled).unwrap().workers(), 3);
+    }
+}
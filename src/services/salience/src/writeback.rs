@@ -0,0 +1,236 @@
+//! Decision write-back to stored events.
+//!
+//! The Executive reads the fast path's decision off the stored event
+//! (`matched_heuristic_id`, salience vector, composite score), but evaluation
+//! never touched storage. With write-back enabled, each live decision is
+//! queued and a background task records them via `UpdateEventSalience`:
+//! - Off the request path: queuing never blocks; a full queue drops the update
+//! - Batched: a batch goes out when full, or once its oldest update has waited
+//!   the flush interval
+//! - Best effort: a failed batch is logged and counted, not requeued (the
+//!   storage client already retries transient errors)
+//!
+//! Replays, failed or degraded evaluations, and events without an ID aren't
+//! written back. Outcomes are exported as
+//! `gladys_salience_writeback_updates_total{outcome}`.
+//!
+//! Configuration via environment variables (see `WritebackConfig`):
+//!   OUTCOME_WRITEBACK: Enable write-back (default: false)
+//!   OUTCOME_WRITEBACK_BATCH_SIZE: Updates per storage call (default: 50)
+//!   OUTCOME_WRITEBACK_FLUSH_MS: Longest an update waits for its batch (default: 1000)
+//!   OUTCOME_WRITEBACK_QUEUE_SIZE: Queued updates before dropping (default: 1024)
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::config::WritebackConfig;
+use crate::metrics::record_writeback;
+use crate::proto::{EvaluateSalienceResponse, EventSalienceUpdate};
+use crate::StorageBackend;
+
+/// Handle for queuing decisions to the background writer.
+#[derive(Clone)]
+pub struct OutcomeWriter {
+    tx: mpsc::Sender<EventSalienceUpdate>,
+}
+
+impl OutcomeWriter {
+    /// Start the background writer; it stops once every handle is dropped
+    /// (after flushing what's queued).
+    pub fn spawn(storage: Arc<dyn StorageBackend>, config: &WritebackConfig) -> Self {
+        let (tx, rx) = mpsc::channel(config.queue_capacity.max(1));
+        let flush_interval = Duration::from_millis(config.flush_interval_ms);
+        tokio::spawn(run_writer(rx, storage, config.batch_size.max(1), flush_interval));
+        Self { tx }
+    }
+
+    /// Queue a decision without waiting (dropped if the queue is full).
+    pub fn record(&self, update: EventSalienceUpdate) {
+        if self.tx.try_send(update).is_err() {
+            record_writeback("dropped", 1);
+            debug!("Write-back queue full; dropping decision");
+        }
+    }
+}
+
+/// The update to record for a decision (None if it shouldn't be written back).
+pub fn decision_update(event_id: &str, response: &EvaluateSalienceResponse) -> Option<EventSalienceUpdate> {
+    if event_id.is_empty() || !response.error.is_empty() || response.degraded {
+        return None;
+    }
+    Some(EventSalienceUpdate {
+        event_id: event_id.to_string(),
+        salience: response.salience.clone(),
+        matched_heuristic_id: response.matched_heuristic_id.clone(),
+        composite_score: response.composite_score,
+    })
+}
+
+async fn run_writer(
+    mut rx: mpsc::Receiver<EventSalienceUpdate>,
+    storage: Arc<dyn StorageBackend>,
+    batch_size: usize,
+    flush_interval: Duration,
+) {
+    while let Some(first) = rx.recv().await {
+        let mut batch = vec![first];
+        let deadline = tokio::time::Instant::now() + flush_interval;
+        while batch.len() < batch_size {
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(update)) => batch.push(update),
+                // Flush interval elapsed, or every handle dropped
+                _ => break,
+            }
+        }
+        flush(storage.as_ref(), batch).await;
+    }
+}
+
+async fn flush(storage: &dyn StorageBackend, batch: Vec<EventSalienceUpdate>) {
+    let sent = batch.len();
+    match storage.update_event_salience(batch, None).await {
+        Ok(updated) => {
+            record_writeback("written", updated as u64);
+            debug!(sent, updated, "Decisions written back");
+        }
+        Err(e) => {
+            record_writeback("failed", sent as u64);
+            warn!(sent, error = %e, "Decision write-back failed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    use crate::metrics::writeback_updates;
+
+    use crate::{CachedHeuristic, StorageError};
+
+    #[derive(Default)]
+    struct RecordingStorage {
+        /// Every batch sent, including failed ones
+        batches: Mutex<Vec<Vec<String>>>,
+        /// Batches to fail before succeeding
+        failures: AtomicUsize,
+    }
+
+    fn writeback_count(outcome: &str) -> u64 {
+        writeback_updates().into_iter().find(|(o, _)| *o == outcome).map_or(0, |(_, count)| count)
+    }
+
+    #[tonic::async_trait]
+    impl StorageBackend for RecordingStorage {
+        async fn query_matching_heuristics(
+            &self,
+            _event_text: &str,
+            _min_confidence: f32,
+            _limit: i32,
+            _source_filter: Option<&str>,
+            _trace_id: Option<&str>,
+        ) -> Result<Vec<CachedHeuristic>, StorageError> {
+            Ok(Vec::new())
+        }
+
+        async fn generate_embedding(&self, _text: &str, _trace_id: Option<&str>) -> Result<Vec<f32>, StorageError> {
+            Ok(Vec::new())
+        }
+
+        async fn update_event_salience(
+            &self,
+            updates: Vec<EventSalienceUpdate>,
+            _trace_id: Option<&str>,
+        ) -> Result<usize, StorageError> {
+            let ids: Vec<String> = updates.into_iter().map(|u| u.event_id).collect();
+            let updated = ids.len();
+            self.batches.lock().unwrap().push(ids);
+            if self.failures.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)).is_ok() {
+                return Err(StorageError::Unavailable("storage restarting".to_string()));
+            }
+            Ok(updated)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decisions_are_batched_and_flushed() {
+        let storage = Arc::new(RecordingStorage::default());
+        let config = WritebackConfig { enabled: true, batch_size: 2, flush_interval_ms: 50, queue_capacity: 16 };
+        let writer = OutcomeWriter::spawn(storage.clone(), &config);
+
+        let response = EvaluateSalienceResponse { matched_heuristic_id: "h1".to_string(), ..Default::default() };
+        for id in ["e1", "e2", "e3"] {
+            writer.record(decision_update(id, &response).unwrap());
+        }
+        assert!(decision_update("", &response).is_none());
+        let failed = EvaluateSalienceResponse { error: "STORAGE_TIMEOUT: slow".to_string(), ..Default::default() };
+        assert!(decision_update("e4", &failed).is_none());
+
+        // The full batch goes out at once; the remainder after the flush interval
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let batches = storage.batches.lock().unwrap().clone();
+        assert_eq!(batches, vec![vec!["e1", "e2"], vec!["e3"]]);
+    }
+
+    #[test]
+    fn test_failed_and_degraded_decisions_are_skipped() {
+        let degraded = EvaluateSalienceResponse { degraded: true, ..Default::default() };
+        assert!(decision_update("e1", &degraded).is_none());
+        let update = decision_update("e2", &EvaluateSalienceResponse { composite_score: 0.4, ..Default::default() }).unwrap();
+        assert_eq!((update.event_id.as_str(), update.composite_score), ("e2", 0.4));
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_without_blocking() {
+        let storage = Arc::new(RecordingStorage::default());
+        let config = WritebackConfig { enabled: true, batch_size: 10, flush_interval_ms: 20, queue_capacity: 2 };
+        let writer = OutcomeWriter::spawn(storage.clone(), &config);
+        let dropped_before = writeback_count("dropped");
+
+        // The writer can't run until we yield, so the third and fourth don't fit
+        let response = EvaluateSalienceResponse::default();
+        for id in ["e1", "e2", "e3", "e4"] {
+            writer.record(decision_update(id, &response).unwrap());
+        }
+        // Other tests share the process-wide counter, so only check the lower bound
+        assert!(writeback_count("dropped") >= dropped_before + 2);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(storage.batches.lock().unwrap().clone(), vec![vec!["e1", "e2"]]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch_is_not_requeued() {
+        let storage = Arc::new(RecordingStorage { failures: AtomicUsize::new(1), ..Default::default() });
+        let config = WritebackConfig { enabled: true, batch_size: 1, flush_interval_ms: 20, queue_capacity: 16 };
+        let writer = OutcomeWriter::spawn(storage.clone(), &config);
+        let failed_before = writeback_count("failed");
+
+        let response = EvaluateSalienceResponse::default();
+        for id in ["e1", "e2"] {
+            writer.record(decision_update(id, &response).unwrap());
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        // e1 failed once and was given up on; e2 still went out
+        assert_eq!(storage.batches.lock().unwrap().clone(), vec![vec!["e1"], vec!["e2"]]);
+        assert!(writeback_count("failed") > failed_before);
+    }
+
+    #[tokio::test]
+    async fn test_dropping_writer_flushes_queue() {
+        let storage = Arc::new(RecordingStorage::default());
+        let config = WritebackConfig { enabled: true, batch_size: 10, flush_interval_ms: 60_000, queue_capacity: 16 };
+        let writer = OutcomeWriter::spawn(storage.clone(), &config);
+
+        writer.record(decision_update("e1", &EvaluateSalienceResponse::default()).unwrap());
+        drop(writer);
+        // Well before the flush interval
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(storage.batches.lock().unwrap().clone(), vec![vec!["e1"]]);
+    }
+}
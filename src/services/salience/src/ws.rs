@@ -0,0 +1,358 @@
+//! WebSocket debug feed (enabled with the `ws` feature).
+//!
+//! For dashboards that can't speak gRPC-Web: with WS_PORT set, browsers can
+//! connect to `ws://host:WS_PORT/` and receive JSON text messages:
+//!
+//! - `{"type": "stats", ...}`: cache stats on connect, then whenever they
+//!   change (checked every WS_STATS_INTERVAL_MS), with `*_delta` fields
+//!   relative to the previous message.
+//! - `{"type": "decision", ...}`: every EvaluateSalience decision as it's
+//!   made, in the intake decision format plus `source`, `raw_text` and
+//!   `timestamp_ms`.
+//! - `{"type": "lagged", "skipped": n}`: the client fell behind and `n`
+//!   decisions were dropped for it.
+//!
+//! The feed is one-way; client messages other than close/ping are ignored.
+//! There is no authentication, so bind it to a trusted network.
+//!
+//! Configuration via environment variables:
+//!   WS_PORT: Port for the feed (default: 0 = disabled)
+//!   WS_STATS_INTERVAL_MS: How often stats are checked for changes (default: 1000)
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, info, warn};
+
+use crate::intake::decision_json;
+use crate::server::RecordedDecision;
+use crate::{CacheHandle, CacheStats};
+
+/// Appended to the client's key before hashing (RFC 6455).
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Largest request head or client frame accepted.
+const MAX_CLIENT_BYTES: usize = 8 * 1024;
+
+const OP_TEXT: u8 = 0x1;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+/// SHA-1 digest (only used for the handshake).
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let t = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = t;
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (out, word) in digest.chunks_mut(4).zip(h) {
+        out.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// `Sec-WebSocket-Accept` for a client's `Sec-WebSocket-Key`.
+fn accept_key(key: &str) -> String {
+    base64(&sha1(format!("{}{}", key.trim(), HANDSHAKE_GUID).as_bytes()))
+}
+
+/// Frame a server message (servers never mask).
+fn frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x80 | opcode];
+    match payload.len() {
+        len @ 0..=125 => out.push(len as u8),
+        len @ 126..=0xFFFF => {
+            out.push(126);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            out.push(127);
+            out.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    out.extend_from_slice(payload);
+    out
+}
+
+/// Read one client frame: (opcode, unmasked payload).
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<(u8, Vec<u8>)> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head).await?;
+    let len = match head[1] & 0x7F {
+        126 => reader.read_u16().await? as usize,
+        127 => reader.read_u64().await? as usize,
+        len => len as usize,
+    };
+    if len > MAX_CLIENT_BYTES {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "client frame too large"));
+    }
+    let mut mask = [0u8; 4];
+    if head[1] & 0x80 != 0 {
+        reader.read_exact(&mut mask).await?;
+    }
+    let mut payload = vec![0; len];
+    reader.read_exact(&mut payload).await?;
+    payload.iter_mut().zip(mask.iter().cycle()).for_each(|(b, m)| *b ^= m);
+    Ok((head[0] & 0x0F, payload))
+}
+
+/// Read the upgrade request and return the client's key, if it is one.
+async fn read_handshake(stream: &mut TcpStream) -> std::io::Result<Option<String>> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.ends_with(b"\r\n\r\n") {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 || head.len() + n > MAX_CLIENT_BYTES {
+            return Ok(None);
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let header = |name: &str| {
+        head.lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(n, _)| n.trim().eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.trim().to_string())
+    };
+    let upgrade = header("upgrade").is_some_and(|u| u.eq_ignore_ascii_case("websocket"));
+    Ok(header("sec-websocket-key").filter(|_| upgrade))
+}
+
+fn stats_json(stats: &CacheStats, previous: Option<&CacheStats>) -> serde_json::Value {
+    let delta = |now: u64, then: Option<u64>| now.saturating_sub(then.unwrap_or(now));
+    serde_json::json!({
+        "type": "stats",
+        "heuristic_count": stats.heuristic_count,
+        "event_count": stats.event_count,
+        "total_hits": stats.total_hits,
+        "total_misses": stats.total_misses,
+        "hits_delta": delta(stats.total_hits, previous.map(|p| p.total_hits)),
+        "misses_delta": delta(stats.total_misses, previous.map(|p| p.total_misses)),
+        "hit_rate": stats.hit_rate(),
+        "approx_memory_bytes": stats.approx_memory_bytes,
+    })
+}
+
+fn changed(stats: &CacheStats, previous: &CacheStats) -> bool {
+    stats.heuristic_count != previous.heuristic_count
+        || stats.event_count != previous.event_count
+        || stats.total_hits != previous.total_hits
+        || stats.total_misses != previous.total_misses
+}
+
+fn live_decision_json(decision: &RecordedDecision) -> serde_json::Value {
+    let mut json = decision_json(&decision.request.event_id, &decision.response);
+    json["type"] = "decision".into();
+    json["source"] = decision.request.source.clone().into();
+    json["raw_text"] = decision.request.raw_text.clone().into();
+    json["timestamp_ms"] = decision.timestamp_ms.into();
+    json
+}
+
+/// Feed one connected client until it disconnects.
+async fn serve_client(
+    mut stream: TcpStream,
+    cache: CacheHandle,
+    mut decisions: broadcast::Receiver<RecordedDecision>,
+    stats_interval: Duration,
+) -> std::io::Result<()> {
+    let Some(key) = read_handshake(&mut stream).await? else {
+        stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await?;
+        return Ok(());
+    };
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(&key)
+    );
+    stream.write_all(response.as_bytes()).await?;
+    let (mut reader, mut writer) = stream.into_split();
+
+    // Client frames are read on their own task: only pings need answering
+    let (control, mut controls) = mpsc::channel::<(u8, Vec<u8>)>(8);
+    let client = tokio::spawn(async move {
+        while let Ok((opcode, payload)) = read_frame(&mut reader).await {
+            let close = opcode == OP_CLOSE;
+            if matches!(opcode, OP_CLOSE | OP_PING) && control.send((opcode, payload)).await.is_err() || close {
+                return;
+            }
+        }
+    });
+
+    let mut previous = cache.stats().await;
+    writer.write_all(&frame(OP_TEXT, stats_json(&previous, None).to_string().as_bytes())).await?;
+    let mut ticker = tokio::time::interval(stats_interval.max(Duration::from_millis(10)));
+    let result = loop {
+        let message = tokio::select! {
+            decision = decisions.recv() => match decision {
+                Ok(decision) => live_decision_json(&decision),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => serde_json::json!({"type": "lagged", "skipped": skipped}),
+                Err(broadcast::error::RecvError::Closed) => break Ok(()),
+            },
+            _ = ticker.tick() => {
+                let stats = cache.stats().await;
+                if !changed(&stats, &previous) {
+                    continue;
+                }
+                let message = stats_json(&stats, Some(&previous));
+                previous = stats;
+                message
+            }
+            control = controls.recv() => match control {
+                Some((OP_PING, payload)) => {
+                    if let Err(e) = writer.write_all(&frame(OP_PONG, &payload)).await {
+                        break Err(e);
+                    }
+                    continue;
+                }
+                // Close (echoed) or the client went away
+                Some((_, payload)) => break writer.write_all(&frame(OP_CLOSE, &payload)).await,
+                None => break Ok(()),
+            },
+        };
+        if let Err(e) = writer.write_all(&frame(OP_TEXT, message.to_string().as_bytes())).await {
+            break Err(e);
+        }
+    };
+    client.abort();
+    result
+}
+
+/// Serve the debug feed on `addr` for as long as the process runs.
+pub async fn serve_ws(
+    addr: SocketAddr,
+    cache: CacheHandle,
+    decisions: broadcast::Sender<RecordedDecision>,
+    stats_interval: Duration,
+) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!("Serving WebSocket debug feed on {}", addr);
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let (cache, decisions) = (cache.clone(), decisions.subscribe());
+        tokio::spawn(async move {
+            debug!(peer = %peer, "WebSocket client connected");
+            if let Err(e) = serve_client(stream, cache, decisions, stats_interval).await {
+                warn!(peer = %peer, error = %e, "WebSocket client dropped");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::{EvaluateSalienceRequest, EvaluateSalienceResponse};
+    use crate::{CacheConfig, MemoryCache};
+
+    #[test]
+    fn test_handshake_accept_key() {
+        let hex: String = sha1(b"abc").iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(hex, "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(base64(b"ab"), "YWI=");
+        // Example from RFC 6455 section 1.3
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    async fn next_message(client: &mut TcpStream) -> serde_json::Value {
+        let (opcode, payload) = read_frame(client).await.unwrap();
+        assert_eq!(opcode, OP_TEXT);
+        serde_json::from_slice(&payload).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_feed_streams_stats_and_decisions() {
+        let cache = CacheHandle::new(MemoryCache::new(CacheConfig::default()));
+        let (decisions, _) = broadcast::channel(16);
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let server = tokio::spawn(serve_ws(addr, cache.clone(), decisions.clone(), Duration::from_millis(20)));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client
+            .write_all(b"GET / HTTP/1.1\r\nHost: x\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n")
+            .await
+            .unwrap();
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(client.read_u8().await.unwrap());
+        }
+        let head = String::from_utf8(head).unwrap();
+        assert!(head.starts_with("HTTP/1.1 101"));
+        assert!(head.contains("s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+
+        let stats = next_message(&mut client).await;
+        assert_eq!(stats["type"], "stats");
+        assert_eq!(stats["heuristic_count"], 0);
+
+        decisions
+            .send(RecordedDecision {
+                request: EvaluateSalienceRequest { event_id: "e-1".to_string(), source: "test".to_string(), ..Default::default() },
+                response: EvaluateSalienceResponse::default(),
+                timestamp_ms: 42,
+            })
+            .unwrap();
+        let decision = next_message(&mut client).await;
+        assert_eq!(decision["type"], "decision");
+        assert_eq!(decision["event_id"], "e-1");
+        assert_eq!(decision["timestamp_ms"], 42);
+
+        cache.write().await.record_miss();
+        let stats = next_message(&mut client).await;
+        assert_eq!(stats["misses_delta"], 1);
+
+        server.abort();
+    }
+}
@@ -0,0 +1,72 @@
+//! Golden-file regression tests for scoring decisions.
+//!
+//! Each directory under `tests/golden/` holds a scenario:
+//!   - `transcript.jsonl`: events to replay (see `gladys_memory::simulate`)
+//!   - `heuristics.json`: seed-format heuristics served by local storage
+//!   - `expected.json`: the committed timeline
+//!
+//! Runs use `LocalStorage` (no Python service) with default config, so
+//! scorer refactors must reproduce the same matches and routing; scores may
+//! drift by `DEFAULT_GOLDEN_TOLERANCE`.
+//!
+//! After an intended behavior change, regenerate and review the diff:
+//!   UPDATE_GOLDEN=1 cargo test --test golden_test
+
+use std::path::{Path, PathBuf};
+
+use gladys_memory::seed::parse_seed_file;
+use gladys_memory::simulate::{
+    diff_timelines, local_service, parse_transcript, run_simulation, TimelineEntry, DEFAULT_GOLDEN_TOLERANCE,
+};
+use gladys_memory::{CacheConfig, SalienceConfig};
+
+fn scenarios() -> Vec<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(&root)
+        .expect("tests/golden exists")
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+async fn run_scenario(dir: &Path) -> Vec<TimelineEntry> {
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap_or_else(|e| panic!("{}/{}: {}", dir.display(), name, e));
+    let heuristics = parse_seed_file(&read("heuristics.json"))
+        .unwrap()
+        .into_iter()
+        .map(|s| s.into_cached(vec![]))
+        .collect();
+    let events = parse_transcript(&read("transcript.jsonl")).unwrap();
+    let (service, cache) = local_service(heuristics, CacheConfig::default(), SalienceConfig::default());
+    run_simulation(&service, &cache, &events).await
+}
+
+#[tokio::test]
+async fn test_golden_timelines() {
+    let update = std::env::var("UPDATE_GOLDEN").is_ok_and(|v| v == "1");
+    let mut failures = Vec::new();
+
+    for dir in scenarios() {
+        let actual = run_scenario(&dir).await;
+        let expected_path = dir.join("expected.json");
+        if update {
+            std::fs::write(&expected_path, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap();
+            continue;
+        }
+        let expected: Vec<TimelineEntry> = serde_json::from_str(
+            &std::fs::read_to_string(&expected_path)
+                .unwrap_or_else(|e| panic!("{}: {} (run with UPDATE_GOLDEN=1)", expected_path.display(), e)),
+        )
+        .unwrap();
+        let name = dir.file_name().unwrap().to_string_lossy().to_string();
+        failures.extend(
+            diff_timelines(&expected, &actual, DEFAULT_GOLDEN_TOLERANCE)
+                .into_iter()
+                .map(|d| format!("{}: {}", name, d)),
+        );
+    }
+
+    assert!(failures.is_empty(), "Golden timelines changed:\n  {}", failures.join("\n  "));
+}
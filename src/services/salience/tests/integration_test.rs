@@ -19,6 +19,7 @@ fn test_config() -> ClientConfig {
         address: "http://localhost:50051".to_string(),
         connect_timeout: Duration::from_secs(5),
         request_timeout: Duration::from_secs(30),
+        ..ClientConfig::default()
     }
 }
 
@@ -122,7 +123,7 @@ async fn test_store_and_query_heuristic() {
 
     // Find our heuristic in the matches
     let found = results.iter().any(|m| {
-        m.heuristic.as_ref().map_or(false, |h| h.id == h_id.to_string())
+        m.heuristic.as_ref().is_some_and(|h| h.id == h_id.to_string())
     });
     assert!(found, "Should find stored heuristic");
 
@@ -0,0 +1,105 @@
+//! Tests for the `memctl` binary: diff output and diff(1)-style exit codes.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use gladys_memory::seed::{parse_seed_file, to_seed_file};
+
+fn memctl(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_memctl")).args(args).output().expect("memctl runs")
+}
+
+/// Write a snapshot under the cargo test temp dir and return its path.
+fn snapshot(name: &str, contents: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("memctl_{}.json", name));
+    std::fs::write(&path, contents).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+fn heuristics(creeper_confidence: f32, with_diamonds: bool, with_friend: bool) -> String {
+    let mut records = vec![format!(
+        r#"{{"name": "creeper nearby", "condition_text": "creeper approaching the player", "effects": {{}}, "confidence": {}}}"#,
+        creeper_confidence
+    )];
+    if with_diamonds {
+        records.push(r#"{"name": "diamond found", "condition_text": "player found diamonds", "effects": {}, "confidence": 0.8}"#.to_string());
+    }
+    if with_friend {
+        records.push(r#"{"condition_text": "friend sent you a message", "effects": {}, "confidence": 0.7}"#.to_string());
+    }
+    format!("[{}]", records.join(","))
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn test_identical_snapshots_exit_zero() {
+    let a = snapshot("same_a", &heuristics(0.9, true, false));
+    let b = snapshot("same_b", &heuristics(0.9, true, false));
+    let output = memctl(&["diff", &a, &b]);
+    assert_eq!(output.status.code(), Some(0), "stderr: {}", stderr(&output));
+    assert!(stdout(&output).is_empty());
+}
+
+#[test]
+fn test_changed_snapshots_exit_one() {
+    let a = snapshot("changed_a", &heuristics(0.9, true, false));
+    let b = snapshot("changed_b", &heuristics(0.5, false, true));
+
+    let output = memctl(&["diff", &a, &b]);
+    assert_eq!(output.status.code(), Some(1), "stderr: {}", stderr(&output));
+    let lines: Vec<String> = stdout(&output).lines().map(str::to_string).collect();
+    assert_eq!(lines.len(), 3, "{:?}", lines);
+    assert!(lines[0].starts_with("+ ") && lines[0].ends_with(" friend sent you a message (confidence 0.700)"));
+    assert!(lines[1].starts_with("- ") && lines[1].ends_with(" diamond found (confidence 0.800)"));
+    assert!(lines[2].starts_with("~ ") && lines[2].ends_with(" creeper nearby confidence 0.900 -> 0.500"));
+
+    let output = memctl(&["diff", &a, &b, "--json"]);
+    assert_eq!(output.status.code(), Some(1));
+    let diff: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(diff["added"].as_array().unwrap().len(), 1);
+    assert_eq!(diff["removed"].as_array().unwrap().len(), 1);
+    assert_eq!(diff["confidence_changed"][0]["after"], 0.5);
+}
+
+#[test]
+fn test_corrupt_records_warn_and_still_diff() {
+    // A valid file with one record whose checksum doesn't match
+    let seeds = parse_seed_file(&heuristics(0.9, false, false)).unwrap();
+    let cached: Vec<_> = seeds.into_iter().map(|s| s.into_cached(vec![])).collect();
+    let file = format!("{}00000000 {{\"bad\": true}}\n", to_seed_file(&cached));
+    let a = snapshot("corrupt_a", &file);
+    let b = snapshot("corrupt_b", &heuristics(0.9, false, false));
+    let output = memctl(&["diff", &a, &b]);
+    assert_eq!(output.status.code(), Some(0), "stderr: {}", stderr(&output));
+    assert!(stderr(&output).contains("skipped 1 corrupt or missing record(s)"), "stderr: {}", stderr(&output));
+}
+
+#[test]
+fn test_errors_exit_two() {
+    let good = snapshot("errors_good", &heuristics(0.9, false, false));
+    let bad = snapshot("errors_bad", "not a heuristic file");
+    let missing = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("memctl_missing.json");
+    let missing = missing.to_string_lossy().into_owned();
+
+    let cases: [(&[&str], &str); 6] = [
+        (&[], "usage: memctl diff"),
+        (&["merge", &good, &good], "unknown command: merge"),
+        (&["diff", &good], "usage: memctl diff"),
+        (&["diff", &good, &good, &good], "unexpected argument"),
+        (&["diff", &good, &missing], "memctl_missing.json"),
+        (&["diff", &bad, &good], "memctl_errors_bad.json"),
+    ];
+    for (args, message) in cases {
+        let output = memctl(args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(stderr(&output).contains(message), "{:?}: {}", args, stderr(&output));
+        assert!(stdout(&output).is_empty());
+    }
+}
@@ -0,0 +1,73 @@
+//! Tests for the `salience-simulate` binary against the golden scenarios.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn simulate(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_salience-simulate")).args(args).output().expect("salience-simulate runs")
+}
+
+fn scenario(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/minecraft_threats").join(name);
+    path.to_string_lossy().into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn test_prints_one_row_per_event() {
+    let output = simulate(&[&scenario("transcript.jsonl"), "--heuristics", &scenario("heuristics.json")]);
+    assert!(output.status.success(), "stderr: {}", stderr(&output));
+    let expected: Vec<serde_json::Value> =
+        serde_json::from_str(&std::fs::read_to_string(scenario("expected.json")).unwrap()).unwrap();
+    let rows: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(rows.len(), expected.len());
+}
+
+#[test]
+fn test_expect_matches_golden_timeline() {
+    let output = simulate(&[
+        &scenario("transcript.jsonl"),
+        "--heuristics",
+        &scenario("heuristics.json"),
+        "--expect",
+        &scenario("expected.json"),
+    ]);
+    assert!(output.status.success(), "stderr: {}", stderr(&output));
+    assert!(output.stdout.is_empty());
+    assert!(stderr(&output).contains("events match"));
+}
+
+#[test]
+fn test_expect_reports_differences() {
+    // Without the heuristics nothing matches, so the golden timeline differs
+    let output = simulate(&[&scenario("transcript.jsonl"), "--expect", &scenario("expected.json")]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("Timeline differs from"), "stderr: {}", stderr(&output));
+}
+
+#[test]
+fn test_errors_exit_nonzero() {
+    let missing = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("simulate_missing.jsonl");
+    let missing = missing.to_string_lossy().into_owned();
+    let transcript = scenario("transcript.jsonl");
+
+    let cases: [(&[&str], &str); 5] = [
+        (&[], "usage: salience-simulate"),
+        (&[&transcript, "--storage", "sqlite"], "unknown storage: sqlite"),
+        (&[&transcript, "--heuristics"], "--heuristics needs a file"),
+        (&[&transcript, &transcript], "unexpected argument"),
+        (&[&missing], "No such file"),
+    ];
+    for (args, message) in cases {
+        let output = simulate(args);
+        assert!(!output.status.success(), "{:?}", args);
+        assert!(stderr(&output).contains(message), "{:?}: {}", args, stderr(&output));
+        assert!(output.stdout.is_empty());
+    }
+}